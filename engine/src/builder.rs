@@ -7,17 +7,24 @@
 // except according to those terms.
 
 use autocxx_parser::file_locations::FileLocationStrategy;
+use itertools::Itertools;
 use miette::Diagnostic;
 use thiserror::Error;
 
-use crate::{generate_rs_single, CodegenOptions};
-use crate::{get_cxx_header_bytes, CppCodegenOptions, ParseError, RebuildDependencyRecorder};
+use crate::{generate_rs_single, CodegenOptions, ParsedFile};
+use crate::{
+    get_cxx_header_bytes, CppCodegenOptions, CppWrapperBodyCustomizer, CppWrapperFunction,
+    ParseError, RebuildDependencyRecorder,
+};
+use indexmap::set::IndexSet as HashSet;
+use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::fs::File;
 use std::io::Write;
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 /// Errors returned during creation of a [`cc::Build`] from an include_cxx
 /// macro.
@@ -35,6 +42,14 @@ pub enum BuilderError {
     NoIncludeCxxMacrosFound,
     #[error("could not create a directory {1}: {0}")]
     UnableToCreateDirectory(std::io::Error, PathBuf),
+    #[error("include directory {0} was not found (looked for it at {1})")]
+    IncludeDirectoryNotFound(PathBuf, PathBuf),
+    #[error("\"{0}\" is not a valid name for a preprocessor definition")]
+    InvalidDefine(String),
+    #[error("the compilation database at {0} isn't a JSON list, so we can't add to it")]
+    InvalidCompileCommands(PathBuf),
+    #[error("precompiled header {0} was not found")]
+    PrecompiledHeaderNotFound(PathBuf),
 }
 
 #[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
@@ -49,6 +64,175 @@ pub struct BuilderSuccess(pub BuilderBuild, pub Vec<PathBuf>, pub Vec<PathBuf>);
 #[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
 pub type BuilderResult = Result<BuilderSuccess, BuilderError>;
 
+/// The C++ and Rust files generated by a [`Builder`], plus the settings
+/// needed to compile the C++, without committing to any particular
+/// [`cc::Build`]. Use this to compile the generated C++ with different
+/// flags from the rest of your code, for instance to optimize it even in
+/// debug builds. Obtain one from [`Builder::generate_files`].
+///
+/// The files live in the generated code directory (by default, within
+/// `OUT_DIR`, or else the directory given to [`Builder::custom_gendir`]),
+/// which must outlive any builds made using them.
+#[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
+#[derive(Debug, Clone)]
+pub struct BuilderFiles {
+    /// C++ implementation files which need to be compiled.
+    pub cpp_files: Vec<PathBuf>,
+    /// C++ headers we generated, which are already within `include_dirs`.
+    pub header_files: Vec<PathBuf>,
+    /// Rust files we generated, which are included by the `include_cpp!`
+    /// macro.
+    pub rs_files: Vec<PathBuf>,
+    /// Directories which must be on the include path when compiling
+    /// `cpp_files`.
+    pub include_dirs: Vec<PathBuf>,
+    /// Preprocessor definitions to apply when compiling `cpp_files`.
+    pub defines: Vec<(String, Option<String>)>,
+    /// Whether to compile `cpp_files` as position-independent code, or
+    /// `None` to leave that to `cc`'s default for the target. See
+    /// [`Builder::position_independent_code`].
+    pub position_independent_code: Option<bool>,
+    /// Headers which were read while generating the bindings, such that
+    /// the bindings must be regenerated if any of them change. System
+    /// headers are not listed.
+    pub header_dependencies: Vec<PathBuf>,
+}
+
+impl BuilderFiles {
+    /// Create a new [`cc::Build`] which will compile the generated C++.
+    /// This can be called as many times as you like; each time you'll get
+    /// a fresh build to which you can apply your own flags.
+    pub fn to_build(&self) -> BuilderBuild {
+        let mut builder = cc::Build::new();
+        builder.cpp(true);
+        if std::env::var_os("AUTOCXX_ASAN").is_some() {
+            builder.flag_if_supported("-fsanitize=address");
+        }
+        builder.includes(&self.include_dirs);
+        for (name, value) in &self.defines {
+            builder.define(name, value.as_deref());
+        }
+        if let Some(pic) = self.position_independent_code {
+            builder.pic(pic);
+        }
+        builder.files(&self.cpp_files);
+        builder
+    }
+
+    /// Tell cargo to rerun the build script if any of the
+    /// [`header_dependencies`](Self::header_dependencies) change.
+    /// `autocxx_build` already does this as it goes along, so this is
+    /// only needed if you're driving the engine's [`Builder`] with your
+    /// own [`BuilderContext`].
+    pub fn emit_rerun_if_changed(&self) {
+        for header in &self.header_dependencies {
+            println!("cargo:rerun-if-changed={}", header.display());
+        }
+    }
+}
+
+/// Function to find the directory containing a header named in
+/// `include_cpp!` which couldn't be found in any of the include directories
+/// passed to [`Builder::new`]. See [`Builder::include_path_resolver`].
+///
+/// This is a newtype wrapper so we can store it in the [`Builder`].
+#[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
+#[allow(clippy::type_complexity)] // it's only complex because of the lifetime
+pub struct IncludePathResolver<'a>(pub Box<dyn 'a + Fn(&str) -> Option<PathBuf>>);
+
+impl IncludePathResolver<'_> {
+    fn resolve(&self, header: &str) -> Option<PathBuf> {
+        self.0(header)
+    }
+}
+
+/// Typed include configuration for a [`Builder`]: the directories to
+/// search for headers, and any preprocessor definitions. Unlike the
+/// include directories passed to [`Builder::new`], these are checked up
+/// front, so a mistyped directory gives a clear error rather than a
+/// missing header later on.
+///
+/// Relative paths are relative to the directory containing the crate's
+/// `Cargo.toml` (`CARGO_MANIFEST_DIR`) if that's set, which is the
+/// case within a `build.rs`, or otherwise the current directory.
+#[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
+#[derive(Default, Debug, Clone)]
+pub struct IncludeConfig {
+    system_dirs: Vec<PathBuf>,
+    user_dirs: Vec<PathBuf>,
+    defines: Vec<(String, Option<String>)>,
+}
+
+impl IncludeConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory of system headers. These are searched by autocxx
+    /// as system include directories (`-isystem`), so warnings within them
+    /// are suppressed, and are passed to the C++ compiler as ordinary
+    /// include directories.
+    pub fn system_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.system_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add a directory of headers belonging to the project (`-I`).
+    pub fn user_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.user_dirs.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add a preprocessor definition, used both when autocxx parses the
+    /// headers and when the generated C++ is compiled. With no value, this
+    /// is equivalent to `#define NAME`.
+    pub fn define(mut self, name: &str, value: Option<&str>) -> Self {
+        self.defines
+            .push((name.to_string(), value.map(|v| v.to_string())));
+        self
+    }
+
+    /// Make all the directories absolute, and check they exist.
+    fn resolve(self) -> Result<Self, BuilderError> {
+        let base = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        let resolve_dirs = |dirs: Vec<PathBuf>| {
+            dirs.into_iter()
+                .map(|dir| {
+                    let resolved = base.join(&dir);
+                    if resolved.is_dir() {
+                        Ok(resolved)
+                    } else {
+                        Err(BuilderError::IncludeDirectoryNotFound(dir, resolved))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        if let Some((name, _)) = self.defines.iter().find(|(name, _)| {
+            name.is_empty() || name.contains(|c: char| c == '=' || c.is_whitespace())
+        }) {
+            return Err(BuilderError::InvalidDefine(name.clone()));
+        }
+        Ok(Self {
+            system_dirs: resolve_dirs(self.system_dirs)?,
+            user_dirs: resolve_dirs(self.user_dirs)?,
+            defines: self.defines,
+        })
+    }
+
+    fn clang_args(&self) -> impl Iterator<Item = String> + '_ {
+        self.system_dirs
+            .iter()
+            .flat_map(|dir| ["-isystem".to_string(), dir.to_string_lossy().to_string()])
+            .chain(self.defines.iter().map(|(name, value)| match value {
+                Some(value) => format!("-D{name}={value}"),
+                None => format!("-D{name}"),
+            }))
+    }
+}
+
 /// The context in which a builder object lives. Callbacks for various
 /// purposes.
 #[cfg_attr(feature = "nightly", doc(cfg(feature = "build")))]
@@ -73,6 +257,12 @@ pub struct Builder<'a, BuilderContext> {
     dependency_recorder: Option<Box<dyn RebuildDependencyRecorder>>,
     custom_gendir: Option<PathBuf>,
     auto_allowlist: bool,
+    include_path_resolver: Option<IncludePathResolver<'a>>,
+    include_config: IncludeConfig,
+    name_manifest: Option<PathBuf>,
+    compile_commands: Option<PathBuf>,
+    precompiled_header: Option<PathBuf>,
+    position_independent_code: Option<bool>,
     codegen_options: CodegenOptions<'a>,
     // This member is to ensure that this type is parameterized
     // by a BuilderContext. The goal is to balance three needs:
@@ -84,7 +274,7 @@ pub struct Builder<'a, BuilderContext> {
     ctx: PhantomData<BuilderContext>,
 }
 
-impl<CTX: BuilderContext> Builder<'_, CTX> {
+impl<'a, CTX: BuilderContext> Builder<'a, CTX> {
     /// Create a new Builder object. You'll need to pass in the Rust file
     /// which contains the bindings (typically an `include_cpp!` macro
     /// though `autocxx` can also handle manually-crafted `cxx::bridge`
@@ -108,12 +298,38 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
             dependency_recorder: CTX::get_dependency_recorder(),
             custom_gendir: None,
             auto_allowlist: false,
+            include_path_resolver: None,
+            include_config: IncludeConfig::default(),
+            name_manifest: None,
+            compile_commands: None,
+            precompiled_header: None,
+            position_independent_code: None,
             codegen_options: CodegenOptions::default(),
             ctx: PhantomData,
         }
     }
 
-    /// Specify extra arguments for clang.
+    /// Create a new Builder object with a typed include configuration,
+    /// as an alternative to passing a list of include directories to
+    /// [`Builder::new`].
+    pub fn new_with_include_config(rs_file: impl AsRef<Path>, config: IncludeConfig) -> Self {
+        Self::new(rs_file, Vec::<PathBuf>::new()).include_config(config)
+    }
+
+    /// Use the given include directories and preprocessor definitions,
+    /// in addition to any include directories passed to [`Builder::new`].
+    /// See [`IncludeConfig`].
+    pub fn include_config(mut self, config: IncludeConfig) -> Self {
+        self.include_config = config;
+        self
+    }
+
+    /// Specify extra arguments for clang. Any preprocessor definitions
+    /// among them (`-DNAME` or `-DNAME=VALUE`) are also applied when the
+    /// generated C++ is compiled, so that autocxx's view of the headers
+    /// matches the compiler's. Otherwise, a method which is only public
+    /// (or a field which only exists) under some definition might be
+    /// bound by autocxx, but be inaccessible when compiling.
     pub fn extra_clang_args(mut self, extra_clang_args: &[&str]) -> Self {
         self.extra_clang_args = extra_clang_args.iter().map(|s| s.to_string()).collect();
         self
@@ -134,6 +350,106 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
         self
     }
 
+    /// Provide a function which may post-process or replace the body of
+    /// any of the C++ wrapper functions which autocxx generates, for
+    /// instance to add logging to particular functions. See
+    /// [`CppWrapperBodyCustomizer`] for details.
+    pub fn wrapper_body_customizer<F>(mut self, customizer: F) -> Self
+    where
+        F: 'a + Fn(&CppWrapperFunction) -> Option<String>,
+    {
+        self.codegen_options
+            .cpp_codegen_options
+            .wrapper_body_customizer = Some(CppWrapperBodyCustomizer(Box::new(customizer)));
+        self
+    }
+
+    /// Provide a function to find headers which can't be found in the include
+    /// directories passed to [`Builder::new`]. This is useful for build systems
+    /// which compute include paths dynamically, or place generated headers in
+    /// sandboxed locations. The function is given the header name exactly as
+    /// written in `#include` and should return the directory in which it can be
+    /// found (such that `directory.join(header)` exists), which is then searched
+    /// along with all the other include directories.
+    ///
+    /// Only the headers named directly in `include_cpp!` are given to the
+    /// function. Headers which they in turn include are found by the C++
+    /// parser in the usual way, so they must be in one of the include
+    /// directories, which by then includes any directory the function
+    /// returned.
+    ///
+    /// The function is consulted at most once for each header, so if it would
+    /// give different answers for the same header on different occasions, the
+    /// first answer is used.
+    pub fn include_path_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: 'a + Fn(&str) -> Option<PathBuf>,
+    {
+        self.include_path_resolver = Some(IncludePathResolver(Box::new(resolver)));
+        self
+    }
+
+    /// Write a JSON manifest to the given path, listing for each generated
+    /// function its Rust name, its name within the `cxx::bridge` mod, and
+    /// its original C++ name. These can all differ, for instance where
+    /// C++ overloads have been renamed in Rust and reached via a C++ wrapper
+    /// function. This is intended for tools which generate documentation
+    /// or further bindings on top of the autocxx-generated bindings.
+    ///
+    /// The manifest is a list with one entry per `include_cpp!` macro, each
+    /// of which has a `mod` name, a list of `functions` and a list of
+    /// `types`. Each function has `rust_name`, `cxxbridge_name` and
+    /// `cpp_name` keys, and each type is given by its Rust name. Rust names
+    /// are relative to the generated mod.
+    pub fn name_manifest(mut self, path: impl AsRef<Path>) -> Self {
+        self.name_manifest = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Write a compilation database (`compile_commands.json`) to the given
+    /// path, describing how each generated C++ file is compiled, so that
+    /// tools such as `clangd` can understand the generated code. The
+    /// commands use the same include directories and definitions as the
+    /// [`cc::Build`] we return, but not any flags you add to that build.
+    ///
+    /// If a database already exists at this path, any entries for our
+    /// generated files are replaced, and all other entries are kept. This
+    /// means the database can be shared with the rest of your C++ code.
+    pub fn compile_commands(mut self, path: impl AsRef<Path>) -> Self {
+        self.compile_commands = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Use a clang precompiled header (`.pch`) when parsing the headers
+    /// named in `include_cpp!`, so that clang can load its contents rather
+    /// than parsing them afresh for every `include_cpp!`. This can make a
+    /// big difference for projects which wrap large sets of headers.
+    ///
+    /// The precompiled header must have been built by a version of clang
+    /// compatible with the `libclang` which autocxx uses, and with the same
+    /// include directories and definitions, or clang will refuse to use it.
+    /// Everything within it is still visible to autocxx, so types declared
+    /// in the precompiled header can be used by APIs in other headers just
+    /// as before. It's used only for autocxx's own parsing, and isn't
+    /// passed to the C++ compiler.
+    pub fn precompiled_header(mut self, path: impl AsRef<Path>) -> Self {
+        self.precompiled_header = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Whether to compile the generated C++ as position-independent code
+    /// (`-fPIC`). It must be, in order to link into a shared library such
+    /// as a `cdylib`, or into a `staticlib` which will in turn be linked
+    /// into a shared library. If you don't call this, it's left to `cc`,
+    /// which turns it on for most targets, but not for Windows, where it
+    /// isn't applicable, nor for bare-metal targets. Cargo doesn't tell
+    /// build scripts what sort of crate they're building for, so we can't
+    /// do better than that by default.
+    pub fn position_independent_code(mut self, pic: bool) -> Self {
+        self.position_independent_code = Some(pic);
+        self
+    }
+
     /// Automatically discover uses of the C++ `ffi` mod and generate the allowlist
     /// from that.
     /// This is a highly experimental option, not currently recommended.
@@ -161,6 +477,17 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
         self
     }
 
+    /// Whether to fail if any allowlist entry matches nothing in the headers.
+    /// An item named in `generate!` or `generate_pod!` must always exist,
+    /// but by default a namespace named in `generate_ns!` may turn out to
+    /// contain nothing which autocxx can generate. Turn this on to catch
+    /// mis-spelled or moved namespaces too. Any error lists every entry
+    /// which didn't match.
+    pub fn strict_allowlist(mut self, do_it: bool) -> Self {
+        self.codegen_options.strict_allowlist = do_it;
+        self
+    }
+
     /// Whether to suppress inclusion of system headers (`memory`, `string` etc.)
     /// from generated C++ bindings code. This should not normally be used,
     /// but can occasionally be useful if you're reducing a test case and you
@@ -197,9 +524,38 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
     /// about the files generated which can subsequently be examined for correctness.
     /// In production, please use simply [`build`].
     pub fn build_listing_files(self) -> Result<BuilderSuccess, BuilderError> {
-        let clang_args = &self
-            .extra_clang_args
+        let files = self.generate_files()?;
+        let mut generated_cpp = files.cpp_files.clone();
+        generated_cpp.extend(files.header_files.iter().cloned());
+        Ok(BuilderSuccess(
+            files.to_build(),
+            files.rs_files.clone(),
+            generated_cpp,
+        ))
+    }
+
+    /// Generate autocxx C++ and Rust files, but rather than returning a
+    /// single [`cc::Build`], return a [`BuilderFiles`] from which you can
+    /// make as many builds as you need, each with its own flags.
+    pub fn generate_files(self) -> Result<BuilderFiles, BuilderError> {
+        let include_config = self.include_config.resolve()?;
+        let header_dependencies = Rc::new(RefCell::new(HashSet::new()));
+        let dependency_recorder = CollectingDepRecorder {
+            inner: self.dependency_recorder,
+            found: header_dependencies.clone(),
+        };
+        let mut include_config_clang_args: Vec<_> = include_config.clang_args().collect();
+        if let Some(pch) = &self.precompiled_header {
+            if !pch.is_file() {
+                return Err(BuilderError::PrecompiledHeaderNotFound(pch.clone()));
+            }
+            dependency_recorder.record_header_file_dependency(&pch.to_string_lossy());
+            include_config_clang_args.push("-include-pch".to_string());
+            include_config_clang_args.push(pch.to_string_lossy().to_string());
+        }
+        let clang_args = &include_config_clang_args
             .iter()
+            .chain(self.extra_clang_args.iter())
             .map(|s| &s[..])
             .collect::<Vec<_>>();
         rust_version_check();
@@ -228,28 +584,36 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
             ),
         )?;
 
-        let autocxx_inc = build_autocxx_inc(self.autocxx_incs, &incdir);
+        let autocxx_inc = build_autocxx_inc(
+            self.autocxx_incs.into_iter().chain(
+                include_config
+                    .user_dirs
+                    .into_iter()
+                    .map(PathBuf::into_os_string),
+            ),
+            &incdir,
+        );
         gen_location_strategy.set_cargo_env_vars_for_build();
 
         let mut parsed_file = crate::parse_file(self.rs_file, self.auto_allowlist)
             .map_err(BuilderError::ParseError)?;
+        let autocxx_inc = add_resolved_include_dirs(
+            autocxx_inc,
+            &parsed_file,
+            self.include_path_resolver.as_ref(),
+        );
         parsed_file
             .resolve_all(
                 autocxx_inc,
                 clang_args,
-                self.dependency_recorder,
+                Some(Box::new(dependency_recorder)),
                 &self.codegen_options,
             )
             .map_err(BuilderError::ParseError)?;
         let mut counter = 0;
-        let mut builder = cc::Build::new();
-        builder.cpp(true);
-        if std::env::var_os("AUTOCXX_ASAN").is_some() {
-            builder.flag_if_supported("-fsanitize=address");
-        }
-        let mut generated_rs = Vec::new();
-        let mut generated_cpp = Vec::new();
-        builder.includes(parsed_file.include_dirs());
+        let mut cpp_files = Vec::new();
+        let mut header_files = Vec::new();
+        let mut rs_files = Vec::new();
         for include_cpp in parsed_file.get_cpp_buildables() {
             let generated_code = include_cpp
                 .generate_h_and_cxx(&self.codegen_options.cpp_codegen_options)
@@ -258,25 +622,84 @@ impl<CTX: BuilderContext> Builder<'_, CTX> {
                 let fname = format!("gen{counter}.cxx");
                 counter += 1;
                 if let Some(implementation) = &filepair.implementation {
-                    let gen_cxx_path = write_to_file(&cxxdir, &fname, implementation)?;
-                    builder.file(&gen_cxx_path);
-                    generated_cpp.push(gen_cxx_path);
+                    cpp_files.push(write_to_file(&cxxdir, &fname, implementation)?);
                 }
                 write_to_file(&incdir, &filepair.header_name, &filepair.header)?;
-                generated_cpp.push(incdir.join(filepair.header_name));
+                header_files.push(incdir.join(filepair.header_name));
             }
         }
 
         for rs_output in parsed_file.get_rs_outputs() {
             let rs = generate_rs_single(rs_output);
-            generated_rs.push(write_to_file(&rsdir, &rs.filename, rs.code.as_bytes())?);
+            rs_files.push(write_to_file(&rsdir, &rs.filename, rs.code.as_bytes())?);
+        }
+        if let Some(name_manifest) = self.name_manifest {
+            write_name_manifest(&name_manifest, &parsed_file)?;
         }
         if counter == 0 {
-            Err(BuilderError::NoIncludeCxxMacrosFound)
-        } else {
-            Ok(BuilderSuccess(builder, generated_rs, generated_cpp))
+            return Err(BuilderError::NoIncludeCxxMacrosFound);
+        }
+        let files = BuilderFiles {
+            cpp_files,
+            header_files,
+            rs_files,
+            include_dirs: parsed_file
+                .include_dirs()
+                .cloned()
+                .chain(include_config.system_dirs)
+                .collect(),
+            defines: include_config
+                .defines
+                .into_iter()
+                .chain(defines_from_clang_args(&self.extra_clang_args))
+                .collect(),
+            position_independent_code: self.position_independent_code,
+            header_dependencies: header_dependencies.take().into_iter().collect(),
+        };
+        if let Some(compile_commands) = self.compile_commands {
+            write_compile_commands(&compile_commands, &files)?;
+        }
+        Ok(files)
+    }
+}
+
+/// Records the header dependencies found while generating, so that we can
+/// report them in [`BuilderFiles`], as well as passing them on to the
+/// [`RebuildDependencyRecorder`] supplied by the [`BuilderContext`], if any.
+#[derive(Debug)]
+struct CollectingDepRecorder {
+    inner: Option<Box<dyn RebuildDependencyRecorder>>,
+    found: Rc<RefCell<HashSet<PathBuf>>>,
+}
+
+impl RebuildDependencyRecorder for CollectingDepRecorder {
+    fn record_header_file_dependency(&self, filename: &str) {
+        self.found.borrow_mut().insert(PathBuf::from(filename));
+        if let Some(inner) = &self.inner {
+            inner.record_header_file_dependency(filename);
+        }
+    }
+}
+
+/// Find the preprocessor definitions among some clang arguments, in
+/// either the `-DNAME` or the `-D NAME` form.
+fn defines_from_clang_args(args: &[String]) -> Vec<(String, Option<String>)> {
+    let mut args = args.iter();
+    let mut defines = Vec::new();
+    while let Some(arg) = args.next() {
+        let define = match arg.strip_prefix("-D") {
+            Some("") => args.next().map(String::as_str),
+            Some(define) => Some(define),
+            None => None,
+        };
+        if let Some(define) = define {
+            defines.push(match define.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (define.to_string(), None),
+            });
         }
     }
+    defines
 }
 
 fn ensure_created(dir: &Path) -> Result<(), BuilderError> {
@@ -296,6 +719,126 @@ where
         .collect()
 }
 
+/// Ask any [`IncludePathResolver`] about headers named in `include_cpp!`
+/// which can't be found in the existing include directories, and add
+/// whatever it tells us.
+fn add_resolved_include_dirs(
+    mut inc_dirs: Vec<PathBuf>,
+    parsed_file: &ParsedFile,
+    resolver: Option<&IncludePathResolver>,
+) -> Vec<PathBuf> {
+    if let Some(resolver) = resolver {
+        let missing_headers: Vec<_> = parsed_file
+            .get_autocxxes()
+            .flat_map(|include_cpp| include_cpp.get_inclusions())
+            .unique()
+            .filter(|header| !inc_dirs.iter().any(|dir| dir.join(header).exists()))
+            .cloned()
+            .collect();
+        for header in missing_headers {
+            if let Some(dir) = resolver.resolve(&header) {
+                if !inc_dirs.contains(&dir) {
+                    inc_dirs.push(dir);
+                }
+            }
+        }
+    }
+    inc_dirs
+}
+
+fn write_name_manifest(path: &Path, parsed_file: &ParsedFile) -> Result<(), BuilderError> {
+    let json: Vec<_> = parsed_file
+        .get_autocxxes()
+        .map(|include_cpp| {
+            let functions: Vec<_> = include_cpp
+                .get_function_name_mappings()
+                .iter()
+                .map(|mapping| {
+                    serde_json::json!({
+                        "rust_name": mapping.rust_name,
+                        "cxxbridge_name": mapping.cxxbridge_name,
+                        "cpp_name": mapping.cpp_name,
+                    })
+                })
+                .collect();
+            serde_json::json!({
+                "mod": include_cpp.get_mod_name(),
+                "functions": functions,
+                "types": include_cpp.generated_type_names(),
+            })
+        })
+        .collect();
+    let content = serde_json::to_vec_pretty(&json).expect("Unable to encode JSON manifest");
+    try_write_to_file(path, &content)
+        .map_err(|e| BuilderError::FileWriteFail(e, path.to_path_buf()))
+}
+
+fn write_compile_commands(path: &Path, files: &BuilderFiles) -> Result<(), BuilderError> {
+    let mut entries = match std::fs::read(path) {
+        Ok(existing) => match serde_json::from_slice(&existing) {
+            Ok(serde_json::Value::Array(entries)) => entries,
+            _ => return Err(BuilderError::InvalidCompileCommands(path.to_path_buf())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(BuilderError::FileWriteFail(e, path.to_path_buf())),
+    };
+    let our_files: Vec<_> = files
+        .cpp_files
+        .iter()
+        .map(|file| file.to_string_lossy().to_string())
+        .collect();
+    entries.retain(|entry| {
+        !matches!(entry.get("file").and_then(|file| file.as_str()),
+            Some(file) if our_files.iter().any(|ours| ours == file))
+    });
+    let compiler_args = compiler_args(files);
+    for file in &our_files {
+        let arguments: Vec<_> = compiler_args
+            .iter()
+            .cloned()
+            .chain(["-c".to_string(), file.clone()])
+            .collect();
+        let directory = Path::new(file)
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push(serde_json::json!({
+            "directory": directory,
+            "file": file,
+            "arguments": arguments,
+        }));
+    }
+    let content =
+        serde_json::to_vec_pretty(&entries).expect("Unable to encode compilation database");
+    try_write_to_file(path, &content)
+        .map_err(|e| BuilderError::FileWriteFail(e, path.to_path_buf()))
+}
+
+/// The compiler and arguments which will be used to compile the generated
+/// C++, other than the file itself.
+fn compiler_args(files: &BuilderFiles) -> Vec<String> {
+    match files.to_build().try_get_compiler() {
+        Ok(tool) => std::iter::once(tool.path().as_os_str())
+            .chain(tool.args().iter().map(OsString::as_os_str))
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect(),
+        // cc can only work out which compiler to use from within a build
+        // script, so elsewhere, describe a generic one.
+        Err(_) => std::iter::once("c++".to_string())
+            .chain(
+                files
+                    .include_dirs
+                    .iter()
+                    .map(|dir| format!("-I{}", dir.to_string_lossy())),
+            )
+            .chain(files.defines.iter().map(|(name, value)| match value {
+                Some(value) => format!("-D{name}={value}"),
+                None => format!("-D{name}"),
+            }))
+            .collect(),
+    }
+}
+
 fn write_to_file(dir: &Path, filename: &str, content: &[u8]) -> Result<PathBuf, BuilderError> {
     let path = dir.join(filename);
     if let Ok(existing_contents) = std::fs::read(&path) {
@@ -11,6 +11,7 @@ use syn::{punctuated::Punctuated, token::Comma, FnArg};
 
 use super::{
     fun::{
+        function_wrapper::{CppFunction, CppFunctionBody},
         FnAnalysis, FnKind, FnPhase, FnPrePhase2, MethodKind, PodAndConstructorAnalysis,
         TraitMethodKind,
     },
@@ -161,7 +162,7 @@ pub(crate) fn mark_types_abstract(apis: ApiVec<FnPrePhase2>) -> ApiVec<FnPrePhas
         .collect();
 
     // We also need to remove any constructors belonging to these
-    // abstract types.
+    // abstract types, and any make_shared wrappers which call them.
     apis.retain(|api| {
         !matches!(&api,
             Api::Function {
@@ -173,6 +174,16 @@ pub(crate) fn mark_types_abstract(apis: ApiVec<FnPrePhase2>) -> ApiVec<FnPrePhas
                     },
                     ..
             } if abstract_classes.contains(self_ty)
+        ) && !matches!(&api,
+            Api::Function {
+                analysis:
+                    FnAnalysis {
+                        kind: FnKind::Method{impl_for: self_ty, ..},
+                        cpp_wrapper: Some(CppFunction { payload: CppFunctionBody::MakeShared(_), .. }),
+                        ..
+                    },
+                    ..
+            } if abstract_classes.contains(self_ty)
         )
     });
 
@@ -0,0 +1,181 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to let Rust read and change the bits of a `std::bitset` which
+//! has been named using `instantiate!` or `concrete!`.
+
+use syn::parse_quote;
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{fun::function_wrapper::CppFunctionBody, pod::PodPhase, ranges::make_accessor};
+
+/// The widest bitset which we can convert to and from an integer.
+const MAX_INTEGER_BITS: usize = 64;
+
+/// For each concrete type which is a `std::bitset`, synthesize methods to
+/// call its members. Those which take a bit position are `_unchecked`: C++
+/// throws if the position is out of range, which we can't propagate, so
+/// codegen wraps each in a method which checks the position first.
+pub(crate) fn add_bitset_accessors(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let accessors = match &api {
+            Api::ConcreteType {
+                name,
+                cpp_definition,
+                ..
+            } => bitset_size(cpp_definition)
+                .map(|size| make_bitset_accessors(&name.name, size))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        results.push(api);
+        results.extend(accessors.into_iter());
+    }
+    results
+}
+
+fn make_bitset_accessors(bitset_name: &QualifiedName, size: usize) -> Vec<Api<PodPhase>> {
+    let typ = bitset_name.to_type_path();
+    let this_ref = || References {
+        ref_params: [make_ident("this")].into_iter().collect(),
+        ..Default::default()
+    };
+    let call = |member: &str| CppFunctionBody::CallMember(make_ident(member));
+    let mut accessors = Vec::new();
+    for (method, member) in [("count", "count"), ("len", "size")] {
+        accessors.push(make_accessor(
+            bitset_name,
+            &make_ident(method),
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> usize },
+            this_ref(),
+            call(member),
+        ));
+    }
+    for member in ["all", "any", "none"] {
+        accessors.push(make_accessor(
+            bitset_name,
+            &make_ident(member),
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> bool },
+            this_ref(),
+            call(member),
+        ));
+    }
+    accessors.push(document_unchecked(
+        make_accessor(
+            bitset_name,
+            &make_ident("test_unchecked"),
+            parse_quote! { this: *const #typ, pos: usize },
+            parse_quote! { -> bool },
+            this_ref(),
+            call("test"),
+        ),
+        "test",
+    ));
+    accessors.push(document_unchecked(
+        make_accessor(
+            bitset_name,
+            &make_ident("set_unchecked"),
+            parse_quote! { this: *mut #typ, pos: usize, value: bool },
+            parse_quote! {},
+            this_ref(),
+            call("set"),
+        ),
+        "set",
+    ));
+    for member in ["reset", "flip"] {
+        accessors.push(document_unchecked(
+            make_accessor(
+                bitset_name,
+                &make_ident(format!("{member}_unchecked")),
+                parse_quote! { this: *mut #typ, pos: usize },
+                parse_quote! {},
+                this_ref(),
+                call(member),
+            ),
+            member,
+        ));
+    }
+    for member in ["set", "reset", "flip"] {
+        accessors.push(make_accessor(
+            bitset_name,
+            &make_ident(format!("{member}_all")),
+            parse_quote! { this: *mut #typ },
+            parse_quote! {},
+            this_ref(),
+            call(member),
+        ));
+    }
+    // Wider bitsets have no integer type which can hold them.
+    if size <= MAX_INTEGER_BITS {
+        accessors.push(make_accessor(
+            bitset_name,
+            &make_ident("to_u64"),
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> u64 },
+            this_ref(),
+            call("to_ullong"),
+        ));
+        // Without a `this`, this becomes a static method; the value is
+        // passed straight to the bitset's constructor.
+        accessors.push(make_accessor(
+            bitset_name,
+            &make_ident("from_u64"),
+            parse_quote! { value: u64 },
+            parse_quote! { -> #typ },
+            References::default(),
+            CppFunctionBody::Cast,
+        ));
+    }
+    accessors
+}
+
+/// Explain that an `_unchecked` method doesn't panic like the `checked`
+/// method which codegen wraps around it.
+fn document_unchecked(mut api: Api<PodPhase>, checked: &str) -> Api<PodPhase> {
+    if let Api::Function { fun, .. } = &mut api {
+        let doc = format!(
+            " As [`Self::{checked}`], but if `pos` is out of range C++ throws an exception, which can't be passed back to Rust, so the process aborts."
+        );
+        fun.doc_attrs = vec![parse_quote! { #[doc = #doc] }];
+    }
+    api
+}
+
+/// If this C++ type is a `std::bitset`, returns its number of bits.
+pub(crate) fn bitset_size(cpp_definition: &str) -> Option<usize> {
+    cpp_definition
+        .trim()
+        .strip_prefix("std::bitset<")?
+        .strip_suffix('>')?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bitset_size;
+
+    #[test]
+    fn test_bitset_size() {
+        assert_eq!(bitset_size("std::bitset<64>"), Some(64));
+        assert_eq!(bitset_size("std::bitset< 200 >"), Some(200));
+        assert_eq!(bitset_size("std::bitset<N>"), None);
+        assert_eq!(bitset_size("std::vector<int>"), None);
+    }
+}
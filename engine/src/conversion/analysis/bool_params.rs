@@ -0,0 +1,42 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+
+use crate::conversion::{api::Api, apivec::ApiVec, convert_error::ConvertErrorFromCpp};
+
+use super::fun::{function_wrapper::CppConversionType, FnPhase};
+
+/// Check that each parameter listed in `bool_param!` was found, and was
+/// an integer which we could therefore turn into a `bool`.
+pub(crate) fn check_bool_params(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> Result<(), ConvertErrorFromCpp> {
+    for (fun, param) in config.get_bool_params() {
+        let found = apis.iter().any(|api| match api {
+            Api::Function { analysis, .. } if api.effective_cpp_name() == fun => {
+                analysis.param_details.iter().any(|pd| {
+                    matches!(&pd.name, syn::Pat::Ident(pp) if pp.ident == *param)
+                        && matches!(
+                            pd.conversion.cpp_conversion,
+                            CppConversionType::FromBoolToInteger
+                        )
+                })
+            }
+            _ => false,
+        });
+        if !found {
+            return Err(ConvertErrorFromCpp::BoolParamNotFound(
+                fun.clone(),
+                param.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+use syn::ReturnType;
+
+use crate::conversion::{api::Api, apivec::ApiVec, convert_error::ConvertErrorFromCpp};
+
+use super::fun::{FnAnalysis, FnKind, FnPhase};
+
+/// Check that each type listed in `byte_buffer!` ended up with both of the
+/// methods named there, so that codegen can implement `AsRef<[u8]>` from
+/// them. The size method must return something for us to convert into a
+/// length.
+pub(crate) fn check_byte_buffers(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> Result<(), ConvertErrorFromCpp> {
+    for byte_buffer in config.get_byte_buffers() {
+        let has_method = |method: &syn::Ident, needs_return: bool| {
+            apis.iter().any(|api| {
+                matches!(api, Api::Function {
+                    analysis: FnAnalysis {
+                        kind: FnKind::Method { impl_for, .. },
+                        rust_name,
+                        ret_type,
+                        ignore_reason: Ok(_),
+                        externally_callable: true,
+                        ..
+                    },
+                    ..
+                } if impl_for.to_cpp_name() == byte_buffer.ty
+                    && method == rust_name
+                    && !(needs_return && matches!(ret_type, ReturnType::Default)))
+            })
+        };
+        if !has_method(&byte_buffer.data_method, false)
+            || !has_method(&byte_buffer.size_method, true)
+        {
+            return Err(ConvertErrorFromCpp::ByteBufferNotFound(
+                byte_buffer.ty.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to call callable data members of C++ types, such as lambdas, as
+//! requested by `callable_member!`.
+
+use autocxx_parser::{CallableMember, IncludeCppConfig};
+use itertools::Itertools;
+use syn::{
+    parse_quote, punctuated::Punctuated, FnArg, ReturnType, Token, Type, TypeBareFn, TypeReference,
+};
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{fun::function_wrapper::CppFunctionBody, pod::PodPhase, ranges::make_accessor};
+
+/// For each member listed in `callable_member!`, synthesize a method of
+/// its struct which calls it with the signature the user gave us.
+/// That's the only way to call a lambda member, since its type can't be
+/// named, and it's simpler than converting a `std::function` member.
+pub(crate) fn add_callable_member_accessors(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if !config.has_callable_members() {
+        return apis;
+    }
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let accessors = match &api {
+            Api::Struct { name, .. } => config
+                .get_callable_members(&name.name.to_cpp_name())
+                .map(|callable| make_call_accessor(&name.name, callable))
+                .collect_vec(),
+            _ => Vec::new(),
+        };
+        results.push(api);
+        results.extend(accessors.into_iter());
+    }
+    results
+}
+
+fn make_call_accessor(struct_name: &QualifiedName, callable: &CallableMember) -> Api<PodPhase> {
+    let typ = struct_name.to_type_path();
+    // Calling a `mutable` lambda changes its captured state, so we
+    // always need a mutable receiver.
+    let mut references = References {
+        ref_params: [make_ident("this")].into_iter().collect(),
+        ..Default::default()
+    };
+    let mut inputs: Punctuated<FnArg, Token![,]> = parse_quote! { this: *mut #typ };
+    add_signature_inputs(&callable.signature, &mut inputs, &mut references);
+    let output = match &callable.signature.output {
+        ReturnType::Type(arrow, ty) => match ty.as_ref() {
+            Type::Reference(r) => {
+                references.ref_return = true;
+                ReturnType::Type(*arrow, Box::new(reference_to_pointer(r)))
+            }
+            _ => callable.signature.output.clone(),
+        },
+        ReturnType::Default => ReturnType::Default,
+    };
+    make_accessor(
+        struct_name,
+        &callable.member,
+        inputs,
+        output,
+        references,
+        CppFunctionBody::CallMember(callable.member.clone()),
+    )
+}
+
+/// Add the parameters of a user-supplied signature to `inputs`, noting
+/// which of them are references.
+pub(super) fn add_signature_inputs(
+    signature: &TypeBareFn,
+    inputs: &mut Punctuated<FnArg, Token![,]>,
+    references: &mut References,
+) {
+    for (counter, arg) in signature.inputs.iter().enumerate() {
+        let arg_name = arg
+            .name
+            .as_ref()
+            .map(|(id, _)| id.clone())
+            .unwrap_or_else(|| make_ident(format!("arg{counter}")));
+        // The user writes references as Rust references, but everything
+        // else downstream expects them as bindgen would express them.
+        let ty = match &arg.ty {
+            Type::Reference(r) => {
+                references.ref_params.insert(arg_name.clone());
+                reference_to_pointer(r)
+            }
+            ty => ty.clone(),
+        };
+        inputs.push(parse_quote! { #arg_name: #ty });
+    }
+}
+
+pub(super) fn reference_to_pointer(r: &TypeReference) -> Type {
+    let elem = &r.elem;
+    match r.mutability {
+        Some(_) => parse_quote! { *mut #elem },
+        None => parse_quote! { *const #elem },
+    }
+}
@@ -56,6 +56,17 @@ impl BridgeNameTracker {
         Self::default()
     }
 
+    /// Ensure we never hand out any of these names, because they're
+    /// already used by hand-written entries in the cxx::bridge mod.
+    pub(crate) fn reserve_names(&mut self, names: impl Iterator<Item = String>) {
+        for name in names {
+            *self
+                .next_cxx_bridge_name_for_prefix
+                .entry(name)
+                .or_default() += 1;
+        }
+    }
+
     /// Figure out the least confusing unique name for this function in the
     /// cxx::bridge section, which has a flat namespace.
     /// We mostly just qualify the name with the namespace_with_underscores.
@@ -155,4 +166,19 @@ mod tests {
             "A_B_ty_do"
         );
     }
+
+    #[test]
+    fn test_reserved() {
+        let mut bnt = BridgeNameTracker::new();
+        bnt.reserve_names(["do".to_string()].into_iter());
+        let ns_root = Namespace::new();
+        assert_eq!(
+            bnt.get_unique_cxx_bridge_name(None, "do", &ns_root),
+            "do_autocxx1"
+        );
+        assert_eq!(
+            bnt.get_unique_cxx_bridge_name(Some("ty"), "do", &ns_root),
+            "ty_do"
+        );
+    }
 }
@@ -7,7 +7,7 @@
 // except according to those terms.
 
 use crate::{
-    conversion::api::SubclassName,
+    conversion::{analysis::operators::CppOperator, api::SubclassName},
     types::{Namespace, QualifiedName},
 };
 use quote::ToTokens;
@@ -26,6 +26,32 @@ pub(crate) enum CppConversionType {
     FromReturnValueToPlacementPtr,
     FromPointerToReference, // unwrapped_type is always Type::Ptr
     FromReferenceToPointer, // unwrapped_type is always Type::Ptr
+    /// Accept a `bool` and rely on C++'s implicit conversion to pass it
+    /// to an integer parameter (such as `int` or a `BOOL` typedef).
+    FromBoolToInteger,
+    /// Return a `std::shared_ptr` which the user told us has a `const` target
+    /// (bindgen doesn't tell us) as a `std::shared_ptr` to a mutable target,
+    /// which is all that `cxx::SharedPtr` can represent.
+    FromConstSharedPtr,
+    /// As `FromConstSharedPtr`, but for a `std::weak_ptr`.
+    FromConstWeakPtr,
+    /// Accept a const reference and `const_cast` it to a mutable one, for
+    /// receivers which the user has told us are safe to share.
+    FromConstReferenceToMutable, // unwrapped_type is always Type::Reference
+    /// Accept a `rust::Slice` and make a `std::span` of the same elements.
+    FromSliceToSpan, // unwrapped_type is always a reference to a Type::Slice
+    /// Accept a `rust::Slice` and copy its elements into a new `std::vector`.
+    FromSliceToVector, // unwrapped_type is always a reference to a Type::Slice
+    /// Pass a `long double` as a `double`, relying on C++'s implicit
+    /// conversions in either direction.
+    FromLongDoubleToDouble,
+    /// Accept a reference and make a `std::reference_wrapper` to its target.
+    FromReferenceToStdReferenceWrapper,
+    /// Return the reference held by a `std::reference_wrapper`.
+    FromStdReferenceWrapperToReference, // unwrapped_type is always Type::Reference
+    /// Accept a pointer to the first element of an array with these
+    /// dimensions, and pass a reference to the whole array.
+    FromPointerToArrayReference(Vec<usize>), // unwrapped_type is always Type::Ptr
 }
 
 impl CppConversionType {
@@ -41,6 +67,11 @@ impl CppConversionType {
             CppConversionType::FromValueToUniquePtr => CppConversionType::FromUniquePtrToValue,
             CppConversionType::FromPointerToReference => CppConversionType::FromReferenceToPointer,
             CppConversionType::FromReferenceToPointer => CppConversionType::FromPointerToReference,
+            // C++ will implicitly convert a smart pointer to a mutable target
+            // into one to a const target.
+            CppConversionType::FromConstSharedPtr | CppConversionType::FromConstWeakPtr => {
+                CppConversionType::None
+            }
             _ => panic!("Did not expect to have to invert this conversion"),
         }
     }
@@ -59,6 +90,40 @@ pub(crate) enum RustConversionType {
     FromRValueParamToPtr,
     FromReferenceWrapperToPointer, // unwrapped_type is always Type::Ptr
     FromPointerToReferenceWrapper, // unwrapped_type is always Type::Ptr
+    /// Accept an `i64` and bounds-check it into a narrower integer.
+    FromWiderIntegerChecked,
+    /// Accept anything which can become a null-terminated wide string,
+    /// and pass a pointer to it.
+    FromWideStrToPtr,
+    /// Accept a reference to an array with these dimensions, and pass
+    /// a pointer to its first element.
+    FromArrayRefToPtr(Vec<usize>),
+    /// Accept a slice, whose exclusivity the borrow checker enforces,
+    /// and pass a pointer to its first element as a `restrict` pointer.
+    FromRestrictSliceToPtr, // unwrapped_type is always Type::Ptr
+    /// Accept a slice of references, or of `CStr`s if the pointers are to
+    /// `c_char`, and pass a null-terminated array of pointers built from it.
+    FromRefSliceToPtrArray, // unwrapped_type is always a Type::Ptr to a Type::Ptr
+    /// Accept a `Vec` and pass a slice of its elements.
+    FromVecToSlice,
+    /// Accept anything which can become a `&CxxString`, converting it
+    /// only if it isn't one already.
+    FromStrRef,
+    /// Accept a reference to the `opaque_handle!` type of this name, and
+    /// pass the pointer which it wraps.
+    FromHandleToPtr(Ident), // unwrapped_type is always Type::Ptr
+    /// Wrap a returned pointer in the `opaque_handle!` type of this name,
+    /// or return `None` if it's null.
+    FromPtrToHandle(Ident), // unwrapped_type is always Type::Ptr
+    /// Wrap the returned address of a `thread_local` variable in a
+    /// `CppThreadLocal`, which can't leave the current thread.
+    FromPtrToThreadLocal, // unwrapped_type is always Type::Ptr
+    /// Turn a returned pointer into an `Option` of a reference, which is
+    /// `None` if the pointer is null.
+    FromNullablePtrToRef, // unwrapped_type is always Type::Ptr
+    /// Take apart a returned struct with these fields, and return a
+    /// tuple of their values instead.
+    FromStructToTuple(Vec<(Ident, Type)>),
 }
 
 impl RustConversionType {
@@ -68,6 +133,12 @@ impl RustConversionType {
             _ => None,
         }
     }
+
+    /// Whether the Rust-side conversion can fail, such that the wrapper
+    /// function must return a `Result`.
+    pub(crate) fn is_fallible(&self) -> bool {
+        matches!(self, Self::FromWiderIntegerChecked)
+    }
 }
 
 /// A policy for converting types. Conversion may occur on both the Rust and
@@ -139,6 +210,74 @@ impl TypeConversionPolicy {
         }
     }
 
+    pub(crate) fn new_from_const_smart_ptr(ty: Type, is_weak: bool) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: ty,
+            cpp_conversion: if is_weak {
+                CppConversionType::FromConstWeakPtr
+            } else {
+                CppConversionType::FromConstSharedPtr
+            },
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_bool_to_integer() -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: parse_quote! { bool },
+            cpp_conversion: CppConversionType::FromBoolToInteger,
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_long_double_to_double() -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: parse_quote! { f64 },
+            cpp_conversion: CppConversionType::FromLongDoubleToDouble,
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_slice_to_span(ty: Type) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: ty,
+            cpp_conversion: CppConversionType::FromSliceToSpan,
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_reference_to_std_reference_wrapper(ty: Type) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: ty,
+            cpp_conversion: CppConversionType::FromReferenceToStdReferenceWrapper,
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_from_std_reference_wrapper(ty: Type) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: ty,
+            cpp_conversion: CppConversionType::FromStdReferenceWrapperToReference,
+            rust_conversion: RustConversionType::None,
+        }
+    }
+
+    pub(crate) fn new_vec_to_vector(elem: Type) -> Self {
+        TypeConversionPolicy {
+            unwrapped_type: parse_quote! { &[#elem] },
+            cpp_conversion: CppConversionType::FromSliceToVector,
+            rust_conversion: RustConversionType::FromVecToSlice,
+        }
+    }
+
+    /// Accept a `&mut [T]` rather than a `&[T]`, for a `std::span` of
+    /// mutable elements.
+    pub(crate) fn make_slice_mutable(&mut self) {
+        if let Type::Reference(TypeReference { mutability, .. }) = &mut self.unwrapped_type {
+            *mutability = Some(parse_quote! { mut });
+        }
+    }
+
     pub(crate) fn new_for_placement_return(ty: Type) -> Self {
         TypeConversionPolicy {
             unwrapped_type: ty,
@@ -181,6 +320,44 @@ impl TypeConversionPolicy {
         }
     }
 
+    /// Whether this is an integer which may be narrower than `i64`, and so
+    /// could instead accept an `i64` with a bounds check. That's either a
+    /// fixed-width integer or one of our C integer newtypes, such as
+    /// `autocxx::c_uint`.
+    pub(crate) fn is_narrow_integer(&self) -> bool {
+        match &self.unwrapped_type {
+            Type::Path(typ) if typ.qself.is_none() => {
+                typ.path.get_ident().is_some_and(|id| {
+                    ["i8", "u8", "i16", "u16", "i32", "u32"].contains(&id.to_string().as_str())
+                }) || self.c_integer_newtype().is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this is an integer of any width, including `typedef`s of
+    /// integers, which by now have been resolved to the underlying type.
+    pub(crate) fn is_integer(&self) -> bool {
+        match &self.unwrapped_type {
+            Type::Path(typ) if typ.qself.is_none() => {
+                typ.path.get_ident().is_some_and(|id| {
+                    [
+                        "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "isize", "usize",
+                    ]
+                    .contains(&id.to_string().as_str())
+                }) || c_integer_newtype(&self.unwrapped_type).is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// If this is one of our newtype wrappers for a C integer type which
+    /// may be narrower than `i64`, the name of the C type it wraps.
+    pub(crate) fn c_integer_newtype(&self) -> Option<Ident> {
+        c_integer_newtype(&self.unwrapped_type)
+            .filter(|id| !["c_longlong", "c_ulonglong"].contains(&id.to_string().as_str()))
+    }
+
     pub(crate) fn rust_work_needed(&self) -> bool {
         !matches!(self.rust_conversion, RustConversionType::None)
     }
@@ -232,6 +409,37 @@ pub(crate) enum CppFunctionBody {
     Destructor(Namespace, Ident),
     AllocUninitialized(QualifiedName),
     FreeUninitialized(QualifiedName),
+    /// Read the discriminant field of a tagged union.
+    UnionTag(Ident),
+    /// Read a member of a union field, by reference.
+    UnionMember(Ident, Ident),
+    /// Apply a C++ operator to all the parameters.
+    Operator(&'static CppOperator),
+    /// Count the elements between a range's `begin()` and `end()`.
+    RangeLen,
+    /// Allocate an iterator at a range's `begin()`, returned as `void*`.
+    RangeBegin,
+    /// Return a pointer to the element at the given range iterator and
+    /// advance it, or return null if it's at `end()`.
+    RangeNext,
+    /// Free a range iterator.
+    RangeFree,
+    /// Call the given callable data member, or method, with the remaining
+    /// parameters.
+    CallMember(Ident),
+    /// Call the given method, which returns an iterator, with the remaining
+    /// parameters. Return a pointer to the element, or to the given member
+    /// of it, or null if the iterator is `end()`.
+    IteratorLookup(Ident, Option<Ident>),
+    /// Construct an object of the given type within a `std::shared_ptr`.
+    MakeShared(QualifiedName),
+    /// Return the index of a `std::variant`'s active alternative.
+    VariantIndex,
+    /// Read the given alternative of a `std::variant`, by reference.
+    VariantGet(usize),
+    /// Take the address of the calling thread's instance of a
+    /// `thread_local` variable.
+    AddressOfThreadLocal(Namespace, Ident),
 }
 
 #[derive(Clone)]
@@ -250,7 +458,40 @@ pub(crate) struct CppFunction {
     pub(crate) original_cpp_name: String,
     pub(crate) return_conversion: Option<TypeConversionPolicy>,
     pub(crate) argument_conversion: Vec<TypeConversionPolicy>,
+    /// C++ expressions to pass after all the converted arguments, for
+    /// parameters which don't appear in the wrapper's own signature.
+    pub(crate) trailing_args: Vec<String>,
     pub(crate) kind: CppFunctionKind,
     pub(crate) pass_obs_field: bool,
     pub(crate) qualification: Option<QualifiedName>,
 }
+
+/// If this is one of our newtype wrappers for a C integer type, such as
+/// `autocxx::c_int`, the name of the C type it wraps.
+pub(crate) fn c_integer_newtype(ty: &Type) -> Option<Ident> {
+    match ty {
+        Type::Path(typ) if typ.qself.is_none() => {
+            let mut segs = typ.path.segments.iter();
+            match (segs.next(), segs.next(), segs.next()) {
+                (Some(ns), Some(ty), None)
+                    if ns.ident == "autocxx"
+                        && [
+                            "c_short",
+                            "c_ushort",
+                            "c_int",
+                            "c_uint",
+                            "c_long",
+                            "c_ulong",
+                            "c_longlong",
+                            "c_ulonglong",
+                        ]
+                        .contains(&ty.ident.to_string().as_str()) =>
+                {
+                    Some(ty.ident.clone())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -257,7 +257,8 @@ pub(super) fn find_constructors_present(
                     TypeKind::Pointer
                     | TypeKind::Reference
                     | TypeKind::MutableReference
-                    | TypeKind::RValueReference => Some(ItemsFound {
+                    | TypeKind::RValueReference
+                    | TypeKind::ReferenceWrapper => Some(ItemsFound {
                         default_constructor: SpecialMemberFound::NotPresent,
                         destructor: SpecialMemberFound::Implicit,
                         const_copy_constructor: SpecialMemberFound::Implicit,
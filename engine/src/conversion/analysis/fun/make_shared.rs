@@ -0,0 +1,61 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use syn::parse_quote;
+
+use crate::{
+    conversion::{
+        analysis::fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+        api::{ApiName, CppVisibility, FuncToConvert, Provenance, Virtualness},
+    },
+    types::{make_ident, QualifiedName},
+};
+
+/// Make a static method which calls `std::make_shared` with the same
+/// parameters as the given constructor, as requested by `make_shared!`.
+/// It's numbered to match the constructor, e.g. `new2` gets `make_shared2`.
+pub(super) fn create_make_shared_fn(
+    self_ty: &QualifiedName,
+    constructor_rust_name: &str,
+    constructor: &FuncToConvert,
+) -> (ApiName, Box<FuncToConvert>) {
+    let suffix = constructor_rust_name
+        .strip_prefix("new")
+        .unwrap_or_default();
+    let method_name = format!("make_shared{suffix}");
+    // Mimic bindgen's naming for methods, {class}_{method}.
+    let ident = make_ident(format!("{}_{}", self_ty.get_final_item(), method_name));
+    let self_ty_path = self_ty.to_type_path();
+    let name =
+        ApiName::new_with_cpp_name(self_ty.get_namespace(), ident.clone(), Some(method_name));
+    let fun = Box::new(FuncToConvert {
+        ident,
+        doc_attrs: constructor.doc_attrs.clone(),
+        // Skip 'this'.
+        inputs: constructor.inputs.iter().skip(1).cloned().collect(),
+        output: parse_quote! { -> root::std::shared_ptr<#self_ty_path> },
+        vis: parse_quote! { pub },
+        virtualness: Virtualness::None,
+        cpp_vis: CppVisibility::Public,
+        special_member: None,
+        unused_template_param: constructor.unused_template_param,
+        references: constructor.references.clone(),
+        original_name: None,
+        self_ty: Some(self_ty.clone()),
+        synthesized_this_type: None,
+        add_to_trait: None,
+        is_deleted: constructor.is_deleted,
+        synthetic_cpp: Some((
+            CppFunctionBody::MakeShared(self_ty.clone()),
+            CppFunctionKind::Function,
+        )),
+        provenance: Provenance::SynthesizedOther,
+        variadic: constructor.variadic,
+    });
+    (name, fun)
+}
@@ -0,0 +1,123 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::{IncludeCppConfig, KeywordNaming};
+use regex::Regex;
+
+use crate::types::validate_ident_ok_for_rust;
+
+/// Gives methods more Rust-like names, according to the rules in any
+/// `method_rename!` directives, followed by conversion to snake case if
+/// `snake_case_methods!` was given. For instance, `GetSize` might become
+/// `size`.
+///
+/// Renaming may well make two methods' names collide. That's fine: the
+/// resulting name is then disambiguated just like any other overload.
+pub(crate) struct MethodRenamer {
+    rules: Vec<(Regex, String)>,
+    snake_case: bool,
+    keyword_naming: KeywordNaming,
+}
+
+impl MethodRenamer {
+    pub(crate) fn new(config: &IncludeCppConfig) -> Self {
+        Self {
+            rules: config
+                .method_renames()
+                .map(|(pattern, replacement)| {
+                    (
+                        Regex::new(pattern).expect("Regex was validated by the parser"),
+                        replacement.to_string(),
+                    )
+                })
+                .collect(),
+            snake_case: config.snake_case_methods(),
+            keyword_naming: config.keyword_naming().clone(),
+        }
+    }
+
+    /// Returns the new name for this method, or `None` if it should keep
+    /// its name. A rename which doesn't result in a usable identifier is
+    /// ignored, except that names which clash with Rust keywords are
+    /// named according to `keyword_names!`.
+    pub(crate) fn rename(&self, name: &str) -> Option<String> {
+        let mut new_name = name.to_string();
+        for (pattern, replacement) in &self.rules {
+            new_name = pattern
+                .replace(&new_name, replacement.as_str())
+                .into_owned();
+        }
+        if self.snake_case {
+            new_name = to_snake_case(&new_name);
+        }
+        if new_name == name || !is_identifier(&new_name) {
+            return None;
+        }
+        if validate_ident_ok_for_rust(&new_name).is_err() {
+            return Some(self.keyword_naming.name_for_keyword(&new_name));
+        }
+        Some(new_name)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Convert `CamelCase` or `mixedCase` to `snake_case`. A run of capitals
+/// is treated as one word, so `ToHTMLString` becomes `to_html_string`.
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::{to_snake_case, MethodRenamer};
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(to_snake_case("GetSize"), "get_size");
+        assert_eq!(to_snake_case("isEmpty"), "is_empty");
+        assert_eq!(to_snake_case("ToHTMLString"), "to_html_string");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut config = parse_quote! {
+            method_rename!("^Get(.+)$", "$1")
+            method_rename!("^Is(.+)$", "is_$1")
+            snake_case_methods!()
+        };
+        autocxx_parser::IncludeCppConfig::confirm_complete(&mut config);
+        let renamer = MethodRenamer::new(&config);
+        assert_eq!(renamer.rename("GetSize").as_deref(), Some("size"));
+        assert_eq!(renamer.rename("IsEmpty").as_deref(), Some("is_empty"));
+        assert_eq!(renamer.rename("GetType").as_deref(), Some("type_"));
+        assert_eq!(renamer.rename("size"), None);
+    }
+}
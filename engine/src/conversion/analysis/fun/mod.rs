@@ -9,6 +9,8 @@
 mod bridge_name_tracker;
 pub(crate) mod function_wrapper;
 mod implicit_constructors;
+mod make_shared;
+mod method_renamer;
 mod overload_tracker;
 mod subclass;
 
@@ -16,7 +18,9 @@ use crate::{
     conversion::{
         analysis::{
             fun::function_wrapper::{CppConversionType, CppFunctionKind},
-            type_converter::{self, add_analysis, TypeConversionContext, TypeConverter},
+            type_converter::{
+                self, add_analysis, is_long_double, TypeConversionContext, TypeConverter,
+            },
         },
         api::{
             ApiName, CastMutability, CppVisibility, DeletedOrDefaulted, FuncToConvert, NullPhase,
@@ -34,14 +38,17 @@ use crate::{
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
-use autocxx_parser::{ExternCppType, IncludeCppConfig, UnsafePolicy};
+use autocxx_parser::{
+    cpp_operator_ident, ExceptionHandling, ExternCppType, IncludeCppConfig, LongDoubleHandling,
+    OpaqueHandle, UnsafePolicy,
+};
 use function_wrapper::{CppFunction, CppFunctionBody, TypeConversionPolicy};
 use itertools::Itertools;
 use proc_macro2::Span;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
-    parse_quote, punctuated::Punctuated, token::Comma, FnArg, Ident, Pat, PatType, ReturnType,
-    Type, TypePath, TypePtr, TypeReference, Visibility,
+    parse_quote, punctuated::Punctuated, token::Comma, Expr, ExprLit, FnArg, Ident, Lit, Pat,
+    PatType, ReturnType, Type, TypePath, TypePtr, TypeReference, Visibility,
 };
 
 use crate::{
@@ -56,6 +63,8 @@ use self::{
     bridge_name_tracker::BridgeNameTracker,
     function_wrapper::RustConversionType,
     implicit_constructors::{find_constructors_present, ItemsFound},
+    make_shared::create_make_shared_fn,
+    method_renamer::MethodRenamer,
     overload_tracker::OverloadTracker,
     subclass::{
         create_subclass_constructor, create_subclass_fn_wrapper, create_subclass_function,
@@ -171,6 +180,12 @@ pub(crate) struct FnAnalysis {
     pub(crate) externally_callable: bool,
     /// Whether we need to generate a Rust-side calling function
     pub(crate) rust_wrapper_needed: bool,
+    /// Whether this `void` function was listed in `throws!`, so should
+    /// return a `Result` with any C++ exception.
+    pub(crate) throws: bool,
+    /// Whether our Rust wrapper should turn any C++ exception into a
+    /// panic, as requested by `exception_handling!(panic)`.
+    pub(crate) panics_on_exception: bool,
 }
 
 #[derive(Clone)]
@@ -183,6 +198,9 @@ pub(crate) struct ArgumentAnalysis {
     pub(crate) deps: HashSet<QualifiedName>,
     pub(crate) requires_unsafe: UnsafetyNeeded,
     pub(crate) is_placement_return_destination: bool,
+    /// Whether `return_borrows!` said that the returned reference borrows
+    /// from this parameter.
+    pub(crate) return_borrows_from: bool,
 }
 
 pub(crate) struct ReturnTypeAnalysis {
@@ -247,6 +265,9 @@ pub(crate) struct FnPhase;
 pub(crate) struct PublicConstructors {
     pub(crate) move_constructor: bool,
     pub(crate) destructor: bool,
+    /// The copy constructor is implicitly defined, so it's trivial if
+    /// all the fields are trivially copyable too.
+    pub(crate) implicit_copy_constructor: bool,
 }
 
 impl PublicConstructors {
@@ -254,6 +275,7 @@ impl PublicConstructors {
         Self {
             move_constructor: items_found.move_constructor.callable_any(),
             destructor: items_found.destructor.callable_any(),
+            implicit_copy_constructor: items_found.implicit_copy_constructor_needed(),
         }
     }
 }
@@ -279,6 +301,9 @@ pub(crate) struct FnAnalyzer<'a> {
     type_converter: TypeConverter<'a>,
     bridge_name_tracker: BridgeNameTracker,
     pod_safe_types: HashSet<QualifiedName>,
+    large_pod_types: HashSet<QualifiedName>,
+    opaque_handles: HashMap<QualifiedName, &'a OpaqueHandle>,
+    destructurable_types: HashMap<QualifiedName, Vec<(Ident, Type)>>,
     moveit_safe_types: HashSet<QualifiedName>,
     config: &'a IncludeCppConfig,
     overload_trackers_by_mod: HashMap<Namespace, OverloadTracker>,
@@ -287,7 +312,12 @@ pub(crate) struct FnAnalyzer<'a> {
     generic_types: HashSet<QualifiedName>,
     types_in_anonymous_namespace: HashSet<QualifiedName>,
     existing_superclass_trait_api_names: HashSet<QualifiedName>,
+    const_overloaded_methods: HashSet<ConstOverloadKey>,
+    method_renamer: MethodRenamer,
     force_wrapper_generation: bool,
+    /// The parameter types of each explicit constructor, by C++ type name,
+    /// spelled as `block_constructor!` expects.
+    constructor_params: HashMap<String, HashSet<String>>,
 }
 
 impl<'a> FnAnalyzer<'a> {
@@ -296,7 +326,7 @@ impl<'a> FnAnalyzer<'a> {
         unsafe_policy: &'a UnsafePolicy,
         config: &'a IncludeCppConfig,
         force_wrapper_generation: bool,
-    ) -> ApiVec<FnPrePhase2> {
+    ) -> Result<ApiVec<FnPrePhase2>, ConvertErrorFromCpp> {
         let mut me = Self {
             unsafe_policy,
             extra_apis: ApiVec::new(),
@@ -305,14 +335,22 @@ impl<'a> FnAnalyzer<'a> {
             config,
             overload_trackers_by_mod: HashMap::new(),
             pod_safe_types: Self::build_pod_safe_type_set(&apis),
+            large_pod_types: Self::build_large_pod_type_set(&apis, config),
+            opaque_handles: Self::build_opaque_handle_map(&apis, config),
+            destructurable_types: Self::build_destructurable_type_map(&apis, config),
             moveit_safe_types: Self::build_correctly_sized_type_set(&apis),
             subclasses_by_superclass: subclass::subclasses_by_superclass(&apis),
             nested_type_name_map: Self::build_nested_type_map(&apis),
             generic_types: Self::build_generic_type_set(&apis),
             existing_superclass_trait_api_names: HashSet::new(),
             types_in_anonymous_namespace: Self::build_types_in_anonymous_namespace(&apis),
+            const_overloaded_methods: Self::build_const_overloaded_method_set(&apis, config),
+            method_renamer: MethodRenamer::new(config),
             force_wrapper_generation,
+            constructor_params: HashMap::new(),
         };
+        me.bridge_name_tracker
+            .reserve_names(config.get_manual_bridge_names().map(|id| id.to_string()));
         let mut results = ApiVec::new();
         convert_apis(
             apis,
@@ -322,10 +360,29 @@ impl<'a> FnAnalyzer<'a> {
             Api::enum_unchanged,
             Api::typedef_unchanged,
         );
+        if let Some((ty, params)) = config.find_unmatched_blocked_constructor(|ty| {
+            me.constructor_params.get(ty).into_iter().flatten()
+        }) {
+            let found = me
+                .constructor_params
+                .get(ty)
+                .map(|found| {
+                    found
+                        .iter()
+                        .map(|params| format!("\"{params}\""))
+                        .join(", ")
+                })
+                .unwrap_or_else(|| "none".into());
+            return Err(ConvertErrorFromCpp::BlockedConstructorNotFound(
+                ty.clone(),
+                params.clone(),
+                found,
+            ));
+        }
         let mut results = me.add_constructors_present(results);
         me.add_subclass_constructors(&mut results);
         results.extend(me.extra_apis.into_iter().map(add_analysis));
-        results
+        Ok(results)
     }
 
     fn build_pod_safe_type_set(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
@@ -359,6 +416,98 @@ impl<'a> FnAnalyzer<'a> {
             .collect()
     }
 
+    /// Return the set of POD types which are big enough that the user wants
+    /// them returned in a `UniquePtr` rather than by value. bindgen tells us
+    /// the complete size of each struct, including any nested structs.
+    fn build_large_pod_type_set(
+        apis: &ApiVec<PodPhase>,
+        config: &IncludeCppConfig,
+    ) -> HashSet<QualifiedName> {
+        let threshold = match config.box_large_pod() {
+            Some(threshold) => threshold,
+            None => return HashSet::new(),
+        };
+        apis.iter()
+            .filter_map(|api| match api {
+                Api::Struct {
+                    name,
+                    details,
+                    analysis:
+                        PodAnalysis {
+                            kind: TypeKind::Pod,
+                            ..
+                        },
+                } if details
+                    .layout
+                    .as_ref()
+                    .is_some_and(|layout| layout.size > threshold) =>
+                {
+                    Some(name.name.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forward-declared types for which the user asked for an
+    /// `opaque_handle!`. Complete types are left alone, since we can
+    /// do better for those.
+    fn build_opaque_handle_map(
+        apis: &ApiVec<PodPhase>,
+        config: &'a IncludeCppConfig,
+    ) -> HashMap<QualifiedName, &'a OpaqueHandle> {
+        apis.iter()
+            .filter_map(|api| match api {
+                Api::ForwardDeclaration { name, .. } => config
+                    .get_opaque_handle(&name.name.to_cpp_name())
+                    .map(|handle| (name.name.clone(), handle)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The fields of each POD struct named in `destructure_return!`,
+    /// so long as they're all plain named fields which we can move into
+    /// a tuple. bindgen's padding, bitfields and the like rule a struct
+    /// out.
+    fn build_destructurable_type_map(
+        apis: &ApiVec<PodPhase>,
+        config: &IncludeCppConfig,
+    ) -> HashMap<QualifiedName, Vec<(Ident, Type)>> {
+        apis.iter()
+            .filter_map(|api| match api {
+                Api::Struct {
+                    name,
+                    details,
+                    analysis:
+                        PodAnalysis {
+                            kind: TypeKind::Pod,
+                            is_generic: false,
+                            field_info,
+                            ..
+                        },
+                } if config.is_destructured_return_type(&name.name.to_cpp_name())
+                    && details.item.fields.len() == field_info.len() =>
+                {
+                    details
+                        .item
+                        .fields
+                        .iter()
+                        .zip(field_info.iter())
+                        .map(|(field, info)| match &field.ident {
+                            Some(id) if !id.to_string().starts_with('_') => {
+                                Some((id.clone(), info.ty.clone()))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .map(|fields| (name.name.clone(), fields))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Return the set of 'moveit safe' types. That must include only types where
     /// the size is known to be correct.
     fn build_correctly_sized_type_set(apis: &ApiVec<PodPhase>) -> HashSet<QualifiedName> {
@@ -410,6 +559,33 @@ impl<'a> FnAnalyzer<'a> {
             .collect()
     }
 
+    /// Returns the methods which have both `const` and non-`const`
+    /// overloads with the same parameters, if we've been asked to name
+    /// them differently.
+    fn build_const_overloaded_method_set(
+        apis: &ApiVec<PodPhase>,
+        config: &IncludeCppConfig,
+    ) -> HashSet<ConstOverloadKey> {
+        if !config.mut_suffix_overloads() {
+            return HashSet::new();
+        }
+        let mut mutabilities: HashMap<ConstOverloadKey, HashSet<bool>> = HashMap::new();
+        for api in apis.iter() {
+            if let Api::Function { name, fun, .. } = api {
+                if let (Virtualness::None, Some((key, is_mut))) =
+                    (&fun.virtualness, const_overload_key(name, fun))
+                {
+                    mutabilities.entry(key).or_default().insert(is_mut);
+                }
+            }
+        }
+        mutabilities
+            .into_iter()
+            .filter(|(_, mutabilities)| mutabilities.len() == 2)
+            .map(|(key, _)| key)
+            .collect()
+    }
+
     /// Builds a mapping from a qualified type name to the last 'nest'
     /// of its name, if it has multiple elements.
     fn build_nested_type_map(apis: &ApiVec<PodPhase>) -> HashMap<QualifiedName, String> {
@@ -671,6 +847,25 @@ impl<'a> FnAnalyzer<'a> {
             }
         }
 
+        // Consider whether we need to accompany a constructor with make_shared.
+        if let FnKind::Method {
+            impl_for,
+            method_kind: MethodKind::Constructor { .. },
+        } = &analysis.kind
+        {
+            if analysis.ignore_reason.is_ok() && self.config.is_make_shared(&impl_for.to_cpp_name())
+            {
+                let (make_shared_name, make_shared_fun) =
+                    create_make_shared_fn(impl_for, &analysis.rust_name, &fun);
+                self.analyze_and_add(
+                    make_shared_name,
+                    make_shared_fun,
+                    &mut results,
+                    TypeConversionSophistication::Regular,
+                );
+            }
+        }
+
         results.push(Api::Function {
             fun,
             analysis,
@@ -730,11 +925,39 @@ impl<'a> FnAnalyzer<'a> {
         let initial_rust_name = fun.ident.to_string();
         let diagnostic_display_name = cpp_name.as_ref().unwrap_or(&initial_rust_name);
 
+        // The user may have told us that a method changes its receiver
+        // despite being const in C++ (e.g. via `mutable` members), or that
+        // a non-const method is safe to call on a shared object. Pretend
+        // bindgen gave us the receiver they want; the C++ wrapper takes care
+        // of the difference.
+        let receiver_mutability_override = match (&fun.self_ty, &fun.virtualness) {
+            (Some(self_ty), Virtualness::None) => self
+                .config
+                .get_receiver_mutability_override(&format!(
+                    "{}::{}",
+                    self_ty.to_cpp_name(),
+                    diagnostic_display_name
+                ))
+                .filter(|mutable| *mutable || !self.config.unsafe_policy.requires_cpprefs()),
+            _ => None,
+        };
+        let overridden_inputs = receiver_mutability_override
+            .and_then(|mutable| with_receiver_mutability(&fun.inputs, mutable));
+        let receiver_overridden = overridden_inputs.is_some();
+
+        // Trailing parameters of any allocator type listed in `allocator!`
+        // are supplied by the C++ wrapper, so we don't analyze them at all.
+        let supplied_allocators = match fun.virtualness {
+            Virtualness::None => trailing_allocators(self.config, &fun.inputs, &fun.references),
+            _ => Vec::new(),
+        };
+        let inputs = overridden_inputs.as_ref().unwrap_or(&fun.inputs);
+
         // Now let's analyze all the parameters.
         // See if any have annotations which our fork of bindgen has craftily inserted...
-        let (param_details, bads): (Vec<_>, Vec<_>) = fun
-            .inputs
+        let (param_details, bads): (Vec<_>, Vec<_>) = inputs
             .iter()
+            .take(inputs.len() - supplied_allocators.len())
             .map(|i| {
                 self.convert_fn_arg(
                     i,
@@ -771,8 +994,9 @@ impl<'a> FnAnalyzer<'a> {
         // Work out naming, part one.
         // bindgen may have mangled the name either because it's invalid Rust
         // syntax (e.g. a keyword like 'async') or it's an overload.
-        // If the former, we respect that mangling. If the latter, we don't,
-        // because we'll add our own overload counting mangling later.
+        // If the former, we name it according to `keyword_names!` (by
+        // default, just as bindgen did). If the latter, we ignore bindgen's
+        // mangling, because we'll add our own overload counting mangling later.
         // Cases:
         //   function, IRN=foo,    CN=<none>                    output: foo    case 1
         //   function, IRN=move_,  CN=move   (keyword problem)  output: move_  case 2
@@ -783,10 +1007,10 @@ impl<'a> FnAnalyzer<'a> {
         let ideal_rust_name = match &cpp_name {
             None => initial_rust_name, // case 1
             Some(cpp_name) => {
-                if initial_rust_name.ends_with('_') {
-                    initial_rust_name // case 2
-                } else if validate_ident_ok_for_rust(cpp_name).is_err() {
-                    format!("{cpp_name}_") // case 5
+                if validate_ident_ok_for_rust(cpp_name).is_err() {
+                    self.config.keyword_naming().name_for_keyword(cpp_name) // cases 2, 5
+                } else if initial_rust_name.ends_with('_') {
+                    initial_rust_name
                 } else {
                     cpp_name.to_string() // cases 3, 4, 6
                 }
@@ -963,6 +1187,23 @@ impl<'a> FnAnalyzer<'a> {
                         Virtualness::PureVirtual => MethodKind::PureVirtual(receiver_mutability),
                     }
                 };
+                // The user may have asked for more Rust-like method names.
+                let rust_name = match method_kind {
+                    MethodKind::Normal(_) | MethodKind::Static => {
+                        self.method_renamer.rename(&rust_name).unwrap_or(rust_name)
+                    }
+                    _ => rust_name,
+                };
+                // The non-const half of a const/non-const pair may be
+                // named foo_mut rather than disambiguated by number.
+                let rust_name = match (&method_kind, const_overload_key(&name, fun)) {
+                    (MethodKind::Normal(_), Some((key, true)))
+                        if self.const_overloaded_methods.contains(&key) =>
+                    {
+                        format!("{rust_name}_mut")
+                    }
+                    _ => rust_name,
+                };
                 // Disambiguate overloads.
                 let rust_name = predetermined_rust_name
                     .unwrap_or_else(|| self.get_overload_name(ns, type_ident, rust_name));
@@ -1131,10 +1372,13 @@ impl<'a> FnAnalyzer<'a> {
         } else if matches!(
             fun.special_member,
             Some(SpecialMemberKind::AssignmentOperator)
-        ) {
+        ) && !matches!(fun.synthetic_cpp, Some((CppFunctionBody::Operator(_), _)))
+        {
             // Be careful with the order of this if-else tree. Anything above here means we won't
             // treat it as an assignment operator, but anything below we still consider when
             // deciding which other C++ special member functions are implicitly defined.
+            // Move-assignment operators have already been turned into regular
+            // operator methods, so don't reach here.
             set_ignore_reason(ConvertErrorFromCpp::AssignmentOperator)
         } else if fun.references.rvalue_ref_return {
             set_ignore_reason(ConvertErrorFromCpp::RValueReturn)
@@ -1175,13 +1419,19 @@ impl<'a> FnAnalyzer<'a> {
         // The name we use within the cxx::bridge mod may be different
         // from both the C++ name and the Rust name, because it's a flat
         // namespace so we might need to prepend some stuff to make it unique.
+        // cxx doesn't accept raw identifiers, so for a Rust name like
+        // `r#move` we use `move_` within the bridge.
+        let bridge_base_name = match rust_name.strip_prefix("r#") {
+            Some(keyword) => format!("{keyword}_"),
+            None => rust_name.clone(),
+        };
         let cxxbridge_name = self.get_cxx_bridge_name(
             match kind {
                 FnKind::Method { ref impl_for, .. } => Some(impl_for.get_final_item()),
                 FnKind::Function => None,
                 FnKind::TraitMethod { ref impl_for, .. } => Some(impl_for.get_final_item()),
             },
-            &rust_name,
+            &bridge_base_name,
             ns,
         );
         if cxxbridge_name != rust_name && cpp_name.is_none() {
@@ -1192,7 +1442,14 @@ impl<'a> FnAnalyzer<'a> {
         // Analyze the return type, just as we previously did for the
         // parameters.
         let mut return_analysis = self
-            .convert_return_type(&fun.output, ns, &fun.references, sophistication)
+            .convert_return_type(
+                &fun.output,
+                ns,
+                &fun.references,
+                sophistication,
+                self.config
+                    .is_const_smart_ptr_return(cpp_name.as_ref().unwrap_or(&rust_name)),
+            )
             .unwrap_or_else(|err| {
                 set_ignore_reason(err);
                 ReturnTypeAnalysis::default()
@@ -1200,6 +1457,403 @@ impl<'a> FnAnalyzer<'a> {
         let mut deps = params_deps;
         deps.extend(return_analysis.deps.drain(..));
 
+        // If the user asked for it, let narrow integer parameters accept a
+        // wider Rust type, checking the bounds in the Rust wrapper. That wrapper
+        // returns a Result, so we can't do this where the return value is
+        // constructed in place.
+        let is_plain_call = matches!(
+            kind,
+            FnKind::Function
+                | FnKind::Method {
+                    method_kind: MethodKind::Normal(..) | MethodKind::Static,
+                    ..
+                }
+        );
+        let checked_narrowing_eligible =
+            is_plain_call && return_analysis.placement_param_needed.is_none();
+        if checked_narrowing_eligible
+            && self
+                .config
+                .is_checked_narrowing(cpp_name.as_ref().unwrap_or(&rust_name))
+        {
+            for pd in param_details.iter_mut() {
+                if pd.self_type.is_none()
+                    && pd.conversion.is_narrow_integer()
+                    && !pd.conversion.rust_work_needed()
+                {
+                    pd.conversion.rust_conversion = RustConversionType::FromWiderIntegerChecked;
+                }
+            }
+        }
+
+        // Functions which the user told us may throw return a Result, which
+        // cxx fills in with any exception. We don't yet do this for functions
+        // which return a value, since that may need converting on the way.
+        let throws = self
+            .config
+            .is_throwing(cpp_name.as_ref().unwrap_or(&rust_name));
+        if throws
+            && (!is_plain_call
+                || !matches!(return_analysis.rt, ReturnType::Default)
+                || return_analysis.conversion.is_some()
+                || matches!(fun.synthetic_cpp, Some((CppFunctionBody::Operator(_), _))))
+        {
+            set_ignore_reason(ConvertErrorFromCpp::ThrowingFunctionNotVoid);
+        }
+        // Otherwise, cxx can still catch exceptions for us if the user would
+        // rather panic than abort. Our Rust wrapper then does the panicking.
+        let panics_on_exception =
+            !throws && self.config.exception_handling() == ExceptionHandling::Panic;
+
+        // Integer parameters which the user told us are really booleans
+        // (e.g. a `BOOL` typedef of `int`) accept a Rust `bool`. The C++
+        // wrapper takes a `bool` and lets C++ convert it to the integer.
+        let effective_name = cpp_name.as_ref().unwrap_or(&rust_name);
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && pd.conversion.is_integer()
+                    && self.config.is_bool_param(effective_name, &pp.ident)
+                {
+                    pd.conversion = TypeConversionPolicy::new_bool_to_integer();
+                }
+            }
+        }
+
+        // Likewise, `const wchar_t*` parameters which the user told us are
+        // strings accept anything which we can turn into a null-terminated
+        // wide string. bindgen represents `wchar_t` as a plain integer of
+        // the right size, so we can't spot these without help.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && is_const_pointer_to_wide_char(pd.conversion.cxxbridge_type())
+                    && self.config.is_wide_string_param(effective_name, &pp.ident)
+                {
+                    pd.conversion.rust_conversion = RustConversionType::FromWideStrToPtr;
+                    pd.requires_unsafe = UnsafetyNeeded::JustBridge;
+                }
+            }
+        }
+
+        // Pointer parameters which the user told us point to a fixed number
+        // of elements (typically because they were declared as arrays, which
+        // decay to pointers) accept a reference to a Rust array of that size.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && matches!(pd.conversion.cxxbridge_type(), Type::Ptr(_))
+                {
+                    if let Some(len) = self.config.get_array_param_len(effective_name, &pp.ident) {
+                        pd.conversion.rust_conversion =
+                            RustConversionType::FromArrayRefToPtr(vec![len]);
+                        pd.requires_unsafe = UnsafetyNeeded::JustBridge;
+                    }
+                }
+            }
+        }
+
+        // Arrays of pointers which the user told us about, such as
+        // `const char* const* argv`, accept a slice of references (or of
+        // `CStr`s) from which the wrapper builds the array. We can't tell
+        // how many elements C++ will read, so the function stays unsafe.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && is_const_pointer_to_const_pointer(pd.conversion.cxxbridge_type())
+                    && self.config.is_ptr_array_param(effective_name, &pp.ident)
+                {
+                    pd.conversion.rust_conversion = RustConversionType::FromRefSliceToPtrArray;
+                }
+            }
+        }
+
+        // bindgen drops `restrict`, so the user has to tell us about such
+        // pointer parameters. Each accepts a slice instead: Rust's borrow
+        // checker then makes sure that a mutable one doesn't alias anything
+        // else passed to the function. We can't tell how many elements the
+        // function will access, so it remains unsafe.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && matches!(pd.conversion.cxxbridge_type(), Type::Ptr(_))
+                    && self.config.is_restrict_param(effective_name, &pp.ident)
+                {
+                    pd.conversion.rust_conversion = RustConversionType::FromRestrictSliceToPtr;
+                }
+            }
+        }
+
+        // Pointer parameters which the user told us the function merely
+        // passes along, such as user data for a callback, can't be
+        // dereferenced on our account, so they needn't make the function
+        // unsafe. They remain raw pointers, which are only unsafe to use.
+        let mut opaque_ptr_params_relaxed = false;
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                    && matches!(pd.requires_unsafe, UnsafetyNeeded::Always)
+                    && matches!(pd.conversion.cxxbridge_type(), Type::Ptr(_))
+                    && self.config.is_opaque_ptr_param(effective_name, &pp.ident)
+                {
+                    pd.requires_unsafe = UnsafetyNeeded::JustBridge;
+                    opaque_ptr_params_relaxed = true;
+                }
+            }
+        }
+
+        // Non-null pointer parameters became references in the cxx::bridge,
+        // but the C++ function still wants a pointer. Any other directive
+        // for the same parameter would want it to remain a pointer.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if pd.self_type.is_some()
+                    || !matches!(
+                        fun.references.param_treatment(&pp.ident),
+                        PointerTreatment::Pointer
+                    )
+                    || !self.config.is_nonnull_param(effective_name, &pp.ident)
+                {
+                    continue;
+                }
+                if self.config.is_restrict_param(effective_name, &pp.ident)
+                    || self.config.is_ptr_array_param(effective_name, &pp.ident)
+                    || self.config.is_wide_string_param(effective_name, &pp.ident)
+                    || self
+                        .config
+                        .get_array_param_len(effective_name, &pp.ident)
+                        .is_some()
+                {
+                    set_ignore_reason(ConvertErrorFromCpp::ConflictingNonnullParam(
+                        pp.ident.to_string(),
+                    ));
+                } else if !pd.conversion.cpp_work_needed()
+                    && !matches!(pd.conversion.cxxbridge_type(), Type::Ptr(_))
+                {
+                    pd.conversion.cpp_conversion = CppConversionType::FromReferenceToPointer;
+                }
+            }
+        }
+
+        // bindgen doesn't tell us whether a span's elements are const, so
+        // we pass a shared slice unless the user told us otherwise.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if matches!(
+                    pd.conversion.cpp_conversion,
+                    CppConversionType::FromSliceToSpan
+                ) && self.config.is_mut_span_param(effective_name, &pp.ident)
+                {
+                    pd.conversion.make_slice_mutable();
+                    pd.is_mutable_reference = true;
+                }
+            }
+        }
+
+        // `std::vector` parameters which the user asked for accept a Rust
+        // `Vec`, whose elements the C++ wrapper copies into a new vector.
+        // That's only possible for elements which are the same in both
+        // languages.
+        for pd in param_details.iter_mut() {
+            if let syn::Pat::Ident(pp) = &pd.name {
+                if is_plain_call
+                    && pd.self_type.is_none()
+                    && matches!(
+                        pd.conversion.rust_conversion,
+                        RustConversionType::FromValueParamToPtr
+                            | RustConversionType::FromRValueParamToPtr
+                    )
+                    && self.config.is_vec_param(effective_name, &pp.ident)
+                {
+                    if let Some(elem) = vector_element(pd.conversion.cxxbridge_type()) {
+                        match &elem {
+                            Type::Path(typ)
+                                if !self
+                                    .pod_safe_types
+                                    .contains(&QualifiedName::from_type_path(typ)) =>
+                            {
+                                set_ignore_reason(ConvertErrorFromCpp::InvalidTypeForVecParam(
+                                    QualifiedName::from_type_path(typ),
+                                ));
+                            }
+                            _ => {
+                                pd.conversion = TypeConversionPolicy::new_vec_to_vector(elem);
+                                pd.requires_unsafe = UnsafetyNeeded::None;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Pointers to incomplete types for which the user asked for an
+        // opaque_handle! are passed and returned as that handle. An owning
+        // handle guarantees that the object is still alive, so functions
+        // taking one needn't be unsafe; a copyable handle can't promise that.
+        // The function which frees the object keeps its raw pointer, or it
+        // could be called on a handle which will later free it again. Only
+        // the functions which the user told us hand over a new object return
+        // an owning handle: others might return a borrowed pointer, which
+        // an owning handle would free.
+        let qualified_cpp_name = QualifiedName::new(ns, make_ident(effective_name)).to_cpp_name();
+        let is_destroyer = self.config.is_opaque_handle_destroyer(&qualified_cpp_name);
+        if is_plain_call && !is_destroyer {
+            for pd in param_details.iter_mut() {
+                if pd.self_type.is_none()
+                    && !pd.conversion.cpp_work_needed()
+                    && !pd.conversion.rust_work_needed()
+                {
+                    if let Some(handle) = self.get_opaque_handle(pd.conversion.cxxbridge_type()) {
+                        pd.conversion.rust_conversion =
+                            RustConversionType::FromHandleToPtr(handle.handle.clone());
+                        if handle.destroy.is_some() {
+                            pd.requires_unsafe = UnsafetyNeeded::JustBridge;
+                        }
+                    }
+                }
+            }
+            if let Some(conversion) = return_analysis.conversion.as_mut() {
+                if !conversion.cpp_work_needed()
+                    && !conversion.rust_work_needed()
+                    && matches!(
+                        conversion.cxxbridge_type(),
+                        Type::Ptr(TypePtr {
+                            mutability: Some(_),
+                            ..
+                        })
+                    )
+                {
+                    if let Some(handle) = self
+                        .get_opaque_handle(conversion.cxxbridge_type())
+                        .filter(|handle| {
+                            handle.destroy.is_none()
+                                || handle.constructors.contains(&qualified_cpp_name)
+                        })
+                    {
+                        conversion.rust_conversion =
+                            RustConversionType::FromPtrToHandle(handle.handle.clone());
+                    }
+                }
+            }
+        }
+
+        // A free function which method_of! turned into a method still
+        // expects its receiver as a pointer, not the reference which we'd
+        // pass to a real method.
+        if matches!(
+            fun.synthetic_cpp,
+            Some((CppFunctionBody::FunctionCall(..), _))
+        ) {
+            for pd in param_details.iter_mut().filter(|pd| pd.self_type.is_some()) {
+                pd.conversion.cpp_conversion =
+                    if matches!(pd.conversion.cxxbridge_type(), Type::Ptr(_)) {
+                        CppConversionType::None
+                    } else {
+                        CppConversionType::FromReferenceToPointer
+                    };
+            }
+        }
+
+        // The address of a thread_local variable is only meaningful on
+        // the thread which asked for it.
+        if matches!(
+            fun.synthetic_cpp,
+            Some((CppFunctionBody::AddressOfThreadLocal(..), _))
+        ) {
+            if let Some(conversion) = return_analysis.conversion.as_mut() {
+                conversion.rust_conversion = RustConversionType::FromPtrToThreadLocal;
+            }
+        }
+
+        // The element which an iterator_lookup! finds is only there if
+        // the iterator wasn't `end()`.
+        if matches!(
+            fun.synthetic_cpp,
+            Some((CppFunctionBody::IteratorLookup(..), _))
+        ) {
+            if let Some(conversion) = return_analysis.conversion.as_mut() {
+                conversion.rust_conversion = RustConversionType::FromNullablePtrToRef;
+            }
+        }
+
+        // A struct which only bundles several return values together can
+        // become a tuple. The struct is still generated as usual, since
+        // other APIs may use it as a type in its own right.
+        if let Some(struct_name) = self.config.get_destructured_return(effective_name) {
+            let fields = match return_analysis.conversion.as_ref() {
+                Some(conversion)
+                    if is_plain_call
+                        && matches!(conversion.cpp_conversion, CppConversionType::None)
+                        && !conversion.rust_work_needed() =>
+                {
+                    match conversion.cxxbridge_type() {
+                        Type::Path(typ) => {
+                            let tn = QualifiedName::from_type_path(typ);
+                            if tn.to_cpp_name() == struct_name {
+                                self.destructurable_types.get(&tn).cloned()
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match (fields, return_analysis.conversion.as_mut()) {
+                (Some(fields), Some(conversion)) => {
+                    conversion.rust_conversion = RustConversionType::FromStructToTuple(fields)
+                }
+                _ => set_ignore_reason(ConvertErrorFromCpp::CannotDestructureReturn(struct_name)),
+            }
+        }
+
+        // A non-const method which we're exposing with a shared receiver
+        // can only be called once we've cast away the constness.
+        if receiver_overridden && receiver_mutability_override == Some(false) {
+            for pd in param_details.iter_mut().filter(|pd| pd.self_type.is_some()) {
+                pd.conversion.cpp_conversion = CppConversionType::FromConstReferenceToMutable;
+            }
+        }
+
+        // The user may know better than the elision rules which parameters
+        // a returned reference borrows from.
+        if let Some(borrowed_params) = self.config.get_return_borrows(effective_name) {
+            if !return_analysis.was_reference {
+                set_ignore_reason(ConvertErrorFromCpp::ReturnBorrowsWithoutReferenceReturn);
+            }
+            for borrowed_param in borrowed_params {
+                let pd = param_details.iter_mut().find(|pd| match &pd.name {
+                    _ if borrowed_param == "self" => pd.self_type.is_some(),
+                    syn::Pat::Ident(pp) => &pp.ident == borrowed_param,
+                    _ => false,
+                });
+                match pd {
+                    Some(pd) if pd.has_lifetime => pd.return_borrows_from = true,
+                    _ => set_ignore_reason(ConvertErrorFromCpp::ReturnBorrowsUnknownParam(
+                        borrowed_param.to_string(),
+                    )),
+                }
+            }
+        }
+
         // Sometimes, the return type will actually be a value type
         // for which we instead want to _pass_ a pointer into which the value
         // can be constructed. Handle that case here.
@@ -1208,14 +1862,48 @@ impl<'a> FnAnalyzer<'a> {
             params.push(extra_param);
         }
 
-        let requires_unsafe = self.should_be_unsafe(&param_details, &kind);
+        let mut requires_unsafe = self.should_be_unsafe(&param_details, &kind);
+
+        // The user may know better than our policy whether a particular
+        // function is safe to call. A function which is made safe despite
+        // its raw pointer parameters still has an unsafe cxx::bridge entry,
+        // so it needs a Rust wrapper to vouch for it.
+        let mut unsafety_relaxed =
+            opaque_ptr_params_relaxed && matches!(requires_unsafe, UnsafetyNeeded::JustBridge);
+        if !matches!(kind, FnKind::TraitMethod { .. }) {
+            match self.config.get_unsafety_override(effective_name) {
+                Some(true) => requires_unsafe = UnsafetyNeeded::Always,
+                Some(false) if matches!(requires_unsafe, UnsafetyNeeded::Always) => {
+                    requires_unsafe = UnsafetyNeeded::JustBridge;
+                    unsafety_relaxed = true;
+                }
+                _ => {}
+            }
+        }
 
         // The following sections reject some types of function because of the arrangement
         // of Rust references. We could lift these restrictions when/if we switch to using
-        // CppRef to represent C++ references.
-        if return_analysis.was_reference {
+        // CppRef to represent C++ references. They don't apply if the user told us
+        // using return_borrows! which parameters the return value borrows from,
+        // since we'll give all of those the same lifetime as the output.
+        let borrowed_params = param_details
+            .iter()
+            .filter(|pd| pd.return_borrows_from)
+            .collect_vec();
+        if !borrowed_params.is_empty() {
+            if return_analysis.was_mutable_reference
+                && borrowed_params.iter().any(|pd| !pd.is_mutable_reference)
+            {
+                set_ignore_reason(ConvertErrorFromCpp::NoMutableInputReference(
+                    rust_name.clone(),
+                ));
+            }
+        } else if return_analysis.was_reference {
             // cxx only allows functions to return a reference if they take exactly
-            // one reference as a parameter. Let's see.
+            // one reference as a parameter. Let's see. A method's receiver
+            // counts, so a const method whose only reference is `this` returns
+            // something borrowed from it. If there are other references, the
+            // output might borrow from any of them, so we don't guess.
             let num_input_references = param_details.iter().filter(|pd| pd.has_lifetime).count();
             if num_input_references == 0 {
                 set_ignore_reason(ConvertErrorFromCpp::NoInputReference(rust_name.clone()));
@@ -1226,7 +1914,7 @@ impl<'a> FnAnalyzer<'a> {
                 ));
             }
         }
-        if return_analysis.was_mutable_reference {
+        if return_analysis.was_mutable_reference && borrowed_params.is_empty() {
             // This one's a bit more subtle. We can't have:
             //    fn foo(thing: &Thing) -> &mut OtherThing
             // because Rust doesn't allow it.
@@ -1293,7 +1981,13 @@ impl<'a> FnAnalyzer<'a> {
             _ if ret_type_conversion_needed => true,
             _ if cpp_name_incompatible_with_cxx => true,
             _ if fun.synthetic_cpp.is_some() => true,
+            // cxx would otherwise refer to the method with the wrong constness.
+            _ if receiver_overridden => true,
+            _ if !supplied_allocators.is_empty() => true,
             _ if self.force_wrapper_generation => true,
+            // Turning exceptions into panics happens in a Rust wrapper,
+            // which for methods in turn needs a distinct cxx::bridge name.
+            _ if panics_on_exception => true,
             _ => false,
         };
 
@@ -1389,6 +2083,7 @@ impl<'a> FnAnalyzer<'a> {
                     .unwrap_or_else(|| cxxbridge_name.to_string()),
                 return_conversion: ret_type_conversion.clone(),
                 argument_conversion: param_details.iter().map(|d| d.conversion.clone()).collect(),
+                trailing_args: supplied_allocators,
                 kind: cpp_function_kind,
                 pass_obs_field: false,
                 qualification: None,
@@ -1397,6 +2092,19 @@ impl<'a> FnAnalyzer<'a> {
             None
         };
 
+        // Instrumentation happens in a Rust wrapper. A method which cxx can
+        // call directly keeps its cxx::bridge binding, just under a
+        // distinct name, so that our wrapper can take the original one.
+        if self.config.instrumentation_macro().is_some()
+            && cpp_wrapper.is_none()
+            && matches!(kind, FnKind::Method { .. })
+        {
+            if cpp_name.is_none() {
+                cpp_name = Some(rust_name.clone());
+            }
+            cxxbridge_name = make_ident(format!("{cxxbridge_name}_autocxx_instrumented"));
+        }
+
         let vis = fun.vis.clone();
 
         let any_param_needs_rust_conversion = param_details
@@ -1405,9 +2113,18 @@ impl<'a> FnAnalyzer<'a> {
 
         let rust_wrapper_needed = match kind {
             _ if any_param_needs_rust_conversion || return_needs_rust_conversion => true,
+            _ if unsafety_relaxed => true,
             FnKind::TraitMethod { .. } => true,
             FnKind::Method { .. } => cxxbridge_name != rust_name,
+            // Operator trait implementations call a Rust function alongside them.
+            FnKind::Function
+                if matches!(fun.synthetic_cpp, Some((CppFunctionBody::Operator(_), _))) =>
+            {
+                true
+            }
             _ if self.force_wrapper_generation => true,
+            _ if self.config.instrumentation_macro().is_some() => true,
+            _ if panics_on_exception => true,
             _ => false,
         };
 
@@ -1416,6 +2133,26 @@ impl<'a> FnAnalyzer<'a> {
         validate_ident_ok_for_cxx(&cxxbridge_name.to_string())
             .map_err(ConvertErrorFromCpp::InvalidIdent)
             .unwrap_or_else(set_ignore_reason);
+        // Constructors listed in block_constructor! are dropped silently,
+        // rather than reported. We still keep them around so that they
+        // inform our decisions about implicit constructors.
+        if self.is_blocked_constructor(&kind, fun) {
+            ignore_reason = Err(ConvertErrorWithContext(
+                ConvertErrorFromCpp::BlockedConstructor,
+                None,
+            ));
+        }
+        // Likewise functions which the user has bound by hand using
+        // manual!, since both would be exposed under the same name.
+        if matches!(kind, FnKind::Function)
+            && ns.is_empty()
+            && self.config.is_manual_bridge_name(&rust_name)
+        {
+            ignore_reason = Err(ConvertErrorWithContext(
+                ConvertErrorFromCpp::ReplacedByManualEntry,
+                None,
+            ));
+        }
         let rust_name_ident = make_ident(&rust_name);
         let rust_rename_strategy = match kind {
             _ if rust_wrapper_needed => RustRenameStrategy::RenameUsingWrapperFunction,
@@ -1441,11 +2178,69 @@ impl<'a> FnAnalyzer<'a> {
             ignore_reason,
             externally_callable,
             rust_wrapper_needed,
+            throws,
+            panics_on_exception,
         };
         let name = ApiName::new_with_cpp_name(ns, cxxbridge_name, cpp_name);
         (analysis, name)
     }
 
+    /// Whether this is an explicit constructor which the user has asked us
+    /// not to generate, using `block_constructor!`. We note the parameter
+    /// types of every such constructor, so that we can report entries which
+    /// match none of them.
+    fn is_blocked_constructor(&mut self, kind: &FnKind, fun: &FuncToConvert) -> bool {
+        let impl_for = match kind {
+            FnKind::Method {
+                impl_for,
+                method_kind: MethodKind::Constructor { .. },
+            }
+            | FnKind::TraitMethod {
+                impl_for,
+                kind: TraitMethodKind::CopyConstructor | TraitMethodKind::MoveConstructor,
+                ..
+            } if matches!(fun.provenance, Provenance::Bindgen) => impl_for,
+            _ => return false,
+        };
+        // Skip 'this'.
+        let params: Result<Vec<_>, _> = fun
+            .inputs
+            .iter()
+            .skip(1)
+            .map(|arg| match arg {
+                FnArg::Typed(PatType { pat, ty, .. }) => {
+                    let cpp_type = self.type_converter.type_to_cpp(ty)?;
+                    let treatment = match pat.as_ref() {
+                        Pat::Ident(pti) => fun.references.param_treatment(&pti.ident),
+                        _ => PointerTreatment::Pointer,
+                    };
+                    // References reach us as pointers.
+                    let pointee = cpp_type.strip_suffix('*').unwrap_or(&cpp_type);
+                    Ok(match treatment {
+                        PointerTreatment::Pointer => cpp_type.clone(),
+                        PointerTreatment::Reference => format!("{pointee}&"),
+                        PointerTreatment::RValueReference => format!("{pointee}&&"),
+                    })
+                }
+                FnArg::Receiver(_) => {
+                    Err(ConvertErrorFromCpp::UnexpectedThisType(impl_for.clone()))
+                }
+            })
+            .collect();
+        let params = match params {
+            Ok(params) => params,
+            Err(_) => return false,
+        };
+        let cpp_type = impl_for.to_cpp_name();
+        let params = params.join(", ");
+        let blocked = self.config.is_blocked_constructor(&cpp_type, &params);
+        self.constructor_params
+            .entry(cpp_type)
+            .or_default()
+            .insert(params);
+        blocked
+    }
+
     fn error_context_for_method(&self, self_ty: &QualifiedName, rust_name: &str) -> ErrorContext {
         if self.is_generic_type(self_ty) {
             // A 'method' error context would end up in an
@@ -1700,6 +2495,15 @@ impl<'a> FnAnalyzer<'a> {
                         validate_ident_ok_for_cxx(&pp.ident.to_string())
                             .map_err(ConvertErrorFromCpp::InvalidIdent)?;
                         pointer_treatment = references.param_treatment(&pp.ident);
+                        // bindgen doesn't tell us about `[[gnu::nonnull]]` or
+                        // `_Nonnull`, so the user lists such pointers. They're
+                        // as good as references.
+                        if matches!(pointer_treatment, PointerTreatment::Pointer)
+                            && matches!(pt.ty.as_ref(), Type::Ptr(_))
+                            && self.config.is_nonnull_param(fn_name, &pp.ident)
+                        {
+                            pointer_treatment = PointerTreatment::Reference;
+                        }
                         syn::Pat::Ident(pp)
                     }
                     _ => old_pat,
@@ -1710,15 +2514,140 @@ impl<'a> FnAnalyzer<'a> {
                         force_rust_conversion,
                         Some(RustConversionType::FromPlacementParamToNewReturn)
                     );
-                let annotated_type = self.convert_boxed_type(pt.ty, ns, pointer_treatment)?;
-                let conversion = self.argument_conversion_details(
-                    &annotated_type,
-                    is_move_constructor,
-                    force_rust_conversion,
-                    sophistication,
-                    self_type.is_some(),
-                    is_placement_return_destination,
-                );
+                // If the user is happy to lose precision, a `long double`
+                // parameter accepts an `f64`, which the C++ wrapper converts.
+                let long_double_to_double = self.long_double_to_double(&pt.ty, sophistication);
+                if long_double_to_double {
+                    pt.ty = parse_quote! { f64 };
+                }
+                // cxx can cope with an array of pointers, such as
+                // `const char* const* argv`, but our type conversion rejects
+                // pointers to pointers. If the user told us to accept a slice
+                // for this parameter, convert the inner pointer instead.
+                let ptr_array_elem = match (&new_pat, pt.ty.as_ref()) {
+                    (syn::Pat::Ident(pp), Type::Ptr(outer))
+                        if outer.mutability.is_none()
+                            && self.config.is_ptr_array_param(fn_name, &pp.ident) =>
+                    {
+                        match outer.elem.as_ref() {
+                            Type::Ptr(inner) if inner.mutability.is_none() => Some(inner.clone()),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                };
+                // bindgen represents a reference to an array, such as
+                // `int (&arr)[8]`, as a pointer to a Rust array, which cxx
+                // would take to be a `std::array`. Instead, pass a pointer to
+                // the first element, which the C++ wrapper turns back into a
+                // reference to the array.
+                let array_ref = match (&pointer_treatment, pt.ty.as_ref()) {
+                    (PointerTreatment::Reference, Type::Ptr(ptr)) => {
+                        array_reference_dimensions(ptr)
+                    }
+                    _ => None,
+                };
+                if let Some((elem, dims)) = array_ref {
+                    if matches!(
+                        sophistication,
+                        TypeConversionSophistication::SimpleForSubclasses
+                    ) {
+                        return Err(ConvertErrorFromCpp::ArrayReferenceInSubclass);
+                    }
+                    let elem_ptr = match pt.ty.as_ref() {
+                        Type::Ptr(TypePtr {
+                            mutability: Some(_),
+                            ..
+                        }) => parse_quote! { *mut #elem },
+                        _ => parse_quote! { *const #elem },
+                    };
+                    let annotated_type =
+                        self.convert_boxed_type(elem_ptr, ns, PointerTreatment::Pointer)?;
+                    if let Type::Ptr(TypePtr { elem, .. }) = annotated_type.ty.as_ref() {
+                        if let Type::Path(elem) = elem.as_ref() {
+                            let elem = QualifiedName::from_type_path(elem);
+                            if !self.pod_safe_types.contains(&elem) {
+                                return Err(ConvertErrorFromCpp::InvalidTypeForArrayReference(
+                                    elem,
+                                ));
+                            }
+                        }
+                    }
+                    pt.pat = Box::new(new_pat.clone());
+                    pt.ty = annotated_type.ty.clone();
+                    return Ok((
+                        FnArg::Typed(pt),
+                        ArgumentAnalysis {
+                            self_type,
+                            name: new_pat,
+                            conversion: TypeConversionPolicy::new(
+                                *annotated_type.ty,
+                                CppConversionType::FromPointerToArrayReference(dims.clone()),
+                                RustConversionType::FromArrayRefToPtr(dims),
+                            ),
+                            has_lifetime: false,
+                            is_mutable_reference: false,
+                            deps: annotated_type.types_encountered,
+                            requires_unsafe: UnsafetyNeeded::JustBridge,
+                            is_placement_return_destination: false,
+                            return_borrows_from: false,
+                        },
+                    ));
+                }
+                let mut annotated_type = match ptr_array_elem {
+                    Some(inner) => {
+                        let mut annotated = self.convert_boxed_type(
+                            Box::new(Type::Ptr(inner)),
+                            ns,
+                            PointerTreatment::Pointer,
+                        )?;
+                        let inner = annotated.ty;
+                        annotated.ty = parse_quote! { *const #inner };
+                        annotated
+                    }
+                    None => self.convert_boxed_type(pt.ty, ns, pointer_treatment)?,
+                };
+                if matches!(
+                    annotated_type.kind,
+                    type_converter::TypeKind::ReferenceWrapper
+                ) {
+                    self.confirm_reference_wrapper_supported(sophistication)?;
+                    // bindgen doesn't tell us whether the target of a
+                    // `std::reference_wrapper` is const, and the callee may
+                    // mutate it, so take a mutable reference.
+                    if let Type::Reference(TypeReference { elem, .. }) = annotated_type.ty.as_ref()
+                    {
+                        annotated_type.ty = parse_quote! { ::core::pin::Pin<&mut #elem> };
+                    }
+                }
+                if let Some(elem) = slice_element(&annotated_type.ty) {
+                    // We can't yet turn a span back into a slice, as we'd need to
+                    // for calls from C++ into Rust subclasses.
+                    if matches!(
+                        sophistication,
+                        TypeConversionSophistication::SimpleForSubclasses
+                    ) {
+                        return Err(ConvertErrorFromCpp::SpanNotParameter);
+                    }
+                    if let Type::Path(elem) = elem {
+                        let elem = QualifiedName::from_type_path(elem);
+                        if !self.pod_safe_types.contains(&elem) {
+                            return Err(ConvertErrorFromCpp::InvalidTypeForSpan(elem));
+                        }
+                    }
+                }
+                let conversion = if long_double_to_double {
+                    TypeConversionPolicy::new_long_double_to_double()
+                } else {
+                    self.argument_conversion_details(
+                        &annotated_type,
+                        is_move_constructor,
+                        force_rust_conversion,
+                        sophistication,
+                        self_type.is_some(),
+                        is_placement_return_destination,
+                    )
+                };
                 let new_ty = annotated_type.ty;
                 pt.pat = Box::new(new_pat.clone());
                 pt.ty = new_ty;
@@ -1742,14 +2671,17 @@ impl<'a> FnAnalyzer<'a> {
                             annotated_type.kind,
                             type_converter::TypeKind::Reference
                                 | type_converter::TypeKind::MutableReference
+                                | type_converter::TypeKind::ReferenceWrapper
                         ),
                         is_mutable_reference: matches!(
                             annotated_type.kind,
                             type_converter::TypeKind::MutableReference
+                                | type_converter::TypeKind::ReferenceWrapper
                         ),
                         deps: annotated_type.types_encountered,
                         requires_unsafe,
                         is_placement_return_destination,
+                        return_borrows_from: false,
                     },
                 )
             }
@@ -1801,6 +2733,15 @@ impl<'a> FnAnalyzer<'a> {
                 RustConversionType::FromPlacementParamToNewReturn,
             );
         }
+        if slice_element(ty).is_some() {
+            return TypeConversionPolicy::new_slice_to_span(ty.clone());
+        }
+        if matches!(
+            annotated_type.kind,
+            type_converter::TypeKind::ReferenceWrapper
+        ) {
+            return TypeConversionPolicy::new_reference_to_std_reference_wrapper(ty.clone());
+        }
         match ty {
             Type::Path(p) => {
                 let ty = ty.clone();
@@ -1889,7 +2830,8 @@ impl<'a> FnAnalyzer<'a> {
                 self.config.unsafe_policy,
                 UnsafePolicy::ReferencesWrappedAllFunctionsSafe
             ) && !rust_conversion_forced
-                && !is_placement_return_destination =>
+                && !is_placement_return_destination
+                && !matches!(elem.as_ref(), Type::Ptr(_)) =>
             {
                 let is_mut = mutability.is_some();
                 TypeConversionPolicy::new(
@@ -1902,6 +2844,23 @@ impl<'a> FnAnalyzer<'a> {
                     RustConversionType::FromReferenceWrapperToPointer,
                 )
             }
+            Type::Reference(TypeReference {
+                elem,
+                mutability: None,
+                ..
+            }) if self.config.flexible_string_params()
+                && !self.config.exclude_utilities()
+                && !is_self
+                && !rust_conversion_forced
+                && matches!(sophistication, TypeConversionSophistication::Regular)
+                && matches!(elem.as_ref(), Type::Path(typ) if known_types().convertible_from_strs(&QualifiedName::from_type_path(typ))) =>
+            {
+                TypeConversionPolicy::new(
+                    ty.clone(),
+                    CppConversionType::None,
+                    RustConversionType::FromStrRef,
+                )
+            }
             _ => {
                 let rust_conversion = force_rust_conversion.unwrap_or(RustConversionType::None);
                 TypeConversionPolicy::new(ty.clone(), CppConversionType::None, rust_conversion)
@@ -1909,20 +2868,91 @@ impl<'a> FnAnalyzer<'a> {
         }
     }
 
+    /// Whether this is a `long double` which we should pass to and from
+    /// Rust as an `f64`. We can't do that for calls from C++ into Rust.
+    fn long_double_to_double(
+        &self,
+        ty: &Type,
+        sophistication: TypeConversionSophistication,
+    ) -> bool {
+        matches!(ty, Type::Path(typ) if is_long_double(typ))
+            && self.config.long_double_handling() == LongDoubleHandling::F64
+            && matches!(sophistication, TypeConversionSophistication::Regular)
+    }
+
+    /// If this is a pointer to a type listed in `opaque_handle!`, the
+    /// details of the handle which should wrap it.
+    fn get_opaque_handle(&self, ty: &Type) -> Option<&'a OpaqueHandle> {
+        match ty {
+            Type::Ptr(TypePtr { elem, .. }) => match elem.as_ref() {
+                Type::Path(typ) => self
+                    .opaque_handles
+                    .get(&QualifiedName::from_type_path(typ))
+                    .copied(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// We represent a `std::reference_wrapper` as a Rust reference, but
+    /// can't yet do so in every circumstance where we represent a C++
+    /// reference.
+    fn confirm_reference_wrapper_supported(
+        &self,
+        sophistication: TypeConversionSophistication,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        // We can't turn a reference back into a reference wrapper, as we'd
+        // need to for calls from C++ into Rust subclasses.
+        if matches!(
+            sophistication,
+            TypeConversionSophistication::SimpleForSubclasses
+        ) {
+            return Err(ConvertErrorFromCpp::ReferenceWrapperNotParameterOrReturn);
+        }
+        if matches!(
+            self.config.unsafe_policy,
+            UnsafePolicy::ReferencesWrappedAllFunctionsSafe
+        ) {
+            return Err(ConvertErrorFromCpp::ReferenceWrapperWithWrappedReferences);
+        }
+        Ok(())
+    }
+
     fn convert_return_type(
         &mut self,
         rt: &ReturnType,
         ns: &Namespace,
         references: &References,
         sophistication: TypeConversionSophistication,
+        const_smart_ptr_target: bool,
     ) -> Result<ReturnTypeAnalysis, ConvertErrorFromCpp> {
         Ok(match rt {
             ReturnType::Default => ReturnTypeAnalysis::default(),
+            ReturnType::Type(rarrow, boxed_type)
+                if self.long_double_to_double(boxed_type, sophistication) =>
+            {
+                ReturnTypeAnalysis {
+                    rt: parse_quote! { #rarrow f64 },
+                    conversion: Some(TypeConversionPolicy::new_long_double_to_double()),
+                    ..Default::default()
+                }
+            }
             ReturnType::Type(rarrow, boxed_type) => {
                 let annotated_type =
                     self.convert_boxed_type(boxed_type.clone(), ns, references.return_treatment())?;
                 let boxed_type = annotated_type.ty;
                 let ty: &Type = boxed_type.as_ref();
+                if slice_element(ty).is_some() {
+                    return Err(ConvertErrorFromCpp::SpanNotParameter);
+                }
+                let is_reference_wrapper = matches!(
+                    annotated_type.kind,
+                    type_converter::TypeKind::ReferenceWrapper
+                );
+                if is_reference_wrapper {
+                    self.confirm_reference_wrapper_supported(sophistication)?;
+                }
                 match ty {
                     Type::Path(p)
                         if !self
@@ -1984,19 +3014,42 @@ impl<'a> FnAnalyzer<'a> {
                             type_converter::TypeKind::MutableReference
                         );
                         let was_reference = was_mutable_reference
+                            || is_reference_wrapper
                             || matches!(annotated_type.kind, type_converter::TypeKind::Reference);
-                        let conversion = Some(
-                            if was_reference
-                                && matches!(
-                                    self.config.unsafe_policy,
-                                    UnsafePolicy::ReferencesWrappedAllFunctionsSafe
-                                )
-                            {
-                                TypeConversionPolicy::return_reference_into_wrapper(ty.clone())
-                            } else {
-                                TypeConversionPolicy::new_unconverted(ty.clone())
-                            },
-                        );
+                        let smart_ptr_kind = match ty {
+                            Type::Path(p) if const_smart_ptr_target && !was_reference => {
+                                known_types().special_cpp_name(&QualifiedName::from_type_path(p))
+                            }
+                            _ => None,
+                        };
+                        let conversion = Some(if is_reference_wrapper {
+                            TypeConversionPolicy::new_from_std_reference_wrapper(ty.clone())
+                        } else if was_reference
+                            && matches!(
+                                self.config.unsafe_policy,
+                                UnsafePolicy::ReferencesWrappedAllFunctionsSafe
+                            )
+                        {
+                            TypeConversionPolicy::return_reference_into_wrapper(ty.clone())
+                        } else if let Some(kind @ ("std::shared_ptr" | "std::weak_ptr")) =
+                            smart_ptr_kind.as_deref()
+                        {
+                            // The user told us the C++ function returns a smart
+                            // pointer to a const target, which cxx can't represent.
+                            TypeConversionPolicy::new_from_const_smart_ptr(
+                                ty.clone(),
+                                kind == "std::weak_ptr",
+                            )
+                        } else if matches!(ty, Type::Path(p)
+                                if !was_reference
+                                    && matches!(sophistication, TypeConversionSophistication::Regular)
+                                    && self.large_pod_types.contains(&QualifiedName::from_type_path(p)))
+                        {
+                            // Save copying a large POD on the stack.
+                            TypeConversionPolicy::new_to_unique_ptr(ty.clone())
+                        } else {
+                            TypeConversionPolicy::new_unconverted(ty.clone())
+                        });
                         ReturnTypeAnalysis {
                             rt: ReturnType::Type(*rarrow, boxed_type),
                             conversion,
@@ -2197,9 +3250,31 @@ impl Api<FnPhase> {
             Api::Function { analysis, .. } => match analysis.kind {
                 FnKind::Method { ref impl_for, .. } => impl_for.clone(),
                 FnKind::TraitMethod { ref impl_for, .. } => impl_for.clone(),
-                FnKind::Function => {
-                    QualifiedName::new(self.name().get_namespace(), make_ident(&analysis.rust_name))
-                }
+                // Free operators are allowlisted by their C++ name, e.g. operator+.
+                FnKind::Function => match &analysis.cpp_wrapper {
+                    Some(CppFunction {
+                        payload: CppFunctionBody::Operator(op),
+                        ..
+                    }) => QualifiedName::new(
+                        self.name().get_namespace(),
+                        make_ident(cpp_operator_ident(&format!("operator{}", op.symbol)).unwrap()),
+                    ),
+                    // Functions named after Rust keywords are allowlisted by
+                    // bindgen's name for them (e.g. move_), whatever
+                    // keyword_names! says we call them in Rust.
+                    _ => match self.cpp_name() {
+                        Some(cpp_name) if validate_ident_ok_for_rust(cpp_name).is_err() => {
+                            QualifiedName::new(
+                                self.name().get_namespace(),
+                                make_ident(format!("{cpp_name}_")),
+                            )
+                        }
+                        _ => QualifiedName::new(
+                            self.name().get_namespace(),
+                            make_ident(&analysis.rust_name),
+                        ),
+                    },
+                },
             },
             Api::RustSubclassFn { subclass, .. } => subclass.0.name.clone(),
             Api::IgnoredItem {
@@ -2236,6 +3311,10 @@ impl Api<FnPhase> {
                 },
                 ..
             } | Api::StringConstructor { .. }
+                | Api::AssertionHandler { .. }
+                | Api::ExceptionTypes { .. }
+                | Api::AbiVersionCheck { .. }
+                | Api::Enum { .. }
                 | Api::ConcreteType { .. }
                 | Api::CType { .. }
                 | Api::RustSubclassFn { .. }
@@ -2257,6 +3336,9 @@ impl Api<FnPhase> {
         match self {
             Api::Function { ref analysis, .. } => Some(analysis.cxxbridge_name.clone()),
             Api::StringConstructor { .. }
+            | Api::ExceptionTypes { .. }
+            | Api::AbiVersionCheck { .. }
+            | Api::NamespaceType { .. }
             | Api::Const { .. }
             | Api::IgnoredItem { .. }
             | Api::RustSubclassFn { .. } => None,
@@ -2342,3 +3424,182 @@ fn describe_arg(arg: &FnArg) -> String {
         },
     }
 }
+
+/// The type, C++ name and other parameter types of a method. Overloads
+/// which differ only in the constness of `this` have the same key.
+type ConstOverloadKey = (QualifiedName, String, String);
+
+/// Returns this method's [`ConstOverloadKey`], and whether its `this`
+/// pointer is mutable, or `None` if it isn't a method.
+fn const_overload_key(name: &ApiName, fun: &FuncToConvert) -> Option<(ConstOverloadKey, bool)> {
+    let self_ty = fun.self_ty.as_ref()?;
+    let mut this_is_mut = None;
+    let mut param_types = Vec::new();
+    for arg in fun.inputs.iter() {
+        if let FnArg::Typed(pt) = arg {
+            match (pt.pat.as_ref(), pt.ty.as_ref()) {
+                (Pat::Ident(pp), Type::Ptr(ptr)) if pp.ident == "this" => {
+                    this_is_mut = Some(ptr.mutability.is_some())
+                }
+                (_, ty) => param_types.push(ty.to_token_stream().to_string()),
+            }
+        }
+    }
+    Some((
+        (self_ty.clone(), name.cpp_name(), param_types.join(", ")),
+        this_is_mut?,
+    ))
+}
+
+/// Returns these parameters with the `this` pointer changed to the given
+/// mutability, or `None` if it already has that mutability.
+fn with_receiver_mutability(
+    inputs: &Punctuated<FnArg, Comma>,
+    mutable: bool,
+) -> Option<Punctuated<FnArg, Comma>> {
+    let mut inputs = inputs.clone();
+    let this_ptr = inputs.iter_mut().find_map(|arg| match arg {
+        FnArg::Typed(pt) if matches!(pt.pat.as_ref(), Pat::Ident(pp) if pp.ident == "this") => {
+            match pt.ty.as_mut() {
+                Type::Ptr(ptr) => Some(ptr),
+                _ => None,
+            }
+        }
+        _ => None,
+    })?;
+    if this_ptr.mutability.is_some() == mutable {
+        return None;
+    }
+    if mutable {
+        this_ptr.const_token = None;
+        this_ptr.mutability = Some(Default::default());
+    } else {
+        this_ptr.mutability = None;
+        this_ptr.const_token = Some(Default::default());
+    }
+    Some(inputs)
+}
+
+/// The C++ values to pass for any trailing parameters whose types were
+/// listed in `allocator!`, in order. Only parameters taken by value or by
+/// const reference qualify.
+fn trailing_allocators(
+    config: &IncludeCppConfig,
+    inputs: &Punctuated<FnArg, Comma>,
+    references: &References,
+) -> Vec<String> {
+    let mut allocators = inputs
+        .iter()
+        .rev()
+        .map_while(|arg| {
+            let pt = match arg {
+                FnArg::Typed(pt) => pt,
+                FnArg::Receiver(_) => return None,
+            };
+            let typ = match (pt.pat.as_ref(), pt.ty.as_ref()) {
+                (_, Type::Path(typ)) => typ,
+                (Pat::Ident(pp), Type::Ptr(ptr))
+                    if ptr.mutability.is_none() && references.ref_params.contains(&pp.ident) =>
+                {
+                    match ptr.elem.as_ref() {
+                        Type::Path(typ) => typ,
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            };
+            config
+                .get_allocator(&QualifiedName::from_type_path(typ).to_cpp_name())
+                .map(str::to_string)
+        })
+        .collect_vec();
+    allocators.reverse();
+    allocators
+}
+
+/// Whether this is bindgen's representation of `const T* const*`.
+fn is_const_pointer_to_const_pointer(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(TypePtr {
+            mutability: None,
+            elem,
+            ..
+        }) => matches!(
+            elem.as_ref(),
+            Type::Ptr(TypePtr {
+                mutability: None,
+                ..
+            })
+        ),
+        _ => false,
+    }
+}
+
+/// Whether this could be bindgen's representation of `const wchar_t*`,
+/// which depends on the size of `wchar_t` on the target.
+fn is_const_pointer_to_wide_char(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(TypePtr {
+            const_token: Some(_),
+            elem,
+            ..
+        }) => {
+            matches!(elem.as_ref(), Type::Path(typ) if typ.path.is_ident("u16") || typ.path.is_ident("u32"))
+        }
+        _ => false,
+    }
+}
+
+/// If this is a `CxxVector<T>`, returns `T`.
+fn vector_element(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::Path(typ) => {
+            let seg = typ.path.segments.last()?;
+            if seg.ident != "CxxVector" {
+                return None;
+            }
+            match &seg.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    syn::GenericArgument::Type(elem) => Some(elem.clone()),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If this is the `&[T]` by which we represent a `std::span<T>`, returns
+/// `T`. bindgen never gives us slices otherwise.
+/// If this pointer is bindgen's representation of a reference to an
+/// array, such as `int (&)[2][3]`, find the type of the innermost
+/// elements, and the dimensions of the array, outermost first.
+fn array_reference_dimensions(ptr: &TypePtr) -> Option<(Type, Vec<usize>)> {
+    let mut dims = Vec::new();
+    let mut ty = ptr.elem.as_ref();
+    while let Type::Array(arr) = ty {
+        match &arr.len {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(len), ..
+            }) => dims.push(len.base10_parse().ok()?),
+            _ => return None,
+        }
+        ty = arr.elem.as_ref();
+    }
+    if dims.is_empty() {
+        None
+    } else {
+        Some((ty.clone(), dims))
+    }
+}
+
+fn slice_element(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(TypeReference { elem, .. }) => match elem.as_ref() {
+            Type::Slice(slice) => Some(slice.elem.as_ref()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
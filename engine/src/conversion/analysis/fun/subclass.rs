@@ -156,6 +156,7 @@ pub(super) fn create_subclass_function(
                 original_cpp_name: name.cpp_name(),
                 return_conversion: analysis.ret_conversion.clone(),
                 argument_conversion,
+                trailing_args: Vec::new(),
                 kind,
                 pass_obs_field: true,
                 qualification: Some(cpp),
@@ -199,6 +200,7 @@ pub(super) fn create_subclass_constructor(
         wrapper_function_name,
         return_conversion: None,
         argument_conversion: args.collect(),
+        trailing_args: Vec::new(),
         kind: CppFunctionKind::SynthesizedConstructor,
         pass_obs_field: false,
         qualification: Some(cpp.clone()),
@@ -35,6 +35,17 @@ use super::{deps::HasDependencies, fun::FnPhase};
 ///    some methods from a given struct/class. In which case, we
 ///    don't care about the other parameter types passed into those
 ///    APIs either.
+///
+/// Methods are found only if their type is itself on the allowlist,
+/// so a type which is needed merely as a dependency (e.g. a parameter
+/// of an allowlisted function) doesn't drag in its own methods, nor the
+/// types they use. That keeps the output to the minimal set of APIs
+/// needed to call the allowlisted items. Cycles between types are fine
+/// since we never visit an API twice.
+///
+/// Types nested within classes may be allowlisted either by the name
+/// we give them in Rust (`Widget_State`) or by their C++ name
+/// (`Widget::State`).
 pub(crate) fn filter_apis_by_following_edges_from_allowlist(
     apis: ApiVec<FnPhase>,
     config: &IncludeCppConfig,
@@ -44,6 +55,9 @@ pub(crate) fn filter_apis_by_following_edges_from_allowlist(
         .filter(|api| {
             let tnforal = api.name_for_allowlist();
             config.is_on_allowlist(&tnforal.to_cpp_name())
+                || (&tnforal == api.name()
+                    && api.cpp_name().is_some()
+                    && config.is_on_allowlist(&api.name_info().qualified_cpp_name()))
         })
         .map(Api::name)
         .cloned()
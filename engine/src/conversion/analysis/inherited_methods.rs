@@ -0,0 +1,89 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to call methods which a type inherits from a base class that
+//! bindgen can't describe, as requested by `inherited_method!`.
+
+use autocxx_parser::{IncludeCppConfig, InheritedMethod};
+use itertools::Itertools;
+use syn::{parse_quote, punctuated::Punctuated, FnArg, ReturnType, Token, Type};
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    callable_members::{add_signature_inputs, reference_to_pointer},
+    fun::function_wrapper::CppFunctionBody,
+    pod::PodPhase,
+    ranges::make_accessor,
+};
+
+/// bindgen doesn't give us the members of template instantiations, so
+/// if a type's base class is one (e.g. `Foo : Base<int>`, including the
+/// CRTP `Foo : Base<Foo>`), we know nothing of the methods it inherits.
+/// For each one listed in `inherited_method!`, synthesize a method of the
+/// derived type which calls it. C++ resolves the call just as it would
+/// for any other caller, so this works equally well for any base.
+pub(crate) fn add_inherited_methods(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if !config.has_inherited_methods() {
+        return apis;
+    }
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let methods = match &api {
+            Api::Struct { name, .. } => config
+                .get_inherited_methods(&name.name.to_cpp_name())
+                .map(|method| make_inherited_method(&name.name, method))
+                .collect_vec(),
+            _ => Vec::new(),
+        };
+        results.push(api);
+        results.extend(methods.into_iter());
+    }
+    results
+}
+
+fn make_inherited_method(struct_name: &QualifiedName, method: &InheritedMethod) -> Api<PodPhase> {
+    let typ = struct_name.to_type_path();
+    let mut references = References {
+        ref_params: [make_ident("this")].into_iter().collect(),
+        ..Default::default()
+    };
+    let mut inputs: Punctuated<FnArg, Token![,]> = if method.mutable {
+        parse_quote! { this: *mut #typ }
+    } else {
+        parse_quote! { this: *const #typ }
+    };
+    add_signature_inputs(&method.signature, &mut inputs, &mut references);
+    let output = match &method.signature.output {
+        ReturnType::Type(arrow, ty) => match ty.as_ref() {
+            Type::Reference(r) => {
+                references.ref_return = true;
+                ReturnType::Type(*arrow, Box::new(reference_to_pointer(r)))
+            }
+            _ => method.signature.output.clone(),
+        },
+        ReturnType::Default => ReturnType::Default,
+    };
+    make_accessor(
+        struct_name,
+        &method.method,
+        inputs,
+        output,
+        references,
+        CppFunctionBody::CallMember(method.method.clone()),
+    )
+}
@@ -0,0 +1,91 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to call C++ methods which return iterators, such as `find`, and
+//! check the result against `end()`, as requested by `iterator_lookup!`.
+
+use autocxx_parser::{IncludeCppConfig, IteratorLookup};
+use itertools::Itertools;
+use syn::{parse_quote, punctuated::Punctuated, FnArg, ReturnType, Token, Type};
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    callable_members::{add_signature_inputs, reference_to_pointer},
+    fun::function_wrapper::CppFunctionBody,
+    pod::PodPhase,
+    ranges::make_named_accessor,
+};
+
+/// For each method listed in `iterator_lookup!`, synthesize a method of
+/// its struct which returns a pointer to the element which the iterator
+/// refers to, or null if it's `end()`. Our Rust wrapper turns that into
+/// an `Option` of a reference, so an iterator never reaches Rust.
+pub(crate) fn add_iterator_lookups(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if !config.has_iterator_lookups() {
+        return apis;
+    }
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let lookups = match &api {
+            Api::Struct { name, .. } => config
+                .get_iterator_lookups(&name.name.to_cpp_name())
+                .map(|lookup| make_lookup(&name.name, lookup))
+                .collect_vec(),
+            _ => Vec::new(),
+        };
+        results.push(api);
+        results.extend(lookups.into_iter());
+    }
+    results
+}
+
+fn make_lookup(struct_name: &QualifiedName, lookup: &IteratorLookup) -> Api<PodPhase> {
+    let typ = struct_name.to_type_path();
+    // We only read through the iterator, so we call the `const` overloads
+    // of the method and of `end()`.
+    let mut references = References {
+        ref_params: [make_ident("this")].into_iter().collect(),
+        ..Default::default()
+    };
+    let mut inputs: Punctuated<FnArg, Token![,]> = parse_quote! { this: *const #typ };
+    add_signature_inputs(&lookup.signature, &mut inputs, &mut references);
+    // The parser insisted on a shared reference. We return a pointer,
+    // which may be null, rather than a reference.
+    let output = match &lookup.signature.output {
+        ReturnType::Type(arrow, ty) => match ty.as_ref() {
+            Type::Reference(r) => ReturnType::Type(*arrow, Box::new(reference_to_pointer(r))),
+            _ => panic!("iterator_lookup! signature didn't return a reference"),
+        },
+        ReturnType::Default => panic!("iterator_lookup! signature didn't return a reference"),
+    };
+    // bindgen will have used {class}_{method} for the C++ method itself.
+    let ident = make_ident(format!(
+        "{}_{}_iterator_lookup",
+        struct_name.get_final_item(),
+        lookup.method
+    ));
+    make_named_accessor(
+        struct_name,
+        ident,
+        &lookup.method,
+        inputs,
+        output,
+        references,
+        CppFunctionBody::IteratorLookup(lookup.method.clone(), lookup.member.clone()),
+    )
+}
@@ -0,0 +1,100 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+use syn::{FnArg, Pat, Type, TypePtr};
+
+use crate::{
+    conversion::{
+        api::{Api, ApiName, Provenance},
+        apivec::ApiVec,
+        convert_error::ErrorContext,
+        ConvertErrorFromCpp,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+};
+
+/// Free functions named in `method_of!` take a pointer to some type as
+/// their first parameter, in the style of object-oriented C libraries.
+/// Rename that parameter to `this`, which is how bindgen represents the
+/// receiver of a method, so that function analysis turns the function
+/// into a method. Its C++ wrapper still calls the original free
+/// function.
+pub(crate) fn recognize_methods_of(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    apis.into_iter()
+        .map(|api| match api {
+            Api::Function {
+                name,
+                mut fun,
+                analysis,
+            } if matches!(fun.provenance, Provenance::Bindgen) && fun.self_ty.is_none() => {
+                let cpp_name = name.cpp_name();
+                let method_of = match config.get_method_of(&cpp_name) {
+                    Some(method_of) => method_of,
+                    None => {
+                        return Api::Function {
+                            name,
+                            fun,
+                            analysis,
+                        }
+                    }
+                };
+                // We'd need to reorder the parameters in the C++ wrapper
+                // to support a receiver anywhere else.
+                let receiver = fun.inputs.first_mut().and_then(|arg| match arg {
+                    FnArg::Typed(pt) => match (pt.pat.as_mut(), pt.ty.as_ref()) {
+                        (Pat::Ident(pp), Type::Ptr(TypePtr { elem, .. })) => match elem.as_ref() {
+                            Type::Path(typ)
+                                if QualifiedName::from_type_path(typ).to_cpp_name()
+                                    == method_of.ty =>
+                            {
+                                Some(pp)
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    },
+                    _ => None,
+                });
+                match receiver {
+                    Some(receiver) => {
+                        receiver.ident = make_ident("this");
+                        let ns = name.name.get_namespace();
+                        fun.synthetic_cpp = Some((
+                            CppFunctionBody::FunctionCall(ns.clone(), make_ident(&cpp_name)),
+                            CppFunctionKind::Function,
+                        ));
+                        Api::Function {
+                            name: ApiName::new_with_cpp_name(
+                                ns,
+                                name.name.get_final_ident(),
+                                Some(method_of.method.to_string()),
+                            ),
+                            fun,
+                            analysis,
+                        }
+                    }
+                    None => Api::IgnoredItem {
+                        err: ConvertErrorFromCpp::MethodOfWithoutReceiver(method_of.ty.clone()),
+                        ctx: Some(ErrorContext::new_for_item(name.name.get_final_ident())),
+                        name,
+                    },
+                }
+            }
+            _ => api,
+        })
+        .collect()
+}
@@ -8,6 +8,10 @@
 
 pub(crate) mod abstract_types;
 pub(crate) mod allocators;
+pub(crate) mod bitsets;
+pub(crate) mod bool_params;
+pub(crate) mod byte_buffers;
+pub(crate) mod callable_members;
 pub(crate) mod casts;
 pub(crate) mod constructor_deps;
 pub(crate) mod ctypes;
@@ -16,12 +20,21 @@ mod depth_first;
 mod doc_label;
 pub(crate) mod fun;
 pub(crate) mod gc;
+pub(crate) mod inherited_methods;
+pub(crate) mod iterator_lookups;
+pub(crate) mod method_of;
 mod name_check;
+pub(crate) mod namespace_types;
+pub(crate) mod operators;
 pub(crate) mod pod; // hey, that rhymes
+pub(crate) mod ranges;
 pub(crate) mod remove_ignored;
 mod replace_hopeless_typedef_targets;
+pub(crate) mod tagged_unions;
 pub(crate) mod tdef;
 mod type_converter;
+pub(crate) mod variants;
+pub(crate) mod vectors;
 
 pub(crate) use name_check::check_names;
 pub(crate) use replace_hopeless_typedef_targets::replace_hopeless_typedef_targets;
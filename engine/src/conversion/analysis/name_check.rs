@@ -68,6 +68,10 @@ pub(crate) fn check_names(apis: ApiVec<FnPhase>) -> ApiVec<FnPhase> {
         Api::ConcreteType { .. }
         | Api::CType { .. }
         | Api::StringConstructor { .. }
+        | Api::AssertionHandler { .. }
+        | Api::ExceptionTypes { .. }
+        | Api::AbiVersionCheck { .. }
+        | Api::NamespaceType { .. }
         | Api::RustType { .. }
         | Api::RustSubclassFn { .. }
         | Api::RustFn { .. }
@@ -0,0 +1,77 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+
+use crate::{
+    conversion::{
+        api::{Api, ApiName, Provenance},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+};
+
+/// Free functions directly within a namespace listed in `namespace_type!`
+/// become associated functions of the type we generate for it. Giving
+/// them a `self_ty` without a receiver is enough for function analysis
+/// to treat them as static methods. Their C++ wrappers still call the
+/// original free functions. Functions in nested namespaces aren't
+/// affected unless their own namespace is listed too.
+pub(crate) fn recognize_namespace_functions(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if config.get_namespace_types().next().is_none() {
+        return apis;
+    }
+    apis.into_iter()
+        .map(|api| match api {
+            Api::Function {
+                name,
+                mut fun,
+                analysis,
+            } if matches!(fun.provenance, Provenance::Bindgen)
+                && fun.self_ty.is_none()
+                && fun.synthetic_cpp.is_none() =>
+            {
+                let ns = name.name.get_namespace();
+                let ty = match config.get_namespace_type(&ns.to_cpp_path()) {
+                    Some(ty) => ty,
+                    None => {
+                        return Api::Function {
+                            name,
+                            fun,
+                            analysis,
+                        }
+                    }
+                };
+                let cpp_name = name.cpp_name();
+                fun.self_ty = Some(QualifiedName::new(ns, ty.clone()));
+                fun.synthetic_cpp = Some((
+                    CppFunctionBody::FunctionCall(ns.clone(), make_ident(&cpp_name)),
+                    CppFunctionKind::Function,
+                ));
+                Api::Function {
+                    name: ApiName::new_with_cpp_name(
+                        ns,
+                        name.name.get_final_ident(),
+                        Some(cpp_name),
+                    ),
+                    fun,
+                    analysis,
+                }
+            }
+            _ => api,
+        })
+        .collect()
+}
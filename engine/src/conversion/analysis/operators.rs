@@ -0,0 +1,241 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::cpp_operator_symbol;
+use syn::ReturnType;
+
+use crate::conversion::{
+    api::{Api, ApiName, DeletedOrDefaulted, FuncToConvert, Provenance, SpecialMemberKind},
+    apivec::ApiVec,
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+};
+
+/// How a C++ operator maps onto a `std::ops` trait, if at all.
+#[derive(Clone, Copy)]
+pub(crate) enum OperatorTrait {
+    /// e.g. `Neg`: `fn neg(self) -> Self::Output`
+    Unary(&'static str, &'static str),
+    /// e.g. `Add`: `fn add(self, rhs: Rhs) -> Self::Output`
+    Binary(&'static str, &'static str),
+    /// e.g. `AddAssign`: `fn add_assign(&mut self, rhs: Rhs)`
+    Assign(&'static str, &'static str),
+}
+
+/// A C++ operator which we wrap as a method (or function) with a
+/// regular name, plus an implementation of the matching Rust operator
+/// trait where there is one.
+pub(crate) struct CppOperator {
+    pub(crate) symbol: &'static str,
+    /// Number of operands, including any receiver, or `None` if any
+    /// number is permitted.
+    operands: Option<usize>,
+    pub(crate) rust_name: &'static str,
+    pub(crate) rust_trait: Option<OperatorTrait>,
+}
+
+impl CppOperator {
+    /// Render the C++ expression which applies this operator to
+    /// the given operands.
+    pub(crate) fn cpp_expression(&self, operands: &[String]) -> String {
+        match (self.symbol, operands) {
+            ("[]", [obj, idx]) => format!("{obj}[{idx}]"),
+            ("()", [obj, args @ ..]) => format!("{obj}({})", args.join(", ")),
+            (symbol, [operand]) => format!("({symbol}{operand})"),
+            (symbol, [lhs, rhs]) => format!("({lhs} {symbol} {rhs})"),
+            _ => panic!("Unexpected number of operands for operator{}", self.symbol),
+        }
+    }
+}
+
+macro_rules! op {
+    ($symbol:literal, $operands:expr, $rust_name:literal) => {
+        op!($symbol, $operands, $rust_name, None)
+    };
+    ($symbol:literal, $operands:expr, $rust_name:literal, $rust_trait:expr) => {
+        CppOperator {
+            symbol: $symbol,
+            operands: $operands,
+            rust_name: $rust_name,
+            rust_trait: $rust_trait,
+        }
+    };
+}
+
+use OperatorTrait::{Assign, Binary, Unary};
+
+/// The operators we support. Comparisons get plain methods only: their
+/// C++ return types rarely fit `PartialEq`/`PartialOrd`, and `!`
+/// becomes `op_not` because Rust's `Not` is taken by `~`. The `Fn`
+/// traits can't be implemented on stable Rust, so `operator()` becomes
+/// a plain `call` method, and overloads of it are numbered like any
+/// other overloaded method.
+static CPP_OPERATORS: &[CppOperator] = &[
+    op!("+", Some(2), "op_add", Some(Binary("Add", "add"))),
+    op!("+", Some(1), "op_plus"),
+    op!("-", Some(2), "op_sub", Some(Binary("Sub", "sub"))),
+    op!("-", Some(1), "op_neg", Some(Unary("Neg", "neg"))),
+    op!("*", Some(2), "op_mul", Some(Binary("Mul", "mul"))),
+    op!("*", Some(1), "op_deref"),
+    op!("/", Some(2), "op_div", Some(Binary("Div", "div"))),
+    op!("%", Some(2), "op_rem", Some(Binary("Rem", "rem"))),
+    op!("^", Some(2), "op_bitxor", Some(Binary("BitXor", "bitxor"))),
+    op!("&", Some(2), "op_bitand", Some(Binary("BitAnd", "bitand"))),
+    op!("|", Some(2), "op_bitor", Some(Binary("BitOr", "bitor"))),
+    op!("<<", Some(2), "op_shl", Some(Binary("Shl", "shl"))),
+    op!(">>", Some(2), "op_shr", Some(Binary("Shr", "shr"))),
+    op!("~", Some(1), "op_bitnot", Some(Unary("Not", "not"))),
+    op!("!", Some(1), "op_not"),
+    op!(
+        "+=",
+        Some(2),
+        "op_add_assign",
+        Some(Assign("AddAssign", "add_assign"))
+    ),
+    op!(
+        "-=",
+        Some(2),
+        "op_sub_assign",
+        Some(Assign("SubAssign", "sub_assign"))
+    ),
+    op!(
+        "*=",
+        Some(2),
+        "op_mul_assign",
+        Some(Assign("MulAssign", "mul_assign"))
+    ),
+    op!(
+        "/=",
+        Some(2),
+        "op_div_assign",
+        Some(Assign("DivAssign", "div_assign"))
+    ),
+    op!(
+        "%=",
+        Some(2),
+        "op_rem_assign",
+        Some(Assign("RemAssign", "rem_assign"))
+    ),
+    op!(
+        "^=",
+        Some(2),
+        "op_bitxor_assign",
+        Some(Assign("BitXorAssign", "bitxor_assign"))
+    ),
+    op!(
+        "&=",
+        Some(2),
+        "op_bitand_assign",
+        Some(Assign("BitAndAssign", "bitand_assign"))
+    ),
+    op!(
+        "|=",
+        Some(2),
+        "op_bitor_assign",
+        Some(Assign("BitOrAssign", "bitor_assign"))
+    ),
+    op!(
+        "<<=",
+        Some(2),
+        "op_shl_assign",
+        Some(Assign("ShlAssign", "shl_assign"))
+    ),
+    op!(
+        ">>=",
+        Some(2),
+        "op_shr_assign",
+        Some(Assign("ShrAssign", "shr_assign"))
+    ),
+    op!("==", Some(2), "op_eq"),
+    op!("!=", Some(2), "op_ne"),
+    op!("<", Some(2), "op_lt"),
+    op!("<=", Some(2), "op_le"),
+    op!(">", Some(2), "op_gt"),
+    op!(">=", Some(2), "op_ge"),
+    op!("&&", Some(2), "op_and"),
+    op!("||", Some(2), "op_or"),
+    op!("[]", Some(2), "op_index"),
+    op!("()", None, "call"),
+];
+
+/// bindgen tells us about assignment operators as special members rather
+/// than by name. We expose only move-assignment, which lets an existing
+/// object reuse its storage rather than being replaced by a new one.
+static MOVE_ASSIGNMENT: CppOperator = op!("=", Some(2), "move_assign_from");
+
+fn find_operator(symbol: &str, operands: usize) -> Option<&'static CppOperator> {
+    CPP_OPERATORS
+        .iter()
+        .find(|op| op.symbol == symbol && op.operands.unwrap_or(operands) == operands)
+}
+
+/// C++ operator functions reach us from bindgen under substitute
+/// identifiers (see [`autocxx_parser::cpp_operator_ident`]). Give each
+/// one a Rust name such as `op_add`, and arrange that its C++ wrapper
+/// applies the operator to its operands, which works equally well for
+/// member and free-function operators.
+pub(crate) fn recognize_operators(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    apis.into_iter()
+        .map(|api| match api {
+            Api::Function {
+                name,
+                mut fun,
+                analysis,
+            } if matches!(fun.provenance, Provenance::Bindgen) => {
+                let op = if is_move_assignment(&fun) {
+                    Some(&MOVE_ASSIGNMENT)
+                } else {
+                    cpp_operator_symbol(name.cpp_name().as_str())
+                        .and_then(|symbol| find_operator(symbol, fun.inputs.len()))
+                };
+                let name = match op {
+                    Some(op) => {
+                        // Compound assignment operators conventionally return
+                        // *this, and the caller already has that. Discarding it
+                        // avoids an awkward (and often ambiguous) lifetime.
+                        if (matches!(op.rust_trait, Some(Assign(..)))
+                            || std::ptr::eq(op, &MOVE_ASSIGNMENT))
+                            && fun.references.ref_return
+                        {
+                            fun.output = ReturnType::Default;
+                            fun.references.ref_return = false;
+                            fun.doc_attrs.retain(|attr| !attr.path.is_ident("must_use"));
+                        }
+                        fun.synthetic_cpp =
+                            Some((CppFunctionBody::Operator(op), CppFunctionKind::Function));
+                        ApiName::new_with_cpp_name(
+                            name.name.get_namespace(),
+                            name.name.get_final_ident(),
+                            Some(op.rust_name.to_string()),
+                        )
+                    }
+                    None => name,
+                };
+                Api::Function {
+                    name,
+                    fun,
+                    analysis,
+                }
+            }
+            _ => api,
+        })
+        .collect()
+}
+
+/// A deleted move-assignment operator can't be called, so we leave it to
+/// be ignored along with all other assignment operators.
+fn is_move_assignment(fun: &FuncToConvert) -> bool {
+    matches!(
+        fun.special_member,
+        Some(SpecialMemberKind::AssignmentOperator)
+    ) && !fun.references.rvalue_ref_params.is_empty()
+        && !matches!(fun.is_deleted, DeletedOrDefaulted::Deleted)
+}
@@ -15,7 +15,7 @@ use crate::{
     },
     types::{Namespace, QualifiedName},
 };
-use autocxx_parser::IncludeCppConfig;
+use autocxx_parser::{IncludeCppConfig, LongDoubleHandling};
 use std::collections::HashMap;
 use syn::{ItemStruct, Type};
 
@@ -84,6 +84,18 @@ impl ByValueChecker {
                 .results
                 .insert(tn, StructDetails::new(safety));
         }
+        // bindgen represents a wide `long double` as a `u128`. Unless we're
+        // to treat it as opaque, a struct containing one can't be POD.
+        let long_double_safety = match config.long_double_handling() {
+            LongDoubleHandling::Opaque => PodState::IsPod,
+            _ => PodState::UnsafeToBePod(
+                "u128 is how bindgen represents a long double, which can only be POD with long_double!(opaque)".into(),
+            ),
+        };
+        byvalue_checker.results.insert(
+            QualifiedName::new_from_cpp_name("u128"),
+            StructDetails::new(long_double_safety),
+        );
         // As we do this analysis, we need to be aware that structs
         // may depend on other types. Ideally we'd use the depth first iterator
         // but that's awkward given that our ApiPhase does not yet have a fixed
@@ -134,6 +146,9 @@ impl ByValueChecker {
                 byvalue_checker.ingest_struct(&details.item, api.name().get_namespace())
             }
         }
+        for opaque in config.get_opaque_requests() {
+            byvalue_checker.ingest_opaque_request(QualifiedName::new_from_cpp_name(opaque));
+        }
         let pod_requests = config
             .get_pod_requests()
             .iter()
@@ -183,6 +198,19 @@ impl ByValueChecker {
         self.results.insert(tyname, my_details);
     }
 
+    /// The user has asked for this type to be opaque, so any request
+    /// to make it POD - directly or as a field of some other POD type -
+    /// is an error.
+    fn ingest_opaque_request(&mut self, tyname: QualifiedName) {
+        let reason =
+            format!("Type {tyname} could not be POD because it was requested to be opaque");
+        let details = self
+            .results
+            .entry(tyname)
+            .or_insert_with(|| StructDetails::new(PodState::SafeToBePod));
+        details.state = PodState::UnsafeToBePod(reason);
+    }
+
     fn ingest_nonpod_type(&mut self, tyname: QualifiedName) {
         let new_reason = format!("Type {tyname} is a typedef to a complex type");
         self.results.insert(
@@ -323,6 +351,30 @@ mod tests {
         assert!(bvc.is_pod(&t_id));
     }
 
+    #[test]
+    fn test_nested_opaque() {
+        let mut bvc = ByValueChecker::new();
+        let t: ItemStruct = parse_quote! {
+            struct Foo {
+                a: i32,
+                b: i64,
+            }
+        };
+        let foo_id = ty_from_ident(&t.ident);
+        bvc.ingest_struct(&t, &Namespace::new());
+        let t: ItemStruct = parse_quote! {
+            struct Bar {
+                a: Foo,
+                b: i64,
+            }
+        };
+        let t_id = ty_from_ident(&t.ident);
+        bvc.ingest_struct(&t, &Namespace::new());
+        bvc.ingest_opaque_request(foo_id.clone());
+        assert!(bvc.satisfy_requests(vec![t_id]).is_err());
+        assert!(!bvc.is_pod(&foo_id));
+    }
+
     #[test]
     fn test_with_up() {
         let mut bvc = ByValueChecker::new();
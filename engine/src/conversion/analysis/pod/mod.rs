@@ -18,17 +18,22 @@ use syn::{ItemEnum, ItemStruct, Type, Visibility};
 use crate::{
     conversion::{
         analysis::type_converter::{self, add_analysis, TypeConversionContext, TypeConverter},
-        api::{AnalysisPhase, Api, ApiName, NullPhase, StructDetails, TypeKind},
+        api::{AnalysisPhase, Api, ApiName, EnumAlias, NullPhase, StructDetails, TypeKind},
         apivec::ApiVec,
         convert_error::{ConvertErrorWithContext, ErrorContext},
         error_reporter::convert_apis,
         parse::BindgenSemanticAttributes,
         ConvertErrorFromCpp,
     },
+    known_types::known_types,
     types::{Namespace, QualifiedName},
 };
 
-use super::tdef::{TypedefAnalysis, TypedefPhase};
+use super::{
+    ranges::RangeAnalysis,
+    tagged_unions::TaggedUnionAnalysis,
+    tdef::{TypedefAnalysis, TypedefPhase},
+};
 
 pub(crate) struct FieldInfo {
     pub(crate) ty: Type,
@@ -52,6 +57,14 @@ pub(crate) struct PodAnalysis {
     pub(crate) field_info: Vec<FieldInfo>,
     pub(crate) is_generic: bool,
     pub(crate) in_anonymous_namespace: bool,
+    /// Whether any field or base, perhaps nested within another struct, is
+    /// of a type whose definition differs between platforms. If so, the
+    /// layout bindgen gave us is only right for the machine which ran it.
+    pub(crate) platform_dependent_layout: bool,
+    /// Filled in later if the user asked for a tagged union accessor.
+    pub(crate) tagged_union: Option<TaggedUnionAnalysis>,
+    /// Filled in later if the user asked for this to be iterable.
+    pub(crate) range: Option<RangeAnalysis>,
 }
 
 pub(crate) struct PodPhase;
@@ -118,16 +131,52 @@ pub(crate) fn analyze_pod_apis(
         Api::typedef_unchanged,
     );
     assert!(more_extra_apis.is_empty());
-    Ok(results)
+    Ok(mark_platform_dependent_layouts(results))
+}
+
+fn mark_platform_dependent_layouts(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let mut dependent: HashSet<QualifiedName> = HashSet::new();
+    // Repeat until nothing changes, since structs can be nested to any depth.
+    loop {
+        let previously_found = dependent.len();
+        for api in apis.iter() {
+            if let Api::Struct { name, analysis, .. } = api {
+                if analysis
+                    .field_deps
+                    .iter()
+                    .chain(analysis.bases.iter())
+                    .any(|tn| known_types().is_platform_typedef(tn) || dependent.contains(tn))
+                {
+                    dependent.insert(name.name.clone());
+                }
+            }
+        }
+        if dependent.len() == previously_found {
+            break;
+        }
+    }
+    apis.into_iter()
+        .map(|mut api| {
+            if let Api::Struct { name, analysis, .. } = &mut api {
+                analysis.platform_dependent_layout = dependent.contains(&name.name);
+            }
+            api
+        })
+        .collect()
 }
 
 fn analyze_enum(
     name: ApiName,
     mut item: ItemEnum,
+    aliases: Vec<EnumAlias>,
 ) -> Result<Box<dyn Iterator<Item = Api<PodPhase>>>, ConvertErrorWithContext> {
     let metadata = BindgenSemanticAttributes::new_retaining_others(&mut item.attrs);
     metadata.check_for_fatal_attrs(&name.name.get_final_ident())?;
-    Ok(Box::new(std::iter::once(Api::Enum { name, item })))
+    Ok(Box::new(std::iter::once(Api::Enum {
+        name,
+        item,
+        aliases,
+    })))
 }
 
 fn analyze_struct(
@@ -197,6 +246,9 @@ fn analyze_struct(
             field_info,
             is_generic,
             in_anonymous_namespace,
+            platform_dependent_layout: false,
+            tagged_union: None,
+            range: None,
         },
     })))
 }
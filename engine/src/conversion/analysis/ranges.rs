@@ -0,0 +1,221 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to make C++ ranges iterable from Rust, as requested by `range!`.
+
+use autocxx_parser::IncludeCppConfig;
+use proc_macro2::Ident;
+use syn::{parse_quote, punctuated::Punctuated, FnArg, Token, Type, Visibility};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, Provenance, References,
+        },
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+    type_converter::{TypeConversionContext, TypeConverter},
+};
+
+/// Details of the iterator we'll generate for a range.
+pub(crate) struct RangeAnalysis {
+    /// Name of the Rust iterator struct we'll generate.
+    pub(crate) iter_name: Ident,
+    /// Name of the synthesized method which counts the elements.
+    pub(crate) len_getter: Ident,
+    /// Name of the synthesized method which allocates a C++ iterator
+    /// at `begin()`.
+    pub(crate) begin: Ident,
+    /// Name of the synthesized method which returns a pointer to the
+    /// element at that iterator and advances it, or null at `end()`.
+    pub(crate) next: Ident,
+    /// Name of the synthesized method which frees the iterator.
+    pub(crate) free: Ident,
+    /// The type of each element, as it'll appear in Rust.
+    pub(crate) item_ty: Type,
+}
+
+/// For each type listed in `range!`, synthesize C++ functions to count
+/// its elements and to walk a C++ iterator over them, and note the details
+/// in the struct's analysis so that we can generate a Rust iterator later.
+/// Those functions become private methods, since they hand out raw
+/// pointers which only that iterator knows how to use safely.
+pub(crate) fn add_range_accessors(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if config.ranges.is_empty() {
+        return apis;
+    }
+    let mut type_converter = TypeConverter::new(config, &apis);
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        match api {
+            Api::Struct {
+                name,
+                details,
+                mut analysis,
+            } => {
+                if let Some(range) = config.get_range(&name.name.to_cpp_name()) {
+                    let item_ty = QualifiedName::new_from_cpp_name(&range.item_ty).to_type_path();
+                    let converted = type_converter.convert_type(
+                        Type::Path(item_ty.clone()),
+                        name.name.get_namespace(),
+                        &TypeConversionContext::WithinReference,
+                    );
+                    match converted {
+                        Ok(converted) => {
+                            let struct_name = &name.name;
+                            let typ = struct_name.to_type_path();
+                            let len_getter = make_ident("range_len");
+                            let begin = make_ident("range_begin");
+                            let next = make_ident("range_next");
+                            let free = make_ident("range_free");
+                            let this_is_ref = || References {
+                                ref_params: [make_ident("this")].into_iter().collect(),
+                                ..Default::default()
+                            };
+                            results.push(make_private(make_accessor(
+                                struct_name,
+                                &len_getter,
+                                parse_quote! { this: *const #typ },
+                                parse_quote! { -> usize },
+                                this_is_ref(),
+                                CppFunctionBody::RangeLen,
+                            )));
+                            results.push(make_private(make_accessor(
+                                struct_name,
+                                &begin,
+                                parse_quote! { this: *const #typ },
+                                parse_quote! { -> *mut ::std::os::raw::c_void },
+                                this_is_ref(),
+                                CppFunctionBody::RangeBegin,
+                            )));
+                            results.push(make_private(make_accessor(
+                                struct_name,
+                                &next,
+                                parse_quote! { this: *const #typ, iter: *mut ::std::os::raw::c_void },
+                                parse_quote! { -> *const #item_ty },
+                                this_is_ref(),
+                                CppFunctionBody::RangeNext,
+                            )));
+                            results.push(make_private(make_accessor(
+                                struct_name,
+                                &free,
+                                parse_quote! { this: *const #typ, iter: *mut ::std::os::raw::c_void },
+                                parse_quote! {},
+                                this_is_ref(),
+                                CppFunctionBody::RangeFree,
+                            )));
+                            analysis.range = Some(RangeAnalysis {
+                                iter_name: make_ident(format!(
+                                    "{}Iter",
+                                    struct_name.get_final_item()
+                                )),
+                                len_getter,
+                                begin,
+                                next,
+                                free,
+                                item_ty: converted.ty,
+                            });
+                        }
+                        Err(err) => {
+                            log::info!("Unable to generate iterator for {}: {}", name.name, err)
+                        }
+                    }
+                }
+                results.push(Api::Struct {
+                    name,
+                    details,
+                    analysis,
+                })
+            }
+            _ => results.push(api),
+        }
+    }
+    results
+}
+
+/// Make an accessor into a private method, for use only by the code we
+/// generate alongside it.
+pub(crate) fn make_private(mut api: Api<PodPhase>) -> Api<PodPhase> {
+    if let Api::Function { fun, .. } = &mut api {
+        fun.vis = Visibility::Inherited;
+    }
+    api
+}
+
+pub(crate) fn make_accessor(
+    struct_name: &QualifiedName,
+    method_name: &Ident,
+    inputs: Punctuated<FnArg, Token![,]>,
+    output: syn::ReturnType,
+    references: References,
+    cpp_function_body: CppFunctionBody,
+) -> Api<PodPhase> {
+    // Mimic bindgen's naming for methods, {class}_{method}, so that
+    // we end up with a method of the expected name.
+    let ident = make_ident(format!("{}_{}", struct_name.get_final_item(), method_name));
+    make_named_accessor(
+        struct_name,
+        ident,
+        method_name,
+        inputs,
+        output,
+        references,
+        cpp_function_body,
+    )
+}
+
+/// As [`make_accessor`], but with our own choice of identifier, for when
+/// bindgen may already have used `{class}_{method}` for a different
+/// function. The method is still named after `method_name`.
+pub(crate) fn make_named_accessor(
+    struct_name: &QualifiedName,
+    ident: Ident,
+    method_name: &Ident,
+    inputs: Punctuated<FnArg, Token![,]>,
+    output: syn::ReturnType,
+    references: References,
+    cpp_function_body: CppFunctionBody,
+) -> Api<PodPhase> {
+    Api::Function {
+        name: ApiName::new_with_cpp_name(
+            struct_name.get_namespace(),
+            ident.clone(),
+            Some(method_name.to_string()),
+        ),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: Vec::new(),
+            inputs,
+            output,
+            vis: parse_quote! { pub },
+            virtualness: crate::conversion::api::Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references,
+            original_name: None,
+            self_ty: Some(struct_name.clone()),
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some((cpp_function_body, CppFunctionKind::Function)),
+            is_deleted: DeletedOrDefaulted::Neither,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
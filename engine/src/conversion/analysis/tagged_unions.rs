@@ -0,0 +1,260 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to create safe accessors for structs which pair a union with
+//! a discriminant field, as requested by `tagged_union!`.
+
+use indexmap::map::IndexMap as HashMap;
+
+use autocxx_parser::{IncludeCppConfig, TaggedUnion};
+use proc_macro2::Ident;
+use syn::{parse_quote, GenericArgument, ItemStruct, PathArguments, Type, Visibility};
+
+use crate::{
+    conversion::{
+        api::{
+            Api, ApiName, CppVisibility, DeletedOrDefaulted, FuncToConvert, Provenance, References,
+        },
+        apivec::ApiVec,
+        convert_error::ConvertErrorFromCpp,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::{CppFunctionBody, CppFunctionKind},
+    pod::PodPhase,
+    type_converter::{TypeConversionContext, TypeConverter},
+};
+
+/// Details of the accessor we'll generate for a tagged union.
+pub(crate) struct TaggedUnionAnalysis {
+    /// Name of the Rust enum we'll generate.
+    pub(crate) enum_name: Ident,
+    /// Name of the Rust method which returns that enum.
+    pub(crate) accessor_name: Ident,
+    /// Name of the synthesized method which reads the tag.
+    pub(crate) tag_getter: Ident,
+    pub(crate) variants: Vec<TaggedUnionVariantAnalysis>,
+}
+
+pub(crate) struct TaggedUnionVariantAnalysis {
+    pub(crate) tag: i64,
+    pub(crate) member: Ident,
+    /// Name of the synthesized method which reads this union member
+    /// without checking the tag.
+    pub(crate) getter: Ident,
+    /// The type of the union member, as it'll appear in Rust.
+    pub(crate) ty: Type,
+}
+
+/// For each struct listed in `tagged_union!`, synthesize C++ functions
+/// to read the tag and each union member, and note the details in the
+/// struct's analysis so that we can generate a safe Rust accessor later.
+/// Those functions become private methods, since reading a member without
+/// checking the tag may read an inactive member.
+pub(crate) fn add_tagged_union_accessors(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> Result<ApiVec<PodPhase>, ConvertErrorFromCpp> {
+    if config.tagged_unions.is_empty() {
+        return Ok(apis);
+    }
+    if let Some(missing) = config
+        .tagged_unions
+        .iter()
+        .find(|tagged_union| !structs_contain(&apis, &tagged_union.ty))
+    {
+        return Err(ConvertErrorFromCpp::TaggedUnionInvalid(
+            missing.ty.clone(),
+            "no struct of that name was generated".into(),
+        ));
+    }
+    let structs: HashMap<QualifiedName, ItemStruct> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct { name, details, .. } => Some((name.name.clone(), details.item.clone())),
+            _ => None,
+        })
+        .collect();
+    let mut type_converter = TypeConverter::new(config, &apis);
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        match api {
+            Api::Struct {
+                name,
+                details,
+                mut analysis,
+            } => {
+                if let Some(tagged_union) = config.get_tagged_union(&name.name.to_cpp_name()) {
+                    let (tagged_union_analysis, functions) = analyze_tagged_union(
+                        &name.name,
+                        &details.item,
+                        tagged_union,
+                        &structs,
+                        &mut type_converter,
+                    )
+                    .map_err(|problem| {
+                        ConvertErrorFromCpp::TaggedUnionInvalid(tagged_union.ty.clone(), problem)
+                    })?;
+                    analysis.tagged_union = Some(tagged_union_analysis);
+                    for function in functions {
+                        results.push(function);
+                    }
+                }
+                results.push(Api::Struct {
+                    name,
+                    details,
+                    analysis,
+                })
+            }
+            _ => results.push(api),
+        }
+    }
+    Ok(results)
+}
+
+fn structs_contain(apis: &ApiVec<PodPhase>, cpp_name: &str) -> bool {
+    apis.iter()
+        .any(|api| matches!(api, Api::Struct { name, .. } if name.name.to_cpp_name() == cpp_name))
+}
+
+/// Returns a description of the problem if the struct, its fields or
+/// the union don't match what `tagged_union!` said.
+fn analyze_tagged_union(
+    struct_name: &QualifiedName,
+    item: &ItemStruct,
+    tagged_union: &TaggedUnion,
+    structs: &HashMap<QualifiedName, ItemStruct>,
+    type_converter: &mut TypeConverter,
+) -> Result<(TaggedUnionAnalysis, Vec<Api<PodPhase>>), String> {
+    let union_field = &tagged_union.union_field;
+    let tag_field = &tagged_union.tag_field;
+    find_field(item, tag_field).ok_or_else(|| format!("it has no field {tag_field}"))?;
+    let union_item = match find_field(item, union_field) {
+        Some(Type::Path(typ)) => structs.get(&QualifiedName::from_type_path(typ)),
+        Some(_) => None,
+        None => return Err(format!("it has no field {union_field}")),
+    }
+    .ok_or_else(|| format!("its field {union_field} isn't a union which autocxx generated"))?;
+    let ns = struct_name.get_namespace();
+    let mut functions = Vec::new();
+    let tag_getter = make_ident(format!("{union_field}_tag"));
+    functions.push(make_getter(
+        struct_name,
+        &tag_getter,
+        parse_quote! { -> i64 },
+        References {
+            ref_params: [make_ident("this")].into_iter().collect(),
+            ..Default::default()
+        },
+        CppFunctionBody::UnionTag(tagged_union.tag_field.clone()),
+    ));
+    let mut variants = Vec::new();
+    for variant in &tagged_union.variants {
+        let member = &variant.member;
+        let member_ty = union_member_type(
+            find_field(union_item, member)
+                .ok_or_else(|| format!("its union has no member {member}"))?,
+        );
+        let converted = type_converter
+            .convert_type(
+                member_ty.clone(),
+                ns,
+                &TypeConversionContext::WithinReference,
+            )
+            .map_err(|e| format!("the type of union member {member} can't be converted: {e}"))?;
+        let getter = make_ident(format!("{}_{}_unchecked", union_field, variant.member));
+        functions.push(make_getter(
+            struct_name,
+            &getter,
+            parse_quote! { -> *const #member_ty },
+            References::new_with_this_and_return_as_reference(),
+            CppFunctionBody::UnionMember(union_field.clone(), variant.member.clone()),
+        ));
+        variants.push(TaggedUnionVariantAnalysis {
+            tag: variant.tag,
+            member: variant.member.clone(),
+            getter,
+            ty: converted.ty,
+        });
+    }
+    Ok((
+        TaggedUnionAnalysis {
+            enum_name: make_ident(format!("{}Variant", struct_name.get_final_item())),
+            accessor_name: make_ident("variant"),
+            tag_getter,
+            variants,
+        },
+        functions,
+    ))
+}
+
+fn find_field<'a>(item: &'a ItemStruct, field_name: &Ident) -> Option<&'a Type> {
+    item.fields
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(field_name))
+        .map(|f| &f.ty)
+}
+
+/// bindgen represents union members as `__BindgenUnionField<T>`.
+fn union_member_type(ty: &Type) -> Type {
+    if let Type::Path(typ) = ty {
+        let last_seg = typ.path.segments.last().unwrap();
+        if last_seg.ident == "__BindgenUnionField" {
+            if let PathArguments::AngleBracketed(args) = &last_seg.arguments {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    return inner.clone();
+                }
+            }
+        }
+    }
+    ty.clone()
+}
+
+/// Make a private method which calls the given synthesized C++.
+fn make_getter(
+    struct_name: &QualifiedName,
+    method_name: &Ident,
+    output: syn::ReturnType,
+    references: References,
+    cpp_function_body: CppFunctionBody,
+) -> Api<PodPhase> {
+    let typ = struct_name.to_type_path();
+    // Mimic bindgen's naming for methods, {class}_{method}, so that
+    // we end up with a method of the expected name.
+    let ident = make_ident(format!("{}_{}", struct_name.get_final_item(), method_name));
+    Api::Function {
+        name: ApiName::new_with_cpp_name(
+            struct_name.get_namespace(),
+            ident.clone(),
+            Some(method_name.to_string()),
+        ),
+        fun: Box::new(FuncToConvert {
+            ident,
+            doc_attrs: Vec::new(),
+            inputs: parse_quote! { this: *const #typ },
+            output,
+            vis: Visibility::Inherited,
+            virtualness: crate::conversion::api::Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references,
+            original_name: None,
+            self_ty: Some(struct_name.clone()),
+            synthesized_this_type: None,
+            add_to_trait: None,
+            synthetic_cpp: Some((cpp_function_body, CppFunctionKind::Function)),
+            is_deleted: DeletedOrDefaulted::Neither,
+            provenance: Provenance::SynthesizedOther,
+            variadic: false,
+        }),
+        analysis: (),
+    }
+}
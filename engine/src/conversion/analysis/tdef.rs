@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
 use autocxx_parser::IncludeCppConfig;
@@ -45,6 +46,28 @@ pub(crate) fn convert_typedef_targets(
     apis: ApiVec<NullPhase>,
 ) -> ApiVec<TypedefPhase> {
     let mut type_converter = TypeConverter::new(config, &apis);
+    // If the user asked for a typedef of a template instantiation by name,
+    // that typedef becomes the concrete type for the instantiation. We need
+    // to know about all such typedefs before converting any types, so that
+    // other typedefs and functions referring to the same instantiation
+    // consistently use the user's name.
+    let concrete_typedefs: HashMap<QualifiedName, String> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Typedef {
+                name,
+                item: TypedefKind::Type(ity),
+                ..
+            } if ity.generics.params.is_empty()
+                && config.is_on_allowlist(&name.name.to_cpp_name()) =>
+            {
+                type_converter
+                    .name_concrete_type_after_typedef(&name.name, &ity.ty)
+                    .map(|cpp_definition| (name.name.clone(), cpp_definition))
+            }
+            _ => None,
+        })
+        .collect();
     let mut extra_apis = ApiVec::new();
     let mut results = ApiVec::new();
     convert_apis(
@@ -55,6 +78,14 @@ pub(crate) fn convert_typedef_targets(
         Api::enum_unchanged,
         |name, item, old_tyname, _| {
             Ok(Box::new(std::iter::once(match item {
+                TypedefKind::Type(ity) if concrete_typedefs.contains_key(&name.name) => {
+                    Api::ConcreteType {
+                        cpp_definition: concrete_typedefs[&name.name].clone(),
+                        name,
+                        rs_definition: Some(ity.ty),
+                        std_variant: None,
+                    }
+                }
                 TypedefKind::Type(ity) => get_replacement_typedef(
                     name,
                     ity,
@@ -16,7 +16,7 @@ use crate::{
     known_types::{known_types, CxxGenericType},
     types::{make_ident, Namespace, QualifiedName},
 };
-use autocxx_parser::IncludeCppConfig;
+use autocxx_parser::{IncludeCppConfig, LongDoubleHandling};
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 use itertools::Itertools;
@@ -38,6 +38,8 @@ pub(crate) enum TypeKind {
     Reference,
     RValueReference,
     MutableReference,
+    /// A `std::reference_wrapper<T>`, represented as a `&T`.
+    ReferenceWrapper,
 }
 
 /// Results of some type conversion, annotated with a list of every type encountered,
@@ -131,6 +133,8 @@ pub(crate) struct TypeConverter<'a> {
     ignored_types: HashSet<QualifiedName>,
     config: &'a IncludeCppConfig,
     original_name_map: CppNameMap,
+    /// How many templates enclose the type currently being converted.
+    template_depth: usize,
 }
 
 impl<'a> TypeConverter<'a> {
@@ -138,17 +142,55 @@ impl<'a> TypeConverter<'a> {
     where
         A::TypedefAnalysis: TypedefTarget,
     {
+        let original_name_map = CppNameMap::new_from_apis(apis);
         Self {
             types_found: find_types(apis),
             typedefs: Self::find_typedefs(apis),
-            concrete_templates: Self::find_concrete_templates(apis),
+            concrete_templates: Self::find_concrete_templates(apis, config, &original_name_map),
             forward_declarations: Self::find_incomplete_types(apis),
             ignored_types: Self::find_ignored_types(apis),
             config,
-            original_name_map: CppNameMap::new_from_apis(apis),
+            original_name_map,
+            template_depth: 0,
         }
     }
 
+    /// If this typedef refers to an instantiation of a template which cxx
+    /// doesn't understand, arrange for the concrete type we make for that
+    /// instantiation to be known by the typedef's name rather than a
+    /// synthesized one. Returns the C++ spelling of the instantiation if so.
+    pub(crate) fn name_concrete_type_after_typedef(
+        &mut self,
+        typedef_name: &QualifiedName,
+        target: &Type,
+    ) -> Option<String> {
+        let typ = match target {
+            Type::Path(typ) => typ,
+            _ => return None,
+        };
+        let tn = QualifiedName::from_type_path(typ);
+        if typ.path.segments.last()?.arguments.is_empty()
+            || known_types().consider_substitution(&tn).is_some()
+            || known_types().cxx_generic_behavior(&tn) != CxxGenericType::Not
+            || self.ignored_types.contains(&tn)
+        {
+            return None;
+        }
+        let cpp_definition = self.original_name_map.type_to_cpp(target).ok()?;
+        if self.concrete_templates.contains_key(&cpp_definition) {
+            // e.g. the user already named it using concrete!
+            return None;
+        }
+        self.concrete_templates
+            .insert(cpp_definition.clone(), typedef_name.clone());
+        Some(cpp_definition)
+    }
+
+    /// The C++ spelling of a type, as best we can reconstruct it.
+    pub(crate) fn type_to_cpp(&self, ty: &Type) -> Result<String, ConvertErrorFromCpp> {
+        self.original_name_map.type_to_cpp(ty)
+    }
+
     pub(crate) fn convert_boxed_type(
         &mut self,
         ty: Box<Type>,
@@ -178,7 +220,15 @@ impl<'a> TypeConverter<'a> {
                     // doesn't simply get renamed to a different type _identifier_.
                     // This plain type-by-value (as far as bindgen is concerned)
                     // is actually a &str.
-                    if known_types().should_dereference_in_cpp(&qn) {
+                    if known_types().is_span(&qn) {
+                        self.convert_span_to_slice(newpp, ctx, newp.types_encountered)?
+                    } else if known_types().is_reference_wrapper(&qn) {
+                        self.convert_reference_wrapper_to_reference(
+                            newpp,
+                            ctx,
+                            newp.types_encountered,
+                        )?
+                    } else if known_types().should_dereference_in_cpp(&qn) {
                         Annotated::new(
                             Type::Reference(parse_quote! {
                                 &str
@@ -226,12 +276,96 @@ impl<'a> TypeConverter<'a> {
         Ok(result)
     }
 
+    /// A `std::span<T>` is passed from Rust as a `&[T]`, from which the
+    /// C++ wrapper function makes the span. We can only do that for
+    /// parameters; function analysis decides whether the slice should
+    /// be mutable.
+    fn convert_span_to_slice(
+        &self,
+        span: &TypePath,
+        ctx: &TypeConversionContext,
+        types_encountered: HashSet<QualifiedName>,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        if !matches!(
+            ctx,
+            TypeConversionContext::OuterType {
+                pointer_treatment: PointerTreatment::Pointer
+            }
+        ) {
+            return Err(ConvertErrorFromCpp::SpanNotParameter);
+        }
+        let elem = match &span.path.segments.last().unwrap().arguments {
+            PathArguments::AngleBracketed(ab) => match ab.args.first() {
+                Some(GenericArgument::Type(elem)) => Some(elem),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| {
+            ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(QualifiedName::from_type_path(
+                span,
+            ))
+        })?;
+        Ok(Annotated::new(
+            parse_quote! { &[#elem] },
+            types_encountered,
+            ApiVec::new(),
+            TypeKind::Reference,
+        ))
+    }
+
+    /// A `std::reference_wrapper<T>` is represented in Rust as a `&T`, with
+    /// the C++ wrapper function calling `.get()` or `std::ref` as needed.
+    /// That's only possible for parameters and return types: in a
+    /// container, for instance, there's nothing for the reference to
+    /// borrow from. Function analysis decides on the mutability.
+    /// Struct fields are converted too, but only so that later analysis
+    /// knows the struct can't be default constructed; such a struct is
+    /// never POD.
+    fn convert_reference_wrapper_to_reference(
+        &self,
+        wrapper: &TypePath,
+        ctx: &TypeConversionContext,
+        types_encountered: HashSet<QualifiedName>,
+    ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        if matches!(
+            ctx,
+            TypeConversionContext::WithinContainer | TypeConversionContext::WithinReference
+        ) {
+            return Err(ConvertErrorFromCpp::ReferenceWrapperNotParameterOrReturn);
+        }
+        let elem = match &wrapper.path.segments.last().unwrap().arguments {
+            PathArguments::AngleBracketed(ab) => match ab.args.first() {
+                Some(GenericArgument::Type(elem)) => Some(elem),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| {
+            ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(QualifiedName::from_type_path(
+                wrapper,
+            ))
+        })?;
+        Ok(Annotated::new(
+            parse_quote! { &#elem },
+            types_encountered,
+            ApiVec::new(),
+            TypeKind::ReferenceWrapper,
+        ))
+    }
+
     fn convert_type_path(
         &mut self,
         mut typ: TypePath,
         ns: &Namespace,
         ctx: &TypeConversionContext,
     ) -> Result<Annotated<Type>, ConvertErrorFromCpp> {
+        if is_long_double(&typ) {
+            match self.config.long_double_handling() {
+                LongDoubleHandling::Opaque => typ = parse_quote! { autocxx::c_longdouble },
+                _ => return Err(ConvertErrorFromCpp::LongDouble),
+            }
+        }
         // First, qualify any unqualified paths.
         if typ.path.segments.iter().next().unwrap().ident != "root" {
             let ty = QualifiedName::from_type_path(&typ);
@@ -294,10 +428,43 @@ impl<'a> TypeConverter<'a> {
             }
         };
 
+        // std::complex of a floating point type can be passed by value.
+        if let Some(complex_tn) = typ
+            .path
+            .segments
+            .last()
+            .and_then(|seg| known_types().complex_type(&tn, &seg.arguments))
+        {
+            deps.remove(&tn);
+            let complex_typ = complex_tn.to_type_path();
+            deps.insert(complex_tn);
+            return Ok(Annotated::new(
+                Type::Path(complex_typ),
+                deps,
+                ApiVec::new(),
+                TypeKind::Regular,
+            ));
+        }
+
+        // Templates nested beyond max_template_depth! are left alone here,
+        // and become opaque concrete types below.
+        let too_deep = typ
+            .path
+            .segments
+            .last()
+            .map(|seg| !seg.arguments.is_empty())
+            .unwrap_or(false)
+            && self
+                .config
+                .max_template_depth()
+                .map(|max| self.template_depth >= max)
+                .unwrap_or(false);
+
         // Now let's see if it's a known type.
         // (We may entirely reject some types at this point too.)
+        let original_typ = typ.clone();
         let mut typ = match known_types().consider_substitution(&tn) {
-            Some(mut substitute_type) => {
+            Some(mut substitute_type) if !too_deep => {
                 if let Some(last_seg_args) =
                     typ.path.segments.into_iter().last().map(|ps| ps.arguments)
                 {
@@ -306,33 +473,53 @@ impl<'a> TypeConverter<'a> {
                 }
                 substitute_type
             }
-            None => typ,
+            _ => typ,
         };
 
         let mut extra_apis = ApiVec::new();
         let mut kind = TypeKind::Regular;
+        let mut unsupported_vector = false;
 
         // Finally let's see if it's generic.
         if let Some(last_seg) = Self::get_generic_args(&mut typ) {
-            let generic_behavior = known_types().cxx_generic_behavior(&tn);
-            let forward_declarations_ok = generic_behavior == CxxGenericType::Rust;
+            let generic_behavior = if too_deep {
+                CxxGenericType::Not
+            } else {
+                known_types().cxx_generic_behavior(&tn)
+            };
+            let forward_declarations_ok = matches!(
+                generic_behavior,
+                CxxGenericType::Rust | CxxGenericType::ReferenceWrapper
+            );
             if generic_behavior != CxxGenericType::Not {
                 // this is a type of generic understood by cxx (e.g. CxxVector)
                 // so let's convert any generic type arguments. This recurses.
                 if let PathArguments::AngleBracketed(ref mut ab) = last_seg.arguments {
-                    let mut innerty = self.convert_punctuated(
+                    self.template_depth += 1;
+                    let innerty = self.convert_punctuated(
                         ab.args.clone(),
                         ns,
                         &TypeConversionContext::WithinContainer,
-                    )?;
+                    );
+                    self.template_depth -= 1;
+                    let mut innerty = innerty?;
                     ab.args = innerty.ty;
-                    kind = self.confirm_inner_type_is_acceptable_generic_payload(
+                    match self.confirm_inner_type_is_acceptable_generic_payload(
                         &ab.args,
                         &tn,
                         generic_behavior,
                         forward_declarations_ok,
-                    )?;
-                    deps.extend(innerty.types_encountered.drain(..));
+                    ) {
+                        Err(
+                            ConvertErrorFromCpp::InvalidTypeForCppVector(_)
+                            | ConvertErrorFromCpp::GenericsWithinVector,
+                        ) => unsupported_vector = true,
+                        result => {
+                            kind = result?;
+                            deps.extend(innerty.types_encountered.drain(..));
+                            extra_apis.append(&mut innerty.extra_apis);
+                        }
+                    }
                 } else {
                     return Err(ConvertErrorFromCpp::TemplatedTypeContainingNonPathArg(
                         tn.clone(),
@@ -377,6 +564,17 @@ impl<'a> TypeConverter<'a> {
                 deps.insert(new_tn);
             }
         }
+        if unsupported_vector {
+            // cxx's CxxVector can't hold elements of this type (for instance
+            // bool, since std::vector<bool> is specialized to pack its bits,
+            // or another template), so this vector instead becomes a concrete
+            // type like any other template.
+            let (new_tn, api) = self.get_templated_typename(&Type::Path(original_typ))?;
+            extra_apis.extend(api.into_iter());
+            deps.remove(&tn);
+            typ = new_tn.to_type_path();
+            deps.insert(new_tn);
+        }
         Ok(Annotated::new(Type::Path(typ), deps, extra_apis, kind))
     }
 
@@ -470,6 +668,22 @@ impl<'a> TypeConverter<'a> {
                     TypeKind::Pointer,
                 ))
             }
+            // A reference to a pointer, typically an out-parameter through
+            // which C++ hands back a pointer. Pointers are Unpin, so there's
+            // no need for a Pin. C++ may well dereference the pointer it's
+            // given, so this is as unsafe as a pointer.
+            PointerTreatment::Reference if matches!(*ptr.elem, Type::Ptr(_)) => {
+                let mutability = ptr.mutability;
+                let mut outer = self
+                    .convert_boxed_type(ptr.elem, ns, &TypeConversionContext::WithinReference)?
+                    .map(|elem| {
+                        Type::Reference(parse_quote! {
+                            & #mutability #elem
+                        })
+                    });
+                outer.kind = TypeKind::Pointer;
+                Ok(outer)
+            }
             PointerTreatment::Reference => {
                 let mutability = ptr.mutability;
                 let elem =
@@ -557,6 +771,7 @@ impl<'a> TypeConverter<'a> {
                     name: ApiName::new_in_root_namespace(make_ident(synthetic_ident)),
                     cpp_definition: cpp_definition.clone(),
                     rs_definition: Some(Box::new(rs_definition.clone())),
+                    std_variant: None,
                 };
                 self.concrete_templates
                     .insert(cpp_definition, api.name().clone());
@@ -605,6 +820,12 @@ impl<'a> TypeConverter<'a> {
                                 return Err(ConvertErrorFromCpp::InvalidTypeForCppPtr(inner_qn));
                             }
                         }
+                        CxxGenericType::Span
+                            if !known_types().permissible_within_span(&inner_qn)
+                                || !typ.path.segments.last().unwrap().arguments.is_empty() =>
+                        {
+                            return Err(ConvertErrorFromCpp::InvalidTypeForSpan(inner_qn));
+                        }
                         CxxGenericType::CppVector => {
                             if !known_types().permissible_within_vector(&inner_qn) {
                                 return Err(ConvertErrorFromCpp::InvalidTypeForCppVector(inner_qn));
@@ -649,9 +870,22 @@ impl<'a> TypeConverter<'a> {
 
     fn find_concrete_templates<A: AnalysisPhase>(
         apis: &ApiVec<A>,
+        config: &IncludeCppConfig,
+        original_name_map: &CppNameMap,
     ) -> HashMap<String, QualifiedName> {
         apis.iter()
             .filter_map(|api| match &api {
+                // An instantiate! may leave out defaulted template arguments,
+                // but other mentions of the same type won't, so go by the
+                // type which bindgen told us it is.
+                Api::ConcreteType {
+                    name,
+                    rs_definition: Some(rs_definition),
+                    ..
+                } if config.is_instantiation(&name.name.get_final_ident()) => original_name_map
+                    .type_to_cpp(rs_definition)
+                    .ok()
+                    .map(|cpp_definition| (cpp_definition, api.name().clone())),
                 Api::ConcreteType { cpp_definition, .. } => {
                     Some((cpp_definition.clone(), api.name().clone()))
                 }
@@ -696,10 +930,12 @@ pub(crate) fn add_analysis<A: AnalysisPhase>(api: UnanalyzedApi) -> Api<A> {
             name,
             rs_definition,
             cpp_definition,
+            std_variant,
         } => Api::ConcreteType {
             name,
             rs_definition,
             cpp_definition,
+            std_variant,
         },
         Api::IgnoredItem { name, err, ctx } => Api::IgnoredItem { name, err, ctx },
         _ => panic!("Function analysis created an unexpected type of extra API"),
@@ -735,8 +971,12 @@ pub(crate) fn find_types<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<Qualifie
             | Api::Struct { .. }
             | Api::Subclass { .. }
             | Api::ExternCppType { .. }
+            | Api::NamespaceType { .. }
             | Api::RustType { .. } => Some(api.name()),
             Api::StringConstructor { .. }
+            | Api::AssertionHandler { .. }
+            | Api::ExceptionTypes { .. }
+            | Api::AbiVersionCheck { .. }
             | Api::Function { .. }
             | Api::Const { .. }
             | Api::CType { .. }
@@ -748,3 +988,12 @@ pub(crate) fn find_types<A: AnalysisPhase>(apis: &ApiVec<A>) -> HashSet<Qualifie
         .cloned()
         .collect()
 }
+
+/// Whether this is how bindgen represents a C++ `long double`. Where
+/// `long double` is wider than a `double` (for instance, on x86-64 Linux)
+/// bindgen gives us an integer of the same size, which cxx doesn't support.
+/// We can't tell this apart from an `unsigned __int128`, but cxx doesn't
+/// support that either.
+pub(crate) fn is_long_double(typ: &TypePath) -> bool {
+    typ.qself.is_none() && typ.path.is_ident("u128")
+}
@@ -0,0 +1,242 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to let Rust inspect a `std::variant` which has been named
+//! using `concrete!`.
+
+use autocxx_parser::IncludeCppConfig;
+use proc_macro2::Ident;
+use syn::{parse_quote, Type, TypePath};
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{
+    fun::function_wrapper::CppFunctionBody,
+    pod::PodPhase,
+    ranges::{make_accessor, make_private},
+    type_converter::{TypeConversionContext, TypeConverter},
+};
+
+/// Details of the Rust enum we'll generate for a `std::variant`.
+pub(crate) struct VariantAnalysis {
+    /// Name of the Rust enum we'll generate.
+    pub(crate) enum_name: Ident,
+    /// Name of the synthesized method which returns `index()`.
+    pub(crate) index_getter: Ident,
+    pub(crate) alternatives: Vec<VariantAlternativeAnalysis>,
+}
+
+pub(crate) enum VariantAlternativeAnalysis {
+    /// `std::monostate`, which carries no value.
+    Monostate,
+    Value {
+        /// Name of the synthesized method which reads this alternative
+        /// without checking that it's the active one.
+        getter: Ident,
+        /// The type of the alternative, as it'll appear in Rust.
+        ty: Box<Type>,
+    },
+}
+
+/// For each `concrete!` type which is a `std::variant`, synthesize C++
+/// functions to read its index and each of its alternatives, and note
+/// the details so that we can generate a Rust enum later.
+///
+/// Alternatives are read by position rather than by type, so a variant
+/// may contain the same type more than once.
+pub(crate) fn add_variant_accessors(
+    apis: ApiVec<PodPhase>,
+    config: &IncludeCppConfig,
+) -> ApiVec<PodPhase> {
+    if !apis.iter().any(|api| {
+        matches!(api, Api::ConcreteType { cpp_definition, .. }
+            if variant_alternatives(cpp_definition).is_some())
+    }) {
+        return apis;
+    }
+    let mut type_converter = TypeConverter::new(config, &apis);
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        match api {
+            Api::ConcreteType {
+                name,
+                rs_definition,
+                cpp_definition,
+                std_variant,
+            } => {
+                let std_variant = match variant_alternatives(&cpp_definition) {
+                    Some(alternatives) => analyze_variant(
+                        &name.name,
+                        &alternatives,
+                        &mut type_converter,
+                        &mut results,
+                    )
+                    .map(Box::new),
+                    None => std_variant,
+                };
+                results.push(Api::ConcreteType {
+                    name,
+                    rs_definition,
+                    cpp_definition,
+                    std_variant,
+                })
+            }
+            _ => results.push(api),
+        }
+    }
+    results
+}
+
+fn analyze_variant(
+    variant_name: &QualifiedName,
+    alternatives: &[&str],
+    type_converter: &mut TypeConverter,
+    results: &mut ApiVec<PodPhase>,
+) -> Option<VariantAnalysis> {
+    let typ = variant_name.to_type_path();
+    let mut accessors = Vec::new();
+    let mut analyzed_alternatives = Vec::new();
+    for (index, alternative) in alternatives.iter().enumerate() {
+        if *alternative == "std::monostate" {
+            analyzed_alternatives.push(VariantAlternativeAnalysis::Monostate);
+            continue;
+        }
+        let alternative_ty = match alternative_type_path(alternative) {
+            Some(alternative_ty) => alternative_ty,
+            None => {
+                log::info!(
+                    "Unable to generate enum for {}: can't name alternative {}",
+                    variant_name,
+                    alternative
+                );
+                return None;
+            }
+        };
+        let converted = type_converter.convert_type(
+            Type::Path(alternative_ty.clone()),
+            variant_name.get_namespace(),
+            &TypeConversionContext::WithinReference,
+        );
+        let converted = match converted {
+            Ok(converted) => converted,
+            Err(err) => {
+                log::info!("Unable to generate enum for {}: {}", variant_name, err);
+                return None;
+            }
+        };
+        let getter = make_ident(format!("variant_{index}_unchecked"));
+        // Private, since it doesn't check that this alternative is active.
+        accessors.push(make_private(make_accessor(
+            variant_name,
+            &getter,
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> *const #alternative_ty },
+            References::new_with_this_and_return_as_reference(),
+            CppFunctionBody::VariantGet(index),
+        )));
+        analyzed_alternatives.push(VariantAlternativeAnalysis::Value {
+            getter,
+            ty: Box::new(converted.ty),
+        });
+    }
+    let index_getter = make_ident("variant_index");
+    results.push(make_accessor(
+        variant_name,
+        &index_getter,
+        parse_quote! { this: *const #typ },
+        parse_quote! { -> usize },
+        References {
+            ref_params: [make_ident("this")].into_iter().collect(),
+            ..Default::default()
+        },
+        CppFunctionBody::VariantIndex,
+    ));
+    results.extend(accessors.into_iter());
+    Some(VariantAnalysis {
+        enum_name: make_ident(format!("{}Variant", variant_name.get_final_item())),
+        index_getter,
+        alternatives: analyzed_alternatives,
+    })
+}
+
+/// If this C++ type is a `std::variant`, returns the spelling of each
+/// of its alternatives.
+fn variant_alternatives(cpp_definition: &str) -> Option<Vec<&str>> {
+    let args = cpp_definition
+        .trim()
+        .strip_prefix("std::variant<")?
+        .strip_suffix('>')?;
+    let mut alternatives = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (pos, c) in args.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                alternatives.push(args[start..pos].trim());
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    alternatives.push(args[start..].trim());
+    if depth != 0 || alternatives.iter().any(|alt| alt.is_empty()) {
+        return None;
+    }
+    Some(alternatives)
+}
+
+/// The Rust type path for an alternative of a variant, if it's simply a
+/// (possibly qualified) name. We don't attempt to parse anything else,
+/// such as `std::pair<int, int>` or `Foo*`.
+fn alternative_type_path(alternative: &str) -> Option<TypePath> {
+    if alternative
+        .split("::")
+        .all(|segment| syn::parse_str::<Ident>(segment).is_ok())
+    {
+        Some(QualifiedName::new_from_cpp_name(alternative).to_type_path())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{alternative_type_path, variant_alternatives};
+
+    #[test]
+    fn test_variant_alternatives() {
+        assert_eq!(
+            variant_alternatives("std::variant<int, float>"),
+            Some(vec!["int", "float"])
+        );
+        assert_eq!(
+            variant_alternatives("std::variant<std::monostate, std::pair<int, int>, int>"),
+            Some(vec!["std::monostate", "std::pair<int, int>", "int"])
+        );
+        assert_eq!(variant_alternatives("std::vector<int>"), None);
+        assert_eq!(variant_alternatives("std::variant<int,>"), None);
+    }
+
+    #[test]
+    fn test_alternative_type_path() {
+        assert!(alternative_type_path("int").is_some());
+        assert!(alternative_type_path("std::string").is_some());
+        assert!(alternative_type_path("std::pair<int, int>").is_none());
+        assert!(alternative_type_path("std::vector<int>").is_none());
+        assert!(alternative_type_path("Foo*").is_none());
+        assert!(alternative_type_path("unsigned int").is_none());
+    }
+}
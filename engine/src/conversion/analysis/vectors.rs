@@ -0,0 +1,70 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Code to give some methods to a `std::vector` which has become a
+//! concrete type, because cxx's `CxxVector` can't hold its elements.
+
+use syn::parse_quote;
+
+use crate::{
+    conversion::{
+        api::{Api, References},
+        apivec::ApiVec,
+    },
+    types::{make_ident, QualifiedName},
+};
+
+use super::{fun::function_wrapper::CppFunctionBody, pod::PodPhase, ranges::make_accessor};
+
+/// For each concrete type which is a `std::vector`, synthesize methods to
+/// find out its length. These are the methods of `CxxVector` which don't
+/// depend on the element type.
+pub(crate) fn add_vector_accessors(apis: ApiVec<PodPhase>) -> ApiVec<PodPhase> {
+    let mut results = ApiVec::new();
+    for api in apis.into_iter() {
+        let accessors = match &api {
+            Api::ConcreteType {
+                name,
+                cpp_definition,
+                ..
+            } if cpp_definition.trim().starts_with("std::vector<") => {
+                make_vector_accessors(&name.name)
+            }
+            _ => Vec::new(),
+        };
+        results.push(api);
+        results.extend(accessors.into_iter());
+    }
+    results
+}
+
+fn make_vector_accessors(vector_name: &QualifiedName) -> Vec<Api<PodPhase>> {
+    let typ = vector_name.to_type_path();
+    let this_ref = || References {
+        ref_params: [make_ident("this")].into_iter().collect(),
+        ..Default::default()
+    };
+    vec![
+        make_accessor(
+            vector_name,
+            &make_ident("len"),
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> usize },
+            this_ref(),
+            CppFunctionBody::CallMember(make_ident("size")),
+        ),
+        make_accessor(
+            vector_name,
+            &make_ident("is_empty"),
+            parse_quote! { this: *const #typ },
+            parse_quote! { -> bool },
+            this_ref(),
+            CppFunctionBody::CallMember(make_ident("empty")),
+        ),
+    ]
+}
@@ -27,6 +27,7 @@ use super::{
             function_wrapper::{CppFunction, CppFunctionBody, CppFunctionKind},
             ReceiverMutability,
         },
+        variants::VariantAnalysis,
         PointerTreatment,
     },
     convert_error::{ConvertErrorWithContext, ErrorContext},
@@ -59,6 +60,16 @@ pub(crate) struct StructDetails {
     pub(crate) has_rvalue_reference_fields: bool,
 }
 
+/// An enumerator with the same value as an earlier one, such as `Primary`
+/// in `enum Color { Red = 1, Primary = 1 }`. A Rust enum can't have two
+/// variants with the same discriminant, so this becomes an associated
+/// constant equal to the earlier variant.
+#[derive(Clone)]
+pub(crate) struct EnumAlias {
+    pub(crate) alias: Ident,
+    pub(crate) canonical: Ident,
+}
+
 /// Layout of a type, equivalent to the same type in ir/layout.rs in bindgen
 #[derive(Clone)]
 pub(crate) struct Layout {
@@ -487,10 +498,24 @@ pub(crate) enum Api<T: AnalysisPhase> {
         name: ApiName,
         rs_definition: Option<Box<Type>>,
         cpp_definition: String,
+        /// If this is a `std::variant`, how we'll expose its alternatives.
+        std_variant: Option<Box<VariantAnalysis>>,
     },
     /// A simple note that we want to make a constructor for
     /// a `std::string` on the heap.
     StringConstructor { name: ApiName },
+    /// A note that we want a C++ function which assertion failures can be
+    /// routed to, which calls into Rust to panic.
+    AssertionHandler { name: ApiName },
+    /// A note that we want to sort C++ exceptions of the types listed in
+    /// `exception_type!` into the variants of an enum.
+    ExceptionTypes { name: ApiName },
+    /// A note that we want a function to check that the C++ library has
+    /// the ABI version given in `abi_version!`.
+    AbiVersionCheck { name: ApiName },
+    /// A type with no contents, listed in `namespace_type!`, whose
+    /// associated functions are the free functions of a namespace.
+    NamespaceType { name: ApiName },
     /// A function. May include some analysis.
     Function {
         name: ApiName,
@@ -512,7 +537,11 @@ pub(crate) enum Api<T: AnalysisPhase> {
     },
     /// An enum encountered in the
     /// `bindgen` output.
-    Enum { name: ApiName, item: ItemEnum },
+    Enum {
+        name: ApiName,
+        item: ItemEnum,
+        aliases: Vec<EnumAlias>,
+    },
     /// A struct encountered in the
     /// `bindgen` output.
     Struct {
@@ -594,6 +623,10 @@ impl<T: AnalysisPhase> Api<T> {
             Api::OpaqueTypedef { name, .. } => name,
             Api::ConcreteType { name, .. } => name,
             Api::StringConstructor { name } => name,
+            Api::AssertionHandler { name } => name,
+            Api::ExceptionTypes { name } => name,
+            Api::AbiVersionCheck { name } => name,
+            Api::NamespaceType { name } => name,
             Api::Function { name, .. } => name,
             Api::Const { name, .. } => name,
             Api::Typedef { name, .. } => name,
@@ -640,6 +673,41 @@ impl<T: AnalysisPhase> Api<T> {
         matches!(self, Api::IgnoredItem { .. })
     }
 
+    /// Whether this API tells us nothing beyond what `other` does, such
+    /// that if both have the same name (for example because they're
+    /// declared in several headers) we can keep just `other`. Attributes
+    /// such as doc comments are disregarded. Two genuinely different
+    /// items of the same name are not redeclarations of each other.
+    pub(crate) fn is_redeclaration_of(&self, other: &Self) -> bool {
+        if self.name() != other.name() {
+            return false;
+        }
+        match (self, other) {
+            (
+                Api::ForwardDeclaration { err: None, .. },
+                Api::ForwardDeclaration { err: None, .. } | Api::Struct { .. },
+            ) => true,
+            (Api::Struct { details: a, .. }, Api::Struct { details: b, .. }) => {
+                totokens_equal(&a.item.generics, &b.item.generics)
+                    && totokens_equal(&a.item.fields, &b.item.fields)
+            }
+            (Api::Enum { item: a, .. }, Api::Enum { item: b, .. }) => {
+                totokens_equal(&a.generics, &b.generics) && totokens_equal(&a.variants, &b.variants)
+            }
+            (
+                Api::Typedef {
+                    item: TypedefKind::Type(a),
+                    ..
+                },
+                Api::Typedef {
+                    item: TypedefKind::Type(b),
+                    ..
+                },
+            ) => totokens_equal(&a.ty, &b.ty),
+            _ => false,
+        }
+    }
+
     pub(crate) fn valid_types(&self) -> Box<dyn Iterator<Item = QualifiedName>> {
         match self {
             Api::Subclass { name, .. } => Box::new(
@@ -720,11 +788,16 @@ impl<T: AnalysisPhase> Api<T> {
     pub(crate) fn enum_unchanged(
         name: ApiName,
         item: ItemEnum,
+        aliases: Vec<EnumAlias>,
     ) -> Result<Box<dyn Iterator<Item = Api<T>>>, ConvertErrorWithContext>
     where
         T: 'static,
     {
-        Ok(Box::new(std::iter::once(Api::Enum { name, item })))
+        Ok(Box::new(std::iter::once(Api::Enum {
+            name,
+            item,
+            aliases,
+        })))
     }
 
     /// Display some details of each API. It's a bit unfortunate that we can't
@@ -56,6 +56,17 @@ impl<P: AnalysisPhase> ApiVec<P> {
                 // This is already an IgnoredItem or something else where
                 // we can silently drop it.
                 log::info!("Discarding duplicate API for {}", name);
+            } else if self.find_redeclaration(|existing| api.is_redeclaration_of(existing)) {
+                // Several headers may declare the same thing, and we've
+                // already got a declaration at least as good as this one.
+                log::info!("Discarding redeclaration of {}", name);
+            } else if self.find_redeclaration(|existing| existing.is_redeclaration_of(&api)) {
+                // This is a definition of something which we've only seen
+                // declared so far.
+                let name = name.clone();
+                log::info!("Replacing declaration of {} with its definition", name);
+                self.retain(|existing| existing.name() != &name);
+                self.push(api)
             } else {
                 log::info!(
                     "Duplicate API for {} - removing all of them and replacing with an IgnoredItem.",
@@ -78,6 +89,10 @@ impl<P: AnalysisPhase> ApiVec<P> {
         self.names.contains(name)
     }
 
+    fn find_redeclaration(&self, f: impl Fn(&Api<P>) -> bool) -> bool {
+        self.apis.iter().any(f)
+    }
+
     pub(crate) fn new() -> Self {
         Self::default()
     }
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use syn::{Type, TypePtr};
+use syn::{GenericArgument, PathArguments, Type, TypePtr, TypeReference};
 
 use crate::conversion::{
     analysis::fun::function_wrapper::{CppConversionType, TypeConversionPolicy},
@@ -84,6 +84,24 @@ impl TypeConversionPolicy {
         ))
     }
 
+    /// The C++ type `T` of a `std::shared_ptr<T>` or `std::weak_ptr<T>`.
+    fn smart_ptr_target_type(
+        &self,
+        cpp_name_map: &CppNameMap,
+    ) -> Result<String, ConvertErrorFromCpp> {
+        let target = match self.cxxbridge_type() {
+            Type::Path(typ) => match typ.path.segments.last().map(|seg| &seg.arguments) {
+                Some(PathArguments::AngleBracketed(args)) => match args.args.first() {
+                    Some(GenericArgument::Type(ty)) => ty,
+                    _ => panic!("Smart pointer without a type argument"),
+                },
+                _ => panic!("Smart pointer without a type argument"),
+            },
+            _ => panic!("Not a smart pointer"),
+        };
+        cpp_name_map.type_to_cpp(target)
+    }
+
     pub(super) fn cpp_conversion(
         &self,
         var_name: &str,
@@ -93,9 +111,10 @@ impl TypeConversionPolicy {
         // If is_return we want to avoid unnecessary std::moves because they
         // make RVO less effective
         Ok(match self.cpp_conversion {
-            CppConversionType::None | CppConversionType::FromReturnValueToPlacementPtr => {
-                Some(var_name.to_string())
-            }
+            CppConversionType::None
+            | CppConversionType::FromReturnValueToPlacementPtr
+            | CppConversionType::FromBoolToInteger
+            | CppConversionType::FromLongDoubleToDouble => Some(var_name.to_string()),
             CppConversionType::FromPointerToReference { .. } => Some(format!("(*{var_name})")),
             CppConversionType::Move => Some(format!("std::move({var_name})")),
             CppConversionType::FromUniquePtrToValue | CppConversionType::FromPtrToMove => {
@@ -116,6 +135,66 @@ impl TypeConversionPolicy {
             }
             CppConversionType::IgnoredPlacementPtrParameter => None,
             CppConversionType::FromReferenceToPointer { .. } => Some(format!("&{var_name}")),
+            CppConversionType::FromConstSharedPtr => Some(format!(
+                "std::const_pointer_cast<{}>({var_name})",
+                self.smart_ptr_target_type(cpp_name_map)?
+            )),
+            CppConversionType::FromConstReferenceToMutable => {
+                let target = match self.cxxbridge_type() {
+                    Type::Reference(TypeReference { elem, .. }) => elem.as_ref(),
+                    _ => panic!("Not a reference"),
+                };
+                Some(format!(
+                    "const_cast<{}&>({var_name})",
+                    cpp_name_map.type_to_cpp(target)?
+                ))
+            }
+            CppConversionType::FromSliceToSpan => {
+                Some(format!("autocxx_slice_to_span({var_name})"))
+            }
+            CppConversionType::FromReferenceToStdReferenceWrapper => {
+                Some(format!("std::ref({var_name})"))
+            }
+            CppConversionType::FromStdReferenceWrapperToReference => {
+                Some(format!("{var_name}.get()"))
+            }
+            CppConversionType::FromSliceToVector => {
+                let elem = match self.cxxbridge_type() {
+                    Type::Reference(TypeReference { elem, .. }) => match elem.as_ref() {
+                        Type::Slice(slice) => slice.elem.as_ref(),
+                        _ => panic!("Not a slice"),
+                    },
+                    _ => panic!("Not a reference"),
+                };
+                Some(format!(
+                    "std::vector<{}>({var_name}.begin(), {var_name}.end())",
+                    cpp_name_map.type_to_cpp(elem)?
+                ))
+            }
+            CppConversionType::FromPointerToArrayReference(ref dims) => {
+                let (const_string, elem) = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr {
+                        mutability: Some(_),
+                        elem,
+                        ..
+                    }) => ("", elem.as_ref()),
+                    Type::Ptr(TypePtr { elem, .. }) => ("const ", elem.as_ref()),
+                    _ => panic!("Not a pointer"),
+                };
+                let dims: String = dims.iter().map(|dim| format!("[{dim}]")).collect();
+                Some(format!(
+                    "(*reinterpret_cast<{const_string}{}(*){dims}>({var_name}))",
+                    cpp_name_map.type_to_cpp(elem)?
+                ))
+            }
+            CppConversionType::FromConstWeakPtr => {
+                let target = self.smart_ptr_target_type(cpp_name_map)?;
+                // There's no const_pointer_cast for weak_ptr, so go via a
+                // shared_ptr which shares the same control block.
+                Some(format!(
+                    "std::weak_ptr<{target}>(std::const_pointer_cast<{target}>({var_name}.lock()))"
+                ))
+            }
         })
     }
 }
@@ -8,30 +8,34 @@
 
 mod function_wrapper_cpp;
 mod new_and_delete_prelude;
+mod span_prelude;
 pub(crate) mod type_to_cpp;
 
 use crate::{
     conversion::analysis::fun::{function_wrapper::CppFunctionKind, FnAnalysis},
     types::{make_ident, QualifiedName},
-    CppCodegenOptions, CppFilePair,
+    CppCodegenOptions, CppFilePair, CppWrapperFunction,
 };
 use autocxx_parser::IncludeCppConfig;
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 use itertools::Itertools;
 use std::borrow::Cow;
+use syn::{parse_quote, Ident, ItemEnum};
 use type_to_cpp::CppNameMap;
 
 use super::{
     analysis::{
         fun::{
-            function_wrapper::{CppFunction, CppFunctionBody},
+            function_wrapper::{CppConversionType, CppFunction, CppFunctionBody},
             FnPhase, PodAndDepAnalysis,
         },
         pod::PodAnalysis,
     },
-    api::{Api, Provenance, SubclassName, TypeKind},
+    api::{Api, ApiName, Layout, Provenance, SubclassName, TypeKind},
     apivec::ApiVec,
+    codegen_rs::copy_types::{find_duplicable_types, Duplicability},
+    utilities::EXCEPTION_TAG_PREFIX,
     ConvertErrorFromCpp,
 };
 
@@ -41,6 +45,7 @@ enum Header {
     CxxH,
     CxxgenH,
     NewDeletePrelude,
+    SpanPrelude,
 }
 
 impl Header {
@@ -64,6 +69,7 @@ impl Header {
                 format!("#include \"{prefix}{cxxgen_header_name}\"")
             }
             Header::NewDeletePrelude => new_and_delete_prelude::NEW_AND_DELETE_PRELUDE.to_string(),
+            Header::SpanPrelude => span_prelude::SPAN_PRELUDE.to_string(),
         }
     }
 
@@ -102,6 +108,7 @@ pub(crate) struct CppCodeGenerator<'a> {
     config: &'a IncludeCppConfig,
     cpp_codegen_options: &'a CppCodegenOptions<'a>,
     cxxgen_header_name: &'a str,
+    duplicable_types: HashMap<QualifiedName, Duplicability>,
 }
 
 struct SubclassFunction<'a> {
@@ -124,6 +131,7 @@ impl<'a> CppCodeGenerator<'a> {
             config,
             cpp_codegen_options,
             cxxgen_header_name,
+            duplicable_types: find_duplicable_types(apis, config),
         };
         // The 'filter' on the following line is designed to ensure we don't accidentally
         // end up out of sync with needs_cpp_codegen
@@ -142,6 +150,9 @@ impl<'a> CppCodeGenerator<'a> {
         for api in apis {
             match &api {
                 Api::StringConstructor { .. } => self.generate_string_constructor(),
+                Api::AssertionHandler { name } => self.generate_assertion_handler(name),
+                Api::ExceptionTypes { .. } => self.generate_exception_trycatch(),
+                Api::AbiVersionCheck { .. } => self.generate_abi_version_check(),
                 Api::Function {
                     analysis:
                         FnAnalysis {
@@ -173,7 +184,7 @@ impl<'a> CppCodeGenerator<'a> {
                         None => Cow::Borrowed(cpp_definition),
                     };
 
-                    self.generate_typedef(api.name(), &effective_cpp_definition)
+                    self.generate_concrete_typedef(api.name(), &effective_cpp_definition)
                 }
                 Api::CType { typename, .. } => self.generate_ctype_typedef(typename),
                 Api::Subclass { .. } => deferred_apis.push(api),
@@ -190,19 +201,32 @@ impl<'a> CppCodeGenerator<'a> {
                 }
                 Api::Struct {
                     name,
+                    details,
                     analysis:
                         PodAndDepAnalysis {
                             pod:
                                 PodAnalysis {
                                     kind: TypeKind::Pod,
+                                    platform_dependent_layout,
                                     ..
                                 },
                             ..
                         },
-                    ..
                 } => {
-                    self.generate_pod_assertion(name.qualified_cpp_name());
+                    let duplicable = !matches!(
+                        self.duplicable_types.get(&name.name),
+                        None | Some(Duplicability::Neither)
+                    );
+                    self.generate_pod_assertion(
+                        name.qualified_cpp_name(),
+                        details
+                            .layout
+                            .as_ref()
+                            .filter(|_| !platform_dependent_layout),
+                        duplicable,
+                    );
                 }
+                Api::Enum { name, item, .. } => self.generate_enum_assertion(name, item)?,
                 _ => panic!("Should have filtered on needs_cpp_codegen"),
             }
         }
@@ -288,7 +312,7 @@ impl<'a> CppCodeGenerator<'a> {
         s
     }
 
-    fn generate_pod_assertion(&mut self, name: String) {
+    fn generate_pod_assertion(&mut self, name: String, layout: Option<&Layout>, duplicable: bool) {
         // These assertions are generated by cxx for trivial ExternTypes but
         // *only if* such types are used as trivial types in the cxx::bridge.
         // It's possible for types which we generate to be used even without
@@ -296,14 +320,59 @@ impl<'a> CppCodeGenerator<'a> {
         // can result in destructors for nested types being called multiple times
         // if we represent them as trivial types. So generate an extra
         // assertion to make sure.
-        let declaration = Some(format!("static_assert(::rust::IsRelocatable<{name}>::value, \"type {name} should be trivially move constructible and trivially destructible to be used with generate_pod! in autocxx\");"));
+        let mut declaration = format!("static_assert(::rust::IsRelocatable<{name}>::value, \"type {name} should be trivially move constructible and trivially destructible to be used with generate_pod! in autocxx\");");
+        // Rust mirrors the layout which bindgen calculated for this type, and
+        // we make a matching assertion on the Rust side. Check that the C++
+        // compiler agrees, in case it's been given different ABI options.
+        if let Some(Layout { size, align, .. }) = layout {
+            declaration.push_str(&format!("\nstatic_assert(sizeof({name}) == {size} && alignof({name}) == {align}, \"type {name} should have the size ({size}) and alignment ({align}) calculated by bindgen to be used with generate_pod! in autocxx\");"));
+        }
+        // Rust copies such types bytewise, using a derived `Copy` or `Clone`,
+        // rather than calling the C++ copy constructor.
+        if duplicable {
+            declaration.push_str(&format!("\nstatic_assert(std::is_trivially_copyable<{name}>::value, \"type {name} should be trivially copyable to be given Clone and Copy in Rust by autocxx\");"));
+        }
+        let declaration = Some(declaration);
         self.additional_functions.push(ExtraCpp {
             declaration,
-            headers: vec![Header::CxxH],
+            headers: vec![Header::CxxH, Header::System("type_traits")],
             ..Default::default()
         })
     }
 
+    fn generate_enum_assertion(
+        &mut self,
+        name: &ApiName,
+        item: &ItemEnum,
+    ) -> Result<(), ConvertErrorFromCpp> {
+        // Enums are passed by value through the cxx::bridge, so the Rust
+        // representation chosen by bindgen must exactly match the C++
+        // underlying type. Anonymous enums can't be named in C++, but
+        // equally can't be used as parameters or return types.
+        if name.name.get_final_item().starts_with("_bindgen_ty_") {
+            return Ok(());
+        }
+        let repr = item
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("repr"))
+            .and_then(|attr| attr.parse_args::<Ident>().ok());
+        let repr = match repr {
+            Some(repr) => self
+                .original_name_map
+                .type_to_cpp(&parse_quote! { #repr })?,
+            None => return Ok(()),
+        };
+        let name = name.qualified_cpp_name();
+        let declaration = Some(format!("static_assert(sizeof({name}) == sizeof({repr}) && alignof({name}) == alignof({repr}), \"enum {name} should have the same size and alignment as its Rust representation, {repr}\");"));
+        self.additional_functions.push(ExtraCpp {
+            declaration,
+            headers: vec![Header::System("cstdint")],
+            ..Default::default()
+        });
+        Ok(())
+    }
+
     fn generate_string_constructor(&mut self) {
         let makestring_name = self.config.get_makestring_name();
         let declaration = Some(format!("inline std::unique_ptr<std::string> {makestring_name}(::rust::Str str) {{ return std::make_unique<std::string>(std::string(str)); }}"));
@@ -318,6 +387,115 @@ impl<'a> CppCodeGenerator<'a> {
         })
     }
 
+    /// The strings are passed as bytes rather than as `rust::Str`, which
+    /// would throw (and thus terminate without a message) if they weren't
+    /// UTF-8.
+    fn generate_assertion_handler(&mut self, name: &ApiName) {
+        let handler_name = self
+            .config
+            .get_assertion_handler()
+            .expect("Assertion handler generated only if requested");
+        let rust_name = name.name.get_final_item();
+        let signature =
+            format!("void {handler_name}(const char* expr, const char* file, int line)");
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(format!("[[noreturn]] {signature};")),
+            definition: Some(format!(
+                "{signature} {{
+auto autocxx_bytes = [](const char* s) {{
+    return rust::Slice<const uint8_t>(reinterpret_cast<const uint8_t*>(s), s ? std::strlen(s) : 0);
+}};
+{rust_name}(autocxx_bytes(expr), autocxx_bytes(file), line);
+std::abort();
+}}
+"
+            )),
+            cpp_headers: vec![
+                Header::System("cstdint"),
+                Header::System("cstdlib"),
+                Header::System("cstring"),
+                Header::CxxgenH,
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// The expected version is evaluated here, so it comes from the headers
+    /// as they were when this was compiled; the actual version is whatever
+    /// the library we end up linked against reports at runtime. The check is
+    /// run by initializing a variable in the header, so that it happens as
+    /// the program starts in each translation unit which includes it, which
+    /// is at least the one cxx generates for these bindings.
+    fn generate_abi_version_check(&mut self) {
+        let (actual, expected) = self
+            .config
+            .get_abi_version()
+            .expect("ABI version check generated only if requested");
+        let check_name = self.config.get_abi_version_check_name();
+        let checked_name = self.config.get_abi_version_checked_name();
+        let declaration = Some(format!(
+            "inline bool {check_name}() {{
+    const uint64_t autocxx_actual = static_cast<uint64_t>({actual});
+    const uint64_t autocxx_expected = static_cast<uint64_t>({expected});
+    if (autocxx_actual != autocxx_expected) {{
+        std::fprintf(stderr, \"C++ library has ABI version %llu but the bindings were generated for version %llu\\n\", static_cast<unsigned long long>(autocxx_actual), static_cast<unsigned long long>(autocxx_expected));
+        std::abort();
+    }}
+    return true;
+}}
+static const bool {checked_name} = {check_name}();"
+        ));
+        self.additional_functions.push(ExtraCpp {
+            declaration,
+            headers: vec![
+                Header::System("cstdint"),
+                Header::System("cstdio"),
+                Header::System("cstdlib"),
+            ],
+            ..Default::default()
+        })
+    }
+
+    /// cxx calls `rust::behavior::trycatch` around each function returning
+    /// a `Result`, and lets us provide our own. Ours tags the message of each
+    /// exception of a type listed in `exception_type!` so that we can later
+    /// sort it into the right variant of an enum. That needs the exception's
+    /// `what()`, so we insist that each type derives from `std::exception`.
+    fn generate_exception_trycatch(&mut self) {
+        let assertions = self
+            .config
+            .get_exception_types()
+            .iter()
+            .map(|(cpp_type, _)| {
+                format!(
+                    "static_assert(std::is_base_of<std::exception, {cpp_type}>::value, \"type {cpp_type} should derive from std::exception to be used with exception_type! in autocxx\");\n"
+                )
+            })
+            .join("");
+        let catches = self
+            .config
+            .get_exception_types()
+            .iter()
+            .enumerate()
+            .map(|(index, (cpp_type, _))| {
+                format!(
+                    "catch (const {cpp_type}& e) {{\n  fail((std::string(\"{EXCEPTION_TAG_PREFIX}{index}:\") + e.what()).c_str());\n}}"
+                )
+            })
+            .join(" ");
+        self.additional_functions.push(ExtraCpp {
+            declaration: Some(format!(
+                "{assertions}namespace rust {{\nnamespace behavior {{\ntemplate <typename Try, typename Fail>\nstatic void trycatch(Try &&func, Fail &&fail) noexcept try {{\n  func();\n}} {catches} catch (const std::exception& e) {{\n  fail(e.what());\n}}\n}} // namespace behavior\n}} // namespace rust\n"
+            )),
+            headers: vec![
+                Header::System("exception"),
+                Header::System("string"),
+                Header::System("type_traits"),
+            ],
+            ..Default::default()
+        })
+    }
+
     fn generate_cpp_function(&mut self, details: &CppFunction) -> Result<(), ConvertErrorFromCpp> {
         self.additional_functions
             .push(self.generate_cpp_function_inner(
@@ -461,12 +639,15 @@ impl<'a> CppCodeGenerator<'a> {
         if matches!(&details.payload, CppFunctionBody::ConstructSuperclass(_)) {
             arg_list.next();
         }
+        let operands = arg_list
+            .chain(details.trailing_args.iter().cloned())
+            .collect_vec();
         let arg_list = if details.pass_obs_field {
             std::iter::once("*obs".to_string())
-                .chain(arg_list)
+                .chain(operands.iter().cloned())
                 .join(",")
         } else {
-            arg_list.join(", ")
+            operands.join(", ")
         };
         let (mut underlying_function_call, field_assignments, need_allocators) = match &details
             .payload
@@ -556,6 +737,92 @@ impl<'a> CppCodeGenerator<'a> {
                 "".to_string(),
                 true,
             ),
+            CppFunctionBody::UnionTag(tag_field) => (
+                format!("static_cast<int64_t>({arg_list}.{tag_field})"),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::UnionMember(union_field, member) => (
+                format!("{arg_list}.{union_field}.{member}"),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::Operator(op) => (op.cpp_expression(&operands), "".to_string(), false),
+            CppFunctionBody::RangeLen => (
+                format!(
+                    "static_cast<size_t>(std::distance({0}.begin(), {0}.end()))",
+                    operands[0]
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::RangeBegin => (
+                format!("new auto({0}.begin())", operands[0]),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::RangeNext => (
+                format!(
+                    "[&] {{ auto& it = *static_cast<decltype({0}.begin())*>({1}); return it == {0}.end() ? nullptr : std::addressof(*it++); }}()",
+                    operands[0], operands[1]
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::RangeFree => (
+                format!(
+                    "delete static_cast<decltype({0}.begin())*>({1})",
+                    operands[0], operands[1]
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::CallMember(member) => (
+                format!(
+                    "{}.{member}({})",
+                    operands[0],
+                    operands.iter().skip(1).join(", ")
+                ),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::IteratorLookup(method, member) => {
+                let element = match member {
+                    Some(member) => format!("it->{member}"),
+                    None => "*it".to_string(),
+                };
+                // A lambda lets us name the iterator within an expression.
+                (
+                    format!(
+                        "[&] {{ auto it = {0}.{method}({1}); return it == {0}.end() ? nullptr : &{element}; }}()",
+                        operands[0],
+                        operands.iter().skip(1).join(", ")
+                    ),
+                    "".to_string(),
+                    false,
+                )
+            }
+            CppFunctionBody::MakeShared(ty) => (
+                format!("std::make_shared<{}>({arg_list})", self.namespaced_name(ty)),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::VariantIndex => {
+                (format!("{}.index()", operands[0]), "".to_string(), false)
+            }
+            CppFunctionBody::VariantGet(index) => (
+                format!("std::get<{index}>({})", operands[0]),
+                "".to_string(),
+                false,
+            ),
+            CppFunctionBody::AddressOfThreadLocal(ns, id) => {
+                let var = ns
+                    .into_iter()
+                    .cloned()
+                    .chain(std::iter::once(id.to_string()))
+                    .join("::");
+                (format!("&{var}"), "".to_string(), false)
+            }
         };
         if let Some(ret) = &details.return_conversion {
             let call_itself = match conversion_direction {
@@ -595,6 +862,16 @@ impl<'a> CppCodeGenerator<'a> {
             } else {
                 "".into()
             };
+        if let Some(customizer) = &self.cpp_codegen_options.wrapper_body_customizer {
+            if let Some(body) = customizer.customize(&CppWrapperFunction {
+                cpp_name: &details.original_cpp_name,
+                wrapper_name: &name,
+                declaration: &declaration,
+                body: &underlying_function_call,
+            }) {
+                underlying_function_call = body;
+            }
+        }
         let definition_after_sig = format!("{field_assignments} {{ {underlying_function_call} }}",);
         let (declaration, definition) = if requires_rust_declarations {
             (
@@ -608,10 +885,36 @@ impl<'a> CppCodeGenerator<'a> {
             )
         };
         let mut headers = vec![Header::System("memory")];
+        if matches!(
+            details.payload,
+            CppFunctionBody::RangeLen
+                | CppFunctionBody::RangeBegin
+                | CppFunctionBody::RangeNext
+                | CppFunctionBody::RangeFree
+        ) {
+            headers.push(Header::System("iterator"));
+        }
+        if matches!(
+            details.payload,
+            CppFunctionBody::VariantIndex | CppFunctionBody::VariantGet(_)
+        ) {
+            headers.push(Header::System("variant"));
+        }
         if need_allocators {
             headers.push(Header::System("stddef.h"));
             headers.push(Header::NewDeletePrelude);
         }
+        if details
+            .argument_conversion
+            .iter()
+            .any(|conv| matches!(conv.cpp_conversion, CppConversionType::FromSliceToSpan))
+        {
+            headers.push(Header::System("cstddef"));
+            headers.push(Header::System("span"));
+            headers.push(Header::System("type_traits"));
+            headers.push(Header::CxxH);
+            headers.push(Header::SpanPrelude);
+        }
         Ok(ExtraCpp {
             declaration,
             definition,
@@ -637,6 +940,20 @@ impl<'a> CppCodeGenerator<'a> {
         })
     }
 
+    /// Concrete types named after the user's own typedefs may live in a
+    /// namespace, in which case we're merely repeating that typedef.
+    fn generate_concrete_typedef(&mut self, tn: &QualifiedName, definition: &str) {
+        let mut type_definition = format!("typedef {definition} {};", tn.get_final_item());
+        let namespaces: Vec<_> = tn.ns_segment_iter().collect();
+        for ns in namespaces.into_iter().rev() {
+            type_definition = format!("namespace {ns} {{ {type_definition} }}");
+        }
+        self.additional_functions.push(ExtraCpp {
+            type_definition: Some(type_definition),
+            ..Default::default()
+        })
+    }
+
     fn generate_subclass(
         &mut self,
         superclass: &QualifiedName,
@@ -0,0 +1,36 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indoc::indoc;
+
+/// This is logic to make a `std::span` from a `rust::Slice`.
+/// bindgen doesn't tell us whether the elements of a `std::span` parameter
+/// are `const`, nor its extent, so we can't check either during analysis.
+/// Instead we convert to whatever span the called function takes, and
+/// refuse to compile where we can't do that soundly.
+pub(super) static SPAN_PRELUDE: &str = indoc! {"
+    #ifndef AUTOCXX_SPAN_PRELUDE
+    #define AUTOCXX_SPAN_PRELUDE
+    // Mechanics to pass a rust::Slice to a std::span parameter
+    template <typename T> struct autocxx_slice_as_span {
+      ::rust::Slice<T> slice;
+      template <typename U, std::size_t Extent>
+      operator std::span<U, Extent>() const {
+        static_assert(std::is_const<U>::value || !std::is_const<T>::value,
+                      \"a std::span of mutable elements must be passed from a mutable slice - use mut_span_param! in autocxx\");
+        static_assert(Extent == std::dynamic_extent,
+                      \"autocxx doesn't support std::span parameters with a fixed extent\");
+        return std::span<U, Extent>(slice.data(), slice.size());
+      }
+    };
+    template <typename T>
+    autocxx_slice_as_span<T> autocxx_slice_to_span(::rust::Slice<T> slice) {
+      return autocxx_slice_as_span<T>{slice};
+    }
+    #endif // AUTOCXX_SPAN_PRELUDE
+"};
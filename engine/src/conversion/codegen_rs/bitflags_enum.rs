@@ -0,0 +1,157 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use proc_macro2::Ident;
+use quote::quote;
+use syn::{parse_quote, Attribute, Item, ItemEnum};
+
+use crate::types::make_ident;
+
+/// Turn an enum which bindgen has represented as a Rust `enum` into a
+/// newtype around its underlying integer, as requested by `bitflags_enum!`.
+/// A Rust `enum` mustn't hold any value other than its variants, whereas
+/// flags are routinely combined into values which have no name.
+///
+/// Returns the struct plus the items implementing its operators, or
+/// `None` if bindgen didn't tell us the underlying integer type.
+pub(super) fn generate_bitflags_enum(item: &ItemEnum) -> Option<(Item, Vec<Item>)> {
+    let repr: Ident = item
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("repr"))
+        .and_then(|attr| attr.parse_args().ok())?;
+    let id = &item.ident;
+    let vis = &item.vis;
+    // Keep doc comments, but replace bindgen's
+    // representation and derives.
+    let attrs: Vec<&Attribute> = item
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path.is_ident("repr") && !attr.path.is_ident("derive"))
+        .collect();
+    let consts = item.variants.iter().map(|variant| {
+        let variant_attrs = &variant.attrs;
+        let variant_id = &variant.ident;
+        let value = variant
+            .discriminant
+            .as_ref()
+            .map(|(_, expr)| expr.clone())
+            .expect("bindgen always gives enumerators explicit values");
+        quote! {
+            #(#variant_attrs)*
+            pub const #variant_id: Self = Self(#value);
+        }
+    });
+    let newtype = parse_quote! {
+        #(#attrs)*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        #vis struct #id(pub #repr);
+    };
+    let mut impls: Vec<Item> = vec![parse_quote! {
+        #[allow(non_upper_case_globals)]
+        impl #id {
+            #(#consts)*
+
+            /// A value with no flags set.
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// The underlying integer value.
+            pub const fn bits(self) -> #repr {
+                self.0
+            }
+
+            /// Make a value from its underlying integer, keeping any bits
+            /// which don't correspond to a named flag.
+            pub const fn from_bits_retain(bits: #repr) -> Self {
+                Self(bits)
+            }
+
+            /// Whether no flags are set.
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether all the flags set in `other` are also set in `self`.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Whether any of the flags set in `other` are also set in `self`.
+            pub const fn intersects(self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+        }
+    }];
+    for (op_trait, op_fn, assign_trait, assign_fn, op) in [
+        (
+            "BitOr",
+            "bitor",
+            "BitOrAssign",
+            "bitor_assign",
+            quote! { | },
+        ),
+        (
+            "BitAnd",
+            "bitand",
+            "BitAndAssign",
+            "bitand_assign",
+            quote! { & },
+        ),
+        (
+            "BitXor",
+            "bitxor",
+            "BitXorAssign",
+            "bitxor_assign",
+            quote! { ^ },
+        ),
+    ] {
+        let op_trait = make_ident(op_trait);
+        let op_fn = make_ident(op_fn);
+        let assign_trait = make_ident(assign_trait);
+        let assign_fn = make_ident(assign_fn);
+        impls.push(parse_quote! {
+            impl ::core::ops::#op_trait for #id {
+                type Output = Self;
+                fn #op_fn(self, other: Self) -> Self {
+                    Self(self.0 #op other.0)
+                }
+            }
+        });
+        impls.push(parse_quote! {
+            impl ::core::ops::#assign_trait for #id {
+                fn #assign_fn(&mut self, other: Self) {
+                    self.0 = self.0 #op other.0;
+                }
+            }
+        });
+    }
+    impls.push(parse_quote! {
+        impl ::core::ops::Not for #id {
+            type Output = Self;
+            fn not(self) -> Self {
+                Self(!self.0)
+            }
+        }
+    });
+    // Any combination of flags is valid, so only reject values which
+    // don't fit.
+    impls.push(parse_quote! {
+        impl ::core::convert::TryFrom<i64> for #id {
+            type Error = autocxx::InvalidEnumValue;
+            fn try_from(value: i64) -> ::core::result::Result<Self, Self::Error> {
+                <#repr as ::core::convert::TryFrom<i64>>::try_from(value)
+                    .map(Self)
+                    .map_err(|_| autocxx::InvalidEnumValue(value))
+            }
+        }
+    });
+    Some((Item::Struct(newtype), impls))
+}
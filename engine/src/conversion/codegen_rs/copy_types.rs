@@ -0,0 +1,164 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+
+use autocxx_parser::IncludeCppConfig;
+use syn::{GenericArgument, PathArguments, Type};
+
+use crate::{
+    conversion::{
+        analysis::{
+            fun::{FnPhase, PodAndDepAnalysis},
+            pod::PodAnalysis,
+        },
+        api::{Api, TypeKind},
+        apivec::ApiVec,
+    },
+    types::QualifiedName,
+};
+
+/// Which of `Clone` and `Copy` we can derive for a type.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Duplicability {
+    Neither,
+    Clone,
+    Copy,
+}
+
+/// Primitive types which bindgen may use for fields, all of which are
+/// `Copy`. This includes the `c_int` family, whichever path they're
+/// reached through.
+const PRIMITIVES: &[&str] = &[
+    "bool",
+    "char",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+    "c_char",
+    "c_schar",
+    "c_uchar",
+    "c_short",
+    "c_ushort",
+    "c_int",
+    "c_uint",
+    "c_long",
+    "c_ulong",
+    "c_longlong",
+    "c_ulonglong",
+];
+
+/// Work out which POD structs we can make `Copy` (or failing that, just
+/// `Clone`) in Rust. A POD struct is trivially copyable in C++ if its copy
+/// constructor is implicit and all of its fields and bases are trivially
+/// copyable, in which case copying its bytes is exactly what C++ would do.
+/// Fields are what decide between `Copy` and `Clone`: for instance,
+/// bindgen's enums are only `Clone`.
+pub(crate) fn find_duplicable_types(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> HashMap<QualifiedName, Duplicability> {
+    let mut results: HashMap<QualifiedName, Duplicability> = apis
+        .iter()
+        .filter_map(|api| match api {
+            Api::Struct {
+                name,
+                analysis:
+                    PodAndDepAnalysis {
+                        pod:
+                            PodAnalysis {
+                                kind: TypeKind::Pod,
+                                is_generic: false,
+                                ..
+                            },
+                        constructors,
+                        ..
+                    },
+                ..
+            } if constructors.implicit_copy_constructor => {
+                Some((name.name.clone(), Duplicability::Copy))
+            }
+            Api::Enum { name, .. } if config.is_bitflags_enum(&name.name.to_cpp_name()) => {
+                Some((name.name.clone(), Duplicability::Copy))
+            }
+            Api::Enum { name, .. } => Some((name.name.clone(), Duplicability::Clone)),
+            _ => None,
+        })
+        .collect();
+    // Each pass can only ever downgrade types, so this terminates.
+    loop {
+        let mut changed = false;
+        for api in apis.iter() {
+            let (name, details) = match api {
+                Api::Struct { name, details, .. } => (name, details),
+                _ => continue,
+            };
+            if let Some(current) = results.get(&name.name).copied() {
+                let mut possible = details
+                    .item
+                    .fields
+                    .iter()
+                    .map(|field| field_duplicability(&field.ty, &results))
+                    .min()
+                    .unwrap_or(Duplicability::Copy);
+                // Rust can't derive `Clone` alone for a packed struct, because
+                // that would need references to unaligned fields.
+                if possible == Duplicability::Clone
+                    && details.layout.as_ref().map(|l| l.packed).unwrap_or(false)
+                {
+                    possible = Duplicability::Neither;
+                }
+                if possible < current {
+                    results.insert(name.name.clone(), possible);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    results
+}
+
+fn field_duplicability(ty: &Type, known: &HashMap<QualifiedName, Duplicability>) -> Duplicability {
+    match ty {
+        Type::Ptr(_) | Type::BareFn(_) => Duplicability::Copy,
+        Type::Array(array) => field_duplicability(&array.elem, known),
+        Type::Path(typ) => {
+            let last_seg = typ.path.segments.last().unwrap();
+            if PRIMITIVES.iter().any(|prim| last_seg.ident == prim) {
+                return Duplicability::Copy;
+            }
+            // bindgen represents nullable function pointers as an `Option`.
+            if last_seg.ident == "Option" {
+                if let PathArguments::AngleBracketed(args) = &last_seg.arguments {
+                    if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                        return field_duplicability(inner, known);
+                    }
+                }
+            }
+            known
+                .get(&QualifiedName::from_type_path(typ))
+                .copied()
+                .unwrap_or(Duplicability::Neither)
+        }
+        _ => Duplicability::Neither,
+    }
+}
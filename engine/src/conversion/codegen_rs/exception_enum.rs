@@ -0,0 +1,80 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+use proc_macro2::{Ident, Literal};
+use quote::quote;
+use syn::{parse_quote, Item};
+
+use crate::conversion::utilities::EXCEPTION_TAG_PREFIX;
+
+/// Generate the enum into which a `cxx::Exception` can be converted, with
+/// a variant for each C++ exception type listed in `exception_type!`.
+/// Our C++ `trycatch` tags the message of each such exception with the
+/// index of its type, which we use here to pick the variant.
+pub(super) fn generate_exception_enum(id: &Ident, config: &IncludeCppConfig) -> Vec<Item> {
+    let exception_types = config.get_exception_types();
+    let variants = exception_types.iter().map(|(cpp_type, variant)| {
+        let doc = format!("A C++ `{cpp_type}`, with the message from its `what()`.");
+        quote! {
+            #[doc = #doc]
+            #variant(::std::string::String)
+        }
+    });
+    let from_arms = exception_types
+        .iter()
+        .enumerate()
+        .map(|(index, (_, variant))| {
+            let index = Literal::string(&index.to_string());
+            quote! {
+                ::core::option::Option::Some((#index, what)) => Self::#variant(what.into())
+            }
+        });
+    let variant_ids = exception_types.iter().map(|(_, variant)| variant);
+    let mut items: Vec<Item> = vec![
+        parse_quote! {
+            /// A C++ exception, sorted according to its type. See `exception_type!`.
+            #[derive(Debug)]
+            pub enum #id {
+                #(#variants,)*
+                /// An exception of any other type.
+                Other(cxx::Exception),
+            }
+        },
+        parse_quote! {
+            impl ::core::convert::From<cxx::Exception> for #id {
+                fn from(exception: cxx::Exception) -> Self {
+                    let tagged = exception
+                        .what()
+                        .strip_prefix(#EXCEPTION_TAG_PREFIX)
+                        .and_then(|rest| rest.split_once(':'));
+                    match tagged {
+                        #(#from_arms,)*
+                        _ => Self::Other(exception),
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl ::core::fmt::Display for #id {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    match self {
+                        #(Self::#variant_ids(what) => f.write_str(what),)*
+                        Self::Other(exception) => ::core::fmt::Display::fmt(exception, f),
+                    }
+                }
+            }
+        },
+    ];
+    if !config.no_std() {
+        items.push(parse_quote! {
+            impl ::std::error::Error for #id {}
+        });
+    }
+    items
+}
@@ -10,14 +10,15 @@ use autocxx_parser::IncludeCppConfig;
 use indexmap::set::IndexSet as HashSet;
 use std::borrow::Cow;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::{
     parse::Parser,
     parse_quote,
     punctuated::Punctuated,
     token::{Comma, Unsafe},
-    Attribute, FnArg, ForeignItem, Ident, ImplItem, Item, ReturnType,
+    Attribute, FnArg, ForeignItem, GenericArgument, Ident, ImplItem, Item, Lifetime, PathArguments,
+    PathSegment, ReturnType, Type, Visibility,
 };
 
 use super::{
@@ -28,12 +29,17 @@ use super::{
 };
 use crate::{
     conversion::{
-        analysis::fun::{
-            function_wrapper::TypeConversionPolicy, ArgumentAnalysis, FnAnalysis, FnKind,
-            MethodKind, RustRenameStrategy, TraitMethodDetails,
+        analysis::{
+            fun::{
+                function_wrapper::{CppFunction, CppFunctionBody, TypeConversionPolicy},
+                ArgumentAnalysis, FnAnalysis, FnKind, MethodKind, RustRenameStrategy,
+                TraitMethodDetails,
+            },
+            operators::{CppOperator, OperatorTrait},
         },
         api::{Pointerness, UnsafetyNeeded},
     },
+    known_types::known_types,
     types::{Namespace, QualifiedName},
 };
 use crate::{
@@ -97,7 +103,12 @@ pub(super) fn gen_function(
     }
     let cxxbridge_name = analysis.cxxbridge_name;
     let rust_name = &analysis.rust_name;
-    let ret_type = analysis.ret_type;
+    // cxx catches any exception from a function which returns `Result`.
+    let ret_type = if analysis.throws {
+        parse_quote! { -> ::core::result::Result<(), cxx::Exception> }
+    } else {
+        analysis.ret_type
+    };
     let ret_conversion = analysis.ret_conversion;
     let param_details = analysis.param_details;
     let wrapper_function_needed = analysis.cpp_wrapper.is_some();
@@ -114,17 +125,51 @@ pub(super) fn gen_function(
         FnKind::TraitMethod { ref details, .. } => details.trait_call_is_unsafe,
         _ => false,
     };
+    // Where there's no C++ wrapper, any Rust wrapper does nothing more
+    // than call through to cxx, so there's no reason not to inline it.
+    let inline_attr = if wrapper_function_needed {
+        None
+    } else if config.inline_always() {
+        Some(parse_quote! { #[inline(always)] })
+    } else {
+        Some(parse_quote! { #[inline] })
+    };
+    // Point panics at the caller's code rather than at our wrapper.
+    let track_caller_attr = if config.track_caller() && analysis.panics_on_exception {
+        Some(parse_quote! { #[track_caller] })
+    } else {
+        None
+    };
     let fn_generator = FnGenerator {
         param_details: &param_details,
         cxxbridge_name: &cxxbridge_name,
         rust_name,
+        vis: &vis,
         unsafety: &analysis.requires_unsafe,
         always_unsafe_due_to_trait_definition,
         doc_attrs: &doc_attrs,
+        inline_attr,
+        track_caller_attr,
         non_pod_types,
         ret_type: &ret_type,
         ret_conversion: &ret_conversion,
         reference_wrappers: config.unsafe_policy.requires_cpprefs(),
+        boxed_returns: config.boxed_returns(),
+        panics_on_exception: analysis.panics_on_exception,
+        calls_cxx_method: !wrapper_function_needed && matches!(kind, FnKind::Method { .. }),
+        instrumentation: config.instrumentation_macro().map(|mac| {
+            let cpp_name = fun
+                .original_name
+                .clone()
+                .unwrap_or_else(|| fun.ident.to_string());
+            let name = match kind {
+                FnKind::Method { ref impl_for, .. } | FnKind::TraitMethod { ref impl_for, .. } => {
+                    format!("{}::{}", impl_for.to_cpp_name(), cpp_name)
+                }
+                FnKind::Function => QualifiedName::new(ns, make_ident(cpp_name)).to_cpp_name(),
+            };
+            (mac, name)
+        }),
     };
     // In rare occasions, we might need to give an explicit lifetime.
     let (lifetime_tokens, params, ret_type) = add_explicit_lifetime_if_necessary(
@@ -165,6 +210,14 @@ pub(super) fn gen_function(
         }
     }
 
+    if let Some(CppFunction {
+        payload: CppFunctionBody::Operator(op),
+        ..
+    }) = &analysis.cpp_wrapper
+    {
+        bindgen_mod_items.extend(fn_generator.generate_operator_trait_impl(op, &kind));
+    }
+
     let materialization = match kind {
         FnKind::Method { .. } | FnKind::TraitMethod { .. } => None,
         FnKind::Function => match analysis.rust_rename_strategy {
@@ -194,7 +247,16 @@ pub(super) fn gen_function(
     // and the following code will act to unqualify only those types
     // which the user has declared.
     let params = unqualify_params(params);
-    let ret_type = unqualify_ret_type(ret_type.into_owned());
+    let ret_type = if analysis.throws {
+        parse_quote! { -> Result<()> }
+    } else if analysis.panics_on_exception {
+        match unqualify_ret_type(ret_type.into_owned()) {
+            ReturnType::Default => parse_quote! { -> Result<()> },
+            ReturnType::Type(_, ty) => parse_quote! { -> Result<#ty> },
+        }
+    } else {
+        unqualify_ret_type(ret_type.into_owned())
+    };
     // And we need to make an attribute for the namespace that the function
     // itself is in.
     let namespace_attr = if ns.is_empty() || wrapper_function_needed {
@@ -233,11 +295,26 @@ struct FnGenerator<'a> {
     ret_type: &'a ReturnType,
     cxxbridge_name: &'a Ident,
     rust_name: &'a str,
+    /// Visibility of any method we generate. (cxx makes its own
+    /// functions `pub` whatever we ask for.)
+    vis: &'a Visibility,
     unsafety: &'a UnsafetyNeeded,
     always_unsafe_due_to_trait_definition: bool,
     doc_attrs: &'a Vec<Attribute>,
+    /// `#[inline]` or similar, for wrappers which merely call through to cxx.
+    inline_attr: Option<Attribute>,
+    /// `#[track_caller]`, for wrappers which may panic, if the user wants it.
+    track_caller_attr: Option<Attribute>,
     non_pod_types: &'a HashSet<QualifiedName>,
     reference_wrappers: bool,
+    boxed_returns: bool,
+    /// Whether the cxx::bridge binding is a method on the type, rather
+    /// than a free function wrapping it.
+    calls_cxx_method: bool,
+    /// The user's instrumentation macro, and the C++ name to pass to it.
+    instrumentation: Option<(&'a syn::Path, String)>,
+    /// Whether to panic with any C++ exception which cxx caught for us.
+    panics_on_exception: bool,
 }
 
 impl<'a> FnGenerator<'a> {
@@ -256,6 +333,7 @@ impl<'a> FnGenerator<'a> {
         let mut local_variables = Vec::new();
         let mut arg_list = Vec::new();
         let mut ptr_arg_name = None;
+        let mut placement_output_ty = None;
         let mut ret_type: Cow<'a, _> = ret_type
             .map(Cow::Owned)
             .unwrap_or_else(|| Cow::Borrowed(self.ret_type));
@@ -295,6 +373,7 @@ impl<'a> FnGenerator<'a> {
                     ret_type = Cow::Owned(parse_quote! {
                         -> impl autocxx::moveit::new::New<Output = #ty>
                     });
+                    placement_output_ty = Some(ty);
                     arg_list.push(pd.name.to_token_stream());
                 }
             }
@@ -310,12 +389,29 @@ impl<'a> FnGenerator<'a> {
         );
 
         let cxxbridge_name = self.cxxbridge_name;
-        let call_body = MaybeUnsafeStmt::maybe_unsafe(
+        let call = if self.calls_cxx_method {
+            quote! {
+                Self::#cxxbridge_name ( #(#arg_list),* )
+            }
+        } else {
             quote! {
                 cxxbridge::#cxxbridge_name ( #(#arg_list),* )
-            },
+            }
+        };
+        // cxx hands us any exception in a `Result`, which we don't expose.
+        let call = if self.panics_on_exception {
+            let msg = format!("C++ exception from {}: {{}}", self.rust_name);
+            quote! {
+                #call.unwrap_or_else(|e| ::core::panic!(#msg, e))
+            }
+        } else {
+            call
+        };
+        let call_body = MaybeUnsafeStmt::maybe_unsafe(
+            call,
             any_conversion_requires_unsafe || matches!(self.unsafety, UnsafetyNeeded::JustBridge),
         );
+        let call_body = self.instrument(call_body);
         let context_is_unsafe = matches!(self.unsafety, UnsafetyNeeded::Always)
             || self.always_unsafe_due_to_trait_definition;
         let (call_body, ret_type) = match self.ret_conversion {
@@ -349,7 +445,41 @@ impl<'a> FnGenerator<'a> {
             _ => (call_body, ret_type),
         };
 
-        let call_stmts = if let Some(ptr_arg_name) = ptr_arg_name {
+        // Any fallible parameter conversions need us to return a Result.
+        let (call_body, ret_type) = if self
+            .param_details
+            .iter()
+            .any(|pd| pd.conversion.rust_conversion.is_fallible())
+        {
+            let ok_ty = match ret_type.as_ref() {
+                ReturnType::Default => parse_quote! { () },
+                ReturnType::Type(_, ty) => ty.as_ref().clone(),
+            };
+            let call_body = match call_body {
+                MaybeUnsafeStmt::Normal(stmt) => {
+                    MaybeUnsafeStmt::Normal(quote! { ::core::result::Result::Ok(#stmt) })
+                }
+                MaybeUnsafeStmt::NeedsUnsafe(stmt) => MaybeUnsafeStmt::binary(
+                    quote! { ::core::result::Result::Ok(unsafe { #stmt }) },
+                    quote! { ::core::result::Result::Ok(#stmt) },
+                ),
+                MaybeUnsafeStmt::Binary {
+                    in_safe_context,
+                    in_unsafe_context,
+                } => MaybeUnsafeStmt::binary(
+                    quote! { ::core::result::Result::Ok(#in_safe_context) },
+                    quote! { ::core::result::Result::Ok(#in_unsafe_context) },
+                ),
+            };
+            let ret_type: Type = parse_quote! {
+                ::core::result::Result<#ok_ty, ::core::num::TryFromIntError>
+            };
+            (call_body, Cow::Owned(parse_quote! { -> #ret_type }))
+        } else {
+            (call_body, ret_type)
+        };
+
+        let (call_stmts, ret_type) = if let Some(ptr_arg_name) = ptr_arg_name {
             let mut closure_stmts = local_variables;
             closure_stmts.push(MaybeUnsafeStmt::binary(
                 quote! { let #ptr_arg_name = unsafe { #ptr_arg_name.get_unchecked_mut().as_mut_ptr() };},
@@ -357,20 +487,62 @@ impl<'a> FnGenerator<'a> {
             ));
             closure_stmts.push(call_body);
             let closure_stmts = maybe_unsafes_to_tokens(closure_stmts, true);
-            vec![MaybeUnsafeStmt::needs_unsafe(parse_quote! {
+            let new_expr = quote! {
                 autocxx::moveit::new::by_raw(move |#ptr_arg_name| {
                     #closure_stmts
                 })
-            })]
+            };
+            if self.boxed_returns {
+                // Emplace into a Box straight away, rather than
+                // handing the caller an `impl New`.
+                let output_ty = placement_output_ty.unwrap_or_else(|| parse_quote! { Self });
+                (
+                    vec![MaybeUnsafeStmt::binary(
+                        quote! { autocxx::WithinBox::within_box(unsafe { #new_expr }) },
+                        quote! { autocxx::WithinBox::within_box(#new_expr) },
+                    )],
+                    Cow::Owned(parse_quote! {
+                        -> ::core::pin::Pin<::std::boxed::Box<#output_ty>>
+                    }),
+                )
+            } else {
+                (vec![MaybeUnsafeStmt::needs_unsafe(new_expr)], ret_type)
+            }
         } else {
             let mut call_stmts = local_variables;
             call_stmts.push(call_body);
-            call_stmts
+            (call_stmts, ret_type)
         };
         let call_body = maybe_unsafes_to_tokens(call_stmts, context_is_unsafe);
         (lifetime_tokens, wrapper_params, ret_type, call_body)
     }
 
+    /// Route the call into C++ through the user's instrumentation macro,
+    /// if they asked for one. We keep the macro invocation itself out of
+    /// any `unsafe` block.
+    fn instrument(&self, call_body: MaybeUnsafeStmt) -> MaybeUnsafeStmt {
+        let (mac, name) = match &self.instrumentation {
+            None => return call_body,
+            Some(instrumentation) => instrumentation,
+        };
+        match call_body {
+            MaybeUnsafeStmt::Normal(stmt) => {
+                MaybeUnsafeStmt::Normal(quote! { #mac!(#name, #stmt) })
+            }
+            MaybeUnsafeStmt::NeedsUnsafe(stmt) => MaybeUnsafeStmt::binary(
+                quote! { #mac!(#name, unsafe { #stmt }) },
+                quote! { #mac!(#name, #stmt) },
+            ),
+            MaybeUnsafeStmt::Binary {
+                in_safe_context,
+                in_unsafe_context,
+            } => MaybeUnsafeStmt::binary(
+                quote! { #mac!(#name, #in_safe_context) },
+                quote! { #mac!(#name, #in_unsafe_context) },
+            ),
+        }
+    }
+
     /// Generate an 'impl Type { methods-go-here }' item
     fn generate_method_impl(
         &self,
@@ -382,12 +554,15 @@ impl<'a> FnGenerator<'a> {
         let rust_name = make_ident(self.rust_name);
         let unsafety = self.unsafety.wrapper_token();
         let doc_attrs = self.doc_attrs;
+        let inline_attr = &self.inline_attr;
+        let track_caller_attr = &self.track_caller_attr;
         let receiver_pointerness = self
             .param_details
             .iter()
             .next()
             .map(|pd| pd.conversion.is_a_pointer())
             .unwrap_or(Pointerness::Not);
+        let vis = self.vis;
         let ty = impl_block_type_name.get_final_ident();
         let ty = match receiver_pointerness {
             Pointerness::MutPtr if self.reference_wrappers => ImplBlockKey {
@@ -410,7 +585,9 @@ impl<'a> FnGenerator<'a> {
         Box::new(ImplBlockDetails {
             item: ImplItem::Method(parse_quote! {
                 #(#doc_attrs)*
-                pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
+                #inline_attr
+                #track_caller_attr
+                #vis #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                     #call_body
                 }
             }),
@@ -423,11 +600,15 @@ impl<'a> FnGenerator<'a> {
         let (lifetime_tokens, wrapper_params, ret_type, call_body) =
             self.common_parts(details.avoid_self, &details.parameter_reordering, None);
         let doc_attrs = self.doc_attrs;
+        let inline_attr = &self.inline_attr;
+        let track_caller_attr = &self.track_caller_attr;
         let unsafety = self.unsafety.wrapper_token();
         let key = details.trt.clone();
         let method_name = &details.method_name;
         let item = parse_quote! {
             #(#doc_attrs)*
+            #inline_attr
+            #track_caller_attr
             #unsafety fn #method_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                 #call_body
             }
@@ -446,11 +627,13 @@ impl<'a> FnGenerator<'a> {
             self.common_parts(true, &None, Some(ret_type));
         let rust_name = make_ident(self.rust_name);
         let doc_attrs = self.doc_attrs;
+        let inline_attr = &self.inline_attr;
         let unsafety = self.unsafety.wrapper_token();
         let ty = impl_block_type_name.get_final_ident();
         let ty = parse_quote! { #ty };
         let stuff = quote! {
                 #(#doc_attrs)*
+                #inline_attr
                 pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                     #call_body
                 }
@@ -467,15 +650,149 @@ impl<'a> FnGenerator<'a> {
             self.common_parts(false, &None, None);
         let rust_name = make_ident(self.rust_name);
         let doc_attrs = self.doc_attrs;
+        let inline_attr = &self.inline_attr;
+        let track_caller_attr = &self.track_caller_attr;
         let unsafety = self.unsafety.wrapper_token();
         Item::Fn(parse_quote! {
             #(#doc_attrs)*
+            #inline_attr
+            #track_caller_attr
             pub #unsafety fn #rust_name #lifetime_tokens ( #wrapper_params ) #ret_type {
                 #call_body
             }
         })
     }
 
+    /// Generate an implementation of the `std::ops` trait corresponding to
+    /// a C++ operator, which delegates to the method or function we've
+    /// generated for it. Operators whose signatures don't fit the trait
+    /// (e.g. they're `unsafe`, or return a reference) just keep the
+    /// named method or function.
+    fn generate_operator_trait_impl(&self, op: &CppOperator, kind: &FnKind) -> Option<Item> {
+        let rust_trait = op.rust_trait?;
+        if self.unsafety.wrapper_token().is_some()
+            || self.reference_wrappers
+            || self
+                .param_details
+                .iter()
+                .any(|pd| pd.conversion.rust_conversion.is_fallible())
+        {
+            return None;
+        }
+        let (_, wrapper_params, ret_type, _) = self.common_parts(false, &None, None);
+        let operands = wrapper_params
+            .iter()
+            .zip(["'a", "'b"].iter())
+            .map(|(param, lifetime)| match param {
+                FnArg::Typed(pt) => operand_type_with_lifetime(&pt.ty, lifetime),
+                FnArg::Receiver(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if operands.len() != wrapper_params.len() {
+            return None;
+        }
+        // Only implement traits for our own types. For methods, the receiver
+        // always is one.
+        let self_operand = self.param_details.first()?;
+        if self_operand.self_type.is_none()
+            && !operand_is_generated_type(self_operand.conversion.cxxbridge_type())
+        {
+            return None;
+        }
+        let rust_name = make_ident(self.rust_name);
+        let function = match kind {
+            FnKind::Method { impl_for, .. } => {
+                let ty = impl_for.get_final_ident();
+                quote! { #ty::#rust_name }
+            }
+            _ => quote! { #rust_name },
+        };
+        // Operators returning non-POD types by value give us an `impl New`,
+        // which can't be an associated type, so put the result in a
+        // `UniquePtr` instead.
+        let placement_output_ty = self
+            .param_details
+            .iter()
+            .filter(|pd| pd.is_placement_return_destination)
+            .find_map(|pd| {
+                match pd
+                    .conversion
+                    .rust_conversion(parse_quote! { placement }, &mut 0usize)
+                {
+                    RustParamConversion::ReturnValue { ty } => Some(ty),
+                    _ => None,
+                }
+            });
+        let boxed = placement_output_ty.is_some();
+        let output: Type = match (placement_output_ty, ret_type.as_ref()) {
+            (Some(ty), _) => parse_quote! { cxx::UniquePtr<#ty> },
+            (None, ReturnType::Default) => parse_quote! { () },
+            (None, ReturnType::Type(_, ty)) => ty.as_ref().clone(),
+        };
+        let call = |args: TokenStream| {
+            if boxed {
+                quote! { autocxx::WithinUniquePtr::within_unique_ptr(#function(#args)) }
+            } else {
+                quote! { #function(#args) }
+            }
+        };
+        let lifetimes = operands
+            .iter()
+            .filter_map(|(_, lifetime)| lifetime.as_ref());
+        let generics = quote! { <#(#lifetimes),*> };
+        let track_caller_attr = &self.track_caller_attr;
+        Some(match (rust_trait, operands.as_slice()) {
+            (OperatorTrait::Unary(trt, method), [(lhs, _)]) => {
+                if type_contains_reference_or_impl(&output) {
+                    return None;
+                }
+                let (trt, method) = (make_ident(trt), make_ident(method));
+                let call = call(quote! { self });
+                parse_quote! {
+                    impl #generics ::core::ops::#trt for #lhs {
+                        type Output = #output;
+                        #track_caller_attr
+                        fn #method(self) -> Self::Output {
+                            #call
+                        }
+                    }
+                }
+            }
+            (OperatorTrait::Binary(trt, method), [(lhs, _), (rhs, _)]) => {
+                if type_contains_reference_or_impl(&output) {
+                    return None;
+                }
+                let (trt, method) = (make_ident(trt), make_ident(method));
+                let call = call(quote! { self, rhs });
+                parse_quote! {
+                    impl #generics ::core::ops::#trt<#rhs> for #lhs {
+                        type Output = #output;
+                        #track_caller_attr
+                        fn #method(self, rhs: #rhs) -> Self::Output {
+                            #call
+                        }
+                    }
+                }
+            }
+            // Compound assignment operators usually return *this, which
+            // we discard.
+            (OperatorTrait::Assign(trt, method), [(lhs, _), (rhs, _)])
+                if self_operand.is_mutable_reference =>
+            {
+                let (trt, method) = (make_ident(trt), make_ident(method));
+                parse_quote! {
+                    impl #generics ::core::ops::#trt<#rhs> for #lhs {
+                        #track_caller_attr
+                        fn #method(&mut self, rhs: #rhs) {
+                            #function(self.as_mut(), rhs);
+                        }
+                    }
+                }
+            }
+            _ => return None,
+        })
+    }
+
     fn reorder_parameters(
         params: Punctuated<FnArg, Comma>,
         parameter_ordering: &[usize],
@@ -487,3 +804,63 @@ impl<'a> FnGenerator<'a> {
             .collect()
     }
 }
+
+/// For an operand of an operator trait implementation: the type with any
+/// reference given the specified lifetime, and that lifetime if it was
+/// needed. `None` if the type can't be used in a trait implementation.
+fn operand_type_with_lifetime(ty: &Type, lifetime: &str) -> Option<(Type, Option<Lifetime>)> {
+    let lifetime = Lifetime::new(lifetime, Span::call_site());
+    let mut ty = ty.clone();
+    let reference = match &mut ty {
+        Type::Reference(r) => Some(r),
+        Type::Path(typ) => match typ.path.segments.last_mut() {
+            Some(PathSegment {
+                ident,
+                arguments: PathArguments::AngleBracketed(args),
+            }) if ident == "Pin" => match args.args.first_mut() {
+                Some(GenericArgument::Type(Type::Reference(r))) => Some(r),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+    let lifetime = match reference {
+        Some(reference) if !type_contains_reference_or_impl(&reference.elem) => {
+            reference.lifetime = Some(lifetime.clone());
+            Some(lifetime)
+        }
+        Some(_) => return None,
+        None => None,
+    };
+    if lifetime.is_none() && type_contains_reference_or_impl(&ty) {
+        return None;
+    }
+    Some((ty, lifetime))
+}
+
+fn type_contains_reference_or_impl(ty: &Type) -> bool {
+    let ty = ty.to_token_stream().to_string();
+    ty.contains('&') || ty.contains('\'') || ty.contains("impl ")
+}
+
+/// Whether this (possibly referenced) type is one we're generating
+/// bindings for, as opposed to a primitive or a `cxx` type.
+fn operand_is_generated_type(ty: &Type) -> bool {
+    let ty = match ty {
+        Type::Reference(r) => r.elem.as_ref(),
+        _ => ty,
+    };
+    match ty {
+        Type::Path(typ) => {
+            let qn = QualifiedName::from_type_path(typ);
+            typ.path
+                .segments
+                .iter()
+                .all(|seg| matches!(seg.arguments, PathArguments::None))
+                && !known_types().is_known_type(&qn)
+                && !known_types().is_ctype(&qn)
+        }
+        _ => false,
+    }
+}
@@ -6,8 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use proc_macro2::TokenStream;
-use syn::{Expr, Type, TypePtr};
+use proc_macro2::{Literal, TokenStream};
+use syn::{Expr, Ident, Type, TypePtr, TypeReference};
 
 use crate::{
     conversion::analysis::fun::function_wrapper::{RustConversionType, TypeConversionPolicy},
@@ -112,6 +112,200 @@ impl TypeConversionPolicy {
                     conversion_requires_unsafe: false,
                 }
             }
+            RustConversionType::FromWideStrToPtr => {
+                let ty = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr { elem, .. }) => elem,
+                    _ => panic!("Not a ptr"),
+                };
+                let var_counter = *counter;
+                *counter += 1;
+                let buffer_var_name = make_ident(format!("wide{var_counter}"));
+                // The buffer must outlive the call, so it goes in a local
+                // variable rather than a temporary.
+                RustParamConversion::Param {
+                    ty: parse_quote! { impl autocxx::ToWideCString<#ty> },
+                    local_variables: vec![MaybeUnsafeStmt::new(quote! {
+                        let #buffer_var_name = autocxx::ToWideCString::to_wide_c_string(#var);
+                    })],
+                    conversion: quote! { #buffer_var_name.as_ptr() },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromArrayRefToPtr(ref dims) => {
+                let (elem, mutability, as_ptr) = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr {
+                        elem, mutability, ..
+                    }) => (
+                        elem,
+                        mutability,
+                        if mutability.is_some() {
+                            quote! { as_mut_ptr }
+                        } else {
+                            quote! { as_ptr }
+                        },
+                    ),
+                    _ => panic!("Not a ptr"),
+                };
+                // The innermost arrays come last, so wrap them first.
+                let array: Type = dims.iter().rev().fold(elem.as_ref().clone(), |inner, dim| {
+                    let dim = Literal::usize_unsuffixed(*dim);
+                    parse_quote! { [#inner; #dim] }
+                });
+                // A multi-dimensional array gives a pointer to its first
+                // row, rather than to its first element.
+                let conversion = if dims.len() > 1 {
+                    quote! { #var.#as_ptr().cast() }
+                } else {
+                    quote! { #var.#as_ptr() }
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { &#mutability #array },
+                    local_variables: Vec::new(),
+                    conversion,
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromRefSliceToPtrArray => {
+                let elem = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr { elem, .. }) => match elem.as_ref() {
+                        Type::Ptr(TypePtr { elem, .. }) => elem.as_ref(),
+                        _ => panic!("Not a ptr to a ptr"),
+                    },
+                    _ => panic!("Not a ptr"),
+                };
+                let is_c_char = matches!(elem, Type::Path(typ) if typ.path.segments.last().unwrap().ident == "c_char");
+                let (ty, to_ptr) = if is_c_char {
+                    (
+                        parse_quote! { &[&::std::ffi::CStr] },
+                        quote! { |s| s.as_ptr() },
+                    )
+                } else {
+                    (
+                        parse_quote! { &[&#elem] },
+                        quote! { |r| *r as *const #elem },
+                    )
+                };
+                let var_counter = *counter;
+                *counter += 1;
+                let array_var_name = make_ident(format!("ptrs{var_counter}"));
+                // The array must outlive the call, so it's a local of the
+                // wrapper. Many such APIs expect a null terminator, and it
+                // does no harm to the rest.
+                RustParamConversion::Param {
+                    ty,
+                    local_variables: vec![MaybeUnsafeStmt::new(quote! {
+                        let #array_var_name: ::std::vec::Vec<_> = #var
+                            .iter()
+                            .map(#to_ptr)
+                            .chain(::core::iter::once(::core::ptr::null()))
+                            .collect();
+                    })],
+                    conversion: quote! { #array_var_name.as_ptr() },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromRestrictSliceToPtr => {
+                let (elem, mutability) = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr {
+                        elem, mutability, ..
+                    }) => (elem, mutability),
+                    _ => panic!("Not a ptr"),
+                };
+                let conversion = if mutability.is_some() {
+                    quote! { #var.as_mut_ptr() }
+                } else {
+                    quote! { #var.as_ptr() }
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { &#mutability [#elem] },
+                    local_variables: Vec::new(),
+                    conversion,
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromStrRef => {
+                let var_counter = *counter;
+                *counter += 1;
+                let holder_var_name = make_ident(format!("str{var_counter}"));
+                // Any newly created string must outlive the call.
+                RustParamConversion::Param {
+                    ty: parse_quote! { impl ToCppStringRef },
+                    local_variables: vec![MaybeUnsafeStmt::new(quote! {
+                        let #holder_var_name = ToCppStringRef::to_cpp_string_ref(#var);
+                    })],
+                    conversion: quote! { &*#holder_var_name },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromVecToSlice => {
+                let elem = match self.cxxbridge_type() {
+                    Type::Reference(TypeReference { elem, .. }) => match elem.as_ref() {
+                        Type::Slice(slice) => slice.elem.as_ref(),
+                        _ => panic!("Not a slice"),
+                    },
+                    _ => panic!("Not a reference"),
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { ::std::vec::Vec<#elem> },
+                    local_variables: Vec::new(),
+                    conversion: quote! { &#var },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromHandleToPtr(ref handle) => {
+                let handle_ty = self.handle_type(handle);
+                RustParamConversion::Param {
+                    ty: parse_quote! { &#handle_ty },
+                    local_variables: Vec::new(),
+                    conversion: quote! { #var.as_ptr() },
+                    conversion_requires_unsafe: false,
+                }
+            }
+            RustConversionType::FromPtrToHandle(ref handle) => {
+                let handle_ty = self.handle_type(handle);
+                RustParamConversion::Param {
+                    ty: parse_quote! { ::core::option::Option<#handle_ty> },
+                    local_variables: Vec::new(),
+                    conversion: quote! { #handle_ty::from_raw(#var) },
+                    conversion_requires_unsafe: true,
+                }
+            }
+            RustConversionType::FromPtrToThreadLocal => {
+                let ty = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr { elem, .. }) => elem,
+                    _ => panic!("Not a pointer"),
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { autocxx::CppThreadLocal<#ty> },
+                    local_variables: Vec::new(),
+                    conversion: quote! { autocxx::CppThreadLocal::from_raw(#var) },
+                    conversion_requires_unsafe: true,
+                }
+            }
+            RustConversionType::FromNullablePtrToRef => {
+                let ty = match self.cxxbridge_type() {
+                    Type::Ptr(TypePtr { elem, .. }) => elem,
+                    _ => panic!("Not a pointer"),
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { ::core::option::Option<&#ty> },
+                    local_variables: Vec::new(),
+                    conversion: quote! { #var.as_ref() },
+                    conversion_requires_unsafe: true,
+                }
+            }
+            RustConversionType::FromStructToTuple(ref fields) => {
+                let (field_names, field_types): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+                RustParamConversion::Param {
+                    ty: parse_quote! { (#(#field_types,)*) },
+                    local_variables: Vec::new(),
+                    conversion: quote! { {
+                        let bundle = #var;
+                        (#(bundle.#field_names,)*)
+                    } },
+                    conversion_requires_unsafe: false,
+                }
+            }
             RustConversionType::FromValueParamToPtr | RustConversionType::FromRValueParamToPtr => {
                 let (handler_type, param_trait) = match self.rust_conversion {
                     RustConversionType::FromValueParamToPtr => ("ValueParamHandler", "ValueParam"),
@@ -186,6 +380,29 @@ impl TypeConversionPolicy {
                     conversion_requires_unsafe: false,
                 }
             }
+            RustConversionType::FromWiderIntegerChecked => {
+                let ty = self.cxxbridge_type();
+                // Our C integer newtypes wrap the primitive of the same name,
+                // whose width depends on the platform.
+                let check = match self.c_integer_newtype() {
+                    Some(prim) => quote! {
+                        #ty(<::core::ffi::#prim as ::core::convert::TryFrom<i64>>::try_from(#var)?)
+                    },
+                    None => quote! {
+                        <#ty as ::core::convert::TryFrom<i64>>::try_from(#var)?
+                    },
+                };
+                RustParamConversion::Param {
+                    ty: parse_quote! { i64 },
+                    local_variables: vec![MaybeUnsafeStmt::new(quote! {
+                        let #var = #check;
+                    })],
+                    conversion: quote! {
+                        #var
+                    },
+                    conversion_requires_unsafe: false,
+                }
+            }
             RustConversionType::FromReferenceWrapperToPointer => {
                 let (is_mut, ty) = match self.cxxbridge_type() {
                     Type::Ptr(TypePtr {
@@ -215,4 +432,18 @@ impl TypeConversionPolicy {
             }
         }
     }
+
+    /// The `opaque_handle!` type of the given name, which lives alongside
+    /// the type to which our pointer points.
+    fn handle_type(&self, handle: &Ident) -> Type {
+        let mut ty = match self.cxxbridge_type() {
+            Type::Ptr(TypePtr { elem, .. }) => elem.as_ref().clone(),
+            _ => panic!("Not a pointer"),
+        };
+        match &mut ty {
+            Type::Path(typ) => typ.path.segments.last_mut().unwrap().ident = handle.clone(),
+            _ => panic!("Not a path"),
+        }
+        ty
+    }
 }
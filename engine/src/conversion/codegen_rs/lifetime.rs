@@ -31,6 +31,8 @@ use syn::{
 ///    built-in type
 /// 3) Any parameter is any form of reference, and we're returning an `impl New`
 ///    3a) an 'impl ValueParam' counts as a reference.
+/// 4) The user told us using `return_borrows!` which parameters the output
+///    borrows from. Those, and only those, share its lifetime.
 pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     param_details: &[ArgumentAnalysis],
     mut params: Punctuated<FnArg, Comma>,
@@ -53,12 +55,17 @@ pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
                 RustConversionType::FromValueParamToPtr
             )
     });
+    let borrowed_params = borrowed_param_names(param_details);
     let return_type_is_impl = return_type_is_impl(&ret_type);
     let non_pod_ref_param = reference_parameter_is_non_pod_reference(&params, non_pod_types);
     let ret_type_pod = return_type_is_pod_or_known_type_reference(&ret_type, non_pod_types);
     let returning_impl_with_a_reference_param = return_type_is_impl && any_param_is_reference;
     let hits_1024_bug = non_pod_ref_param && ret_type_pod;
-    if !(has_mutable_receiver || hits_1024_bug || returning_impl_with_a_reference_param) {
+    if !(has_mutable_receiver
+        || hits_1024_bug
+        || returning_impl_with_a_reference_param
+        || !borrowed_params.is_empty())
+    {
         return (None, params, ret_type);
     }
     let new_return_type = match ret_type.as_ref() {
@@ -91,7 +98,11 @@ pub(crate) fn add_explicit_lifetime_if_necessary<'r>(
     match new_return_type {
         None => (None, params, ret_type),
         Some(new_return_type) => {
-            for mut param in params.iter_mut() {
+            // In case 4 only the parameters which the user listed share the
+            // lifetime of the output.
+            for mut param in params.iter_mut().filter(|param| {
+                borrowed_params.is_empty() || borrowed_params.contains(&param_name(param))
+            }) {
                 if let FnArg::Typed(PatType { ty, .. }) = &mut param {
                     match ty.as_mut() {
                         Type::Path(TypePath {
@@ -148,6 +159,30 @@ fn return_type_is_pod_or_known_type_reference(
     }
 }
 
+/// The names of the parameters which `return_borrows!` said the output
+/// borrows from. A receiver may appear either as `self` or under its
+/// original name, depending on how we're representing it.
+fn borrowed_param_names(param_details: &[ArgumentAnalysis]) -> HashSet<String> {
+    param_details
+        .iter()
+        .filter(|pd| pd.return_borrows_from)
+        .flat_map(|pd| {
+            let name = pd.name.to_token_stream().to_string();
+            match pd.self_type {
+                Some(_) => vec![name, "self".to_string()],
+                None => vec![name],
+            }
+        })
+        .collect()
+}
+
+fn param_name(param: &FnArg) -> String {
+    match param {
+        FnArg::Receiver(_) => "self".to_string(),
+        FnArg::Typed(PatType { pat, .. }) => pat.to_token_stream().to_string(),
+    }
+}
+
 fn return_type_is_impl(ret_type: &ReturnType) -> bool {
     matches!(ret_type, ReturnType::Type(_, boxed_type) if matches!(boxed_type.as_ref(), Type::ImplTrait(..)))
 }
@@ -6,38 +6,48 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+mod bitflags_enum;
+pub(crate) mod copy_types;
+mod exception_enum;
 mod fun_codegen;
 mod function_wrapper_rs;
 mod impl_item_creator;
 mod lifetime;
 mod namespace_organizer;
+mod no_std;
 mod non_pod_struct;
+mod string_constructors;
 pub(crate) mod unqualify;
 
 use indexmap::map::IndexMap as HashMap;
 use indexmap::set::IndexSet as HashSet;
 
-use autocxx_parser::{ExternCppType, IncludeCppConfig, RustFun, UnsafePolicy};
+use autocxx_parser::{ExternCppType, IncludeCppConfig, OpaqueHandle, RustFun, UnsafePolicy};
 
 use itertools::Itertools;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Literal, Span, TokenStream};
 use syn::{
     parse_quote, punctuated::Punctuated, token::Comma, Attribute, Expr, FnArg, ForeignItem,
-    ForeignItemFn, Ident, ImplItem, Item, ItemForeignMod, ItemMod, Lifetime, TraitItem, Type,
-    TypePath,
+    ForeignItemFn, Ident, ImplItem, Item, ItemEnum, ItemForeignMod, ItemMod, Lifetime, ReturnType,
+    TraitItem, Type, TypePath,
 };
 
 use crate::{
     conversion::{
         codegen_rs::{
-            non_pod_struct::{make_non_pod, new_non_pod_struct},
+            non_pod_struct::{make_lit_int, make_non_pod, new_non_pod_struct},
             unqualify::{unqualify_params, unqualify_ret_type},
         },
         doc_attr::get_doc_attrs,
     },
     types::{make_ident, Namespace, QualifiedName},
 };
+use bitflags_enum::generate_bitflags_enum;
+use copy_types::{find_duplicable_types, Duplicability};
+use exception_enum::generate_exception_enum;
 use impl_item_creator::create_impl_items;
+use no_std::rewrite_std_paths;
+use string_constructors::generate_from_str_impls;
 
 use self::{
     fun_codegen::gen_function,
@@ -46,10 +56,17 @@ use self::{
 
 use super::{
     analysis::{
-        fun::{FnPhase, PodAndDepAnalysis, ReceiverMutability},
+        bitsets::bitset_size,
+        fun::{
+            function_wrapper::c_integer_newtype, FnAnalysis, FnKind, FnPhase, PodAndDepAnalysis,
+            ReceiverMutability,
+        },
         pod::PodAnalysis,
+        ranges::RangeAnalysis,
+        tagged_unions::TaggedUnionAnalysis,
+        variants::{VariantAlternativeAnalysis, VariantAnalysis},
     },
-    api::{AnalysisPhase, Api, SubclassName, TypeKind, TypedefKind},
+    api::{AnalysisPhase, Api, EnumAlias, SubclassName, TypeKind, TypedefKind},
     convert_error::ErrorContextType,
 };
 use super::{
@@ -131,6 +148,55 @@ fn get_string_items() -> Vec<Item> {
                 }
             }
         }),
+        // For `const std::string&` parameters, if `flexible_string_params!`
+        // is used. Existing C++ strings are passed straight through, which
+        // is the only way to pass one which isn't valid UTF-8.
+        Item::Trait(parse_quote! {
+            pub trait ToCppStringRef {
+                type Holder: ::core::ops::Deref<Target = cxx::CxxString>;
+                fn to_cpp_string_ref(self) -> Self::Holder;
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ToCppStringRef for &str {
+                type Holder = cxx::UniquePtr<cxx::CxxString>;
+                fn to_cpp_string_ref(self) -> Self::Holder {
+                    make_string(self)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ToCppStringRef for String {
+                type Holder = cxx::UniquePtr<cxx::CxxString>;
+                fn to_cpp_string_ref(self) -> Self::Holder {
+                    make_string(&self)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl ToCppStringRef for &String {
+                type Holder = cxx::UniquePtr<cxx::CxxString>;
+                fn to_cpp_string_ref(self) -> Self::Holder {
+                    make_string(self)
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl<'a> ToCppStringRef for &'a cxx::CxxString {
+                type Holder = &'a cxx::CxxString;
+                fn to_cpp_string_ref(self) -> Self::Holder {
+                    self
+                }
+            }
+        }),
+        Item::Impl(parse_quote! {
+            impl<'a> ToCppStringRef for &'a cxx::UniquePtr<cxx::CxxString> {
+                type Holder = &'a cxx::CxxString;
+                fn to_cpp_string_ref(self) -> Self::Holder {
+                    self
+                }
+            }
+        }),
     ]
     .to_vec()
 }
@@ -144,6 +210,7 @@ pub(crate) struct RsCodeGenerator<'a> {
     bindgen_mod: ItemMod,
     original_name_map: CppNameMap,
     config: &'a IncludeCppConfig,
+    reexports: Vec<(QualifiedName, Ident)>,
     header_name: Option<String>,
 }
 
@@ -155,6 +222,7 @@ impl<'a> RsCodeGenerator<'a> {
         include_list: &'a [String],
         bindgen_mod: ItemMod,
         config: &'a IncludeCppConfig,
+        reexports: Vec<(QualifiedName, Ident)>,
         header_name: Option<String>,
     ) -> Vec<Item> {
         let c = Self {
@@ -163,6 +231,7 @@ impl<'a> RsCodeGenerator<'a> {
             bindgen_mod,
             original_name_map: CppNameMap::new_from_apis(&all_apis),
             config,
+            reexports,
             header_name,
         };
         c.rs_codegen(all_apis)
@@ -176,6 +245,8 @@ impl<'a> RsCodeGenerator<'a> {
         let subclasses_with_a_single_trivial_constructor =
             find_trivially_constructed_subclasses(&all_apis);
         let non_pod_types = find_non_pod_types(&all_apis);
+        let from_str_impls = generate_from_str_impls(&all_apis, self.config);
+        let duplicable_types = find_duplicable_types(&all_apis, self.config);
         // Now let's generate the Rust code.
         let (rs_codegen_results_and_namespaces, additional_cpp_needs): (Vec<_>, Vec<_>) = all_apis
             .into_iter()
@@ -187,6 +258,8 @@ impl<'a> RsCodeGenerator<'a> {
                     &methods_by_superclass,
                     &subclasses_with_a_single_trivial_constructor,
                     &non_pod_types,
+                    &from_str_impls,
+                    &duplicable_types,
                 );
                 ((name, gen), more_cpp_needed)
             })
@@ -229,6 +302,18 @@ impl<'a> RsCodeGenerator<'a> {
         let mut all_items: Vec<Item> = all_items.into_iter().flatten().collect();
         // And finally any C++ we need to generate. And by "we" I mean autocxx not cxx.
         let has_additional_cpp_needs = additional_cpp_needs.into_iter().any(std::convert::identity);
+        // Then anything the user has written by hand, which we re-export
+        // alongside our own items.
+        extern_c_mod_items.extend(self.config.get_manual_bridge_items().iter().cloned().map(
+            |mut item| {
+                match &mut item {
+                    ForeignItem::Fn(f) => f.vis = parse_quote! { pub },
+                    ForeignItem::Type(t) => t.vis = parse_quote! { pub },
+                    _ => {}
+                }
+                item
+            },
+        ));
         extern_c_mod_items.extend(self.build_include_foreign_items(has_additional_cpp_needs));
         // We will always create an extern "C" mod even if bindgen
         // didn't generate one, e.g. because it only generated types.
@@ -268,9 +353,40 @@ impl<'a> RsCodeGenerator<'a> {
             use bindgen::root;
         }));
         all_items.append(&mut use_statements);
+        if !self.reexports.is_empty() {
+            all_items.push(Self::generate_reexports_mod(self.config, &self.reexports));
+        }
+        all_items.extend(self.config.get_manual_bridge_names().map(|id| -> Item {
+            parse_quote! {
+                pub use cxxbridge::#id;
+            }
+        }));
+        if self.config.no_std() {
+            rewrite_std_paths(&mut all_items);
+        }
         all_items
     }
 
+    /// A mod containing just the items listed in `reexport!`, under their
+    /// new names, so that users can `pub use` all of them at once.
+    fn generate_reexports_mod(
+        config: &IncludeCppConfig,
+        reexports: &[(QualifiedName, Ident)],
+    ) -> Item {
+        let mod_name = make_ident(config.get_reexports_mod_name());
+        let uses = reexports.iter().map(|(name, alias)| {
+            let path = name.type_path_from_root();
+            quote! {
+                pub use super::#path as #alias;
+            }
+        });
+        parse_quote! {
+            pub mod #mod_name {
+                #(#uses)*
+            }
+        }
+    }
+
     fn accumulate_superclass_methods(
         &self,
         apis: &ApiVec<FnPhase>,
@@ -368,6 +484,7 @@ impl<'a> RsCodeGenerator<'a> {
         let mut imports_from_super = vec!["cxxbridge"];
         if !self.config.exclude_utilities() {
             imports_from_super.push("ToCppString");
+            imports_from_super.push("ToCppStringRef");
         }
         let imports_from_super = imports_from_super.into_iter().map(make_ident);
         let super_duper = std::iter::repeat(make_ident("super")); // I'll get my coat
@@ -471,6 +588,8 @@ impl<'a> RsCodeGenerator<'a> {
         associated_methods: &HashMap<QualifiedName, Vec<SuperclassMethod>>,
         subclasses_with_a_single_trivial_constructor: &HashSet<QualifiedName>,
         non_pod_types: &HashSet<QualifiedName>,
+        from_str_impls: &HashMap<QualifiedName, Item>,
+        duplicable_types: &HashMap<QualifiedName, Duplicability>,
     ) -> RsCodegenResult {
         let name = api.name().clone();
         let id = name.get_final_ident();
@@ -489,14 +608,62 @@ impl<'a> RsCodeGenerator<'a> {
                     ..Default::default()
                 }
             }
-            Api::Function { fun, analysis, .. } => gen_function(
-                name.get_namespace(),
-                *fun,
-                analysis,
-                cpp_call_name,
-                non_pod_types,
-                self.config,
-            ),
+            Api::AssertionHandler { .. } => RsCodegenResult {
+                extern_rust_mod_items: vec![ForeignItem::Fn(parse_quote! {
+                    fn #id(expr: &[u8], file: &[u8], line: u32);
+                })],
+                // cxx will abort once this panic reaches the FFI boundary,
+                // but the panic hook gets to report it first.
+                global_items: vec![parse_quote! {
+                    fn #id(expr: &[u8], file: &[u8], line: u32) {
+                        panic!(
+                            "C++ assertion failed at {}:{}: {}",
+                            String::from_utf8_lossy(file),
+                            line,
+                            String::from_utf8_lossy(expr)
+                        )
+                    }
+                }],
+                ..Default::default()
+            },
+            Api::ExceptionTypes { .. } => RsCodegenResult {
+                global_items: generate_exception_enum(&id, self.config),
+                ..Default::default()
+            },
+            // The check is entirely in C++, run as the program starts, so
+            // there's nothing for Rust to call.
+            Api::AbiVersionCheck { .. } => RsCodegenResult::default(),
+            Api::NamespaceType { .. } => {
+                let doc = format!(
+                    "The functions of the C++ namespace `{}`.",
+                    name.get_namespace().to_cpp_path()
+                );
+                RsCodegenResult {
+                    bindgen_mod_items: vec![parse_quote! {
+                        #[doc = #doc]
+                        #[derive(Clone, Copy, Debug)]
+                        pub struct #id;
+                    }],
+                    materializations: vec![Use::UsedFromBindgen],
+                    ..Default::default()
+                }
+            }
+            Api::Function { fun, analysis, .. } => {
+                let byte_buffer_impl = self.generate_byte_buffer_impl(&analysis);
+                let mut result = gen_function(
+                    name.get_namespace(),
+                    *fun,
+                    analysis,
+                    cpp_call_name,
+                    non_pod_types,
+                    self.config,
+                );
+                result
+                    .bindgen_mod_items
+                    .extend(from_str_impls.get(&name).cloned());
+                result.bindgen_mod_items.extend(byte_buffer_impl);
+                result
+            }
             Api::Const { const_item, .. } => RsCodegenResult {
                 bindgen_mod_items: vec![Item::Const(const_item)],
                 materializations: vec![Use::UsedFromBindgen],
@@ -511,12 +678,17 @@ impl<'a> RsCodeGenerator<'a> {
                 ..Default::default()
             },
             Api::Struct {
-                details,
+                mut details,
                 analysis:
                     PodAndDepAnalysis {
                         pod:
                             PodAnalysis {
-                                is_generic, kind, ..
+                                is_generic,
+                                kind,
+                                tagged_union,
+                                range,
+                                platform_dependent_layout,
+                                ..
                             },
                         constructors,
                         ..
@@ -524,10 +696,26 @@ impl<'a> RsCodeGenerator<'a> {
                 ..
             } => {
                 let doc_attrs = get_doc_attrs(&details.item.attrs);
-                let layout = details.layout.clone();
-                self.generate_type(
+                match duplicable_types.get(&name) {
+                    Some(Duplicability::Copy) => details
+                        .item
+                        .attrs
+                        .push(parse_quote! { #[derive(Clone, Copy)] }),
+                    Some(Duplicability::Clone) => {
+                        details.item.attrs.push(parse_quote! { #[derive(Clone)] })
+                    }
+                    _ => {}
+                }
+                // A POD type's fields give it the right layout on each
+                // platform, so there's nothing to assert if that differs from
+                // the layout which bindgen found. Other types need it anyway.
+                let layout = details
+                    .layout
+                    .clone()
+                    .filter(|_| !(platform_dependent_layout && matches!(kind, TypeKind::Pod)));
+                let mut result = self.generate_type(
                     &name,
-                    id,
+                    id.clone(),
                     kind,
                     constructors.move_constructor,
                     constructors.destructor,
@@ -535,44 +723,149 @@ impl<'a> RsCodeGenerator<'a> {
                     associated_methods,
                     layout,
                     is_generic,
-                )
+                );
+                if let Some(tagged_union) = tagged_union.filter(|_| !is_generic) {
+                    result
+                        .bindgen_mod_items
+                        .extend(Self::generate_tagged_union_accessor(
+                            &id,
+                            &tagged_union,
+                            matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsUnsafe),
+                        ));
+                    result
+                        .materializations
+                        .push(Use::SpecificNameFromBindgen(tagged_union.enum_name));
+                }
+                if let Some(range) = range.filter(|_| !is_generic) {
+                    result
+                        .bindgen_mod_items
+                        .extend(Self::generate_range_iterator(
+                            &id,
+                            &range,
+                            matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsUnsafe),
+                        ));
+                    result
+                        .materializations
+                        .push(Use::SpecificNameFromBindgen(range.iter_name));
+                }
+                if !is_generic && self.config.is_raii_guard(&name.to_cpp_name()) {
+                    result.bindgen_mod_items.push(parse_quote! {
+                        impl autocxx::RaiiGuard for #id {}
+                    });
+                }
+                result
             }
-            Api::Enum { item, .. } => {
+            Api::Enum { item, aliases, .. } => {
                 let doc_attrs = get_doc_attrs(&item.attrs);
-                self.generate_type(
+                let bitflags = if self.config.is_bitflags_enum(&name.to_cpp_name()) {
+                    generate_bitflags_enum(&item)
+                } else {
+                    None
+                };
+                let (item, bitflags_impls) = match bitflags {
+                    Some((newtype, impls)) => (newtype, impls),
+                    None => {
+                        let try_from = Self::generate_enum_try_from(&item);
+                        (Item::Enum(item), vec![try_from])
+                    }
+                };
+                let mut result = self.generate_type(
                     &name,
-                    id,
+                    id.clone(),
                     TypeKind::Pod,
                     true,
                     true,
-                    || Some((Item::Enum(item), doc_attrs)),
+                    || Some((item, doc_attrs)),
                     associated_methods,
                     None,
                     false,
-                )
+                );
+                result.bindgen_mod_items.extend(bitflags_impls);
+                if !aliases.is_empty() {
+                    result
+                        .bindgen_mod_items
+                        .push(Self::generate_enum_aliases(&id, &aliases));
+                }
+                result
+            }
+            Api::ConcreteType {
+                std_variant,
+                cpp_definition,
+                ..
+            } => {
+                let mut result = self.generate_type(
+                    &name,
+                    id.clone(),
+                    TypeKind::Abstract,
+                    false, // assume for now that these types can't be kept in a Vector
+                    true,  // assume for now that these types can be put in a smart pointer
+                    || None,
+                    associated_methods,
+                    None,
+                    false,
+                );
+                if let Some(std_variant) = std_variant {
+                    result
+                        .bindgen_mod_items
+                        .extend(Self::generate_variant_accessor(
+                            &id,
+                            &std_variant,
+                            matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsUnsafe),
+                        ));
+                    result
+                        .materializations
+                        .push(Use::SpecificNameFromBindgen(std_variant.enum_name));
+                }
+                if let Some(size) = bitset_size(&cpp_definition) {
+                    result
+                        .bindgen_mod_items
+                        .push(Self::generate_bitset_accessors(
+                            &id,
+                            size,
+                            self.config.track_caller(),
+                            matches!(self.unsafe_policy, UnsafePolicy::AllFunctionsUnsafe),
+                        ));
+                }
+                result
+            }
+            Api::ForwardDeclaration { .. } => {
+                let mut result = self.generate_type(
+                    &name,
+                    id.clone(),
+                    TypeKind::Abstract,
+                    false, // these types can't be kept in a Vector
+                    false, // these types can't be put in a smart pointer
+                    || None,
+                    associated_methods,
+                    None,
+                    false,
+                );
+                Self::note_incomplete_type(&name, &mut result);
+                if let Some(handle) = self.config.get_opaque_handle(&name.to_cpp_name()) {
+                    self.add_opaque_handle(&name, &id, handle, &mut result);
+                }
+                result
+            }
+            Api::OpaqueTypedef {
+                forward_declaration,
+                ..
+            } => {
+                let mut result = self.generate_type(
+                    &name,
+                    id,
+                    TypeKind::Abstract,
+                    false, // these types can't be kept in a Vector
+                    false, // these types can't be put in a smart pointer
+                    || None,
+                    associated_methods,
+                    None,
+                    false,
+                );
+                if forward_declaration {
+                    Self::note_incomplete_type(&name, &mut result);
+                }
+                result
             }
-            Api::ConcreteType { .. } => self.generate_type(
-                &name,
-                id,
-                TypeKind::Abstract,
-                false, // assume for now that these types can't be kept in a Vector
-                true,  // assume for now that these types can be put in a smart pointer
-                || None,
-                associated_methods,
-                None,
-                false,
-            ),
-            Api::ForwardDeclaration { .. } | Api::OpaqueTypedef { .. } => self.generate_type(
-                &name,
-                id,
-                TypeKind::Abstract,
-                false, // these types can't be kept in a Vector
-                false, // these types can't be put in a smart pointer
-                || None,
-                associated_methods,
-                None,
-                false,
-            ),
             Api::CType { .. } => RsCodegenResult {
                 extern_c_mod_items: vec![ForeignItem::Verbatim(quote! {
                     type #id = autocxx::#id;
@@ -636,14 +929,33 @@ impl<'a> RsCodeGenerator<'a> {
                 self.generate_subclass(name, &superclass, methods, generate_peer_constructor)
             }
             Api::ExternCppType {
-                details: ExternCppType { rust_path, .. },
+                details:
+                    ExternCppType {
+                        rust_path,
+                        from_cbindgen,
+                        ..
+                    },
                 ..
-            } => self.generate_extern_cpp_type(&name, rust_path, name.ns_segment_iter().count()),
+            } => {
+                let mut result = self.generate_extern_cpp_type(
+                    &name,
+                    rust_path.clone(),
+                    name.ns_segment_iter().count(),
+                );
+                if from_cbindgen {
+                    result
+                        .global_items
+                        .push(Self::generate_cbindgen_extern_type_impl(&name, &rust_path));
+                }
+                result
+            }
+            // Placeholders for types may have placeholders for their methods
+            // attached, so we have to leave out all or none of them.
             Api::IgnoredItem {
                 err,
                 ctx: Some(ctx),
                 ..
-            } => Self::generate_error_entry(err, ctx),
+            } if !self.config.suppress_ignored_items() => Self::generate_error_entry(err, ctx),
             Api::IgnoredItem { .. } | Api::SubclassTraitItem { .. } => RsCodegenResult::default(),
         }
     }
@@ -915,6 +1227,12 @@ impl<'a> RsCodeGenerator<'a> {
                 let mut item = orig_item
                     .expect("Instantiable types must provide instance")
                     .0;
+                let layout_assertion = match (&type_kind, &layout) {
+                    (TypeKind::Pod, Some(layout)) if !is_generic => {
+                        Some(Self::generate_layout_assertion(name, &id, layout))
+                    }
+                    _ => None,
+                };
                 if matches!(type_kind, TypeKind::NonPod) {
                     if let Item::Struct(ref mut s) = item {
                         // Retain generics and doc attrs.
@@ -925,6 +1243,7 @@ impl<'a> RsCodeGenerator<'a> {
                     }
                 }
                 bindgen_mod_items.push(item);
+                bindgen_mod_items.extend(layout_assertion);
 
                 if is_generic {
                     // Still generate the type as emitted by bindgen,
@@ -969,6 +1288,27 @@ impl<'a> RsCodeGenerator<'a> {
         }
     }
 
+    /// POD types are passed by value between Rust and C++, so the Rust
+    /// struct generated by bindgen must have exactly the layout which the
+    /// C++ compiler gives the type. The C++ side of this check is
+    /// generated alongside the other POD assertions.
+    fn generate_layout_assertion(name: &QualifiedName, id: &Ident, layout: &Layout) -> Item {
+        let size = make_lit_int(layout.size);
+        let align = make_lit_int(layout.align);
+        let message = format!(
+            "type {} should have the size ({}) and alignment ({}) calculated by bindgen to be used with generate_pod! in autocxx",
+            name.to_cpp_name(),
+            layout.size,
+            layout.align
+        );
+        parse_quote! {
+            const _: () = assert!(
+                ::core::mem::size_of::<#id>() == #size && ::core::mem::align_of::<#id>() == #align,
+                #message
+            );
+        }
+    }
+
     fn add_superclass_stuff_to_type(
         name: &QualifiedName,
         bindgen_mod_items: &mut Vec<Item>,
@@ -1150,6 +1490,489 @@ impl<'a> RsCodeGenerator<'a> {
         })]
     }
 
+    /// A Rust type which cbindgen exported to C++ isn't otherwise known to
+    /// cxx, so we tell cxx that it's the C++ type of the same name. It's
+    /// `#[repr(C)]` with no destructor or move constructor in C++, so it's
+    /// trivial.
+    fn generate_cbindgen_extern_type_impl(name: &QualifiedName, rust_path: &TypePath) -> Item {
+        let tynamestring = name.to_cpp_name();
+        Item::Impl(parse_quote! {
+            unsafe impl cxx::ExternType for #rust_path {
+                type Id = cxx::type_id!(#tynamestring);
+                type Kind = cxx::kind::Trivial;
+            }
+        })
+    }
+
+    /// Generate `AsRef<[u8]>` for types listed in `byte_buffer!`. The
+    /// slice borrows `self`, so it can't outlive the C++ object, and the
+    /// object can't be mutated (and thus reallocate its buffer) through
+    /// a `Pin<&mut>` while the slice is alive.
+    ///
+    /// We generate this alongside the size method, since its return type
+    /// tells us how to turn the size into a `usize`.
+    fn generate_byte_buffer_impl(&self, analysis: &FnAnalysis) -> Option<Item> {
+        let impl_for = match &analysis.kind {
+            FnKind::Method { impl_for, .. } => impl_for,
+            _ => return None,
+        };
+        let byte_buffer = self.config.get_byte_buffer(&impl_for.to_cpp_name())?;
+        if byte_buffer.size_method != analysis.rust_name {
+            return None;
+        }
+        let size_ty = match &analysis.ret_type {
+            ReturnType::Type(_, ty) => ty.as_ref(),
+            ReturnType::Default => return None,
+        };
+        let id = impl_for.get_final_ident();
+        let data_method = &byte_buffer.data_method;
+        let size_method = &byte_buffer.size_method;
+        let len = if matches!(size_ty, Type::Path(typ) if typ.path.is_ident("usize")) {
+            quote! { self.#size_method() }
+        } else {
+            // Our C integer newtypes wrap the primitive we can convert.
+            let size = if c_integer_newtype(size_ty).is_some() {
+                quote! { self.#size_method().0 }
+            } else {
+                quote! { self.#size_method() }
+            };
+            quote! {
+                <usize as ::core::convert::TryFrom<_>>::try_from(#size)
+                    .expect("byte buffer size doesn't fit in a usize")
+            }
+        };
+        Some(Item::Impl(parse_quote! {
+            impl AsRef<[u8]> for #id {
+                fn as_ref(&self) -> &[u8] {
+                    unsafe {
+                        let len = #len;
+                        if len == 0 {
+                            // The data pointer may be null for an empty buffer.
+                            return &[];
+                        }
+                        ::core::slice::from_raw_parts(self.#data_method().cast::<u8>(), len)
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Generate the `#[repr(transparent)]` handle type requested by
+    /// `opaque_handle!`. A handle which owns its object frees it on drop,
+    /// by calling the destroy function through a cxx::bridge entry of our
+    /// own. Otherwise, the handle is a plain copyable pointer.
+    fn add_opaque_handle(
+        &self,
+        name: &QualifiedName,
+        id: &Ident,
+        handle: &OpaqueHandle,
+        result: &mut RsCodegenResult,
+    ) {
+        let handle_id = &handle.handle;
+        let doc = format!(
+            "A handle to a C++ `{}`, which is only ever used through pointers.",
+            name.to_cpp_name()
+        );
+        let (derives, owning_items) = match &handle.destroy {
+            Some(destroy) => {
+                let destroy_name = QualifiedName::new_from_cpp_name(destroy);
+                let destroy_cpp_id = destroy_name.get_final_item();
+                let destroy_id = make_ident(format!("{handle_id}_destroy"));
+                let ns = destroy_name.get_namespace();
+                let namespace_attr = if ns.is_empty() {
+                    None
+                } else {
+                    let ns_string = ns.to_string();
+                    Some(quote! { #[namespace = #ns_string] })
+                };
+                result
+                    .extern_c_mod_items
+                    .push(ForeignItem::Verbatim(quote! {
+                        #namespace_attr
+                        #[cxx_name = #destroy_cpp_id]
+                        unsafe fn #destroy_id(handle: *mut #id);
+                    }));
+                (
+                    quote! { #[derive(Debug, PartialEq, Eq)] },
+                    vec![
+                        parse_quote! {
+                            impl #handle_id {
+                                /// Give up ownership of the object, which
+                                /// will no longer be freed on drop.
+                                pub fn into_raw(self) -> *mut #id {
+                                    let ptr = self.as_ptr();
+                                    ::core::mem::forget(self);
+                                    ptr
+                                }
+                            }
+                        },
+                        parse_quote! {
+                            impl Drop for #handle_id {
+                                fn drop(&mut self) {
+                                    unsafe { cxxbridge::#destroy_id(self.as_ptr()) }
+                                }
+                            }
+                        },
+                    ],
+                )
+            }
+            None => (
+                quote! { #[derive(Debug, Clone, Copy, PartialEq, Eq)] },
+                Vec::new(),
+            ),
+        };
+        result.bindgen_mod_items.push(parse_quote! {
+            #[doc = #doc]
+            #derives
+            #[repr(transparent)]
+            pub struct #handle_id(::core::ptr::NonNull<#id>);
+        });
+        result.bindgen_mod_items.push(parse_quote! {
+            impl #handle_id {
+                /// Wrap a pointer to the object, or return `None` if
+                /// it's null.
+                ///
+                /// # Safety
+                ///
+                /// The pointer must point to a live object, and if this
+                /// handle frees the object on drop, nothing else may.
+                pub unsafe fn from_raw(ptr: *mut #id) -> ::core::option::Option<Self> {
+                    ::core::ptr::NonNull::new(ptr).map(Self)
+                }
+
+                /// The pointer to the object.
+                pub fn as_ptr(&self) -> *mut #id {
+                    self.0.as_ptr()
+                }
+            }
+        });
+        result.bindgen_mod_items.extend(owning_items);
+        result
+            .materializations
+            .push(Use::SpecificNameFromBindgen(handle_id.clone()));
+    }
+
+    /// Generate an enum with a variant per union member, and a safe
+    /// accessor which reads the tag to decide which variant to return.
+    /// Enumerators which have the same value as an earlier one become
+    /// constants equal to it. That works just the same for a
+    /// `bitflags_enum!`, whose enumerators are all constants anyway.
+    fn generate_enum_aliases(id: &Ident, aliases: &[EnumAlias]) -> Item {
+        let consts = aliases.iter().map(|EnumAlias { alias, canonical }| {
+            quote! {
+                pub const #alias: Self = Self::#canonical;
+            }
+        });
+        parse_quote! {
+            #[allow(non_upper_case_globals)]
+            impl #id {
+                #(#consts)*
+            }
+        }
+    }
+
+    /// Conjuring up a Rust enum holding a value which isn't one of its
+    /// variants is undefined behavior, so integers from elsewhere have to
+    /// be checked against each variant. `i64` is wide enough for nearly
+    /// any enum, and comparing as `i128` works whatever its representation.
+    fn generate_enum_try_from(item: &ItemEnum) -> Item {
+        let id = &item.ident;
+        let checks = item.variants.iter().map(|variant| {
+            let variant_id = &variant.ident;
+            quote! {
+                if i128::from(value) == Self::#variant_id as i128 {
+                    return ::core::result::Result::Ok(Self::#variant_id);
+                }
+            }
+        });
+        parse_quote! {
+            impl ::core::convert::TryFrom<i64> for #id {
+                type Error = autocxx::InvalidEnumValue;
+                fn try_from(value: i64) -> ::core::result::Result<Self, Self::Error> {
+                    #(#checks)*
+                    ::core::result::Result::Err(autocxx::InvalidEnumValue(value))
+                }
+            }
+        }
+    }
+
+    /// Generate an enum with a variant per union member, and a safe accessor
+    /// which reads the tag to decide which to return. It calls the private
+    /// getters, which are `unsafe` only if every function is.
+    fn generate_tagged_union_accessor(
+        id: &Ident,
+        tagged_union: &TaggedUnionAnalysis,
+        unchecked_is_unsafe: bool,
+    ) -> Vec<Item> {
+        let enum_name = &tagged_union.enum_name;
+        let accessor_name = &tagged_union.accessor_name;
+        let tag_getter = &tagged_union.tag_getter;
+        let variant_defs = tagged_union.variants.iter().map(|v| {
+            let member = &v.member;
+            let ty = &v.ty;
+            quote! { #member(&'a #ty) }
+        });
+        let match_arms = tagged_union.variants.iter().map(|v| {
+            let member = &v.member;
+            let getter = &v.getter;
+            let tag = Literal::u64_unsuffixed(v.tag.unsigned_abs());
+            let tag = if v.tag < 0 {
+                quote! { -#tag }
+            } else {
+                quote! { #tag }
+            };
+            quote! { #tag => #enum_name::#member(self.#getter()) }
+        });
+        let enum_doc = format!(
+            "The active member of the union within [`{id}`], as determined by its tag. `Unknown` holds any tag value which doesn't correspond to a known member."
+        );
+        let body = quote! {
+            match self.#tag_getter() {
+                #(#match_arms,)*
+                other => #enum_name::Unknown(other),
+            }
+        };
+        let body = if unchecked_is_unsafe {
+            quote! { unsafe { #body } }
+        } else {
+            body
+        };
+        vec![
+            Item::Enum(parse_quote! {
+                #[doc = #enum_doc]
+                #[allow(non_camel_case_types)]
+                pub enum #enum_name<'a> {
+                    #(#variant_defs,)*
+                    Unknown(i64),
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl #id {
+                    /// Reads the tag and returns a reference to the matching union member.
+                    pub fn #accessor_name(&self) -> #enum_name<'_> {
+                        #body
+                    }
+                }
+            }),
+        ]
+    }
+
+    /// Generate an enum with a variant per alternative of a `std::variant`,
+    /// and a safe accessor which reads `index()` to decide which to return.
+    /// It calls the private `_unchecked` getters, which are `unsafe` only
+    /// if every function is.
+    fn generate_variant_accessor(
+        id: &Ident,
+        std_variant: &VariantAnalysis,
+        unchecked_is_unsafe: bool,
+    ) -> Vec<Item> {
+        let enum_name = &std_variant.enum_name;
+        let index_getter = &std_variant.index_getter;
+        let variant_defs =
+            std_variant
+                .alternatives
+                .iter()
+                .enumerate()
+                .map(|(index, alternative)| {
+                    let member = make_ident(format!("Alt{index}"));
+                    match alternative {
+                        VariantAlternativeAnalysis::Monostate => quote! { #member },
+                        VariantAlternativeAnalysis::Value { ty, .. } => quote! { #member(&'a #ty) },
+                    }
+                });
+        let match_arms = std_variant
+            .alternatives
+            .iter()
+            .enumerate()
+            .map(|(index, alternative)| {
+                let member = make_ident(format!("Alt{index}"));
+                let index = Literal::usize_unsuffixed(index);
+                match alternative {
+                    VariantAlternativeAnalysis::Monostate => {
+                        quote! { #index => #enum_name::#member }
+                    }
+                    VariantAlternativeAnalysis::Value { getter, .. } => {
+                        quote! { #index => #enum_name::#member(self.#getter()) }
+                    }
+                }
+            });
+        let enum_doc = format!(
+            "The active alternative of a [`{id}`], numbered as in C++. `std::monostate` alternatives carry no value. `ValuelessByException` corresponds to C++'s `valueless_by_exception()`."
+        );
+        let body = quote! {
+            match self.#index_getter() {
+                #(#match_arms,)*
+                _ => #enum_name::ValuelessByException,
+            }
+        };
+        let body = if unchecked_is_unsafe {
+            quote! { unsafe { #body } }
+        } else {
+            body
+        };
+        vec![
+            Item::Enum(parse_quote! {
+                #[doc = #enum_doc]
+                pub enum #enum_name<'a> {
+                    #(#variant_defs,)*
+                    ValuelessByException,
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl #id {
+                    /// Reads `index()` and returns a reference to the active alternative.
+                    pub fn variant(&self) -> #enum_name<'_> {
+                        #body
+                    }
+                }
+            }),
+        ]
+    }
+
+    /// Generate methods to read and change single bits of a `std::bitset`,
+    /// which check the position so that C++ never throws. They call the
+    /// `_unchecked` methods, which are `unsafe` only if every function is.
+    fn generate_bitset_accessors(
+        id: &Ident,
+        size: usize,
+        track_caller: bool,
+        unchecked_is_unsafe: bool,
+    ) -> Item {
+        let size = Literal::usize_unsuffixed(size);
+        let check = quote! {
+            assert!(pos < #size, "bit {} is out of range for a bitset of {} bits", pos, #size);
+        };
+        let track_caller = track_caller.then(|| quote! { #[track_caller] });
+        let call_unchecked = |call: TokenStream| {
+            if unchecked_is_unsafe {
+                quote! { unsafe { #call } }
+            } else {
+                call
+            }
+        };
+        let test = call_unchecked(quote! { self.test_unchecked(pos) });
+        let set = call_unchecked(quote! { self.set_unchecked(pos, value) });
+        let reset = call_unchecked(quote! { self.reset_unchecked(pos) });
+        let flip = call_unchecked(quote! { self.flip_unchecked(pos) });
+        Item::Impl(parse_quote! {
+            impl #id {
+                /// Returns whether the bit at `pos` is set.
+                /// Panics if `pos` is out of range.
+                #track_caller
+                pub fn test(&self, pos: usize) -> bool {
+                    #check
+                    #test
+                }
+                /// Sets the bit at `pos` to `value`.
+                /// Panics if `pos` is out of range.
+                #track_caller
+                pub fn set(self: ::core::pin::Pin<&mut Self>, pos: usize, value: bool) {
+                    #check
+                    #set
+                }
+                /// Clears the bit at `pos`.
+                /// Panics if `pos` is out of range.
+                #track_caller
+                pub fn reset(self: ::core::pin::Pin<&mut Self>, pos: usize) {
+                    #check
+                    #reset
+                }
+                /// Toggles the bit at `pos`.
+                /// Panics if `pos` is out of range.
+                #track_caller
+                pub fn flip(self: ::core::pin::Pin<&mut Self>, pos: usize) {
+                    #check
+                    #flip
+                }
+            }
+        })
+    }
+
+    /// Generate an iterator over a range, plus `IntoIterator` for references
+    /// to the range so that it can be used directly in `for` loops. The
+    /// iterator owns a C++ iterator, which it advances one element at a time.
+    /// The length is only for `ExactSizeIterator`, so it's `unsafe` to get
+    /// only if every function is; the other calls take raw pointers.
+    fn generate_range_iterator(
+        id: &Ident,
+        range: &RangeAnalysis,
+        len_is_unsafe: bool,
+    ) -> Vec<Item> {
+        let iter_name = &range.iter_name;
+        let len_getter = &range.len_getter;
+        let begin = &range.begin;
+        let next = &range.next;
+        let free = &range.free;
+        let item_ty = &range.item_ty;
+        let iter_doc = format!("An iterator over references to the elements of a [`{id}`].");
+        let (len, begin) = if len_is_unsafe {
+            (
+                quote! { unsafe { range.#len_getter() } },
+                quote! { unsafe { range.#begin() } },
+            )
+        } else {
+            (quote! { range.#len_getter() }, quote! { range.#begin() })
+        };
+        vec![
+            Item::Struct(parse_quote! {
+                #[doc = #iter_doc]
+                pub struct #iter_name<'a> {
+                    range: &'a #id,
+                    iter: *mut autocxx::c_void,
+                    remaining: usize,
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl<'a> Iterator for #iter_name<'a> {
+                    type Item = &'a #item_ty;
+                    fn next(&mut self) -> Option<Self::Item> {
+                        // The C++ iterator points into `self.range`, which we borrow
+                        // for 'a, and returns null once it reaches `end()`.
+                        let item = unsafe { self.range.#next(self.iter).as_ref() }?;
+                        self.remaining = self.remaining.saturating_sub(1);
+                        Some(item)
+                    }
+                    fn size_hint(&self) -> (usize, Option<usize>) {
+                        (self.remaining, Some(self.remaining))
+                    }
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl<'a> ExactSizeIterator for #iter_name<'a> {}
+            }),
+            Item::Impl(parse_quote! {
+                impl<'a> Drop for #iter_name<'a> {
+                    fn drop(&mut self) {
+                        // We allocated this iterator in `iter()`, for this range.
+                        unsafe { self.range.#free(self.iter) }
+                    }
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl #id {
+                    /// Returns an iterator over references to the elements of this range.
+                    pub fn iter(&self) -> #iter_name<'_> {
+                        let range = self;
+                        #iter_name {
+                            range,
+                            iter: #begin,
+                            remaining: #len,
+                        }
+                    }
+                }
+            }),
+            Item::Impl(parse_quote! {
+                impl<'a> IntoIterator for &'a #id {
+                    type Item = &'a #item_ty;
+                    type IntoIter = #iter_name<'a>;
+                    fn into_iter(self) -> Self::IntoIter {
+                        self.iter()
+                    }
+                }
+            }),
+        ]
+    }
+
     fn generate_cxxbridge_type(
         &self,
         name: &QualifiedName,
@@ -1209,6 +2032,25 @@ impl<'a> RsCodeGenerator<'a> {
         ForeignItem::Verbatim(for_extern_c_ts)
     }
 
+    /// Types which are only forward-declared can't have any methods, and
+    /// can only be used by pointer or reference. Say so in their
+    /// documentation, so it's clear why there's so little to them.
+    fn note_incomplete_type(name: &QualifiedName, result: &mut RsCodegenResult) {
+        let doc = format!(
+            " `{}` is only forward-declared in the headers given to autocxx, so this \
+            is an opaque type which can only be used by pointer or reference.",
+            name.to_cpp_name()
+        );
+        for item in result.extern_c_mod_items.iter_mut() {
+            if let ForeignItem::Verbatim(ts) = item {
+                *ts = quote! {
+                    #[doc = #doc]
+                    #ts
+                };
+            }
+        }
+    }
+
     fn find_output_mod_root(ns: &Namespace) -> impl Iterator<Item = Ident> {
         std::iter::repeat(make_ident("super")).take(ns.depth())
     }
@@ -0,0 +1,47 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use syn::{visit_mut::VisitMut, Item, Path, PathSegment};
+
+use crate::types::make_ident;
+
+/// Rewrites paths to `::std` in the generated code to refer to `::core`
+/// or `::alloc` instead, for use in `no_std` crates. Everything we (and
+/// bindgen) refer to within `std` is a re-export of one of those.
+pub(super) fn rewrite_std_paths(items: &mut [Item]) {
+    for item in items {
+        StdPathRewriter.visit_item_mut(item);
+    }
+}
+
+struct StdPathRewriter;
+
+impl VisitMut for StdPathRewriter {
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        if path.leading_colon.is_some()
+            && path.segments.len() > 2
+            && path.segments[0].ident == "std"
+        {
+            if path.segments[1].ident == "os" && path.segments[2].ident == "raw" {
+                // `core::ffi` has all the C types from `std::os::raw`.
+                let rest: Vec<PathSegment> = path.segments.iter().skip(3).cloned().collect();
+                path.segments = [make_ident("core"), make_ident("ffi")]
+                    .into_iter()
+                    .map(PathSegment::from)
+                    .chain(rest)
+                    .collect();
+            } else {
+                let in_alloc = ["boxed", "vec", "string", "rc", "borrow"]
+                    .iter()
+                    .any(|module| path.segments[1].ident == module);
+                path.segments[0].ident = make_ident(if in_alloc { "alloc" } else { "core" });
+            }
+        }
+        syn::visit_mut::visit_path_mut(self, path);
+    }
+}
@@ -127,6 +127,6 @@ pub(crate) fn make_non_pod(s: &mut ItemStruct, layout: Option<Layout>) {
     } })
 }
 
-fn make_lit_int(val: usize) -> LitInt {
+pub(crate) fn make_lit_int(val: usize) -> LitInt {
     LitInt::new(&val.to_string(), Span::call_site())
 }
@@ -0,0 +1,152 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use indexmap::map::IndexMap as HashMap;
+
+use autocxx_parser::IncludeCppConfig;
+use quote::quote;
+use syn::{parse_quote, Item, Type, TypeReference};
+
+use crate::{
+    conversion::{
+        analysis::fun::{
+            function_wrapper::RustConversionType, FnAnalysis, FnKind, FnPhase, MethodKind,
+        },
+        api::{Api, UnsafetyNeeded},
+        apivec::ApiVec,
+    },
+    known_types::known_types,
+    types::{make_ident, QualifiedName},
+};
+
+/// How a constructor accepts its string.
+enum StringParam {
+    /// By value, so the wrapper accepts anything implementing `ToCppString`.
+    Value,
+    /// By const reference, so the wrapper accepts a `&CxxString`.
+    Reference,
+}
+
+/// For each type listed in `string_constructible!`, find its constructor
+/// which takes a single string, and implement `From<&str>` using it.
+/// Returns the implementation keyed by the name of the constructor
+/// function, so it can be emitted alongside it.
+///
+/// The implementation is for `Pin<Box<T>>` because the orphan rules don't
+/// allow us to implement `From` for `UniquePtr<T>`.
+pub(super) fn generate_from_str_impls(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> HashMap<QualifiedName, Item> {
+    if config.unsafe_policy.requires_cpprefs() || config.exclude_utilities() {
+        return HashMap::new();
+    }
+    let mut candidates: HashMap<&QualifiedName, Vec<_>> = HashMap::new();
+    for api in apis.iter() {
+        if let Api::Function {
+            name,
+            analysis:
+                FnAnalysis {
+                    kind:
+                        FnKind::Method {
+                            impl_for,
+                            method_kind: MethodKind::Constructor { .. },
+                        },
+                    ignore_reason: Ok(_),
+                    externally_callable: true,
+                    rust_name,
+                    param_details,
+                    requires_unsafe,
+                    ..
+                },
+            ..
+        } = api
+        {
+            if !config.is_string_constructible(&impl_for.to_cpp_name())
+                || matches!(requires_unsafe, UnsafetyNeeded::Always)
+            {
+                continue;
+            }
+            let mut params = param_details
+                .iter()
+                .filter(|pd| pd.self_type.is_none() && !pd.is_placement_return_destination);
+            let string_param = match (params.next(), params.next()) {
+                (Some(pd), None) => match (
+                    &pd.conversion.rust_conversion,
+                    pd.conversion.cxxbridge_type(),
+                ) {
+                    (RustConversionType::FromStr | RustConversionType::FromStrRef, _) => {
+                        Some(StringParam::Value)
+                    }
+                    (
+                        RustConversionType::None,
+                        Type::Reference(TypeReference {
+                            mutability: None,
+                            elem,
+                            ..
+                        }),
+                    ) => match elem.as_ref() {
+                        Type::Path(typ)
+                            if known_types()
+                                .convertible_from_strs(&QualifiedName::from_type_path(typ)) =>
+                        {
+                            Some(StringParam::Reference)
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(string_param) = string_param {
+                candidates.entry(impl_for).or_default().push((
+                    name.name.clone(),
+                    rust_name,
+                    string_param,
+                ));
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .filter_map(|(ty, mut constructors)| {
+            if constructors.len() > 1 {
+                log::info!(
+                    "Not implementing From<&str> for {} because it has {} constructors taking a string",
+                    ty,
+                    constructors.len()
+                );
+                return None;
+            }
+            let (fn_name, rust_name, string_param) = constructors.pop()?;
+            let ty = ty.get_final_ident();
+            let constructor = make_ident(rust_name);
+            let construction = match string_param {
+                StringParam::Value => quote! { #ty::#constructor(s) },
+                StringParam::Reference => quote! {
+                    #ty::#constructor(&ToCppString::into_cpp(s))
+                },
+            };
+            let construction = if config.boxed_returns() {
+                construction
+            } else {
+                quote! { autocxx::WithinBox::within_box(#construction) }
+            };
+            Some((
+                fn_name,
+                parse_quote! {
+                    impl From<&str> for ::core::pin::Pin<::std::boxed::Box<#ty>> {
+                        fn from(s: &str) -> Self {
+                            #construction
+                        }
+                    }
+                },
+            ))
+        })
+        .collect()
+}
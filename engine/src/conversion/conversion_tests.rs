@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use autocxx_parser::UnsafePolicy;
+use autocxx_parser::{IncludeCppConfig, UnsafePolicy};
+use quote::ToTokens;
 #[allow(unused_imports)]
 use syn::parse_quote;
 use syn::ItemMod;
@@ -45,3 +46,1586 @@ fn do_test(input: ItemMod) {
 // fn test_xyz() {
 //      do_test(parse_quote!{ /* paste bindgen output here */})
 // }
+
+/// Like `do_test` but with some `include_cpp!` directives, returning
+/// the generated Rust code as a string.
+fn do_test_with_directives(directives: IncludeCppConfig, input: ItemMod) -> String {
+    let mut tc = directives;
+    tc.confirm_complete();
+    let bc = BridgeConverter::new(&[], &tc);
+    let inclusions = "".into();
+    bc.convert(
+        input,
+        UnsafePolicy::AllFunctionsSafe,
+        inclusions,
+        &CodegenOptions::default(),
+        "",
+    )
+    .unwrap()
+    .rs
+    .into_iter()
+    .map(|item| item.into_token_stream().to_string())
+    .collect()
+}
+
+// bindgen's own [[nodiscard]] detection gives a bare #[must_use], so we
+// can only test the mapping of a reason from annotated bindgen output.
+#[test]
+fn test_nodiscard_function() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("try_lock") generate!("compute") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        #[cpp_semantics(nodiscard("the lock may not be held"))]
+                        pub fn try_lock() -> bool;
+                        #[must_use]
+                        pub fn compute() -> u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("# [must_use = \"the lock may not be held\"] pub fn try_lock"));
+    assert!(rs.contains("# [must_use] pub fn compute"));
+}
+
+#[test]
+fn test_pod_layout_assertion() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate_pod!("Widget") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[cpp_semantics(layout(16, 8, false))]
+                    pub struct Widget {
+                        pub a: i64,
+                        pub b: ::std::os::raw::c_char,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains(
+        ":: core :: mem :: size_of :: < Widget > () == 16 && :: core :: mem :: align_of :: < Widget > () == 8"
+    ));
+}
+
+#[test]
+fn test_bitflags_enum() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("Permissions") bitflags_enum!("Permissions") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(u32)]
+                    #[derive(Clone, Hash, PartialEq, Eq)]
+                    pub enum Permissions {
+                        Read = 1,
+                        Write = 2,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub struct Permissions (pub u32)"));
+    assert!(rs.contains("pub const Write : Self = Self (2)"));
+    assert!(rs.contains("impl :: core :: ops :: BitOr for Permissions"));
+    assert!(rs.contains(
+        "< u32 as :: core :: convert :: TryFrom < i64 > > :: try_from (value) . map (Self)"
+    ));
+}
+
+#[test]
+fn test_enum_try_from() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("Color") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(i32)]
+                    #[derive(Clone, Hash, PartialEq, Eq)]
+                    pub enum Color {
+                        Red = -1,
+                        Green = 2,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("impl :: core :: convert :: TryFrom < i64 > for Color"));
+    assert!(rs.contains(
+        "if i128 :: from (value) == Self :: Red as i128 { return :: core :: result :: Result :: Ok (Self :: Red) ; }"
+    ));
+    assert!(rs.contains(":: core :: result :: Result :: Err (autocxx :: InvalidEnumValue (value))"));
+}
+
+#[test]
+fn test_inline_passthrough_wrapper() {
+    // A safe wrapper around a function taking a raw pointer calls straight
+    // through to cxx, with no C++ wrapper.
+    let input: ItemMod = parse_quote! {
+        mod bindgen {
+            pub mod root {
+                extern "C" {
+                    #[link_name = "\u{1}_Z5countPKj"]
+                    pub fn count(values: *const u32) -> u32;
+                }
+            }
+        }
+    };
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("count") safe_fn!("count") },
+        input.clone(),
+    );
+    assert!(rs.contains("# [inline] pub fn count (values : * const u32) -> u32"));
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("count") safe_fn!("count") inline_always!() },
+        input,
+    );
+    assert!(rs.contains("# [inline (always)] pub fn count (values : * const u32) -> u32"));
+}
+
+#[test]
+fn test_span_params() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("sum") generate!("fill") mut_span_param!("fill", out) },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod std {
+                        #[repr(C)]
+                        pub struct span<T> {
+                            pub ptr: *mut T,
+                            pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        }
+                    }
+                    extern "C" {
+                        #[link_name = "\u{1}_Z3sumSt4spanIKjLm18446744073709551615EE"]
+                        pub fn sum(values: root::std::span<u32>) -> u32;
+                        #[link_name = "\u{1}_Z4fillSt4spanIjLm18446744073709551615EEj"]
+                        pub fn fill(out: root::std::span<u32>, value: u32);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("(values : & [u32]) -> u32"));
+    assert!(rs.contains("(out : & mut [u32] , value : u32)"));
+}
+
+#[test]
+fn test_vec_params() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("total")
+            generate!("consume")
+            vec_param!("total", values)
+            vec_param!("consume", values)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod std {
+                        #[repr(C)]
+                        pub struct vector<T> {
+                            pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                            pub _address: u8,
+                        }
+                    }
+                    extern "C" {
+                        pub fn total(values: root::std::vector<u32>) -> u32;
+                        #[cpp_semantics(arg_type_rvalue_reference(values))]
+                        pub fn consume(values: *mut root::std::vector<u32>);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn total (values : :: std :: vec :: Vec < u32 >) -> u32"));
+    assert!(rs.contains("pub fn consume (values : :: std :: vec :: Vec < u32 >)"));
+}
+
+#[test]
+fn test_vector_fallback() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("total")
+            generate!("count_set")
+            generate!("count_words")
+            instantiate!("std::vector", ["bool"], BoolVector)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod std {
+                        #[repr(C)]
+                        pub struct vector<T> {
+                            pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                            pub _address: u8,
+                        }
+                    }
+                    extern "C" {
+                        pub fn total(values: *const root::std::vector<u32>) -> u32;
+                        pub fn count_set(values: *const root::std::vector<bool>) -> usize;
+                        pub fn count_words(
+                            lines: *const root::std::vector<root::std::vector<u32>>,
+                        ) -> usize;
+                    }
+                }
+            }
+        },
+    );
+    // cxx can hold u32s in a CxxVector, but not bools or other vectors.
+    assert!(rs.contains("pub unsafe fn total (values : * const CxxVector < u32 >) -> u32"));
+    assert!(rs.contains("pub unsafe fn count_set (values : * const BoolVector) -> usize"));
+    assert!(rs.contains(
+        "pub unsafe fn count_words (lines : * const std_vector_std_vector_uint32_t_AutocxxConcrete) -> usize"
+    ));
+    assert!(rs.contains("pub fn len (self : & root :: BoolVector) -> usize"));
+}
+
+#[test]
+fn test_complex() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("conj")
+            generate!("magnitude")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod std {
+                        #[repr(C)]
+                        pub struct complex<T> {
+                            pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                            pub _address: u8,
+                        }
+                    }
+                    extern "C" {
+                        pub fn conj(z: root::std::complex<f64>) -> root::std::complex<f64>;
+                        pub fn magnitude(z: *const root::std::complex<f32>) -> f32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn conj (z : c_complex_double) -> c_complex_double"));
+    assert!(rs.contains("pub unsafe fn magnitude (z : * const c_complex_float)"));
+    assert!(rs.contains("type c_complex_float = autocxx :: c_complex_float"));
+}
+
+fn nested_template_bindings() -> ItemMod {
+    parse_quote! {
+        mod bindgen {
+            pub mod root {
+                pub mod std {
+                    #[repr(C)]
+                    pub struct vector<T> {
+                        pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        pub _address: u8,
+                    }
+                    #[repr(C)]
+                    pub struct unique_ptr<T> {
+                        pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        pub _address: u8,
+                    }
+                }
+                #[repr(C)]
+                pub struct Widget {
+                    pub _bindgen_opaque_blob: [u64; 2usize],
+                }
+                extern "C" {
+                    pub fn make_counts() -> root::std::unique_ptr<root::std::vector<u32>>;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_max_template_depth() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("make_counts")
+            max_template_depth!(2)
+        },
+        nested_template_bindings(),
+    );
+    // Exactly at the limit, so converted in full.
+    assert!(rs.contains("pub fn make_counts () -> UniquePtr < CxxVector < u32 > >"));
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("make_counts")
+            max_template_depth!(1)
+        },
+        nested_template_bindings(),
+    );
+    assert!(
+        rs.contains("pub fn make_counts () -> UniquePtr < std_vector_uint32_t_AutocxxConcrete >")
+    );
+}
+
+#[test]
+fn test_opaque_handles() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("file_open")
+            generate!("file_size")
+            generate!("window_current")
+            generate!("window_width")
+            opaque_handle!("File", FileHandle, "file_close", "file_open")
+            opaque_handle!("Window", WindowHandle)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct File {
+                        _unused: [u8; 0],
+                    }
+                    #[repr(C)]
+                    pub struct Window {
+                        _unused: [u8; 0],
+                    }
+                    extern "C" {
+                        pub fn file_open() -> *mut root::File;
+                        pub fn file_size(file: *mut root::File) -> u32;
+                        pub fn window_current() -> *mut root::Window;
+                        pub fn window_width(window: *const root::Window) -> u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub struct FileHandle (:: core :: ptr :: NonNull < File >)"));
+    assert!(rs.contains("impl Drop for FileHandle"));
+    assert!(rs.contains("# [cxx_name = \"file_close\"] unsafe fn FileHandle_destroy"));
+    assert!(rs.contains("derive (Debug , Clone , Copy , PartialEq , Eq)"));
+    assert!(
+        rs.contains("pub fn file_open () -> :: core :: option :: Option < root :: FileHandle >")
+    );
+    // An owning handle keeps the object alive...
+    assert!(rs.contains("pub fn file_size (file : & root :: FileHandle) -> u32"));
+    // ...but a copyable one can't.
+    assert!(rs.contains("pub unsafe fn window_width (window : & root :: WindowHandle) -> u32"));
+}
+
+// Where several headers declare the same types, we keep one of each,
+// preferring a full definition over a forward declaration.
+#[test]
+fn test_redeclarations_merged() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("use_both")
+            generate_pod!("Point")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Widget {
+                        _unused: [u8; 0],
+                    }
+                    /// From the first header.
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 4, false))]
+                    pub struct Point {
+                        pub x: i32,
+                        pub y: i32,
+                    }
+                    /// From the second header.
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 4, false))]
+                    pub struct Point {
+                        pub x: i32,
+                        pub y: i32,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(4, 4, false))]
+                    pub struct Widget {
+                        pub size: i32,
+                    }
+                    extern "C" {
+                        pub fn use_both(point: root::Point, widget: *mut root::Widget) -> i32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub unsafe fn use_both"));
+    assert_eq!(rs.matches("pub struct Point").count(), 1);
+    assert!(rs.contains("pub struct Widget"));
+}
+
+// bindgen's representation of `long double` depends on the platform, so
+// we test with its x86-64 Linux output, where it's a `u128`.
+#[test]
+fn test_long_double() {
+    let long_double_output = || -> ItemMod {
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[repr(align(16))]
+                    #[cpp_semantics(layout(32, 16, false))]
+                    pub struct Reading {
+                        pub value: u128,
+                        pub id: ::std::os::raw::c_int,
+                    }
+                    extern "C" {
+                        pub fn half(x: u128) -> u128;
+                    }
+                }
+            }
+        }
+    };
+    let rs = do_test_with_directives(parse_quote! { generate!("half") }, long_double_output());
+    assert!(!rs.contains("fn half"));
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("half") long_double!(f64) },
+        long_double_output(),
+    );
+    assert!(rs.contains("(x : f64) -> f64"));
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("half") generate_pod!("Reading") long_double!(opaque) },
+        long_double_output(),
+    );
+    assert!(rs.contains("fn half (x : c_longdouble) -> c_longdouble"));
+    // Fields of POD structs keep bindgen's representation.
+    assert!(rs.contains("pub struct Reading { pub value : u128"));
+}
+
+#[test]
+fn test_assertion_handler() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("checked_div") assertion_handler!(my_assert_failed) },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn checked_div(
+                            a: ::std::os::raw::c_int,
+                            b: ::std::os::raw::c_int,
+                        ) -> ::std::os::raw::c_int;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("extern \"Rust\" { fn autocxx_assertion_failed_"));
+    assert!(rs.contains("(expr : & [u8] , file : & [u8] , line : u32)"));
+    assert!(rs.contains("panic ! (\"C++ assertion failed at {}:{}: {}\""));
+    assert!(rs.contains("String :: from_utf8_lossy (expr)"));
+}
+
+#[test]
+fn test_abi_version() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("do_thing")
+            abi_version!("mylib_abi_version()", "MYLIB_ABI_VERSION")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn do_thing();
+                    }
+                }
+            }
+        },
+    );
+    // The check happens entirely in C++, as the program starts.
+    assert!(rs.contains("pub fn do_thing"));
+    assert!(!rs.contains("abi_version"));
+}
+
+#[test]
+fn test_platform_typedefs() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("read_some")
+            generate_pod!("Buffer")
+            generate_pod!("Request")
+            generate_pod!("Widget")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub type __ssize_t = ::std::os::raw::c_long;
+                    pub type ssize_t = root::__ssize_t;
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 8, false))]
+                    pub struct Buffer {
+                        pub len: root::ssize_t,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(16, 8, false))]
+                    pub struct Request {
+                        pub id: u32,
+                        pub buf: root::Buffer,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 4, false))]
+                    pub struct Widget {
+                        pub a: u32,
+                        pub b: u32,
+                    }
+                    extern "C" {
+                        pub fn read_some(buf: *mut root::Buffer, len: root::ssize_t) -> root::ssize_t;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub type ssize_t = isize ;"));
+    assert!(rs.contains("fn read_some (buf : * mut Buffer , len : isize) -> isize ;"));
+    // Only the layout which doesn't depend on the platform is asserted.
+    assert!(!rs.contains("size_of :: < Buffer >"));
+    assert!(!rs.contains("size_of :: < Request >"));
+    assert!(rs.contains("size_of :: < Widget >"));
+}
+
+#[test]
+fn test_flexible_string_params() {
+    let bindgen_output = || -> ItemMod {
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        #[cpp_semantics(arg_type_reference(name))]
+                        pub fn greet(name: *const root::std::string) -> u32;
+                    }
+                }
+            }
+        }
+    };
+    let rs = do_test_with_directives(parse_quote! { generate!("greet") }, bindgen_output());
+    assert!(rs.contains("fn greet (name : & CxxString) -> u32"));
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("greet") flexible_string_params!() },
+        bindgen_output(),
+    );
+    assert!(rs.contains("fn greet (name : impl ToCppStringRef) -> u32"));
+    assert!(rs.contains("let str0 = ToCppStringRef :: to_cpp_string_ref (name) ;"));
+}
+
+#[test]
+fn test_no_std() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate_pod!("Point") generate!("flip") exclude_utilities!() no_std!() },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 4, false))]
+                    pub struct Point {
+                        pub x: ::std::os::raw::c_int,
+                        pub y: ::std::os::raw::c_int,
+                    }
+                    extern "C" {
+                        pub fn flip(p: root::Point) -> root::Point;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("fn flip (p : Point) -> Point"));
+    assert!(rs.contains("pub x : :: core :: ffi :: c_int"));
+    assert!(!rs.contains(":: std ::"));
+}
+
+#[test]
+fn test_reference_wrappers() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("pick") generate!("bump") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod std {
+                        #[repr(C)]
+                        pub struct reference_wrapper<T> {
+                            pub ptr: *mut T,
+                            pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        }
+                    }
+                    extern "C" {
+                        #[link_name = "\u{1}_Z4pickRKjj"]
+                        #[cpp_semantics(arg_type_reference(values))]
+                        pub fn pick(values: *const u32, index: u32) -> root::std::reference_wrapper<u32>;
+                        #[link_name = "\u{1}_Z4bumpSt17reference_wrapperIjE"]
+                        pub fn bump(value: root::std::reference_wrapper<u32>);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("(values : & u32 , index : u32) -> & u32"));
+    assert!(rs.contains("(value : Pin < & mut u32 >)"));
+}
+
+#[test]
+fn test_callable_members() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("Widget")
+            callable_member!("Widget", on_click, fn(x: i32, y: i32) -> bool)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Widget {
+                        pub _bindgen_opaque_blob: [u64; 4usize],
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("fn on_click (self : :: core :: pin :: Pin < & mut root :: Widget > , x : i32 , y : i32) -> bool"));
+}
+
+#[test]
+fn test_iterator_lookups() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("Registry")
+            iterator_lookup!("Registry", find -> second, fn(key: &u32) -> &u64)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Registry {
+                        pub _bindgen_opaque_blob: [u64; 6usize],
+                    }
+                    // bindgen's own binding for find, which we can't use.
+                    #[repr(C)]
+                    pub struct Iter {
+                        pub _bindgen_opaque_blob: [u64; 1usize],
+                    }
+                    extern "C" {
+                        #[bindgen_original_name("find")]
+                        pub fn Registry_find(this: *const root::Registry, key: u32) -> root::Iter;
+                    }
+                    impl Registry {
+                        #[inline]
+                        pub unsafe fn find(&self, key: u32) -> root::Iter {
+                            Registry_find(self, key)
+                        }
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains(
+        "pub fn find (self : & root :: Registry , key : & u32) -> :: core :: option :: Option < & u64 >"
+    ));
+}
+
+// A Rust type, exported to C++ by cbindgen, used within a C++ type.
+#[test]
+fn test_cbindgen_types() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate_pod!("Shape")
+            cbindgen_type!("Point", crate::geometry::Point)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Point {
+                        pub x: f64,
+                        pub y: f64,
+                    }
+                    #[repr(C)]
+                    pub struct Shape {
+                        pub origin: root::Point,
+                        pub sides: u32,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains(
+        "unsafe impl cxx :: ExternType for crate :: geometry :: Point { type Id = cxx :: type_id ! (\"Point\") ; type Kind = cxx :: kind :: Trivial ; }"
+    ));
+    assert!(rs.contains("pub use crate :: geometry :: Point as Point ;"));
+    assert!(!rs.contains("pub struct Point"));
+    assert!(rs.contains("pub origin : root :: Point"));
+    // Shape is still POD, since Point is trivial.
+    assert!(rs.contains("type_id ! (\"Shape\") ; type Kind = cxx :: kind :: Trivial ;"));
+}
+
+#[test]
+fn test_ptr_array_params() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("run")
+            generate!("total")
+            ptr_array_param!("run", argv)
+            ptr_array_param!("total", values)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn run(argc: ::std::os::raw::c_int, argv: *const *const ::std::os::raw::c_char);
+                        pub fn total(values: *const *const u32, count: usize) -> u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("argv : * const * const c_char"));
+    assert!(rs.contains("argv : & [& :: std :: ffi :: CStr]"));
+    assert!(rs.contains("values : & [& u32]"));
+    assert!(rs.contains(". chain (:: core :: iter :: once (:: core :: ptr :: null ()))"));
+}
+
+#[test]
+fn test_allocators() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("make_buffer")
+            allocator!("arena::Allocator")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod arena {
+                        #[repr(C)]
+                        pub struct Allocator {
+                            pub _address: u8,
+                        }
+                    }
+                    extern "C" {
+                        #[cpp_semantics(arg_type_reference(alloc))]
+                        pub fn make_buffer(
+                            size: u32,
+                            alloc: *const root::arena::Allocator,
+                        ) -> u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("make_buffer_autocxx_wrapper_"));
+    assert!(rs.contains("(size : u32) -> u32 ;"));
+    assert!(!rs.contains("alloc :"));
+}
+
+#[test]
+fn test_nonnull_params() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("fill")
+            nonnull_param!("fill", out)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn fill(out: *mut u32, hint: *const u32);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("(out : Pin < & mut u32 > , hint : * const u32)"));
+}
+
+#[test]
+fn test_thread_local_var() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            thread_local_var!("counter")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub static mut counter: u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn counter () -> autocxx :: CppThreadLocal < u32 >"));
+}
+
+#[test]
+fn test_constexpr_fns() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            constexpr_fn!("limits::max_size")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod limits {
+                        pub const autocxx_constexpr_max_size: u32 = 64;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub const max_size : u32 = 64"));
+    assert!(!rs.contains("autocxx_constexpr_"));
+}
+
+#[test]
+fn test_string_constants() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("VERSION")
+            generate!("RAW")
+            str_constants!()
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub const VERSION: &[u8; 6usize] = b"1.2.3\0";
+                    pub const RAW: &[u8; 3usize] = b"\xff\xfe\0";
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub const VERSION : & str = \"1.2.3\""));
+    assert!(rs.contains("pub const RAW : & [u8 ; 3usize] = b\"\\xff\\xfe\\0\""));
+}
+
+#[test]
+fn test_return_borrows() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("second")
+            return_borrows!("second", b)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        #[cpp_semantics(arg_type_reference(a))]
+                        #[cpp_semantics(arg_type_reference(b))]
+                        #[cpp_semantics(ret_type_reference)]
+                        pub fn second(a: *const u32, b: *const u32) -> *const u32;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("fn second < 'a > (a : & u32 , b : & 'a u32) -> & 'a u32"));
+}
+
+#[test]
+fn test_move_assignment() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("Buffer") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Buffer {
+                        pub _bindgen_opaque_blob: [u64; 3usize],
+                    }
+                    extern "C" {
+                        #[cpp_semantics(original_name("operator="))]
+                        #[cpp_semantics(special_member("assignment_operator"))]
+                        #[cpp_semantics(arg_type_rvalue_reference(other))]
+                        #[cpp_semantics(ret_type_reference)]
+                        #[link_name = "\u{1}_ZN6BufferaSEOS_"]
+                        pub fn Buffer_operator_equals(
+                            this: *mut root::Buffer,
+                            other: *mut root::Buffer,
+                        ) -> *mut root::Buffer;
+                    }
+                    impl Buffer {
+                        #[inline]
+                        pub unsafe fn operator_equals(&mut self, other: *mut root::Buffer) -> *mut root::Buffer {
+                            Buffer_operator_equals(self, other)
+                        }
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn move_assign_from (self : :: core :: pin :: Pin < & mut root :: Buffer > , other : impl autocxx :: RValueParam < root :: Buffer >)"));
+}
+
+#[test]
+fn test_call_operator() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate!("Adder") },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Adder {
+                        pub base: u32,
+                    }
+                    extern "C" {
+                        #[cpp_semantics(original_name("autocxx_operator_call"))]
+                        #[link_name = "\u{1}_ZNK5AdderclEj"]
+                        pub fn Adder_autocxx_operator_call(this: *const root::Adder, a: u32) -> u32;
+                        #[cpp_semantics(original_name("autocxx_operator_call"))]
+                        #[link_name = "\u{1}_ZNK5AdderclEjj"]
+                        pub fn Adder_autocxx_operator_call1(
+                            this: *const root::Adder,
+                            a: u32,
+                            b: u32,
+                        ) -> u32;
+                    }
+                    impl Adder {
+                        #[inline]
+                        pub unsafe fn autocxx_operator_call(&self, a: u32) -> u32 {
+                            Adder_autocxx_operator_call(self, a)
+                        }
+                        #[inline]
+                        pub unsafe fn autocxx_operator_call1(&self, a: u32, b: u32) -> u32 {
+                            Adder_autocxx_operator_call1(self, a, b)
+                        }
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn call (self : & root :: Adder , a : u32) -> u32"));
+    assert!(rs.contains("pub fn call1 (self : & root :: Adder , a : u32 , b : u32) -> u32"));
+}
+
+#[test]
+fn test_inherited_methods() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("Counter")
+            inherited_method!("Counter", count, fn() -> u32)
+            inherited_method!("Counter", mut add, fn(n: u32))
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Base<T> {
+                        pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        pub total: u32,
+                    }
+                    #[repr(C)]
+                    pub struct Counter {
+                        pub _base: root::Base<root::Counter>,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn count (self : & root :: Counter) -> u32"));
+    assert!(rs
+        .contains("pub fn add (self : :: core :: pin :: Pin < & mut root :: Counter > , n : u32)"));
+}
+
+#[test]
+fn test_exception_types() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("open")
+            throws!("open")
+            exception_type!("valve::Stuck", Stuck)
+            exception_type!("valve::Broken", Broken)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn open();
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub enum CppException"));
+    assert!(rs.contains("Stuck (:: std :: string :: String)"));
+    assert!(rs.contains("Other (cxx :: Exception)"));
+    assert!(rs.contains(
+        ":: core :: option :: Option :: Some ((\"1\" , what)) => Self :: Broken (what . into ())"
+    ));
+}
+
+fn widget_with_unusable_method() -> ItemMod {
+    parse_quote! {
+        mod bindgen {
+            pub mod root {
+                #[repr(C)]
+                pub struct Widget {
+                    pub size: u32,
+                }
+                extern "C" {
+                    #[link_name = "\u{1}_ZN6Widget5scaleEe"]
+                    pub fn Widget_scale(this: *mut root::Widget, factor: u128);
+                }
+                impl Widget {
+                    #[inline]
+                    pub unsafe fn scale(&mut self, factor: u128) {
+                        Widget_scale(self, factor)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_suppress_ignored_items() {
+    let rs = do_test_with_directives(
+        parse_quote! { generate_pod!("Widget") },
+        widget_with_unusable_method(),
+    );
+    assert!(rs.contains("fn Widget_scale (_uhoh : autocxx :: BindingGenerationFailure)"));
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate_pod!("Widget")
+            suppress_ignored_items!()
+        },
+        widget_with_unusable_method(),
+    );
+    assert!(rs.contains("pub struct Widget"));
+    assert!(!rs.contains("BindingGenerationFailure"));
+    assert!(!rs.contains("couldn't be generated"));
+}
+
+#[test]
+fn test_exceptions_panic() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("count")
+            generate!("open")
+            throws!("open")
+            exception_handling!(panic)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn count() -> u32;
+                        pub fn open();
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("() -> Result < u32 > ;"));
+    assert!(rs.contains("pub fn count () -> u32 {"));
+    assert!(rs.contains(
+        "() . unwrap_or_else (| e | :: core :: panic ! (\"C++ exception from count: {}\" , e))"
+    ));
+    // Functions listed in throws! still return the exception.
+    assert!(rs.contains("pub fn open () -> Result < () > ;"));
+    assert!(!rs.contains("C++ exception from open"));
+    assert!(!rs.contains("track_caller"));
+}
+
+#[test]
+fn test_track_caller() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("count")
+            generate!("open")
+            throws!("open")
+            exception_handling!(panic)
+            instantiate!("std::bitset", [8])
+            track_caller!()
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        pub fn count() -> u32;
+                        pub fn open();
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("# [track_caller] pub fn count () -> u32 {"));
+    assert!(rs.contains("# [track_caller] pub fn test (& self , pos : usize) -> bool"));
+    // Functions returning a Result never panic.
+    assert!(!rs.contains("# [track_caller] pub fn open"));
+}
+
+// bindgen represents a forward-declared type as a struct with an
+// `_unused` field.
+#[test]
+fn test_forward_declared_type() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("take_widget")
+            generate!("make_widget")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Widget {
+                        _unused: [u8; 0],
+                    }
+                    extern "C" {
+                        pub fn take_widget(widget: *const root::Widget) -> i32;
+                        pub fn make_widget() -> *mut root::Widget;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("`Widget` is only forward-declared"));
+    assert!(rs.contains("type Widget ;"));
+    assert!(rs.contains("pub unsafe fn take_widget (widget : * const Widget) -> i32"));
+    assert!(rs.contains("fn make_widget () -> * mut Widget"));
+}
+
+// Entries in manual! are passed straight through to the cxx::bridge.
+#[test]
+fn test_manual_bridge_entries() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("add")
+            generate!("A")
+            manual!(
+                fn add(a: u32, b: u32) -> u32;
+                #[cxx_name = "subtract"]
+                fn sub(a: u32, b: u32) -> u32;
+            )
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[cpp_semantics(layout(4, 4, false))]
+                    pub struct A {
+                        pub a: u32,
+                    }
+                    extern "C" {
+                        pub fn add(a: u32, b: u32) -> u32;
+                        #[bindgen_original_name("add")]
+                        pub fn A_add(this: *const root::A, b: u32) -> u32;
+                    }
+                }
+            }
+        },
+    );
+    // Our own binding of `add` gives way to the hand-written one, whose
+    // name also isn't reused for the method in the cxx::bridge.
+    assert!(rs.contains("pub fn add (a : u32 , b : u32) -> u32 ;"));
+    assert!(rs.contains("# [cxx_name = \"subtract\"] pub fn sub (a : u32 , b : u32) -> u32 ;"));
+    assert_eq!(rs.matches("fn add (").count(), 1);
+    assert!(rs.contains("pub fn A_add (self : & A , b : u32) -> u32 ;"));
+    assert!(rs.contains("pub use cxxbridge :: add ;"));
+    assert!(rs.contains("pub use cxxbridge :: sub ;"));
+}
+
+// bindgen only lets us know about special members which are declared
+// explicitly.
+#[test]
+fn test_trivially_copyable_pods() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate_pod!("Point")
+            generate_pod!("Pixel")
+            generate_pod!("Handle")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 4, false))]
+                    pub struct Point {
+                        pub x: ::std::os::raw::c_int,
+                        pub y: ::std::os::raw::c_int,
+                    }
+                    #[repr(u32)]
+                    #[derive(Clone, Hash, PartialEq, Eq)]
+                    pub enum Color {
+                        Red = 0,
+                        Green = 1,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(12, 4, false))]
+                    pub struct Pixel {
+                        pub at: root::Point,
+                        pub color: root::Color,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 8, false))]
+                    pub struct Handle {
+                        pub ptr: *mut ::std::os::raw::c_void,
+                    }
+                    extern "C" {
+                        #[cpp_semantics(special_member("copy_ctor"))]
+                        #[cpp_semantics(arg_type_reference(other))]
+                        #[link_name = "\u{1}_ZN6HandleC1ERKS_"]
+                        pub fn Handle_Handle(this: *mut root::Handle, other: *const root::Handle);
+                        #[cpp_semantics(special_member("move_ctor"))]
+                        #[cpp_semantics(defaulted)]
+                        #[cpp_semantics(arg_type_rvalue_reference(other))]
+                        #[link_name = "\u{1}_ZN6HandleC1EOS_"]
+                        pub fn Handle_Handle1(this: *mut root::Handle, other: *mut root::Handle);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("# [derive (Clone , Copy)] pub struct Point"));
+    // bindgen's enums aren't Copy, so neither is a struct containing one.
+    assert!(rs.contains("# [derive (Clone)] pub struct Pixel"));
+    // A user-defined copy constructor isn't trivial.
+    assert!(rs.contains("pub struct Handle"));
+    assert!(!rs.contains("# [derive (Clone , Copy)] pub struct Handle"));
+    assert!(!rs.contains("# [derive (Clone)] pub struct Handle"));
+}
+
+#[test]
+fn test_reexports() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate_pod!("widgets::Widget")
+            generate!("widgets::widget_count")
+            reexport!("widgets::Widget", Gadget)
+            reexport!("widgets::widget_count", count_gadgets)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod widgets {
+                        #[repr(C)]
+                        #[cpp_semantics(layout(4, 4, false))]
+                        pub struct Widget {
+                            pub size: u32,
+                        }
+                        extern "C" {
+                            pub fn widget_count() -> u32;
+                        }
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains(
+        "pub mod exports { pub use super :: widgets :: Widget as Gadget ; \
+         pub use super :: widgets :: widget_count as count_gadgets ; }"
+    ));
+}
+
+#[test]
+fn test_enum_aliases() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("Color")
+            generate!("Access")
+            bitflags_enum!("Access")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    impl Color {
+                        pub const Primary: Color = Color::Red;
+                    }
+                    #[repr(u32)]
+                    #[derive(Clone, Hash, PartialEq, Eq)]
+                    pub enum Color {
+                        Red = 1,
+                        Green = 2,
+                    }
+                    impl Access {
+                        pub const Default: Access = Access::ReadOnly;
+                        pub const Any: Access = Access::ReadOnly;
+                    }
+                    #[repr(u32)]
+                    #[derive(Clone, Hash, PartialEq, Eq)]
+                    pub enum Access {
+                        ReadOnly = 1,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("impl Color { pub const Primary : Self = Self :: Red ; }"));
+    assert!(rs.contains("pub struct Access (pub u32)"));
+    assert!(rs.contains(
+        "impl Access { pub const Default : Self = Self :: ReadOnly ; \
+         pub const Any : Self = Self :: ReadOnly ; }"
+    ));
+}
+
+#[test]
+fn test_array_reference_params() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("fill")
+            generate!("sum")
+            generate!("trace")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    extern "C" {
+                        #[cpp_semantics(arg_type_reference(arr))]
+                        pub fn fill(arr: *mut [::std::os::raw::c_int; 8usize]);
+                        #[cpp_semantics(arg_type_reference(arr))]
+                        pub fn sum(arr: *const [u32; 4usize]) -> u32;
+                        #[cpp_semantics(arg_type_reference(matrix))]
+                        pub fn trace(matrix: *const [[f64; 3usize]; 3usize]) -> f64;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn fill (arr : & mut [autocxx :: c_int ; 8])"));
+    assert!(rs.contains("(arr . as_mut_ptr ())"));
+    assert!(rs.contains("pub fn sum (arr : & [u32 ; 4]) -> u32"));
+    assert!(rs.contains("pub fn trace (matrix : & [[f64 ; 3] ; 3]) -> f64"));
+    assert!(rs.contains("(matrix . as_ptr () . cast ())"));
+    assert!(rs.contains("(matrix : * const f64) -> f64"));
+}
+
+#[test]
+fn test_raii_guards() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("LockGuard")
+            generate!("Counter")
+            raii_guard!("LockGuard")
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    #[cpp_semantics(layout(8, 8, false))]
+                    pub struct LockGuard {
+                        pub _bindgen_opaque_blob: u64,
+                    }
+                    #[repr(C)]
+                    #[cpp_semantics(layout(4, 4, false))]
+                    pub struct Counter {
+                        pub count: u32,
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("impl autocxx :: RaiiGuard for LockGuard { }"));
+    assert!(!rs.contains("impl autocxx :: RaiiGuard for Counter"));
+}
+
+#[test]
+fn test_keyword_names() {
+    let bindgen = || -> syn::ItemMod {
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Cursor {
+                        pub pos: u32,
+                    }
+                    extern "C" {
+                        #[cpp_semantics(original_name("move"))]
+                        pub fn move_(a: u32) -> u32;
+                        #[cpp_semantics(original_name("type"))]
+                        pub fn Cursor_type(this: *const root::Cursor) -> u32;
+                    }
+                    impl Cursor {
+                        #[inline]
+                        pub unsafe fn type_(&self) -> u32 {
+                            Cursor_type(self)
+                        }
+                    }
+                }
+            }
+        }
+    };
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("move_")
+            generate_pod!("Cursor")
+            keyword_names!(raw)
+        },
+        bindgen(),
+    );
+    assert!(rs.contains(" as r#move ;"));
+    assert!(rs.contains("pub fn r#type (self : & root :: Cursor) -> u32"));
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("move_")
+            generate_pod!("Cursor")
+            keyword_names!(prefix = "cpp_")
+        },
+        bindgen(),
+    );
+    assert!(rs.contains(" as cpp_move ;"));
+    assert!(rs.contains("pub fn cpp_type (self : & root :: Cursor) -> u32"));
+}
+
+#[test]
+fn test_destructure_return() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("compute")
+            generate!("origin")
+            generate_pod!("MinMax")
+            destructure_return!("compute", MinMax)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct MinMax {
+                        pub min: u32,
+                        pub max: u32,
+                        pub mean: f64,
+                    }
+                    extern "C" {
+                        pub fn compute(a: u32, b: u32) -> root::MinMax;
+                        pub fn origin() -> root::MinMax;
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains(
+        "pub fn compute (a : u32 , b : u32) -> (u32 , u32 , f64 ,) { { \
+         let bundle = cxxbridge :: compute (a , b) ; \
+         (bundle . min , bundle . max , bundle . mean ,) } }"
+    ));
+    // Other uses of the struct are unaffected.
+    assert!(rs.contains("pub fn origin () -> MinMax ;"));
+}
+
+#[test]
+fn test_instantiate_with_defaulted_template_args() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate!("fill")
+            instantiate!("Container", ["uint32_t"])
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    #[repr(C)]
+                    pub struct Allocator<T> {
+                        pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        pub _address: u8,
+                    }
+                    #[repr(C)]
+                    pub struct Container<T, Alloc> {
+                        pub _phantom_0: ::core::marker::PhantomData<::core::cell::UnsafeCell<T>>,
+                        pub _phantom_1: ::core::marker::PhantomData<::core::cell::UnsafeCell<Alloc>>,
+                        pub _address: u8,
+                    }
+                    // Our probe for the instantiate!, in which clang has
+                    // filled in the defaulted argument.
+                    pub type Container_uint32_t = root::Container<u32, root::Allocator<u32>>;
+                    extern "C" {
+                        #[cpp_semantics(arg_type_reference(c))]
+                        pub fn fill(c: *mut root::Container<u32, root::Allocator<u32>>);
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn fill (c : Pin < & mut Container_uint32_t >) ;"));
+    assert!(!rs.contains("AutocxxConcrete"));
+}
+
+#[test]
+fn test_instantiate_bitset() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            instantiate!("std::bitset", [8])
+            instantiate!("std::bitset", [100], BigBitset)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub fn test (& self , pos : usize) -> bool { assert ! (pos < 8"));
+    assert!(rs.contains("pub fn from_u64 (value : u64) -> cxx :: UniquePtr < root :: bitset_8 >"));
+    assert!(rs.contains("pub fn count (self : & root :: BigBitset) -> usize"));
+    // No integer is wide enough for 100 bits.
+    assert!(!rs.contains("pub fn to_u64 (self : & root :: BigBitset)"));
+}
+
+#[test]
+fn test_opaque_ptr_param() {
+    let input: ItemMod = parse_quote! {
+        mod bindgen {
+            pub mod root {
+                extern "C" {
+                    #[link_name = "\u{1}_Z5storePj"]
+                    pub fn store(user_data: *mut u32);
+                    #[link_name = "\u{1}_Z5countPKjS0_"]
+                    pub fn count(values: *const u32, user_data: *mut u32) -> u32;
+                }
+            }
+        }
+    };
+    let rs = do_test_with_directives(
+        parse_quote! {
+            safety!(unsafe_ffi)
+            generate!("store")
+            generate!("count")
+            opaque_ptr_param!("store", user_data)
+            opaque_ptr_param!("count", user_data)
+        },
+        input,
+    );
+    assert!(rs.contains("# [inline] pub fn store (user_data : * mut u32) { unsafe { cxxbridge :: store (user_data) } }"));
+    // `values` is still dereferenced, so `count` stays unsafe.
+    assert!(
+        rs.contains("pub unsafe fn count (values : * const u32 , user_data : * mut u32) -> u32")
+    );
+    assert!(rs.contains("pub use cxxbridge :: count ;"));
+}
+
+#[test]
+fn test_namespace_type() {
+    let rs = do_test_with_directives(
+        parse_quote! {
+            generate_ns!("math")
+            namespace_type!("math", Math)
+        },
+        parse_quote! {
+            mod bindgen {
+                pub mod root {
+                    pub mod math {
+                        #[allow(unused_imports)]
+                        use self::super::super::root;
+                        extern "C" {
+                            #[link_name = "\u{1}_ZN4math4sqrtEd"]
+                            pub fn sqrt(x: f64) -> f64;
+                            #[link_name = "\u{1}_ZN4math3absEi"]
+                            pub fn abs(x: ::std::os::raw::c_int) -> ::std::os::raw::c_int;
+                            #[link_name = "\u{1}_ZN4math3absEd"]
+                            pub fn abs1(x: f64) -> f64;
+                        }
+                        pub mod detail {
+                            #[allow(unused_imports)]
+                            use self::super::super::super::root;
+                            extern "C" {
+                                #[link_name = "\u{1}_ZN4math6detail6helperEv"]
+                                pub fn helper() -> u32;
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    );
+    assert!(rs.contains("pub struct Math ; impl Math { pub fn sqrt (x : f64) -> f64"));
+    assert!(rs.contains("pub mod math { pub use super :: bindgen :: root :: math :: Math ;"));
+    // Nested namespaces keep their free functions.
+    assert!(rs.contains("pub mod detail { pub use super :: super :: cxxbridge :: helper ; }"));
+}
@@ -57,7 +57,7 @@ pub enum ConvertErrorFromCpp {
     UnacceptableParam(String),
     #[error("Function {0} has a reference return value, but no reference parameters, so the lifetime of the output reference cannot be deduced.")]
     NoInputReference(String),
-    #[error("Function {0} has a reference return value, but >1 input reference parameters, so the lifetime of the output reference cannot be deduced.")]
+    #[error("Function {0} has a reference return value, but >1 input reference parameters, so the lifetime of the output reference cannot be deduced. Use return_borrows! to say which parameters it borrows from.")]
     MultipleInputReferences(String),
     #[error("Function {0} has a mutable reference return value, but no mutable reference parameters, so the lifetime of the output reference cannot be deduced.")]
     NoMutableInputReference(String),
@@ -69,6 +69,14 @@ pub enum ConvertErrorFromCpp {
     UnknownType(String),
     #[error("Encountered mutable static data, not yet supported: {0}")]
     StaticData(String),
+    #[error("nonnull_param! was used for parameter {0}, which another directive says to pass as a pointer.")]
+    ConflictingNonnullParam(String),
+    #[error("return_borrows! named {0}, which isn't a reference parameter of this function.")]
+    ReturnBorrowsUnknownParam(String),
+    #[error("return_borrows! was used for a function which doesn't return a reference.")]
+    ReturnBorrowsWithoutReferenceReturn,
+    #[error("A variable listed in thread_local_var! is const. Only mutable thread_local variables are supported.")]
+    ConstThreadLocal,
     #[error("Encountered typedef to itself - this is a known bindgen bug: {0}")]
     InfinitelyRecursiveTypedef(QualifiedName),
     #[error("Unexpected 'use' statement encountered: {}", .0.as_ref().map(|s| s.as_str()).unwrap_or("<unknown>"))]
@@ -81,12 +89,32 @@ pub enum ConvertErrorFromCpp {
     InvalidPointerPointee,
     #[error("Pointer pointed to something unsupported (autocxx only supports pointers to named types): {0}")]
     InvalidPointee(String),
-    #[error("The 'generate' or 'generate_pod' directive for '{0}' did not result in any code being generated. Perhaps this was mis-spelled or you didn't qualify the name with any namespaces? Otherwise please report a bug.")]
-    DidNotGenerateAnything(String),
+    #[error("The 'generate', 'generate_pod' or 'generate_ns' directives for {} did not result in any code being generated. Perhaps these were mis-spelled or you didn't qualify the names with any namespaces? Otherwise please report a bug.", .0.iter().map(|name| format!("'{name}'")).join(", "))]
+    DidNotGenerateAnything(Vec<String>),
+    #[error("constexpr_fn!(\"{0}\") did not result in a constant. Perhaps the function doesn't exist, takes parameters, or can't be evaluated at compile time, or its result isn't a type which bindgen can represent as a Rust constant.")]
+    ConstexprFnNotEvaluated(String),
+    #[error("reexport!(\"{0}\") didn't match exactly one generated type or free function. Perhaps it isn't otherwise generated, isn't fully qualified with its namespace, or is an overloaded function?")]
+    ReexportNotFound(String),
+    #[error("reexport! items are placed in a mod called '{0}', but there's already a C++ namespace of that name.")]
+    ReexportModClash(String),
+    #[error("byte_buffer! listed {0}, but autocxx didn't generate both of the methods it names for that type. Perhaps the type or methods are mis-spelled, aren't on the allowlist, or couldn't be generated, or the size method doesn't return anything?")]
+    ByteBufferNotFound(String),
+    #[error("block_constructor!(\"{0}\", \"{1}\") didn't match any constructor of that type. Its constructors' parameter types are spelled: {2}.")]
+    BlockedConstructorNotFound(String, String, String),
+    #[error("bool_param!(\"{0}\", {1}) didn't match an integer parameter of a generated function. Perhaps the function or parameter is mis-spelled, the function isn't on the allowlist or couldn't be generated, or the parameter isn't an integer type?")]
+    BoolParamNotFound(String, String),
+    #[error("tagged_union!(\"{0}\", ...) can't be used because {1}.")]
+    TaggedUnionInvalid(String, String),
+    #[error("destructure_return! asked for this function's return value to be destructured from {0}, but it doesn't return that type by value, or the type isn't a POD struct with only plain named fields.")]
+    CannotDestructureReturn(String),
     #[error("Found an attempt at using a forward declaration ({}) inside a templated cxx type such as UniquePtr or CxxVector. If the forward declaration is a typedef, perhaps autocxx wasn't sure whether or not it involved a forward declaration. If you're sure it didn't, then you may be able to solve this by using instantiable!.", .0.to_cpp_name())]
     TypeContainingForwardDeclaration(QualifiedName),
     #[error("Found an attempt at using a type marked as blocked! ({})", .0.to_cpp_name())]
     Blocked(QualifiedName),
+    #[error("This constructor was blocked using block_constructor!")]
+    BlockedConstructor,
+    #[error("This function was replaced by an entry of the same name in manual!")]
+    ReplacedByManualEntry,
     #[error("This function or method uses a type where one of the template parameters was incomprehensible to bindgen/autocxx - probably because it uses template specialization.")]
     UnusedTemplateParam,
     #[error("This item relies on a type not known to autocxx ({})", .0.to_cpp_name())]
@@ -137,6 +165,24 @@ pub enum ConvertErrorFromCpp {
     InvalidTypeForCppPtr(QualifiedName),
     #[error("A C++ std::vector was found containing some type that cxx can't accommodate as a vector element ({})", .0.to_cpp_name())]
     InvalidTypeForCppVector(QualifiedName),
+    #[error("A C++ std::span was found containing some type that can't be an element of a Rust slice ({}). Only primitive and POD types can be.", .0.to_cpp_name())]
+    InvalidTypeForSpan(QualifiedName),
+    #[error("A C++ std::span was found somewhere other than as a function parameter passed by value. autocxx can only pass a std::span from a Rust slice.")]
+    SpanNotParameter,
+    #[error("A reference to an array was found containing some type that can't be an element of a Rust array ({}). Only primitive and POD types can be.", .0.to_cpp_name())]
+    InvalidTypeForArrayReference(QualifiedName),
+    #[error("A reference to an array was found as a parameter of a virtual method, which isn't yet supported if Rust subclasses might override it.")]
+    ArrayReferenceInSubclass,
+    #[error("A C++ std::reference_wrapper was found somewhere other than as a parameter or return type of a function called from Rust, for instance within a container or a subclass method. autocxx can only represent a std::reference_wrapper as a Rust reference.")]
+    ReferenceWrapperNotParameterOrReturn,
+    #[error("A C++ std::reference_wrapper can't yet be represented when references are wrapped by CppRef (the safety!(unsafe_references_wrapped) policy).")]
+    ReferenceWrapperWithWrappedReferences,
+    #[error("A C++ std::vector parameter was listed in vec_param!, but its elements ({}) aren't primitive or POD types. Only those can be copied from a Rust Vec.", .0.to_cpp_name())]
+    InvalidTypeForVecParam(QualifiedName),
+    #[error("This function was listed in throws!, but that's only supported for functions and methods which return void.")]
+    ThrowingFunctionNotVoid,
+    #[error("This item uses a C++ long double (or another 128-bit type), which has no Rust equivalent. See the long_double! directive for other ways to handle it.")]
+    LongDouble,
     #[error("Variadic functions are not supported by cxx or autocxx.")]
     Variadic,
     #[error("A type had a template inside a std::vector, which is not supported.")]
@@ -153,6 +199,8 @@ pub enum ConvertErrorFromCpp {
     ReferringToGenericTypeParam,
     #[error("This forward declaration was nested within another struct/class. autocxx is unable to represent inner types if they are forward declarations.")]
     ForwardDeclaredNestedType,
+    #[error("method_of! asked for this function to become a method of {0}, but its first parameter isn't a pointer to that type.")]
+    MethodOfWithoutReceiver(String),
     #[error("Problem handling function argument {arg}: {err}")]
     Argument {
         arg: String,
@@ -16,3 +16,9 @@ pub(super) fn get_doc_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
         .cloned()
         .collect()
 }
+
+/// Returns the `#[must_use]` attribute, if any, which bindgen adds to
+/// functions marked `[[nodiscard]]` in C++.
+pub(super) fn get_must_use_attr(attrs: &[Attribute]) -> Option<Attribute> {
+    attrs.iter().find(|a| a.path.is_ident("must_use")).cloned()
+}
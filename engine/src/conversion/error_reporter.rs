@@ -9,7 +9,7 @@
 use syn::ItemEnum;
 
 use super::{
-    api::{AnalysisPhase, Api, ApiName, FuncToConvert, StructDetails, TypedefKind},
+    api::{AnalysisPhase, Api, ApiName, EnumAlias, FuncToConvert, StructDetails, TypedefKind},
     apivec::ApiVec,
     convert_error::{ConvertErrorWithContext, ErrorContext},
     ConvertErrorFromCpp,
@@ -78,6 +78,7 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B: 'static>(
     EF: FnMut(
         ApiName,
         ItemEnum,
+        Vec<EnumAlias>,
     ) -> Result<Box<dyn Iterator<Item = Api<B>>>, ConvertErrorWithContext>,
     TF: FnMut(
         ApiName,
@@ -94,10 +95,12 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B: 'static>(
                 name,
                 rs_definition,
                 cpp_definition,
+                std_variant,
             } => Ok(Box::new(std::iter::once(Api::ConcreteType {
                 name,
                 rs_definition,
                 cpp_definition,
+                std_variant,
             }))),
             Api::ForwardDeclaration { name, err } => {
                 Ok(Box::new(std::iter::once(Api::ForwardDeclaration {
@@ -115,6 +118,18 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B: 'static>(
             Api::StringConstructor { name } => {
                 Ok(Box::new(std::iter::once(Api::StringConstructor { name })))
             }
+            Api::AssertionHandler { name } => {
+                Ok(Box::new(std::iter::once(Api::AssertionHandler { name })))
+            }
+            Api::ExceptionTypes { name } => {
+                Ok(Box::new(std::iter::once(Api::ExceptionTypes { name })))
+            }
+            Api::AbiVersionCheck { name } => {
+                Ok(Box::new(std::iter::once(Api::AbiVersionCheck { name })))
+            }
+            Api::NamespaceType { name } => {
+                Ok(Box::new(std::iter::once(Api::NamespaceType { name })))
+            }
             Api::Const { name, const_item } => {
                 Ok(Box::new(std::iter::once(Api::Const { name, const_item })))
             }
@@ -167,7 +182,11 @@ pub(crate) fn convert_apis<FF, SF, EF, TF, A, B: 'static>(
                 })))
             }
             // Apply a mapping to the following
-            Api::Enum { name, item } => enum_conversion(name, item),
+            Api::Enum {
+                name,
+                item,
+                aliases,
+            } => enum_conversion(name, item, aliases),
             Api::Typedef {
                 name,
                 item,
@@ -16,6 +16,7 @@ mod conversion_tests;
 mod convert_error;
 mod doc_attr;
 mod error_reporter;
+mod name_mappings;
 mod parse;
 mod utilities;
 
@@ -25,6 +26,7 @@ pub(crate) use codegen_cpp::CppCodeGenerator;
 pub(crate) use convert_error::ConvertError;
 use convert_error::ConvertErrorFromCpp;
 use itertools::Itertools;
+pub use name_mappings::FunctionNameMapping;
 use syn::{Item, ItemMod};
 
 use crate::{
@@ -35,19 +37,33 @@ use self::{
     analysis::{
         abstract_types::{discard_ignored_functions, mark_types_abstract},
         allocators::create_alloc_and_frees,
+        bitsets::add_bitset_accessors,
+        bool_params::check_bool_params,
+        byte_buffers::check_byte_buffers,
+        callable_members::add_callable_member_accessors,
         casts::add_casts,
         check_names,
         constructor_deps::decorate_types_with_constructor_deps,
         fun::FnPhase,
         gc::filter_apis_by_following_edges_from_allowlist,
+        inherited_methods::add_inherited_methods,
+        iterator_lookups::add_iterator_lookups,
+        method_of::recognize_methods_of,
+        namespace_types::recognize_namespace_functions,
+        operators::recognize_operators,
         pod::analyze_pod_apis,
+        ranges::add_range_accessors,
         remove_ignored::filter_apis_by_ignored_dependents,
         replace_hopeless_typedef_targets,
+        tagged_unions::add_tagged_union_accessors,
         tdef::convert_typedef_targets,
+        variants::add_variant_accessors,
+        vectors::add_vector_accessors,
     },
     api::AnalysisPhase,
     apivec::ApiVec,
     codegen_rs::RsCodeGenerator,
+    name_mappings::{find_function_name_mappings, find_generated_type_names, find_reexports},
     parse::ParseBindgen,
 };
 
@@ -75,6 +91,8 @@ pub(crate) struct CodegenResults {
     pub(crate) rs: Vec<Item>,
     pub(crate) cpp: Option<CppFilePair>,
     pub(crate) cxxgen_header_name: String,
+    pub(crate) function_name_mappings: Vec<FunctionNameMapping>,
+    pub(crate) generated_type_names: Vec<String>,
 }
 
 impl<'a> BridgeConverter<'a> {
@@ -131,7 +149,11 @@ impl<'a> BridgeConverter<'a> {
                 // Parse the bindgen mod.
                 let items_to_process = items.drain(..).collect();
                 let parser = ParseBindgen::new(self.config);
-                let apis = parser.parse_items(items_to_process, source_file_contents)?;
+                let apis = parser.parse_items(
+                    items_to_process,
+                    source_file_contents,
+                    codegen_options.strict_allowlist,
+                )?;
                 Self::dump_apis("parsing", &apis);
                 // Inside parse_results, we now have a list of APIs.
                 // We now enter various analysis phases.
@@ -151,6 +173,18 @@ impl<'a> BridgeConverter<'a> {
                 Self::dump_apis("pod analysis", &analyzed_apis);
                 let analyzed_apis = replace_hopeless_typedef_targets(self.config, analyzed_apis);
                 let analyzed_apis = add_casts(analyzed_apis);
+                let analyzed_apis = add_tagged_union_accessors(analyzed_apis, self.config)
+                    .map_err(ConvertError::Cpp)?;
+                let analyzed_apis = add_range_accessors(analyzed_apis, self.config);
+                let analyzed_apis = add_callable_member_accessors(analyzed_apis, self.config);
+                let analyzed_apis = add_iterator_lookups(analyzed_apis, self.config);
+                let analyzed_apis = add_inherited_methods(analyzed_apis, self.config);
+                let analyzed_apis = add_variant_accessors(analyzed_apis, self.config);
+                let analyzed_apis = add_bitset_accessors(analyzed_apis);
+                let analyzed_apis = add_vector_accessors(analyzed_apis);
+                let analyzed_apis = recognize_operators(analyzed_apis);
+                let analyzed_apis = recognize_methods_of(analyzed_apis, self.config);
+                let analyzed_apis = recognize_namespace_functions(analyzed_apis, self.config);
                 let analyzed_apis = create_alloc_and_frees(analyzed_apis);
                 // Next, figure out how we materialize different functions.
                 // Some will be simple entries in the cxx::bridge module; others will
@@ -163,7 +197,8 @@ impl<'a> BridgeConverter<'a> {
                     &unsafe_policy,
                     self.config,
                     codegen_options.force_wrapper_gen,
-                );
+                )
+                .map_err(ConvertError::Cpp)?;
                 // If any of those functions turned out to be pure virtual, don't attempt
                 // to generate UniquePtr implementations for the type, since it can't
                 // be instantiated.
@@ -197,6 +232,12 @@ impl<'a> BridgeConverter<'a> {
                     .cpp_codegen_options
                     .cxxgen_header_namer
                     .name_header();
+                let function_name_mappings = find_function_name_mappings(&analyzed_apis);
+                let generated_type_names = find_generated_type_names(&analyzed_apis);
+                let reexports =
+                    find_reexports(&analyzed_apis, self.config).map_err(ConvertError::Cpp)?;
+                check_byte_buffers(&analyzed_apis, self.config).map_err(ConvertError::Cpp)?;
+                check_bool_params(&analyzed_apis, self.config).map_err(ConvertError::Cpp)?;
                 let cpp = CppCodeGenerator::generate_cpp_code(
                     inclusions,
                     &analyzed_apis,
@@ -211,12 +252,15 @@ impl<'a> BridgeConverter<'a> {
                     self.include_list,
                     bindgen_mod,
                     self.config,
+                    reexports,
                     cpp.as_ref().map(|file_pair| file_pair.header_name.clone()),
                 );
                 Ok(CodegenResults {
                     rs,
                     cpp,
                     cxxgen_header_name,
+                    function_name_mappings,
+                    generated_type_names,
                 })
             }
         }
@@ -0,0 +1,189 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use autocxx_parser::IncludeCppConfig;
+use itertools::Itertools;
+use proc_macro2::Ident;
+
+use crate::types::{make_ident, QualifiedName};
+
+use super::{
+    analysis::fun::{
+        function_wrapper::{CppFunction, CppFunctionBody, CppFunctionKind},
+        FnAnalysis, FnKind, FnPhase,
+    },
+    api::{Api, Provenance},
+    apivec::ApiVec,
+    ConvertErrorFromCpp,
+};
+
+/// The names by which a single C++ function is known at each layer
+/// of the generated bindings.
+#[derive(Debug, Clone)]
+pub struct FunctionNameMapping {
+    /// Path to the function from within the generated mod (e.g. `ffi`),
+    /// which is how Rust callers will refer to it. For methods, this
+    /// is of the form `Type::method`.
+    pub rust_name: String,
+    /// Name of the function within the `cxx::bridge` mod. This may be a
+    /// C++ wrapper function generated by autocxx.
+    pub cxxbridge_name: String,
+    /// Fully-qualified name of the original C++ function.
+    pub cpp_name: String,
+}
+
+/// Gather the names of each function which originated in the C++ headers
+/// and which we're going to make callable from Rust.
+pub(crate) fn find_function_name_mappings(apis: &ApiVec<FnPhase>) -> Vec<FunctionNameMapping> {
+    apis.iter()
+        .filter_map(|api| match api {
+            Api::Function {
+                name,
+                fun,
+                analysis:
+                    FnAnalysis {
+                        cxxbridge_name,
+                        rust_name,
+                        kind,
+                        cpp_wrapper,
+                        ignore_reason: Ok(_),
+                        externally_callable: true,
+                        ..
+                    },
+            } if matches!(fun.provenance, Provenance::Bindgen) => {
+                let unqualified_cpp_name = match cpp_wrapper {
+                    Some(CppFunction {
+                        payload: CppFunctionBody::Operator(op),
+                        ..
+                    }) => format!("operator{}", op.symbol),
+                    _ => name.cpp_name(),
+                };
+                let (rust_name, cpp_name) = match kind {
+                    FnKind::Function => (
+                        QualifiedName::new(name.name.get_namespace(), make_ident(rust_name))
+                            .to_string(),
+                        name.name
+                            .ns_segment_iter()
+                            .cloned()
+                            .chain(std::iter::once(unqualified_cpp_name))
+                            .join("::"),
+                    ),
+                    // A free function which method_of! presented as a method.
+                    FnKind::Method { impl_for, .. }
+                        if matches!(
+                            cpp_wrapper,
+                            Some(CppFunction {
+                                payload: CppFunctionBody::FunctionCall(..),
+                                kind: CppFunctionKind::Function,
+                                ..
+                            })
+                        ) =>
+                    {
+                        (format!("{impl_for}::{rust_name}"), name.name.to_cpp_name())
+                    }
+                    FnKind::Method { impl_for, .. } => (
+                        format!("{impl_for}::{rust_name}"),
+                        format!("{}::{}", impl_for.to_cpp_name(), unqualified_cpp_name),
+                    ),
+                    // Trait methods aren't called by name.
+                    FnKind::TraitMethod { .. } => return None,
+                };
+                Some(FunctionNameMapping {
+                    rust_name,
+                    cxxbridge_name: cxxbridge_name.to_string(),
+                    cpp_name,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Gather the Rust names of the types we're going to generate, relative
+/// to the generated mod. These may differ from the C++ names: for example
+/// a nested type `A::B` becomes `A_B`.
+pub(crate) fn find_generated_type_names(apis: &ApiVec<FnPhase>) -> Vec<String> {
+    apis.iter()
+        .filter(|api| {
+            matches!(
+                api,
+                Api::Struct { .. }
+                    | Api::Enum { .. }
+                    | Api::Typedef { .. }
+                    | Api::OpaqueTypedef { .. }
+                    | Api::ForwardDeclaration { .. }
+                    | Api::ConcreteType { .. }
+            )
+        })
+        .map(|api| api.name().to_string())
+        .collect()
+}
+
+/// Find the Rust name of each item listed in `reexport!`, relative to the
+/// generated mod, along with the name under which it's to be re-exported.
+/// Only types and free functions can be re-exported; methods come along
+/// with their type.
+pub(crate) fn find_reexports(
+    apis: &ApiVec<FnPhase>,
+    config: &IncludeCppConfig,
+) -> Result<Vec<(QualifiedName, Ident)>, ConvertErrorFromCpp> {
+    if config.get_reexports().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mod_name = config.get_reexports_mod_name();
+    if apis
+        .iter()
+        .any(|api| api.name().ns_segment_iter().next().map(String::as_str) == Some(mod_name))
+    {
+        return Err(ConvertErrorFromCpp::ReexportModClash(mod_name.to_string()));
+    }
+    config
+        .get_reexports()
+        .iter()
+        .map(|(cpp_name, alias)| {
+            let candidates = apis
+                .iter()
+                .filter_map(|api| match api {
+                    Api::Struct { name, .. }
+                    | Api::Enum { name, .. }
+                    | Api::Typedef { name, .. }
+                    | Api::OpaqueTypedef { name, .. }
+                    | Api::ForwardDeclaration { name, .. }
+                        if &name.qualified_cpp_name() == cpp_name =>
+                    {
+                        Some(name.name.clone())
+                    }
+                    Api::Function {
+                        name,
+                        fun,
+                        analysis:
+                            FnAnalysis {
+                                rust_name,
+                                kind: FnKind::Function,
+                                ignore_reason: Ok(_),
+                                externally_callable: true,
+                                ..
+                            },
+                    } if matches!(fun.provenance, Provenance::Bindgen)
+                        && &name.qualified_cpp_name() == cpp_name =>
+                    {
+                        Some(QualifiedName::new(
+                            name.name.get_namespace(),
+                            make_ident(rust_name),
+                        ))
+                    }
+                    _ => None,
+                })
+                .collect_vec();
+            match candidates.as_slice() {
+                [name] => Ok((name.clone(), alias.clone())),
+                _ => Err(ConvertErrorFromCpp::ReexportNotFound(cpp_name.clone())),
+            }
+        })
+        .collect()
+}
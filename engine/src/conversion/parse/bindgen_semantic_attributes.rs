@@ -10,7 +10,7 @@ use proc_macro2::{Ident, TokenStream};
 use syn::{
     parenthesized,
     parse::{Parse, Parser},
-    Attribute, LitStr,
+    parse_quote, Attribute, LitStr,
 };
 
 use crate::conversion::{
@@ -130,6 +130,17 @@ impl BindgenSemanticAttributes {
         self.string_if_present("original_name")
     }
 
+    /// Any C++ `[[nodiscard]]` marker, as the equivalent Rust attribute,
+    /// keeping the reason if one was given.
+    pub(super) fn get_must_use_attr(&self) -> Option<Attribute> {
+        self.0.iter().find(|a| a.is_ident("nodiscard")).map(|a| {
+            match a.body.as_ref().and_then(|_| a.parse_args::<LitStr>().ok()) {
+                Some(reason) => parse_quote! { #[must_use = #reason] },
+                None => parse_quote! { #[must_use] },
+            }
+        })
+    }
+
     /// Whether this is a move constructor or other special member.
     pub(super) fn special_member_kind(&self) -> Option<SpecialMemberKind> {
         self.string_if_present("special_member")
@@ -11,11 +11,15 @@ use indexmap::set::IndexSet as HashSet;
 
 use crate::{
     conversion::{
-        api::{Api, ApiName, NullPhase, StructDetails, SubclassName, TypedefKind, UnanalyzedApi},
+        api::{
+            Api, ApiName, EnumAlias, NullPhase, StructDetails, SubclassName, TypedefKind,
+            UnanalyzedApi,
+        },
         apivec::ApiVec,
         convert_error::LocatedConvertErrorFromRust,
         ConvertError, ConvertErrorFromCpp,
     },
+    known_types::known_types,
     types::Namespace,
     types::QualifiedName,
 };
@@ -24,13 +28,20 @@ use crate::{
         convert_error::{ConvertErrorWithContext, ErrorContext},
         error_reporter::report_any_error,
     },
-    types::validate_ident_ok_for_cxx,
+    types::{make_ident, validate_ident_ok_for_cxx},
+};
+use autocxx_parser::{cpp_operator_ident, IncludeCppConfig, RustPath};
+use syn::{
+    parse_quote, Expr, ExprLit, Fields, Ident, ImplItem, Item, ItemConst, Lit, LitStr, Type,
+    TypeArray, TypePath, TypeReference, UseTree,
 };
-use autocxx_parser::{IncludeCppConfig, RustPath};
-use syn::{parse_quote, Fields, Ident, Item, Type, TypePath, UseTree};
 
 use super::{
-    super::utilities::generate_utilities, bindgen_semantic_attributes::BindgenSemanticAttributes,
+    super::utilities::{
+        generate_abi_version_check, generate_assertion_handler, generate_exception_types,
+        generate_namespace_types, generate_utilities,
+    },
+    bindgen_semantic_attributes::BindgenSemanticAttributes,
 };
 
 use super::parse_foreign_mod::ParseForeignMod;
@@ -39,6 +50,9 @@ use super::parse_foreign_mod::ParseForeignMod;
 pub(crate) struct ParseBindgen<'a> {
     config: &'a IncludeCppConfig,
     apis: ApiVec<NullPhase>,
+    /// What bindgen tells us each `instantiate!` really is, including any
+    /// defaulted template arguments.
+    instantiation_targets: HashMap<Ident, Box<Type>>,
 }
 
 fn api_name(ns: &Namespace, id: Ident, attrs: &BindgenSemanticAttributes) -> ApiName {
@@ -67,6 +81,7 @@ impl<'a> ParseBindgen<'a> {
         ParseBindgen {
             config,
             apis: ApiVec::new(),
+            instantiation_targets: HashMap::new(),
         }
     }
 
@@ -76,16 +91,22 @@ impl<'a> ParseBindgen<'a> {
         mut self,
         items: Vec<Item>,
         source_file_contents: &str,
+        strict_allowlist: bool,
     ) -> Result<ApiVec<NullPhase>, ConvertError> {
         let items = Self::find_items_in_root(items).map_err(ConvertError::Cpp)?;
         if !self.config.exclude_utilities() {
             generate_utilities(&mut self.apis, self.config);
         }
+        generate_assertion_handler(&mut self.apis, self.config);
+        generate_exception_types(&mut self.apis, self.config);
+        generate_abi_version_check(&mut self.apis, self.config);
+        generate_namespace_types(&mut self.apis, self.config);
         self.add_apis_from_config(source_file_contents)
             .map_err(ConvertError::Rust)?;
         let root_ns = Namespace::new();
         self.parse_mod_items(items, root_ns);
-        self.confirm_all_generate_directives_obeyed()
+        self.add_instantiations();
+        self.confirm_all_generate_directives_obeyed(strict_allowlist)
             .map_err(ConvertError::Cpp)?;
         self.replace_extern_cpp_types();
         Ok(self.apis)
@@ -126,18 +147,36 @@ impl<'a> ParseBindgen<'a> {
                 .concretes
                 .0
                 .iter()
+                .filter(|(_, rust_id)| !self.config.is_instantiation(rust_id))
                 .map(|(cpp_definition, rust_id)| {
                     let name = ApiName::new_in_root_namespace(rust_id.clone());
                     Api::ConcreteType {
                         name,
                         cpp_definition: cpp_definition.clone(),
                         rs_definition: None,
+                        std_variant: None,
                     }
                 }),
         );
         Ok(())
     }
 
+    /// Add a concrete type for each `instantiate!`. Where bindgen told us
+    /// the instantiation in full, we use that, so that it matches any other
+    /// mentions of the same type, which list every template argument
+    /// whether or not it was defaulted. Otherwise we fall back to exactly
+    /// what the user wrote.
+    fn add_instantiations(&mut self) {
+        for (cpp_definition, rust_id) in self.config.get_instantiations() {
+            self.apis.push(Api::ConcreteType {
+                name: ApiName::new_in_root_namespace(rust_id.clone()),
+                cpp_definition: cpp_definition.to_string(),
+                rs_definition: self.instantiation_targets.swap_remove(rust_id),
+                std_variant: None,
+            });
+        }
+    }
+
     /// We do this last, _after_ we've parsed all the APIs, because we might want to actually
     /// replace some of the existing APIs (structs/enums/etc.) with replacements.
     fn replace_extern_cpp_types(&mut self) {
@@ -149,7 +188,9 @@ impl<'a> ParseBindgen<'a> {
             .iter()
             .map(|(cpp_definition, details)| {
                 let qn = QualifiedName::new_from_cpp_name(cpp_definition);
-                let pod = pod_requests.contains(&qn.to_cpp_name());
+                // cbindgen only exports `#[repr(C)]` types, which
+                // are necessarily trivial.
+                let pod = pod_requests.contains(&qn.to_cpp_name()) || details.from_cbindgen;
                 (
                     qn.clone(),
                     Api::ExternCppType {
@@ -188,21 +229,51 @@ impl<'a> ParseBindgen<'a> {
     fn parse_mod_items(&mut self, items: Vec<Item>, ns: Namespace) {
         // This object maintains some state specific to this namespace, i.e.
         // this particular mod.
-        let mut mod_converter = ParseForeignMod::new(ns.clone());
+        let mut mod_converter = ParseForeignMod::new(ns.clone(), self.config);
+        let mut enum_aliases = Self::find_enum_aliases(&items);
         let mut more_apis = ApiVec::new();
         for item in items {
             report_any_error(&ns, &mut more_apis, || {
-                self.parse_item(item, &mut mod_converter, &ns)
+                self.parse_item(item, &mut mod_converter, &mut enum_aliases, &ns)
             });
         }
         self.apis.append(&mut more_apis);
         mod_converter.finished(&mut self.apis);
     }
 
+    /// bindgen represents each enumerator which has the same value as an
+    /// earlier one as an associated constant, in an `impl` block beside the
+    /// enum, which we'd otherwise ignore. Gather them up by enum.
+    fn find_enum_aliases(items: &[Item]) -> HashMap<Ident, Vec<EnumAlias>> {
+        let mut results: HashMap<Ident, Vec<EnumAlias>> = HashMap::new();
+        let impl_items = items.iter().flat_map(|item| match item {
+            Item::Impl(imp) => imp.items.as_slice(),
+            _ => &[],
+        });
+        for impl_item in impl_items {
+            if let ImplItem::Const(const_item) = impl_item {
+                if let Expr::Path(expr) = &const_item.expr {
+                    let mut segments = expr.path.segments.iter().rev();
+                    if let (Some(canonical), Some(enum_id)) = (segments.next(), segments.next()) {
+                        results
+                            .entry(enum_id.ident.clone())
+                            .or_default()
+                            .push(EnumAlias {
+                                alias: const_item.ident.clone(),
+                                canonical: canonical.ident.clone(),
+                            });
+                    }
+                }
+            }
+        }
+        results
+    }
+
     fn parse_item(
         &mut self,
         item: Item,
         mod_converter: &mut ParseForeignMod,
+        enum_aliases: &mut HashMap<Ident, Vec<EnumAlias>>,
         ns: &Namespace,
     ) -> Result<(), ConvertErrorWithContext> {
         match item {
@@ -265,6 +336,7 @@ impl<'a> ParseBindgen<'a> {
                 let annotations = BindgenSemanticAttributes::new(&e.attrs);
                 let api = UnanalyzedApi::Enum {
                     name: api_name_qualified(ns, e.ident.clone(), &annotations)?,
+                    aliases: enum_aliases.remove(&e.ident).unwrap_or_default(),
                     item: e,
                 };
                 if !self.config.is_on_blocklist(&api.name().to_cpp_name()) {
@@ -350,15 +422,37 @@ impl<'a> ParseBindgen<'a> {
                 }
                 Ok(())
             }
-            Item::Const(const_item) => {
+            Item::Const(mut const_item) => {
+                // The variable which we used to evaluate a `constexpr_fn!`
+                // stands in for the function itself.
+                if let Some(fun) = self.config.get_constexpr_fn_for_probe(
+                    &QualifiedName::new(ns, const_item.ident.clone()).to_cpp_name(),
+                ) {
+                    const_item.ident =
+                        make_ident(QualifiedName::new_from_cpp_name(fun).get_final_item());
+                }
                 let annotations = BindgenSemanticAttributes::new(&const_item.attrs);
+                let const_item = if self.config.str_constants() {
+                    convert_string_constant(const_item)
+                } else {
+                    const_item
+                };
                 self.apis.push(UnanalyzedApi::Const {
                     name: api_name(ns, const_item.ident.clone(), &annotations),
                     const_item,
                 });
                 Ok(())
             }
-            Item::Type(ity) => {
+            Item::Type(ity) if ns.is_empty() && self.config.is_instantiation(&ity.ident) => {
+                self.instantiation_targets.insert(ity.ident, ity.ty);
+                Ok(())
+            }
+            Item::Type(mut ity) => {
+                if let Some(target) = known_types()
+                    .platform_typedef_target(&QualifiedName::new(ns, ity.ident.clone()))
+                {
+                    ity.ty = Box::new(Type::Path(target));
+                }
                 let annotations = BindgenSemanticAttributes::new(&ity.attrs);
                 // It's known that sometimes bindgen will give us duplicate typedefs with the
                 // same name - see test_issue_264.
@@ -391,19 +485,80 @@ impl<'a> ParseBindgen<'a> {
             .any(|id| id == desired_id)
     }
 
-    fn confirm_all_generate_directives_obeyed(&self) -> Result<(), ConvertErrorFromCpp> {
+    /// Check that each item the user asked us to generate was found. If
+    /// we're being strict, also check that each namespace contained
+    /// something.
+    fn confirm_all_generate_directives_obeyed(
+        &self,
+        strict_allowlist: bool,
+    ) -> Result<(), ConvertErrorFromCpp> {
         let api_names: HashSet<_> = self
             .apis
             .iter()
             .map(|api| api.name().to_cpp_name())
             .collect();
-        for generate_directive in self.config.must_generate_list() {
-            if !api_names.contains(&generate_directive) {
-                return Err(ConvertErrorFromCpp::DidNotGenerateAnything(
-                    generate_directive,
-                ));
-            }
+        let mut missing: Vec<_> = self
+            .config
+            .must_generate_list()
+            .filter(|generate_directive| {
+                // Operators are known to bindgen by a substitute identifier.
+                let bindgen_name = cpp_operator_ident(generate_directive);
+                !api_names.contains(bindgen_name.as_ref().unwrap_or(generate_directive))
+            })
+            .collect();
+        if strict_allowlist {
+            missing.extend(
+                self.config
+                    .allowlisted_namespaces()
+                    .filter(|ns| {
+                        let prefix = format!("{ns}::");
+                        !api_names.iter().any(|name| name.starts_with(&prefix))
+                    })
+                    .cloned(),
+            );
         }
-        Ok(())
+        if !missing.is_empty() {
+            return Err(ConvertErrorFromCpp::DidNotGenerateAnything(missing));
+        }
+        match self
+            .config
+            .constexpr_fns()
+            .find(|fun| !api_names.contains(*fun))
+        {
+            Some(fun) => Err(ConvertErrorFromCpp::ConstexprFnNotEvaluated(
+                fun.to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// bindgen gives us string constants as null-terminated byte arrays.
+/// Where the string is valid UTF-8, expose it as a `&str` instead, which
+/// is what people will want for things like version strings.
+fn convert_string_constant(const_item: ItemConst) -> ItemConst {
+    let is_byte_array = matches!(
+        &*const_item.ty,
+        Type::Reference(TypeReference { elem, .. })
+            if matches!(&**elem, Type::Array(TypeArray { elem, .. })
+                if matches!(&**elem, Type::Path(typ) if typ.path.is_ident("u8")))
+    );
+    let lit = match &*const_item.expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::ByteStr(lit),
+            ..
+        }) if is_byte_array => lit,
+        _ => return const_item,
+    };
+    // Anything which isn't UTF-8 stays as bytes.
+    let bytes = lit.value();
+    let value = match bytes.strip_suffix(&[0]).map(std::str::from_utf8) {
+        Some(Ok(value)) => LitStr::new(value, lit.span()),
+        _ => return const_item,
+    };
+    ItemConst {
+        ty: parse_quote! { &str },
+        expr: parse_quote! { #value },
+        ..const_item
     }
 }
@@ -6,9 +6,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::conversion::api::{ApiName, NullPhase, Provenance};
+use crate::conversion::analysis::fun::function_wrapper::{CppFunctionBody, CppFunctionKind};
+use crate::conversion::api::{
+    ApiName, CppVisibility, DeletedOrDefaulted, NullPhase, Provenance, References, Virtualness,
+};
 use crate::conversion::apivec::ApiVec;
-use crate::conversion::doc_attr::get_doc_attrs;
+use crate::conversion::doc_attr::{get_doc_attrs, get_must_use_attr};
 use crate::conversion::error_reporter::report_any_error;
 use crate::conversion::{
     api::{FuncToConvert, UnanalyzedApi},
@@ -19,16 +22,21 @@ use crate::{
     conversion::ConvertErrorFromCpp,
     types::{Namespace, QualifiedName},
 };
+use autocxx_parser::IncludeCppConfig;
 use std::collections::HashMap;
-use syn::{Block, Expr, ExprCall, ForeignItem, Ident, ImplItem, ItemImpl, Stmt, Type};
+use syn::{
+    parse_quote, Block, Expr, ExprCall, ForeignItem, ForeignItemStatic, Ident, ImplItem, ItemImpl,
+    ReturnType, Stmt, Type,
+};
 
 use super::bindgen_semantic_attributes::BindgenSemanticAttributes;
 
 /// Parses a given bindgen-generated 'mod' into suitable
 /// [Api]s. In bindgen output, a given mod concerns
 /// a specific C++ namespace.
-pub(crate) struct ParseForeignMod {
+pub(crate) struct ParseForeignMod<'a> {
     ns: Namespace,
+    config: &'a IncludeCppConfig,
     // We mostly act upon the functions we see within the 'extern "C"'
     // block of bindgen output, but we can't actually do this until
     // we've seen the (possibly subsequent) 'impl' blocks so we can
@@ -42,10 +50,11 @@ pub(crate) struct ParseForeignMod {
     ignored_apis: ApiVec<NullPhase>,
 }
 
-impl ParseForeignMod {
-    pub(crate) fn new(ns: Namespace) -> Self {
+impl<'a> ParseForeignMod<'a> {
+    pub(crate) fn new(ns: Namespace, config: &'a IncludeCppConfig) -> Self {
         Self {
             ns,
+            config,
             funcs_to_convert: Vec::new(),
             method_receivers: HashMap::new(),
             ignored_apis: ApiVec::new(),
@@ -68,14 +77,31 @@ impl ParseForeignMod {
         match i {
             ForeignItem::Fn(item) => {
                 let annotations = BindgenSemanticAttributes::new(&item.attrs);
-                let doc_attrs = get_doc_attrs(&item.attrs);
+                let mut doc_attrs = get_doc_attrs(&item.attrs);
+                // Prefer a `[[nodiscard]]` annotation, which keeps any reason,
+                // over bindgen's own `[[nodiscard]]` detection, which produces a
+                // bare `#[must_use]` and so drops the reason.
+                doc_attrs.extend(
+                    annotations
+                        .get_must_use_attr()
+                        .or_else(|| get_must_use_attr(&item.attrs)),
+                );
+                // Turning on bindgen's function attribute detection (for
+                // `[[nodiscard]]`) also makes it report `[[noreturn]]` functions
+                // as returning `!`, which cxx doesn't understand. Treat them as
+                // returning nothing, as we did before: callers just won't know
+                // that the call diverges.
+                let output = match item.sig.output {
+                    ReturnType::Type(_, ty) if matches!(*ty, Type::Never(_)) => ReturnType::Default,
+                    output => output,
+                };
                 self.funcs_to_convert.push(FuncToConvert {
                     provenance: Provenance::Bindgen,
                     self_ty: None,
                     ident: item.sig.ident,
                     doc_attrs,
                     inputs: item.sig.inputs,
-                    output: item.sig.output,
+                    output,
                     vis: item.vis,
                     virtualness: annotations.get_virtualness(),
                     cpp_vis: annotations.get_cpp_visibility(),
@@ -92,6 +118,13 @@ impl ParseForeignMod {
                 });
                 Ok(())
             }
+            ForeignItem::Static(item)
+                if self.config.is_thread_local_var(
+                    &QualifiedName::new(&self.ns, item.ident.clone()).to_cpp_name(),
+                ) =>
+            {
+                self.convert_thread_local_var(item)
+            }
             ForeignItem::Static(item) => Err(ConvertErrorWithContext(
                 ConvertErrorFromCpp::StaticData(item.ident.to_string()),
                 Some(ErrorContext::new_for_item(item.ident)),
@@ -103,6 +136,51 @@ impl ParseForeignMod {
         }
     }
 
+    /// We can't simply link to a C++ `thread_local` variable, because each
+    /// thread has its own instance which may need dynamic initialization.
+    /// Instead synthesize a function which returns the address of the
+    /// calling thread's instance.
+    fn convert_thread_local_var(
+        &mut self,
+        item: ForeignItemStatic,
+    ) -> Result<(), ConvertErrorWithContext> {
+        if item.mutability.is_none() {
+            return Err(ConvertErrorWithContext(
+                ConvertErrorFromCpp::ConstThreadLocal,
+                Some(ErrorContext::new_for_item(item.ident)),
+            ));
+        }
+        let ty = &item.ty;
+        let mut doc_attrs = get_doc_attrs(&item.attrs);
+        doc_attrs.push(parse_quote! {
+            #[doc = "Returns the calling thread's instance of this C++ `thread_local` variable."]
+        });
+        self.funcs_to_convert.push(FuncToConvert {
+            provenance: Provenance::SynthesizedOther,
+            self_ty: None,
+            ident: item.ident.clone(),
+            doc_attrs,
+            inputs: Default::default(),
+            output: parse_quote! { -> *mut #ty },
+            vis: item.vis,
+            virtualness: Virtualness::None,
+            cpp_vis: CppVisibility::Public,
+            special_member: None,
+            unused_template_param: false,
+            references: References::default(),
+            original_name: None,
+            synthesized_this_type: None,
+            add_to_trait: None,
+            is_deleted: DeletedOrDefaulted::Neither,
+            synthetic_cpp: Some((
+                CppFunctionBody::AddressOfThreadLocal(self.ns.clone(), item.ident),
+                CppFunctionKind::Function,
+            )),
+            variadic: false,
+        });
+        Ok(())
+    }
+
     /// Record information from impl blocks encountered in bindgen
     /// output.
     pub(crate) fn convert_impl_items(&mut self, imp: ItemImpl) {
@@ -28,3 +28,59 @@ pub(crate) fn generate_utilities(apis: &mut ApiVec<NullPhase>, config: &IncludeC
         name: ApiName::new(&Namespace::new(), make_ident(config.get_makestring_name())),
     });
 }
+
+/// Adds a function which C++ code can call when an assertion fails, if the
+/// user asked for one. This is independent of the other utilities, since
+/// it's only generated on request.
+pub(crate) fn generate_assertion_handler(apis: &mut ApiVec<NullPhase>, config: &IncludeCppConfig) {
+    if config.get_assertion_handler().is_some() {
+        apis.push(UnanalyzedApi::AssertionHandler {
+            name: ApiName::new(
+                &Namespace::new(),
+                make_ident(config.get_assertion_handler_rust_name()),
+            ),
+        });
+    }
+}
+
+/// Our C++ `trycatch` prefixes the message of each C++ exception whose type
+/// was listed in `exception_type!` with this, then the index of the type
+/// in that list and a colon, so that Rust can tell which type it was.
+pub(crate) const EXCEPTION_TAG_PREFIX: &str = "autocxx-exception:";
+
+/// Adds an enum into which C++ exceptions can be converted, if the user
+/// listed any exception types.
+pub(crate) fn generate_exception_types(apis: &mut ApiVec<NullPhase>, config: &IncludeCppConfig) {
+    if !config.get_exception_types().is_empty() {
+        apis.push(UnanalyzedApi::ExceptionTypes {
+            name: ApiName::new(
+                &Namespace::new(),
+                make_ident(config.get_exception_enum_name()),
+            ),
+        });
+    }
+}
+
+/// Adds a check, run as the program starts, which compares the ABI version
+/// of the C++ library we're linked against with that of the headers we were
+/// built against, if the user asked for one with `abi_version!`.
+pub(crate) fn generate_abi_version_check(apis: &mut ApiVec<NullPhase>, config: &IncludeCppConfig) {
+    if config.get_abi_version().is_some() {
+        apis.push(UnanalyzedApi::AbiVersionCheck {
+            name: ApiName::new(
+                &Namespace::new(),
+                make_ident(config.get_abi_version_check_name()),
+            ),
+        });
+    }
+}
+
+/// Adds the types which the user asked for in `namespace_type!`. Free
+/// functions in each namespace later become their associated functions.
+pub(crate) fn generate_namespace_types(apis: &mut ApiVec<NullPhase>, config: &IncludeCppConfig) {
+    for (ns, ty) in config.get_namespace_types() {
+        apis.push(UnanalyzedApi::NamespaceType {
+            name: ApiName::new(&Namespace::from_user_input(ns), ty.clone()),
+        });
+    }
+}
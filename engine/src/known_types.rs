@@ -10,13 +10,15 @@ use crate::types::{make_ident, QualifiedName};
 use indexmap::map::IndexMap as HashMap;
 use indoc::indoc;
 use once_cell::sync::OnceCell;
-use syn::{parse_quote, TypePath};
+use syn::{parse_quote, GenericArgument, PathArguments, Type, TypePath};
 
 //// The behavior of the type.
 #[derive(Debug)]
 enum Behavior {
     CxxContainerPtr,
     CxxContainerVector,
+    CxxSpan,
+    CxxReferenceWrapper,
     CxxString,
     RustStr,
     RustString,
@@ -71,12 +73,16 @@ impl TypeDetails {
             | Behavior::CxxString
             | Behavior::CxxContainerPtr
             | Behavior::CxxContainerVector
+            | Behavior::CxxSpan
+            | Behavior::CxxReferenceWrapper
             | Behavior::RustContainerByValueSafe => {
                 let tn = QualifiedName::new_from_cpp_name(&self.rs_name);
                 let cxx_name = tn.get_final_item();
                 let (templating, payload) = match self.behavior {
                     Behavior::CxxContainerPtr
                     | Behavior::CxxContainerVector
+                    | Behavior::CxxSpan
+                    | Behavior::CxxReferenceWrapper
                     | Behavior::RustContainerByValueSafe => ("template<typename T> ", "T* ptr"),
                     _ => ("", "char* ptr"),
                 };
@@ -120,6 +126,8 @@ impl TypeDetails {
         match self.behavior {
             Behavior::CxxContainerPtr => CxxGenericType::CppPtr,
             Behavior::CxxContainerVector => CxxGenericType::CppVector,
+            Behavior::CxxSpan => CxxGenericType::Span,
+            Behavior::CxxReferenceWrapper => CxxGenericType::ReferenceWrapper,
             Behavior::RustContainerByValueSafe => CxxGenericType::Rust,
             _ => CxxGenericType::Not,
         }
@@ -150,10 +158,30 @@ pub enum CxxGenericType {
     /// Some generic like cxx::Vector where the contents must be a
     /// complete type, and some types of int are allowed too.
     CppVector,
+    /// std::span, which we represent as a Rust slice, so the contents
+    /// must be plain old data.
+    Span,
+    /// std::reference_wrapper, which we represent as a Rust reference,
+    /// so forward declarations are OK.
+    ReferenceWrapper,
     /// Some generic like rust::Box where forward declarations are OK
     Rust,
 }
 
+/// C++ typedefs which are defined differently on different platforms, each
+/// with the Rust type which differs between platforms in just the same way.
+/// bindgen would otherwise tell us how they're defined on whichever machine
+/// generated the bindings, which needn't be the target.
+const PLATFORM_TYPEDEFS: &[(&str, &str)] = &[
+    ("ssize_t", "isize"),
+    ("ptrdiff_t", "isize"),
+    ("std::ptrdiff_t", "isize"),
+    ("intptr_t", "isize"),
+    ("std::intptr_t", "isize"),
+    ("uintptr_t", "usize"),
+    ("std::uintptr_t", "usize"),
+];
+
 pub struct KnownTypeConstructorDetails {
     pub has_move_constructor: bool,
     pub has_const_copy_constructor: bool,
@@ -209,9 +237,11 @@ impl TypeDatabase {
                         | Behavior::CVariableLengthByValue
                         | Behavior::CChar16
                         | Behavior::RustContainerByValueSafe => true,
-                        Behavior::CxxString | Behavior::CxxContainerVector | Behavior::CVoid => {
-                            false
-                        }
+                        Behavior::CxxString
+                        | Behavior::CxxContainerVector
+                        | Behavior::CxxSpan
+                        | Behavior::CxxReferenceWrapper
+                        | Behavior::CVoid => false,
                     },
                 )
             })
@@ -281,6 +311,21 @@ impl TypeDatabase {
             .filter_map(|(_, td)| td.get_prelude_entry().map(|_| td.cpp_name.as_str()))
     }
 
+    /// If this is a typedef which is defined differently on different
+    /// platforms, the Rust type to use instead of its definition.
+    pub(crate) fn platform_typedef_target(&self, tn: &QualifiedName) -> Option<TypePath> {
+        PLATFORM_TYPEDEFS
+            .iter()
+            .find(|(cpp_name, _)| tn == &QualifiedName::new_from_cpp_name(cpp_name))
+            .and_then(|(_, rs_name)| {
+                self.known_type_type_path(&QualifiedName::new_from_cpp_name(rs_name))
+            })
+    }
+
+    pub(crate) fn is_platform_typedef(&self, tn: &QualifiedName) -> bool {
+        self.platform_typedef_target(tn).is_some()
+    }
+
     /// Whether this is one of the ctypes (mostly variable length integers)
     /// which we need to wrap.
     pub(crate) fn is_ctype(&self, ty: &QualifiedName) -> bool {
@@ -303,6 +348,33 @@ impl TypeDatabase {
             .unwrap_or(CxxGenericType::Not)
     }
 
+    /// The known type to use in place of a `std::complex` with the given
+    /// type arguments, if we can pass it by value.
+    pub(crate) fn complex_type(
+        &self,
+        tn: &QualifiedName,
+        args: &PathArguments,
+    ) -> Option<QualifiedName> {
+        if tn != &QualifiedName::new_from_cpp_name("std::complex") {
+            return None;
+        }
+        let elem = match args {
+            PathArguments::AngleBracketed(ab) if ab.args.len() == 1 => match ab.args.first() {
+                Some(GenericArgument::Type(Type::Path(elem))) if elem.qself.is_none() => {
+                    elem.path.get_ident()?
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        COMPLEX_ELEMENTS
+            .iter()
+            .find(|(rust_elem, _)| elem == rust_elem)
+            .map(|(_, cpp_elem)| {
+                QualifiedName::new_from_cpp_name(&format!("autocxx::c_complex_{cpp_elem}"))
+            })
+    }
+
     pub(crate) fn is_cxx_acceptable_receiver(&self, ty: &QualifiedName) -> bool {
         self.get(ty).is_none() // at present, none of our known types can have
                                // methods attached.
@@ -314,6 +386,37 @@ impl TypeDatabase {
             .unwrap_or(true)
     }
 
+    /// Whether this can be an element of a Rust slice which we pass to
+    /// C++ as a `std::span`. Types we don't know about here might be
+    /// acceptable if they turn out to be POD.
+    pub(crate) fn permissible_within_span(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| {
+                matches!(
+                    x.behavior,
+                    Behavior::CByValue
+                        | Behavior::CByValueVecSafe
+                        | Behavior::CVariableLengthByValue
+                )
+            })
+            .unwrap_or(true)
+    }
+
+    /// Whether this is `std::span`, which we pass from Rust as a slice.
+    pub(crate) fn is_span(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| matches!(x.behavior, Behavior::CxxSpan))
+            .unwrap_or(false)
+    }
+
+    /// Whether this is `std::reference_wrapper`, which we represent as a
+    /// Rust reference.
+    pub(crate) fn is_reference_wrapper(&self, ty: &QualifiedName) -> bool {
+        self.get(ty)
+            .map(|x| matches!(x.behavior, Behavior::CxxReferenceWrapper))
+            .unwrap_or(false)
+    }
+
     pub(crate) fn permissible_within_unique_ptr(&self, ty: &QualifiedName) -> bool {
         self.get(ty)
             .map(|x| {
@@ -362,6 +465,12 @@ impl TypeDatabase {
     }
 }
 
+/// Element types (as bindgen gives them, then in C++) for which we pass
+/// `std::complex` by value: these are laid out like a two-element array.
+/// `std::complex<long double>` isn't listed, so it becomes an opaque
+/// concrete type like any other template instantiation.
+const COMPLEX_ELEMENTS: [(&str, &str); 2] = [("f32", "float"), ("f64", "double")];
+
 fn create_type_database() -> TypeDatabase {
     let mut db = TypeDatabase::default();
     db.insert(TypeDetails::new(
@@ -380,6 +489,22 @@ fn create_type_database() -> TypeDatabase {
         false,
         true,
     ));
+    db.insert(TypeDetails::new(
+        "CxxSpan",
+        "std::span",
+        Behavior::CxxSpan,
+        None,
+        true,
+        true,
+    ));
+    db.insert(TypeDetails::new(
+        "CxxReferenceWrapper",
+        "std::reference_wrapper",
+        Behavior::CxxReferenceWrapper,
+        None,
+        true,
+        true,
+    ));
     db.insert(TypeDetails::new(
         "cxx::SharedPtr",
         "std::shared_ptr",
@@ -534,6 +659,15 @@ fn create_type_database() -> TypeDatabase {
         true,
         true,
     ));
+    // cxx defines this as `ssize_t`, or its equivalent on Windows.
+    db.insert(TypeDetails::new(
+        "isize",
+        "rust::isize",
+        Behavior::CByValueVecSafe,
+        None,
+        true,
+        true,
+    ));
     db.insert(TypeDetails::new(
         "autocxx::c_void",
         "void",
@@ -542,6 +676,24 @@ fn create_type_database() -> TypeDatabase {
         false,
         false,
     ));
+    db.insert(TypeDetails::new(
+        "autocxx::c_longdouble",
+        "long double",
+        Behavior::CVariableLengthByValue,
+        None,
+        true,
+        true,
+    ));
+    for (_, elem) in COMPLEX_ELEMENTS {
+        db.insert(TypeDetails::new(
+            format!("autocxx::c_complex_{elem}"),
+            format!("std::complex<{elem}>"),
+            Behavior::CVariableLengthByValue,
+            None,
+            true,
+            true,
+        ));
+    }
     db.insert(TypeDetails::new(
         "autocxx::c_char16_t",
         "char16_t",
@@ -30,7 +30,7 @@ use autocxx_bindgen::BindgenError;
 use autocxx_parser::{IncludeCppConfig, UnsafePolicy};
 use conversion::BridgeConverter;
 use miette::{SourceOffset, SourceSpan};
-use parse_callbacks::AutocxxParseCallbacks;
+use parse_callbacks::{AutocxxParseCallbacks, OperatorParseCallbacks};
 use parse_file::CppBuildable;
 use proc_macro2::TokenStream as TokenStream2;
 use regex::Regex;
@@ -64,8 +64,10 @@ use autocxx_bindgen as bindgen;
 
 #[cfg(any(test, feature = "build"))]
 pub use builder::{
-    Builder, BuilderBuild, BuilderContext, BuilderError, BuilderResult, BuilderSuccess,
+    Builder, BuilderBuild, BuilderContext, BuilderError, BuilderFiles, BuilderResult,
+    BuilderSuccess, IncludeConfig, IncludePathResolver,
 };
+pub use conversion::FunctionNameMapping;
 pub use output_generators::{generate_rs_archive, generate_rs_single, RsOutput};
 pub use parse_file::{parse_file, ParseError, ParsedFile};
 
@@ -139,6 +141,8 @@ struct GenerationResults {
     #[allow(dead_code)]
     inc_dirs: Vec<PathBuf>,
     cxxgen_header_name: String,
+    function_name_mappings: Vec<FunctionNameMapping>,
+    generated_type_names: Vec<String>,
 }
 enum State {
     NotGenerated,
@@ -152,6 +156,9 @@ pub struct CodegenOptions<'a> {
     // An option used by the test suite to force a more convoluted
     // route through our code, to uncover bugs.
     pub force_wrapper_gen: bool,
+    /// Whether to insist that each `generate_ns!` namespace contains
+    /// something which we generate, as well as each `generate!` item.
+    pub strict_allowlist: bool,
     /// Options about the C++ code generation.
     pub cpp_codegen_options: CppCodegenOptions<'a>,
 }
@@ -161,6 +168,7 @@ const AUTOCXX_CLANG_ARGS: &[&str; 4] = &["-x", "c++", "-std=c++14", "-DBINDGEN"]
 /// Implement to learn of header files which get included
 /// by this build process, such that your build system can choose
 /// to rerun the build process if any such file changes in future.
+/// System headers aren't reported, since they aren't expected to change.
 pub trait RebuildDependencyRecorder: std::fmt::Debug {
     /// Records that this autocxx build depends on the given
     /// header file. Full paths will be provided.
@@ -314,6 +322,12 @@ impl IncludeCppEngine {
         &mut self.config
     }
 
+    /// The headers which this `include_cpp!` includes.
+    #[cfg(any(test, feature = "build"))]
+    fn get_inclusions(&self) -> impl Iterator<Item = &String> {
+        self.config.inclusions.iter()
+    }
+
     fn build_header(&self) -> String {
         join(
             self.config
@@ -324,6 +338,42 @@ impl IncludeCppEngine {
         )
     }
 
+    /// Variables initialized by calling each `constexpr_fn!`, which we
+    /// show to bindgen so that clang evaluates them for us. They're not
+    /// part of the C++ we generate: if a call can't be evaluated at
+    /// compile time, it would need a dynamic initializer.
+    fn build_constexpr_probes(&self) -> String {
+        join(
+            self.config.constexpr_fns().map(|fun| {
+                let probe = self.config.get_constexpr_probe_name(fun);
+                let mut segments = probe.split("::").collect_vec();
+                let probe_name = segments.pop().unwrap();
+                format!(
+                    "{}static const auto {probe_name} = ::{fun}();{}\n",
+                    segments
+                        .iter()
+                        .map(|ns| format!("namespace {ns} {{ "))
+                        .join(""),
+                    " }".repeat(segments.len())
+                )
+            }),
+            "",
+        )
+    }
+
+    /// A typedef for each `instantiate!`, which we show to bindgen so that
+    /// clang fills in any template arguments which the user left to their
+    /// defaults. As with `constexpr_fn!` probes, these aren't part of the
+    /// C++ we generate.
+    fn build_instantiation_probes(&self) -> String {
+        join(
+            self.config
+                .get_instantiations()
+                .map(|(cpp_definition, rust_id)| format!("typedef {cpp_definition} {rust_id};\n")),
+            "",
+        )
+    }
+
     fn make_bindgen_builder(
         &self,
         inc_dirs: &[PathBuf],
@@ -345,6 +395,9 @@ impl IncludeCppEngine {
             .cpp_semantic_attributes(true)
             .represent_cxx_operators(true)
             .use_distinct_char16_t(true)
+            .enable_function_attribute_detection()
+            .parse_callbacks(Box::new(OperatorParseCallbacks))
+            .explicit_padding(self.config.explicit_padding())
             .layout_tests(false); // TODO revisit later
         for item in known_types().get_initial_blocklist() {
             builder = builder.blocklist_item(item);
@@ -389,6 +442,45 @@ impl IncludeCppEngine {
         }
     }
 
+    /// The Rust bindings as formatted source code, for build scripts which
+    /// want to embed or transform them rather than have `include_cpp!`
+    /// expand to them. This is formatted from the same module as
+    /// [`Self::get_rs_output`], so it's token-for-token identical to what
+    /// the macro produces. Call `generate` first.
+    pub fn generate_rs_string(&self) -> String {
+        match &self.state {
+            State::NotGenerated => panic!("Generate first"),
+            State::Generated(gen_results) => {
+                rust_pretty_printer::pretty_print(&gen_results.item_mod)
+            }
+            State::ParseOnly => String::new(),
+        }
+    }
+
+    /// The Rust, `cxx::bridge` and C++ names of each function for which
+    /// we generated bindings. Call `generate` first.
+    pub fn get_function_name_mappings(&self) -> &[FunctionNameMapping] {
+        match &self.state {
+            State::NotGenerated => panic!("Generate first"),
+            State::Generated(gen_results) => &gen_results.function_name_mappings,
+            State::ParseOnly => &[],
+        }
+    }
+
+    /// The Rust names of the types for which we generated bindings,
+    /// relative to the generated mod (e.g. `a::B`). Nested C++ types are
+    /// named as in Rust, so `A::B` is listed as `A_B`. Template
+    /// instantiations are listed under the name given in `concrete!`, or
+    /// else the name we made up for them. This doesn't include
+    /// types provided by cxx or by `extern_cpp_type!`. Call `generate` first.
+    pub fn generated_type_names(&self) -> &[String] {
+        match &self.state {
+            State::NotGenerated => panic!("Generate first"),
+            State::Generated(gen_results) => &gen_results.generated_type_names,
+            State::ParseOnly => &[],
+        }
+    }
+
     /// Returns the name of the mod which this `include_cpp!` will generate.
     /// Can and should be used to ensure multiple mods in a file don't conflict.
     pub fn get_mod_name(&self) -> String {
@@ -440,11 +532,20 @@ impl IncludeCppEngine {
         let mod_name = self.config.get_mod_name();
         let mut builder = self.make_bindgen_builder(&inc_dirs, extra_clang_args);
         if let Some(dep_recorder) = dep_recorder {
-            builder = builder.parse_callbacks(Box::new(AutocxxParseCallbacks(dep_recorder)));
+            builder = builder.parse_callbacks(Box::new(AutocxxParseCallbacks::new(
+                dep_recorder,
+                extra_clang_args,
+            )));
         }
         let header_contents = self.build_header();
         self.dump_header_if_so_configured(&header_contents, &inc_dirs, extra_clang_args);
-        let header_and_prelude = format!("{}\n\n{}", known_types().get_prelude(), header_contents);
+        let header_and_prelude = format!(
+            "{}\n\n{}\n{}{}",
+            known_types().get_prelude(),
+            header_contents,
+            self.build_constexpr_probes(),
+            self.build_instantiation_probes()
+        );
         log::info!("Header and prelude for bindgen:\n{}", header_and_prelude);
         builder = builder.header_contents("example.hpp", &header_and_prelude);
 
@@ -480,6 +581,7 @@ impl IncludeCppEngine {
             }
         };
         new_bindings.content.as_mut().unwrap().1.append(&mut items);
+        let new_bindings = self.enclose_bindings(new_bindings);
         info!(
             "New bindings:\n{}",
             rust_pretty_printer::pretty_print(&new_bindings)
@@ -489,10 +591,47 @@ impl IncludeCppEngine {
             cpp: conversion.cpp,
             inc_dirs,
             cxxgen_header_name: conversion.cxxgen_header_name,
+            function_name_mappings: conversion.function_name_mappings,
+            generated_type_names: conversion.generated_type_names,
         }));
         Ok(())
     }
 
+    /// Nest the generated mod within any modules named by `rust_mod_path!`.
+    /// Each of those glob-imports its parent, so the relative paths from
+    /// generated code back to the `include_cpp!` module still work.
+    fn enclose_bindings(&self, mut item_mod: ItemMod) -> ItemMod {
+        let path = match self.config.get_rust_mod_path() {
+            None => return item_mod,
+            Some(path) => path,
+        };
+        item_mod.vis = parse_quote! { pub };
+        for id in self
+            .config
+            .get_enclosing_mods()
+            .collect_vec()
+            .into_iter()
+            .rev()
+        {
+            item_mod = parse_quote! {
+                pub mod #id {
+                    #[allow(unused_imports)]
+                    use super::*;
+                    #item_mod
+                }
+            };
+        }
+        // A path from the crate root only makes sense if that's where
+        // the include_cpp! is, so check it's where we think it is.
+        if path.segments[0].ident == "crate" {
+            item_mod.content.as_mut().unwrap().1.push(parse_quote! {
+                #[allow(unused_imports)]
+                use #path as _;
+            });
+        }
+        item_mod
+    }
+
     /// Return the include directories used for this include_cpp invocation.
     #[cfg(any(test, feature = "build"))]
     fn include_dirs(&self) -> impl Iterator<Item = &PathBuf> {
@@ -654,10 +793,28 @@ impl CppBuildable for IncludeCppEngine {
                 }
             }
         };
+        let extra_includes = self.config.get_extra_cpp_includes();
+        if !extra_includes.is_empty() {
+            for implementation in files.iter_mut().filter_map(|f| f.implementation.as_mut()) {
+                prepend_includes(implementation, extra_includes);
+            }
+        }
         Ok(GeneratedCpp(files))
     }
 }
 
+/// Add `#include`s for the given headers before anything else in
+/// a generated C++ implementation file, so that they can set up
+/// the environment (macros, forward declarations) seen by all our
+/// own `#include`s.
+fn prepend_includes(implementation: &mut Vec<u8>, headers: &[String]) {
+    let prelude = headers
+        .iter()
+        .map(|hdr| format!("#include \"{hdr}\"\n"))
+        .join("");
+    implementation.splice(0..0, prelude.into_bytes());
+}
+
 /// Get clang args as if we were operating clang the same way as we operate
 /// bindgen.
 pub fn make_clang_args<'a>(
@@ -760,6 +917,51 @@ impl CxxgenHeaderNamer<'_> {
     }
 }
 
+/// Details of a C++ wrapper function which autocxx is about to generate,
+/// as given to a [`CppWrapperBodyCustomizer`].
+pub struct CppWrapperFunction<'a> {
+    /// The name of the C++ function, method or constructor which this
+    /// wraps, without any namespace or class qualification.
+    pub cpp_name: &'a str,
+    /// The name of the wrapper function itself.
+    pub wrapper_name: &'a str,
+    /// The wrapper's declaration, for example
+    /// `uint32_t get_autocxx_wrapper(const Foo& autocxx_gen_this, uint32_t arg1)`.
+    /// Parameters are always named `arg0`, `arg1` and so on, except for the
+    /// receiver of a method, which is `autocxx_gen_this`.
+    pub declaration: &'a str,
+    /// The body which autocxx would generate, without the enclosing braces.
+    /// This is the complete statement, including any `return` and the
+    /// trailing semicolon, for example `return arg0.get(arg1);`. It may be
+    /// empty, for instance for a constructor which only initializes fields,
+    /// since any initializer list isn't part of the body.
+    pub body: &'a str,
+}
+
+/// Function to post-process or replace the bodies of the C++ wrapper
+/// functions which autocxx generates. It's given each wrapper function in
+/// turn, and may return a new body (without the enclosing braces) or `None`
+/// to keep the one autocxx made.
+///
+/// The declaration of the wrapper can't be changed, since the Rust side
+/// relies upon it, so a replacement body must do whatever is needed to fit
+/// it: for instance, returning the declared return type. If it doesn't,
+/// the generated C++ won't compile. This is a last resort for cases where
+/// autocxx's own C++ isn't quite right, such as to add logging or to
+/// handle exceptions differently.
+///
+/// This is a newtype wrapper so we can store it in [`CppCodegenOptions`].
+#[allow(clippy::type_complexity)] // it's only complex because of the lifetime
+pub struct CppWrapperBodyCustomizer<'a>(
+    pub Box<dyn 'a + Fn(&CppWrapperFunction) -> Option<String>>,
+);
+
+impl CppWrapperBodyCustomizer<'_> {
+    fn customize(&self, function: &CppWrapperFunction) -> Option<String> {
+        self.0(function)
+    }
+}
+
 /// Options for C++ codegen
 #[derive(Default)]
 pub struct CppCodegenOptions<'a> {
@@ -783,6 +985,9 @@ pub struct CppCodegenOptions<'a> {
     /// An annotation optionally to include on each C++ function.
     /// For example to export the symbol from a library.
     pub cxx_impl_annotations: Option<String>,
+    /// Optionally, a function which may change the bodies of the C++
+    /// wrapper functions which autocxx generates.
+    pub wrapper_body_customizer: Option<CppWrapperBodyCustomizer<'a>>,
 }
 
 fn proc_macro_span_to_miette_span(span: &proc_macro2::Span) -> SourceSpan {
@@ -7,17 +7,141 @@
 // except according to those terms.
 
 use std::panic::UnwindSafe;
+use std::path::{Path, PathBuf};
 
 use crate::RebuildDependencyRecorder;
 use autocxx_bindgen::callbacks::ParseCallbacks;
+use autocxx_parser::cpp_operator_ident;
 
+/// Tells a [`RebuildDependencyRecorder`] about each header which clang
+/// reads, except for system headers: those in `-isystem` directories, and
+/// those in the directories clang searches by default (which bindgen also
+/// passes as `-isystem`). They don't change in the normal course of
+/// development, and reporting them would merely make the build system
+/// check lots of extra files.
 #[derive(Debug)]
-pub(crate) struct AutocxxParseCallbacks(pub(crate) Box<dyn RebuildDependencyRecorder>);
+pub(crate) struct AutocxxParseCallbacks {
+    dep_recorder: Box<dyn RebuildDependencyRecorder>,
+    system_dirs: Vec<PathBuf>,
+}
+
+impl AutocxxParseCallbacks {
+    pub(crate) fn new(
+        dep_recorder: Box<dyn RebuildDependencyRecorder>,
+        extra_clang_args: &[&str],
+    ) -> Self {
+        let system_dirs = system_dirs_from_clang_args(extra_clang_args)
+            .chain(clang_builtin_dirs())
+            .map(|dir| canonicalize(&dir))
+            .collect();
+        Self {
+            dep_recorder,
+            system_dirs,
+        }
+    }
+
+    fn is_system_header(&self, filename: &str) -> bool {
+        let path = canonicalize(Path::new(filename));
+        self.system_dirs.iter().any(|dir| path.starts_with(dir))
+    }
+}
 
 impl UnwindSafe for AutocxxParseCallbacks {}
 
 impl ParseCallbacks for AutocxxParseCallbacks {
     fn include_file(&self, filename: &str) {
-        self.0.record_header_file_dependency(filename);
+        if !self.is_system_header(filename) {
+            self.dep_recorder.record_header_file_dependency(filename);
+        }
+    }
+}
+
+/// The directories which clang searches for C++ headers by default, as
+/// found by bindgen when it sets up its own include paths.
+fn clang_builtin_dirs() -> Vec<PathBuf> {
+    clang_sys::support::Clang::find(None, &[])
+        .and_then(|clang| clang.cpp_search_paths)
+        .unwrap_or_default()
+}
+
+/// Find the system include directories among some clang arguments, in
+/// either the `-isystemdir` or the `-isystem dir` form.
+fn system_dirs_from_clang_args<'a>(args: &'a [&str]) -> impl Iterator<Item = PathBuf> + 'a {
+    let mut args = args.iter();
+    std::iter::from_fn(move || loop {
+        let arg = args.next()?;
+        let dir = match arg.strip_prefix("-isystem") {
+            Some("") => args.next().copied(),
+            Some(dir) => Some(dir),
+            None => None,
+        };
+        if let Some(dir) = dir {
+            return Some(PathBuf::from(dir));
+        }
+    })
+}
+
+/// Canonicalize a path if we can, so that `..` segments and symlinks
+/// don't stop us spotting that a header lies within an include directory.
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Gives C++ operator functions (e.g. `operator+`) identifiers which bindgen
+/// can represent, so that they aren't discarded. See
+/// [`autocxx_parser::cpp_operator_ident`].
+#[derive(Debug)]
+pub(crate) struct OperatorParseCallbacks;
+
+impl ParseCallbacks for OperatorParseCallbacks {
+    fn generated_name_override(&self, function_name: &str) -> Option<String> {
+        cpp_operator_ident(function_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug)]
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl RebuildDependencyRecorder for Recorder {
+        fn record_header_file_dependency(&self, filename: &str) {
+            self.0.borrow_mut().push(filename.to_string());
+        }
+    }
+
+    #[test]
+    fn test_system_headers_skipped() {
+        let system_dir = tempfile::tempdir().unwrap();
+        let other_system_dir = tempfile::tempdir().unwrap();
+        // Headers needn't be in an include directory to count, e.g. if
+        // they're included by a relative path.
+        let user_dir = tempfile::tempdir().unwrap();
+        let headers: Vec<_> = [&system_dir, &other_system_dir, &user_dir]
+            .iter()
+            .map(|dir| {
+                let header = dir.path().join("a.h");
+                std::fs::write(&header, "").unwrap();
+                header.to_str().unwrap().to_string()
+            })
+            .collect();
+        let other_system_arg = format!("-isystem{}", other_system_dir.path().display());
+        let found = Rc::new(RefCell::new(Vec::new()));
+        let callbacks = AutocxxParseCallbacks::new(
+            Box::new(Recorder(found.clone())),
+            &[
+                "-isystem",
+                system_dir.path().to_str().unwrap(),
+                &other_system_arg,
+            ],
+        );
+        for header in &headers {
+            callbacks.include_file(header);
+        }
+        assert_eq!(*found.borrow(), vec![headers[2].clone()]);
     }
 }
@@ -17,7 +17,11 @@ use thiserror::Error;
 use crate::known_types::known_types;
 
 pub(crate) fn make_ident<S: AsRef<str>>(id: S) -> Ident {
-    Ident::new(id.as_ref(), Span::call_site())
+    let id = id.as_ref();
+    match id.strip_prefix("r#") {
+        Some(raw) => Ident::new_raw(raw, Span::call_site()),
+        None => Ident::new(id, Span::call_site()),
+    }
 }
 
 /// Newtype wrapper for a C++ namespace.
@@ -45,7 +49,6 @@ impl Namespace {
         self.0.iter()
     }
 
-    #[cfg(test)]
     pub(crate) fn from_user_input(input: &str) -> Self {
         Self(Arc::new(input.split("::").map(|x| x.to_string()).collect()))
     }
@@ -13,6 +13,7 @@ use indexmap::set::IndexSet as HashSet;
 use std::{io::Write, sync::Mutex};
 
 pub type Builder = autocxx_engine::Builder<'static, CargoBuilderContext>;
+pub use autocxx_engine::{BuilderFiles, CppWrapperFunction, IncludeConfig};
 
 #[doc(hidden)]
 pub struct CargoBuilderContext;
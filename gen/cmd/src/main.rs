@@ -169,6 +169,11 @@ fn main() -> miette::Result<()> {
                 .long("suppress-system-headers")
                 .help("Do not refer to any system headers from generated code. May be useful for minimization.")
         )
+        .arg(
+            Arg::new("strict-allowlist")
+                .long("strict-allowlist")
+                .help("Fail if any generate_ns! namespace contains nothing which can be generated, as well as any generate! item.")
+        )
         .arg(
             Arg::new("cxx-impl-annotations")
                 .long("cxx-impl-annotations")
@@ -251,9 +256,11 @@ fn main() -> miette::Result<()> {
         path_to_cxxgen_h: get_option_string("cxxgen-h-path", &matches),
         autocxxgen_header_namer,
         cxxgen_header_namer,
+        wrapper_body_customizer: None,
     };
     let codegen_options = autocxx_engine::CodegenOptions {
         cpp_codegen_options,
+        strict_allowlist: matches.is_present("strict-allowlist"),
         ..Default::default()
     };
     let depfile = match matches.value_of("depfile") {
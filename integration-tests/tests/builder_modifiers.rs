@@ -6,7 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use autocxx_engine::Builder;
+use std::path::PathBuf;
+
+use autocxx_engine::{Builder, IncludeConfig};
 
 use autocxx_integration_tests::{BuilderModifier, BuilderModifierFns, TestBuilderContext};
 
@@ -60,6 +62,17 @@ impl BuilderModifierFns for SetSuppressSystemHeaders {
     }
 }
 
+pub(crate) struct SetStrictAllowlist;
+
+impl BuilderModifierFns for SetStrictAllowlist {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.strict_allowlist(true)
+    }
+}
+
 pub(crate) struct EnableAutodiscover;
 
 impl BuilderModifierFns for EnableAutodiscover {
@@ -70,3 +83,217 @@ impl BuilderModifierFns for EnableAutodiscover {
         builder.auto_allowlist(true)
     }
 }
+
+/// Writes a header into a directory which isn't among the include
+/// directories, and resolves it using an include path resolver.
+pub(crate) struct IncludePathResolverAdder {
+    header_name: String,
+    dir: tempfile::TempDir,
+}
+
+pub(crate) fn make_include_path_resolver(
+    header_name: &str,
+    header_code: &str,
+) -> Option<BuilderModifier> {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(header_name), header_code).unwrap();
+    Some(Box::new(IncludePathResolverAdder {
+        header_name: header_name.to_string(),
+        dir,
+    }))
+}
+
+impl BuilderModifierFns for IncludePathResolverAdder {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        let header_name = self.header_name.clone();
+        let dir: PathBuf = self.dir.path().to_path_buf();
+        builder.include_path_resolver(move |header| {
+            if header == header_name {
+                Some(dir.clone())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Writes a header into a directory which is then provided as a user
+/// include directory, along with some preprocessor definitions, using
+/// an [`IncludeConfig`].
+pub(crate) struct IncludeConfigAdder {
+    dir: tempfile::TempDir,
+    extra_dir: Option<PathBuf>,
+    defines: Vec<(String, Option<String>)>,
+}
+
+pub(crate) fn make_include_config_adder(
+    header_name: &str,
+    header_code: &str,
+    defines: &[(&str, Option<&str>)],
+) -> Option<BuilderModifier> {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(header_name), header_code).unwrap();
+    Some(Box::new(IncludeConfigAdder {
+        dir,
+        extra_dir: None,
+        defines: defines
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.map(|v| v.to_string())))
+            .collect(),
+    }))
+}
+
+/// Like [`make_include_config_adder`] but also asks for a nonexistent
+/// include directory.
+pub(crate) fn make_missing_include_dir_adder() -> Option<BuilderModifier> {
+    Some(Box::new(IncludeConfigAdder {
+        dir: tempfile::tempdir().unwrap(),
+        extra_dir: Some(PathBuf::from("no/such/directory")),
+        defines: Vec::new(),
+    }))
+}
+
+impl BuilderModifierFns for IncludeConfigAdder {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        let mut config = IncludeConfig::new().user_dir(self.dir.path());
+        if let Some(extra_dir) = &self.extra_dir {
+            config = config.system_dir(extra_dir);
+        }
+        for (name, value) in &self.defines {
+            config = config.define(name, value.as_deref());
+        }
+        builder.include_config(config)
+    }
+}
+
+/// Asks for a name manifest, and checks that it contains the given strings.
+pub(crate) struct NameManifestChecker {
+    expected: Vec<String>,
+    dir: tempfile::TempDir,
+}
+
+pub(crate) fn make_name_manifest_checker(expected: &[&str]) -> Option<BuilderModifier> {
+    Some(Box::new(NameManifestChecker {
+        expected: expected.iter().map(|s| s.to_string()).collect(),
+        dir: tempfile::tempdir().unwrap(),
+    }))
+}
+
+impl NameManifestChecker {
+    fn manifest_path(&self) -> PathBuf {
+        self.dir.path().join("names.json")
+    }
+}
+
+impl BuilderModifierFns for NameManifestChecker {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.name_manifest(self.manifest_path())
+    }
+
+    // Called once autocxx has done its work, so the manifest now exists.
+    fn modify_cc_builder<'a>(&self, builder: &'a mut cc::Build) -> &'a mut cc::Build {
+        let manifest = std::fs::read_to_string(self.manifest_path()).unwrap();
+        for expected in &self.expected {
+            assert!(
+                manifest.contains(expected.as_str()),
+                "Couldn't find {expected} in manifest {manifest}"
+            );
+        }
+        builder
+    }
+}
+
+/// Asks for a compilation database, having first put an unrelated entry
+/// in it, and checks that both that and an entry for our generated C++
+/// are present afterwards.
+pub(crate) struct CompileCommandsChecker {
+    dir: tempfile::TempDir,
+}
+
+pub(crate) fn make_compile_commands_checker() -> Option<BuilderModifier> {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("compile_commands.json"),
+        r#"[{"directory": "/src", "file": "/src/other.cc", "command": "c++ -c other.cc"}]"#,
+    )
+    .unwrap();
+    Some(Box::new(CompileCommandsChecker { dir }))
+}
+
+impl CompileCommandsChecker {
+    fn database_path(&self) -> PathBuf {
+        self.dir.path().join("compile_commands.json")
+    }
+}
+
+impl BuilderModifierFns for CompileCommandsChecker {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.compile_commands(self.database_path())
+    }
+
+    // Called once autocxx has done its work, so the database now exists.
+    fn modify_cc_builder<'a>(&self, builder: &'a mut cc::Build) -> &'a mut cc::Build {
+        let database = std::fs::read_to_string(self.database_path()).unwrap();
+        for expected in ["\"/src/other.cc\"", "gen0.cxx\"", "\"-I", "\"-c\""] {
+            assert!(
+                database.contains(expected),
+                "Couldn't find {expected} in database {database}"
+            );
+        }
+        builder
+    }
+}
+
+/// Asks for a precompiled header which doesn't exist.
+pub(crate) struct MissingPrecompiledHeader;
+
+impl BuilderModifierFns for MissingPrecompiledHeader {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        builder.precompiled_header("no/such/header.pch")
+    }
+}
+
+/// Passes some arguments to autocxx's clang, but unlike
+/// [`make_clang_arg_adder`] leaves autocxx to pass on anything needed
+/// when compiling the generated C++.
+pub(crate) struct AutocxxOnlyClangArgs(pub(crate) Vec<String>);
+
+impl BuilderModifierFns for AutocxxOnlyClangArgs {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        let refs: Vec<_> = self.0.iter().map(|s| s.as_str()).collect();
+        builder.extra_clang_args(&refs)
+    }
+}
+
+/// Replaces the body of the C++ wrapper for the given function.
+pub(crate) struct ReplaceWrapperBody(pub(crate) &'static str, pub(crate) &'static str);
+
+impl BuilderModifierFns for ReplaceWrapperBody {
+    fn modify_autocxx_builder<'a>(
+        &self,
+        builder: Builder<'a, TestBuilderContext>,
+    ) -> Builder<'a, TestBuilderContext> {
+        let (cpp_name, body) = (self.0, self.1);
+        builder.wrapper_body_customizer(move |function| {
+            (function.cpp_name == cpp_name).then(|| body.to_string())
+        })
+    }
+}
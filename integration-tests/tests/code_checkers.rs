@@ -122,6 +122,29 @@ pub(crate) fn make_rust_code_finder(code: Vec<TokenStream>) -> CodeChecker {
     Box::new(RustCodeFinder(code))
 }
 
+struct RustCodeAbsenceChecker(Vec<TokenStream>);
+
+impl CodeCheckerFns for RustCodeAbsenceChecker {
+    fn check_rust(&self, rs: syn::File) -> Result<(), TestError> {
+        let haystack = rs.to_token_stream().to_string();
+        for msg in &self.0 {
+            let needle = msg.to_string();
+            if haystack.contains(&needle) {
+                return Err(TestError::RsCodeExaminationFail(format!(
+                    "Unexpectedly found tokens '{needle}'"
+                )));
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Returns a code checker which ensures the given Rust tokens are _not_
+/// in the output
+pub(crate) fn make_rust_code_absence_checker(code: Vec<TokenStream>) -> CodeChecker {
+    Box::new(RustCodeAbsenceChecker(code))
+}
+
 /// Searches generated C++ for strings we want to find, or want _not_ to find,
 /// or both.
 pub(crate) struct CppMatcher<'a> {
@@ -8,22 +8,27 @@
 
 use crate::{
     builder_modifiers::{
-        make_clang_arg_adder, make_clang_optional_arg_adder, make_cpp17_adder, EnableAutodiscover,
+        make_clang_arg_adder, make_clang_optional_arg_adder, make_compile_commands_checker,
+        make_cpp17_adder, make_include_config_adder, make_include_path_resolver,
+        make_missing_include_dir_adder, make_name_manifest_checker, AutocxxOnlyClangArgs,
+        EnableAutodiscover, MissingPrecompiledHeader, ReplaceWrapperBody, SetStrictAllowlist,
         SetSuppressSystemHeaders,
     },
     code_checkers::{
-        make_error_finder, make_rust_code_finder, make_string_finder, CppMatcher,
-        NoSystemHeadersChecker,
+        make_error_finder, make_rust_code_absence_checker, make_rust_code_finder,
+        make_string_finder, CppMatcher, NoSystemHeadersChecker,
     },
 };
+use autocxx_engine::Builder;
 use autocxx_integration_tests::{
     directives_from_lists, do_run_test, do_run_test_manual, run_generate_all_test, run_test,
-    run_test_ex, run_test_expect_fail, run_test_expect_fail_ex, BuilderModifier, TestError,
+    run_test_ex, run_test_expect_fail, run_test_expect_fail_ex, BuilderModifier,
+    TestBuilderContext, TestError,
 };
 use indoc::indoc;
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{parse_quote, Token};
 use test_log::test;
 
@@ -2546,6 +2551,59 @@ fn test_return_reference_non_pod_string() {
     run_test(cxx, hdr, rs, &["give_bob", "Bob"], &[]);
 }
 
+#[test]
+fn test_const_method_return_reference_with_ref_param() {
+    // The output could borrow from either `this` or `key`, so we need to
+    // be told which.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct Dictionary {
+            std::string definitions[2];
+            const std::string& lookup(const uint32_t& key) const { return definitions[key]; }
+        };
+        inline Dictionary make_dictionary() {
+            Dictionary d;
+            d.definitions[0] = \"zero\";
+            d.definitions[1] = \"one\";
+            return d;
+        }
+    "};
+    let rs = quote! {
+        let d = ffi::make_dictionary().within_box();
+        assert_eq!(d.lookup(&1).to_str().unwrap(), "one");
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Dictionary", "make_dictionary"],
+            &[],
+            Some(quote! { return_borrows!("lookup", self) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_const_method_return_reference_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Pair {
+            uint32_t values[2];
+            const uint32_t& first() const { return values[0]; }
+        };
+    "};
+    let rs = quote! {
+        let p = ffi::Pair { values: [3, 4] };
+        assert_eq!(*p.first(), 3);
+    };
+    run_test("", hdr, rs, &[], &["Pair"]);
+}
+
 #[test]
 fn test_member_return_reference() {
     let hdr = indoc! {"
@@ -3067,6 +3125,34 @@ fn test_string_constant() {
     run_test("", hdr, rs, &["STRING"], &[]);
 }
 
+#[test]
+fn test_constexpr_string_constants() {
+    let hdr = indoc! {"
+        constexpr const char* VERSION = \"1.2.3\";
+        constexpr const char* NOT_UTF8 = \"\\xff\";
+        #define BOB \"foo\"
+    "};
+    let rs = quote! {
+        let version: &'static str = ffi::VERSION;
+        assert_eq!(version, "1.2.3");
+        assert_eq!(&ffi::NOT_UTF8[..], b"\xff\0");
+        assert_eq!(ffi::BOB, "foo");
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["VERSION", "NOT_UTF8", "BOB"],
+            &[],
+            Some(quote! { str_constants!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 fn test_string_let_cxx_string() {
     let hdr = indoc! {"
@@ -5419,13 +5505,13 @@ fn test_vec_and_up_of_primitives() {
         public:
             Value(std::vector<uint32_t>) {} // OK
             Value(std::unique_ptr<uint32_t>) {} // should be ignored
-            Value(std::vector<int>) {} // should be ignored
+            Value(std::vector<int>) {} // opaque vector
             Value(std::unique_ptr<int>) {} // should be ignored
-            Value(std::vector<char>) {} // should be ignored
+            Value(std::vector<char>) {} // opaque vector
             Value(std::unique_ptr<char>) {} // should be ignored
             Value(std::vector<float>) {} // OK
             Value(std::unique_ptr<float>) {} // should be ignored
-            Value(std::vector<bool>) {} // should be ignored
+            Value(std::vector<bool>) {} // opaque vector
             Value(std::unique_ptr<bool>) {} // should be ignored
             Value(std::vector<size_t>) {} // OK
             Value(std::unique_ptr<size_t>) {} // should be ignored
@@ -7008,6 +7094,46 @@ fn test_shared_ptr() {
     );
 }
 
+#[test]
+fn test_shared_ptr_const_struct() {
+    let hdr = indoc! {"
+        #include <memory>
+        struct A {
+            int a;
+        };
+        inline std::shared_ptr<const A> make_shared_a() {
+            return std::make_shared<const A>(A { 3 });
+        }
+        inline int take_shared_a(std::shared_ptr<const A> a) {
+            return a->a;
+        }
+        inline std::weak_ptr<const A> shared_to_weak(std::shared_ptr<const A> a) {
+            return std::weak_ptr<const A>(a);
+        }
+    "};
+    let rs = quote! {
+        let a = ffi::make_shared_a();
+        assert_eq!(ffi::take_shared_a(a.clone()), autocxx::c_int(3));
+        assert!(!ffi::shared_to_weak(a).upgrade().is_null());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_shared_a", "take_shared_a", "shared_to_weak"],
+            &[],
+            Some(quote! {
+                const_smart_ptr_return!("make_shared_a")
+                const_smart_ptr_return!("shared_to_weak")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
 #[test]
 #[ignore] // https://github.com/google/autocxx/issues/799
 fn test_shared_ptr_const() {
@@ -12167,6 +12293,3916 @@ fn test_ignore_va_list() {
     run_test("", hdr, rs, &["A"], &[]);
 }
 
+#[test]
+fn test_checked_narrowing() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t add_narrow(uint8_t a, int16_t b) {
+            return a + b;
+        }
+        inline unsigned int add_unsigned(unsigned int a, int b) {
+            return a + b;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::add_narrow(200, 3).unwrap(), 203);
+        assert!(ffi::add_narrow(256, 0).is_err());
+        assert!(ffi::add_narrow(-1, 0).is_err());
+        assert!(ffi::add_narrow(0, 40000).is_err());
+        assert_eq!(ffi::add_unsigned(4000000000, -1).unwrap(), autocxx::c_uint(3999999999));
+        assert!(ffi::add_unsigned(-1, 0).is_err());
+        assert!(ffi::add_unsigned(0, 1i64 << 40).is_err());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["add_narrow", "add_unsigned"],
+            &[],
+            Some(quote! {
+                checked_narrowing!("add_narrow")
+                checked_narrowing!("add_unsigned")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_boxed_returns() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        struct Anna {
+            Anna() : a(3) {}
+            uint32_t get() const { return a; }
+            uint32_t a;
+            std::string b;
+        };
+        inline Anna give_anna() {
+            Anna a;
+            a.a = 10;
+            return a;
+        }
+    "};
+    let rs = quote! {
+        let a: ::core::pin::Pin<Box<ffi::Anna>> = ffi::Anna::new();
+        assert_eq!(a.get(), 3);
+        let b: ::core::pin::Pin<Box<ffi::Anna>> = ffi::give_anna();
+        assert_eq!(b.get(), 10);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Anna", "give_anna"],
+            &[],
+            Some(quote! { boxed_returns!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_include_path_resolver() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t get_input() { return 3; }
+    "};
+    let resolved_hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t get_resolved() { return 4; }
+    "};
+    let hexathorpe = Token![#](Span::call_site());
+    let rs = quote! {
+        assert_eq!(ffi::get_input(), 3);
+        assert_eq!(ffi::get_resolved(), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            #hexathorpe include "resolved.h"
+            generate!("get_input")
+            generate!("get_resolved")
+        },
+        make_include_path_resolver("resolved.h", resolved_hdr),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_byte_buffer() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cstddef>
+        #include <vector>
+        class ByteArray {
+        public:
+            ByteArray() {}
+            void push(uint8_t b) { bytes.push_back(b); }
+            const uint8_t* data() const { return bytes.data(); }
+            size_t size() const { return bytes.size(); }
+        private:
+            std::vector<uint8_t> bytes;
+        };
+        class SmallBuffer {
+        public:
+            SmallBuffer() : bytes{3, 4, 5} {}
+            const char* data() const { return bytes; }
+            int size() const { return 3; }
+        private:
+            char bytes[3];
+        };
+    "};
+    let rs = quote! {
+        let mut a = ffi::ByteArray::new().within_unique_ptr();
+        assert!(a.as_ref().unwrap().as_ref().is_empty());
+        a.pin_mut().push(1);
+        a.pin_mut().push(2);
+        let bytes: &[u8] = a.as_ref().unwrap().as_ref();
+        assert_eq!(bytes, &[1, 2]);
+        let b = ffi::SmallBuffer::new().within_unique_ptr();
+        let bytes: &[u8] = b.as_ref().unwrap().as_ref();
+        assert_eq!(bytes, &[3, 4, 5]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["ByteArray", "SmallBuffer"],
+            &[],
+            Some(quote! {
+                byte_buffer!("ByteArray", data, size)
+                byte_buffer!("SmallBuffer", data, size)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_byte_buffer_missing_method() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cstddef>
+        class ByteArray {
+        public:
+            const uint8_t* data() const { return nullptr; }
+            size_t size() const { return 0; }
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["ByteArray"],
+            &[],
+            Some(quote! {
+                byte_buffer!("ByteArray", data, len)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_instrument_calls() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace a {
+            inline uint32_t give_int() { return 5; }
+        }
+        struct Bob {
+            uint32_t a;
+            uint32_t get() const { return a; }
+            uint32_t get(uint32_t b) const { return a + b; }
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::a::give_int(), 5);
+        let b = ffi::Bob { a: 3 };
+        assert_eq!(b.get(), 3);
+        assert_eq!(b.get1(1), 4);
+        let calls = CALLS.with(|calls| calls.borrow().clone());
+        assert_eq!(calls, vec!["a::give_int", "Bob::get", "Bob::get"]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["a::give_int"],
+            &["Bob"],
+            Some(quote! { instrument_calls!(crate::record_call) }),
+        ),
+        None,
+        // cxx binds the methods directly, without any C++ wrapper.
+        Some(make_rust_code_finder(vec![
+            quote! { #[cxx_name = "get"] },
+            quote! { fn get_autocxx_instrumented(self: &Bob) -> u32; },
+        ])),
+        Some(quote! {
+            thread_local! {
+                static CALLS: std::cell::RefCell<Vec<&'static str>> = std::cell::RefCell::new(Vec::new());
+            }
+
+            #[macro_export]
+            macro_rules! record_call {
+                ($name:literal, $call:expr) => {{
+                    $crate::CALLS.with(|calls| calls.borrow_mut().push($name));
+                    $call
+                }};
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_tagged_union() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        union ShapeData {
+            uint32_t radius;
+            float side;
+        };
+        struct Shape {
+            uint32_t kind;
+            ShapeData data;
+        };
+        inline Shape make_circle(uint32_t radius) {
+            Shape s;
+            s.kind = 0;
+            s.data.radius = radius;
+            return s;
+        }
+        inline Shape make_square(float side) {
+            Shape s;
+            s.kind = 1;
+            s.data.side = side;
+            return s;
+        }
+        inline Shape make_mystery() {
+            Shape s;
+            s.kind = 7;
+            return s;
+        }
+    "};
+    let rs = quote! {
+        let circle = ffi::make_circle(3).within_box();
+        assert!(matches!(circle.variant(), ffi::ShapeVariant::radius(&3)));
+        let square = ffi::make_square(2.0).within_box();
+        assert!(matches!(square.variant(), ffi::ShapeVariant::side(s) if *s == 2.0));
+        let mystery = ffi::make_mystery().within_box();
+        assert!(matches!(mystery.variant(), ffi::ShapeVariant::Unknown(7)));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_circle", "make_square", "make_mystery", "Shape"],
+            &[],
+            Some(quote! { tagged_union!("Shape", kind, data, 0 => radius, 1 => side) }),
+        ),
+        None,
+        // The getters which don't check the tag are private.
+        Some(make_rust_code_absence_checker(vec![
+            quote! { pub fn data_radius_unchecked },
+            quote! { allow(unused_unsafe) },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_tagged_union_all_unsafe() {
+    // The accessor is safe even when the C++ it calls isn't.
+    let hdr = indoc! {"
+        #include <cstdint>
+        union ShapeData {
+            uint32_t radius;
+            float side;
+        };
+        struct Shape {
+            uint32_t kind;
+            ShapeData data;
+        };
+        inline Shape make_circle(uint32_t radius) {
+            Shape s;
+            s.kind = 0;
+            s.data.radius = radius;
+            return s;
+        }
+    "};
+    let rs = quote! {
+        let circle = unsafe { ffi::make_circle(3) }.within_box();
+        assert!(matches!(circle.variant(), ffi::ShapeVariant::radius(&3)));
+    };
+    do_run_test(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("make_circle")
+            generate!("Shape")
+            tagged_union!("Shape", kind, data, 0 => radius, 1 => side)
+        },
+        None,
+        None,
+        None,
+        "unsafe",
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_tagged_union_missing_member() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        union ShapeData {
+            uint32_t radius;
+            float side;
+        };
+        struct Shape {
+            uint32_t kind;
+            ShapeData data;
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["Shape"],
+            &[],
+            Some(quote! { tagged_union!("Shape", kind, data, 0 => radius, 1 => diameter) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_bool_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        typedef int BOOL;
+        inline uint32_t count_flags(BOOL a, int b, uint32_t c) {
+            return (a ? 1 : 0) + (b ? 1 : 0) + c;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::count_flags(true, false, 2), 3);
+        assert_eq!(ffi::count_flags(false, true, 0), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["count_flags"],
+            &[],
+            Some(quote! {
+                bool_param!("count_flags", a)
+                bool_param!("count_flags", b)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_bool_param_missing() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t count_flags(int a) { return a ? 1 : 0; }
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["count_flags"],
+            &[],
+            Some(quote! { bool_param!("count_flags", b) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_bool_param_not_integer() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Flags {
+            uint32_t bits;
+        };
+        inline uint32_t count_flags(Flags a) { return a.bits; }
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["count_flags"],
+            &["Flags"],
+            Some(quote! { bool_param!("count_flags", a) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_allowlist_generates_only_transitive_dependencies() {
+    // Node is reached only as a dependency of the allowlisted functions,
+    // and it refers to itself. We should generate Node, but neither its
+    // method nor the type which that method uses.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        struct Unneeded {
+            uint32_t a;
+        };
+        struct Node {
+            std::unique_ptr<Node> next;
+            uint32_t value;
+            Unneeded make_unneeded() const { return Unneeded { value }; }
+        };
+        inline std::unique_ptr<Node> make_list() {
+            auto head = std::make_unique<Node>();
+            head->value = 3;
+            head->next = std::make_unique<Node>();
+            head->next->value = 2;
+            return head;
+        }
+        inline uint32_t sum(const Node& n) {
+            return n.value + (n.next ? sum(*n.next) : 0);
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::sum(&ffi::make_list()), 5);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["make_list", "sum"], &[], None),
+        None,
+        Some(make_rust_code_absence_checker(vec![
+            quote! { Unneeded },
+            quote! { make_unneeded },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_name_manifest() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace a {
+            inline uint32_t twice(uint32_t x) { return x * 2; }
+            inline uint32_t twice(uint32_t x, uint32_t y) { return (x + y) * 2; }
+            struct B {
+                uint32_t get() const { return 4; }
+                uint32_t get(uint32_t x) const { return x; }
+                struct C {
+                    uint32_t c;
+                };
+            };
+        }
+        template<typename T> struct Holder {
+            T t;
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::a::twice(2), 4);
+        assert_eq!(ffi::a::twice1(1, 2), 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["a::twice", "a::B", "a::B::C"],
+            &[],
+            Some(quote! { concrete!("Holder<uint32_t>", HolderOfInt) }),
+        ),
+        make_name_manifest_checker(&[
+            "\"mod\": \"ffi\"",
+            "\"a::B\"",
+            "\"a::B_C\"",
+            "\"HolderOfInt\"",
+            "\"rust_name\": \"a::twice1\"",
+            "\"cpp_name\": \"a::twice\"",
+            "\"rust_name\": \"a::B::get1\"",
+            "\"cpp_name\": \"a::B::get\"",
+            "\"cxxbridge_name\": \"",
+        ]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_enum_by_value_round_trip() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum class Small : uint8_t {
+            A = 1,
+            B = 200,
+        };
+        enum class Signed : int16_t {
+            Neg = -300,
+            Pos = 300,
+        };
+        enum {
+            ANON_VALUE = 4,
+        };
+        struct Outer {
+            enum Inner {
+                X,
+                Y,
+            };
+        };
+        inline Small flip_small(Small s) { return s == Small::A ? Small::B : Small::A; }
+        inline Signed flip_signed(Signed s) {
+            return s == Signed::Neg ? Signed::Pos : Signed::Neg;
+        }
+        inline Outer::Inner flip_inner(Outer::Inner i) {
+            return i == Outer::X ? Outer::Y : Outer::X;
+        }
+    "};
+    let rs = quote! {
+        assert!(ffi::flip_small(ffi::Small::A) == ffi::Small::B);
+        assert!(ffi::flip_small(ffi::Small::B) == ffi::Small::A);
+        assert!(ffi::flip_signed(ffi::Signed::Neg) == ffi::Signed::Pos);
+        assert!(ffi::flip_inner(ffi::Outer_Inner::X) == ffi::Outer_Inner::Y);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["flip_small", "flip_signed", "flip_inner"], &[], None),
+        None,
+        Some(Box::new(CppMatcher::new(
+            &["sizeof(Small) == sizeof(uint8_t)", "sizeof(Outer::Inner)"],
+            &["_bindgen_ty_"],
+        ))),
+        None,
+    );
+}
+
+#[test]
+fn test_operators() {
+    let hdr = indoc! {"
+        class Vec2 {
+        public:
+            Vec2(int x, int y) : x_(x), y_(y) {}
+            int x() const { return x_; }
+            int y() const { return y_; }
+            Vec2 operator+(const Vec2& other) const { return Vec2(x_ + other.x_, y_ + other.y_); }
+            Vec2 operator-() const { return Vec2(-x_, -y_); }
+            Vec2& operator+=(const Vec2& other) {
+                x_ += other.x_;
+                y_ += other.y_;
+                return *this;
+            }
+            bool operator==(const Vec2& other) const { return x_ == other.x_ && y_ == other.y_; }
+            int operator[](int i) const { return i == 0 ? x_ : y_; }
+        private:
+            int x_;
+            int y_;
+        };
+        inline Vec2 operator-(const Vec2& a, const Vec2& b) { return a + -b; }
+        inline Vec2 operator*(int s, const Vec2& v) { return Vec2(s * v.x(), s * v.y()); }
+    "};
+    let rs = quote! {
+        let a = ffi::Vec2::new(autocxx::c_int(1), autocxx::c_int(2)).within_unique_ptr();
+        let b = ffi::Vec2::new(autocxx::c_int(10), autocxx::c_int(20)).within_unique_ptr();
+        // Member operators, via std::ops traits and named methods.
+        let sum = a.as_ref().unwrap() + b.as_ref().unwrap();
+        assert_eq!(sum.x(), autocxx::c_int(11));
+        assert_eq!(sum.op_index(autocxx::c_int(1)), autocxx::c_int(22));
+        let neg = -a.as_ref().unwrap();
+        assert_eq!(neg.y(), autocxx::c_int(-2));
+        assert!(a.op_eq(&a));
+        assert!(!a.op_eq(&b));
+        let mut c = ffi::Vec2::new(autocxx::c_int(0), autocxx::c_int(0)).within_unique_ptr();
+        let mut c_pin = c.pin_mut();
+        c_pin += a.as_ref().unwrap();
+        c_pin += a.as_ref().unwrap();
+        assert_eq!(c.x(), autocxx::c_int(2));
+        // Free operators.
+        let diff = b.as_ref().unwrap() - a.as_ref().unwrap();
+        assert_eq!(diff.y(), autocxx::c_int(18));
+        let scaled = ffi::op_mul(autocxx::c_int(3), &a).within_unique_ptr();
+        assert_eq!(scaled.x(), autocxx::c_int(3));
+    };
+    run_test("", hdr, rs, &["Vec2", "operator-", "operator*"], &[]);
+}
+
+#[test]
+fn test_include_config() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t get_input() { return 3; }
+    "};
+    let bundled_hdr = indoc! {"
+        #include <cstdint>
+        #ifdef BUNDLE_ENABLED
+        inline uint32_t get_bundled() { return BUNDLE_VALUE; }
+        #endif
+    "};
+    let hexathorpe = Token![#](Span::call_site());
+    let rs = quote! {
+        assert_eq!(ffi::get_input(), 3);
+        assert_eq!(ffi::get_bundled(), 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            #hexathorpe include "bundled.h"
+            generate!("get_input")
+            generate!("get_bundled")
+        },
+        make_include_config_adder(
+            "bundled.h",
+            bundled_hdr,
+            &[("BUNDLE_ENABLED", None), ("BUNDLE_VALUE", Some("7"))],
+        ),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_include_config_missing_dir() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t get_input() { return 3; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::get_input(), 3);
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["get_input"], &[], None),
+        make_missing_include_dir_adder(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_type_declared_in_two_headers() {
+    // Forward declared here, defined in the second header.
+    let hdr = indoc! {"
+        struct Widget;
+        inline bool is_null(const Widget* w) { return w == nullptr; }
+    "};
+    let widget_hdr = indoc! {"
+        #pragma once
+        #include <cstdint>
+        struct Widget;
+        struct Widget {
+            uint32_t size;
+        };
+        inline Widget make_widget() { return Widget { 4 }; }
+    "};
+    let hexathorpe = Token![#](Span::call_site());
+    let rs = quote! {
+        let w = ffi::make_widget();
+        assert_eq!(w.size, 4);
+        assert!(!unsafe { ffi::is_null(&w) });
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            #hexathorpe include "widget.h"
+            generate!("is_null")
+            generate!("make_widget")
+            generate_pod!("Widget")
+        },
+        make_include_config_adder("widget.h", widget_hdr, &[]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_range_into_iterator() {
+    let hdr = indoc! {"
+        #include <vector>
+        class Numbers {
+        public:
+            Numbers() : values({1, 2, 3}) {}
+            std::vector<int>::const_iterator begin() const { return values.begin(); }
+            std::vector<int>::const_iterator end() const { return values.end(); }
+        private:
+            std::vector<int> values;
+        };
+    "};
+    let rs = quote! {
+        let numbers = ffi::Numbers::new().within_box();
+        let mut total = 0;
+        for n in &*numbers {
+            total += n.0;
+        }
+        assert_eq!(total, 6);
+        assert_eq!(numbers.iter().len(), 3);
+        let collected: Vec<_> = numbers.iter().map(|n| n.0).collect();
+        assert_eq!(collected, vec![1, 2, 3]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["Numbers"], &[], Some(quote! { range!("Numbers", "int") })),
+        None,
+        // Only the iterator can use the raw pointers behind it.
+        Some(make_rust_code_absence_checker(vec![
+            quote! { pub fn range_next },
+            quote! { allow(unused_unsafe) },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_range_list() {
+    // A range whose iterators aren't random-access.
+    let hdr = indoc! {"
+        #include <list>
+        class Numbers {
+        public:
+            Numbers() : values({1, 2, 3}) {}
+            std::list<int>::const_iterator begin() const { return values.begin(); }
+            std::list<int>::const_iterator end() const { return values.end(); }
+        private:
+            std::list<int> values;
+        };
+    "};
+    let rs = quote! {
+        let numbers = unsafe { ffi::Numbers::new() }.within_box();
+        let mut iter = numbers.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next().map(|n| n.0), Some(1));
+        assert_eq!(iter.len(), 2);
+        let rest: Vec<_> = iter.map(|n| n.0).collect();
+        assert_eq!(rest, vec![2, 3]);
+        let mut abandoned = numbers.iter();
+        assert_eq!(abandoned.next().map(|n| n.0), Some(1));
+        drop(abandoned);
+    };
+    do_run_test(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("Numbers")
+            range!("Numbers", "int")
+        },
+        None,
+        None,
+        None,
+        "unsafe",
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_wide_string_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cwchar>
+        typedef const wchar_t* LPCWSTR;
+        inline uint32_t wide_length(LPCWSTR text, uint32_t extra) {
+            return static_cast<uint32_t>(wcslen(text)) + extra;
+        }
+        inline bool is_hello(const wchar_t* text) {
+            return wcscmp(text, L\"hello\") == 0;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::wide_length("hello", 1), 6);
+        assert_eq!(ffi::wide_length(String::from("hi"), 0), 2);
+        assert!(ffi::is_hello("hello"));
+        assert!(!ffi::is_hello("hell"));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["wide_length", "is_hello"],
+            &[],
+            Some(quote! {
+                wide_string_param!("wide_length", text)
+                wide_string_param!("is_hello", text)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_symbol_prefix() {
+    let hdr = indoc! {"
+        #include <string>
+        #include <memory>
+        inline std::unique_ptr<std::string> greeting() {
+            return std::make_unique<std::string>(\"hello\");
+        }
+        inline std::string shout(std::string input) {
+            return input + \"!\";
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::shout(ffi::greeting()).to_str().unwrap(), "hello!");
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["greeting", "shout"],
+            &[],
+            Some(quote! { symbol_prefix!(my_crate) }),
+        ),
+        None,
+        Some(make_string_finder(vec![
+            "my_crate_shout_autocxx_wrapper".into(),
+            "my_crate_autocxx_make_string".into(),
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_variant_return() {
+    let hdr = indoc! {"
+        #include <variant>
+        inline std::variant<int, float> get_int() { return 3; }
+        inline std::variant<int, float> get_float() { return 2.5f; }
+        inline std::variant<std::monostate, int, int> get_second_int() {
+            return std::variant<std::monostate, int, int>(std::in_place_index<2>, 7);
+        }
+    "};
+    let rs = quote! {
+        let v = ffi::get_int();
+        assert!(matches!(v.variant(), ffi::IntOrFloatVariant::Alt0(i) if i.0 == 3));
+        let v = ffi::get_float();
+        assert!(matches!(v.variant(), ffi::IntOrFloatVariant::Alt1(f) if *f == 2.5));
+        let v = ffi::get_second_int();
+        assert!(matches!(v.variant(), ffi::MaybeIntVariant::Alt2(i) if i.0 == 7));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["get_int", "get_float", "get_second_int"],
+            &[],
+            Some(quote! {
+                concrete!("std::variant<int, float>", IntOrFloat)
+                concrete!("std::variant<std::monostate, int, int>", MaybeInt)
+            }),
+        ),
+        make_cpp17_adder(),
+        // The getters which don't check the index are private.
+        Some(make_rust_code_absence_checker(vec![
+            quote! { pub fn variant_0_unchecked },
+            quote! { allow(unused_unsafe) },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_variant_return_all_unsafe() {
+    // The accessor is safe even when the C++ it calls isn't.
+    let hdr = indoc! {"
+        #include <variant>
+        inline std::variant<int, float> get_int() { return 3; }
+    "};
+    let rs = quote! {
+        let v = unsafe { ffi::get_int() };
+        assert!(matches!(v.variant(), ffi::IntOrFloatVariant::Alt0(i) if i.0 == 3));
+    };
+    do_run_test(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get_int")
+            concrete!("std::variant<int, float>", IntOrFloat)
+        },
+        make_cpp17_adder(),
+        None,
+        None,
+        "unsafe",
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_block_constructor() {
+    let hdr = indoc! {"
+        class Widget {
+        public:
+            Widget(int a) : value(a) {}
+            Widget(int a, int b) : value(a + b) {}
+            Widget(float f) : value(static_cast<int>(f)) {}
+            int get() const { return value; }
+        private:
+            int value;
+        };
+    "};
+    let rs = quote! {
+        let w = ffi::Widget::new(autocxx::c_int(3)).within_unique_ptr();
+        assert_eq!(w.get(), autocxx::c_int(3));
+        // Blocking the second constructor doesn't renumber the third.
+        let w = ffi::Widget::new2(2.5).within_unique_ptr();
+        assert_eq!(w.get(), autocxx::c_int(2));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Widget"],
+            &[],
+            Some(quote! { block_constructor!("Widget", "int, int") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_block_constructor_unmatched() {
+    // Parameter types are spelled as declared, so `unsigned int` doesn't
+    // match a `uint32_t` parameter.
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Widget {
+        public:
+            Widget(uint32_t a) : value(a) {}
+            uint32_t get() const { return value; }
+        private:
+            uint32_t value;
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(
+            &["Widget"],
+            &[],
+            Some(quote! { block_constructor!("Widget", "unsigned int") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_typedef_to_template_instantiation() {
+    let hdr = indoc! {"
+        template <typename T> class Holder {
+        public:
+            Holder(T t) : t(t) {}
+            T t;
+        };
+        using IntHolder = Holder<int>;
+        using T2 = IntHolder;
+        inline IntHolder make_holder(int i) { return IntHolder(i); }
+        inline int get_from_holder(const T2& h) { return h.t; }
+        inline int get_from_template(const Holder<int>& h) { return h.t; }
+    "};
+    let rs = quote! {
+        let h: cxx::UniquePtr<ffi::IntHolder> = ffi::make_holder(autocxx::c_int(3));
+        assert_eq!(ffi::get_from_holder(&h), autocxx::c_int(3));
+        assert_eq!(ffi::get_from_template(&h), autocxx::c_int(3));
+    };
+    run_test(
+        "",
+        hdr,
+        rs,
+        &[
+            "IntHolder",
+            "T2",
+            "make_holder",
+            "get_from_holder",
+            "get_from_template",
+        ],
+        &[],
+    );
+}
+
+#[test]
+fn test_make_shared() {
+    let hdr = indoc! {"
+        class Widget {
+        public:
+            Widget(int a) : value(a) {}
+            Widget(int a, int b) : value(a + b) {}
+            int get() const { return value; }
+        private:
+            int value;
+        };
+    "};
+    let rs = quote! {
+        let w: cxx::SharedPtr<ffi::Widget> = ffi::Widget::make_shared(autocxx::c_int(3));
+        assert_eq!(w.get(), autocxx::c_int(3));
+        let w = ffi::Widget::make_shared1(autocxx::c_int(2), autocxx::c_int(4));
+        assert_eq!(w.get(), autocxx::c_int(6));
+        // The usual constructors are still available.
+        let w = ffi::Widget::new(autocxx::c_int(5)).within_unique_ptr();
+        assert_eq!(w.get(), autocxx::c_int(5));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["Widget"], &[], Some(quote! { make_shared!("Widget") })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_pod_layout_with_tail_padding() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            int64_t a;
+            char b;
+        };
+        inline char get_b(A a) { return a.b; }
+    "};
+    let rs = quote! {
+        let a = ffi::A { a: 1, b: 2 };
+        assert_eq!(std::mem::size_of::<ffi::A>(), 16);
+        assert_eq!(ffi::get_b(a), 2);
+    };
+    run_test("", hdr, rs, &["get_b"], &["A"]);
+}
+
+#[test]
+fn test_bitflags_enum() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum Permissions : uint32_t {
+            Read = 1,
+            Write = 2,
+            Execute = 4,
+        };
+        inline bool can_write(Permissions p) { return p & Write; }
+        inline Permissions all_permissions() {
+            return static_cast<Permissions>(Read | Write | Execute);
+        }
+    "};
+    let rs = quote! {
+        let rw = ffi::Permissions::Read | ffi::Permissions::Write;
+        assert!(ffi::can_write(rw));
+        assert!(!ffi::can_write(rw & !ffi::Permissions::Write));
+        let all = ffi::all_permissions();
+        assert!(all.contains(rw | ffi::Permissions::Execute));
+        assert_eq!(all.bits(), 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Permissions", "can_write", "all_permissions"],
+            &[],
+            Some(quote! { bitflags_enum!("Permissions") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_compile_commands() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t give_int() { return 5; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::give_int(), 5);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["give_int"], &[], None),
+        make_compile_commands_checker(),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_generate_files_for_several_builds() {
+    // Generate the C++ once, then compile it twice with different flags,
+    // as a project might to optimize it even in debug builds.
+    let tdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tdir.path().join("input.h"),
+        "#pragma once\n#include <cstdint>\ninline uint32_t give_int() { return 5; }\n",
+    )
+    .unwrap();
+    let rs_path = tdir.path().join("input.rs");
+    std::fs::write(
+        &rs_path,
+        indoc! {r#"
+            use autocxx::prelude::*;
+            include_cpp! {
+                #include "input.h"
+                safety!(unsafe_ffi)
+                generate!("give_int")
+            }
+        "#},
+    )
+    .unwrap();
+    let gendir = tdir.path().join("gen");
+    let files = Builder::<TestBuilderContext>::new(&rs_path, [tdir.path()])
+        .custom_gendir(gendir.clone())
+        .generate_files()
+        .unwrap();
+    assert!(!files.cpp_files.is_empty());
+    assert!(!files.rs_files.is_empty());
+    let target = rust_info::get().target_triple.unwrap();
+    for (opt_level, lib_name) in [(0, "gen-debug"), (3, "gen-optimized")] {
+        files
+            .to_build()
+            .host(&target)
+            .target(&target)
+            .opt_level(opt_level)
+            .flag_if_supported("-std=c++14")
+            .cargo_metadata(false)
+            .out_dir(&gendir)
+            .try_compile(lib_name)
+            .unwrap();
+        // Each build leaves the generated files in place for the next.
+        assert!(files.cpp_files.iter().all(|f| f.exists()));
+    }
+}
+
+#[test]
+fn test_position_independent_code_only_when_asked() {
+    let tdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tdir.path().join("input.h"),
+        "#pragma once\n#include <cstdint>\ninline uint32_t give_int() { return 5; }\n",
+    )
+    .unwrap();
+    let rs_path = tdir.path().join("input.rs");
+    std::fs::write(
+        &rs_path,
+        indoc! {r#"
+            use autocxx::prelude::*;
+            include_cpp! {
+                #include "input.h"
+                safety!(unsafe_ffi)
+                generate!("give_int")
+            }
+        "#},
+    )
+    .unwrap();
+    // By default we leave it to cc, which knows which targets want it.
+    let files = Builder::<TestBuilderContext>::new(&rs_path, [tdir.path()])
+        .custom_gendir(tdir.path().join("gen-default"))
+        .generate_files()
+        .unwrap();
+    assert_eq!(files.position_independent_code, None);
+    for pic in [false, true] {
+        let files = Builder::<TestBuilderContext>::new(&rs_path, [tdir.path()])
+            .custom_gendir(tdir.path().join(format!("gen-{pic}")))
+            .position_independent_code(pic)
+            .generate_files()
+            .unwrap();
+        assert_eq!(files.position_independent_code, Some(pic));
+    }
+}
+
+#[test]
+fn test_generate_rs_string_matches_macro_output() {
+    // generate_rs_string is formatted with prettyplease, whereas the
+    // macro path stringifies the token stream, so check they agree once
+    // parsed.
+    let tdir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        tdir.path().join("input.h"),
+        indoc! {r#"
+            #pragma once
+            #include <cstdint>
+            #include <string>
+            /// Gives an int.
+            inline uint32_t give_int() { return 5; }
+            struct A {
+                /// Doubles it.
+                uint32_t twice(uint32_t a) const { return a * 2; }
+                std::string name;
+            };
+        "#},
+    )
+    .unwrap();
+    let rs_path = tdir.path().join("input.rs");
+    std::fs::write(
+        &rs_path,
+        indoc! {r#"
+            use autocxx::prelude::*;
+            include_cpp! {
+                #include "input.h"
+                safety!(unsafe_ffi)
+                generate!("give_int")
+                generate!("A")
+            }
+        "#},
+    )
+    .unwrap();
+    let mut parsed_file = autocxx_engine::parse_file(&rs_path, false).unwrap();
+    parsed_file
+        .resolve_all(
+            vec![tdir.path().to_path_buf()],
+            &[],
+            None,
+            &autocxx_engine::CodegenOptions::default(),
+        )
+        .unwrap();
+    let mut found = false;
+    for include_cpp in parsed_file.get_autocxxes() {
+        let formatted: syn::File = syn::parse_str(&include_cpp.generate_rs_string()).unwrap();
+        let from_macro: syn::File =
+            syn::parse_str(&autocxx_engine::generate_rs_single(include_cpp.get_rs_output()).code)
+                .unwrap();
+        assert_eq!(
+            formatted.into_token_stream().to_string(),
+            from_macro.into_token_stream().to_string()
+        );
+        found = true;
+    }
+    assert!(found);
+}
+
+#[test]
+fn test_array_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline void fill(uint32_t arr[4]) {
+            for (int i = 0; i < 4; i++) {
+                arr[i] = i * 2;
+            }
+        }
+        inline uint32_t sum(const uint32_t arr[3]) {
+            return arr[0] + arr[1] + arr[2];
+        }
+    "};
+    let rs = quote! {
+        let mut arr = [0u32; 4];
+        ffi::fill(&mut arr);
+        assert_eq!(arr, [0, 2, 4, 6]);
+        assert_eq!(ffi::sum(&[1, 2, 3]), 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["fill", "sum"],
+            &[],
+            Some(quote! {
+                array_param!("fill", arr, 4)
+                array_param!("sum", arr, 3)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_method_of() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Widget {
+            uint32_t count;
+        };
+        inline void widget_add(Widget* w, uint32_t n) {
+            w->count += n;
+        }
+        inline uint32_t widget_count(const Widget* w) {
+            return w->count;
+        }
+    "};
+    let rs = quote! {
+        let mut w = ffi::Widget { count: 1 };
+        std::pin::Pin::new(&mut w).add(3);
+        assert_eq!(w.count(), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["widget_add", "widget_count"],
+            &["Widget"],
+            Some(quote! {
+                method_of!("Widget", "widget_add", "add")
+                method_of!("Widget", "widget_count", "count")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_explicit_padding() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Padded {
+            uint8_t a;
+            uint32_t b;
+        };
+        inline Padded make_padded() {
+            Padded p;
+            p.a = 1;
+            p.b = 2;
+            return p;
+        }
+    "};
+    let rs = quote! {
+        let p = ffi::make_padded();
+        let padding: [u8; 3] = p.__bindgen_padding_0;
+        assert_eq!(padding.len(), 3);
+        assert_eq!(p.a, 1);
+        assert_eq!(p.b, 2);
+        assert_eq!(std::mem::size_of::<ffi::Padded>(), 8);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_padded"],
+            &["Padded"],
+            Some(quote! {
+                explicit_padding!()
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_unsafety_overrides() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline bool same_object(const uint32_t* a, const uint32_t* b) {
+            return a == b;
+        }
+        inline uint32_t checked_add(uint32_t a, uint32_t b) {
+            return a + b;
+        }
+    "};
+    let rs = quote! {
+        let a = 1u32;
+        let b = 1u32;
+        assert!(ffi::same_object(&a, &a));
+        assert!(!ffi::same_object(&a, &b));
+        assert_eq!(unsafe { ffi::checked_add(2, 3) }, 5);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["same_object", "checked_add"],
+            &[],
+            Some(quote! {
+                safe_fn!("same_object")
+                unsafe_fn!("checked_add")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_string_constructible() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <string>
+        class Path {
+        public:
+            Path(const std::string& p) : p(p) {}
+            uint32_t len() const { return p.size(); }
+        private:
+            std::string p;
+        };
+        class Name {
+        public:
+            Name(std::string n) : n(n) {}
+            uint32_t len() const { return n.size(); }
+        private:
+            std::string n;
+        };
+    "};
+    let rs = quote! {
+        let p: std::pin::Pin<Box<ffi::Path>> = "/tmp".into();
+        assert_eq!(p.len(), 4);
+        let n = std::pin::Pin::<Box<ffi::Name>>::from("Ferris");
+        assert_eq!(n.len(), 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Path", "Name"],
+            &[],
+            Some(quote! {
+                string_constructible!("Path")
+                string_constructible!("Name")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_receiver_mutability_overrides() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <atomic>
+        class Cache {
+        public:
+            Cache() : lookups(0), total(0) {}
+            uint32_t lookup() const { lookups++; return total; }
+            void add(uint32_t n) { total += n; }
+            uint32_t get_lookups() const { return lookups; }
+        private:
+            mutable uint32_t lookups;
+            std::atomic<uint32_t> total;
+        };
+    "};
+    let rs = quote! {
+        let mut cache = ffi::Cache::new().within_box();
+        let shared: &ffi::Cache = &cache;
+        shared.add(3);
+        shared.add(4);
+        assert_eq!(cache.as_mut().lookup(), 7);
+        assert_eq!(cache.get_lookups(), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Cache"],
+            &[],
+            Some(quote! {
+                mutable_receiver!("Cache::lookup")
+                const_receiver!("Cache::add")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_missing_precompiled_header() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t get_input() { return 3; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::get_input(), 3);
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["get_input"], &[], None),
+        Some(Box::new(MissingPrecompiledHeader)),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_mut_suffix_overloads() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Counter {
+        public:
+            Counter() : value(1) {}
+            const uint32_t& get() const { return value; }
+            uint32_t& get() { return value; }
+            void reset() { value = 0; }
+        private:
+            uint32_t value;
+        };
+    "};
+    let rs = quote! {
+        let mut counter = ffi::Counter::new().within_box();
+        assert_eq!(*counter.get(), 1);
+        *counter.as_mut().get_mut() = 5;
+        assert_eq!(*counter.get(), 5);
+        counter.as_mut().reset();
+        assert_eq!(*counter.get(), 0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["Counter"], &[], Some(quote! { mut_suffix_overloads!() })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_reference_to_pointer_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Widget {
+            uint32_t id;
+        };
+        inline bool make_widget(uint32_t id, Widget*& out) {
+            static Widget widget;
+            widget.id = id;
+            out = &widget;
+            return true;
+        }
+        inline void advance(const uint32_t*& cursor) {
+            cursor++;
+        }
+    "};
+    let rs = quote! {
+        let mut widget: *mut ffi::Widget = ::core::ptr::null_mut();
+        assert!(unsafe { ffi::make_widget(7, &mut widget) });
+        assert_eq!(unsafe { (*widget).id }, 7);
+        let values = [1u32, 2u32];
+        let mut cursor = values.as_ptr();
+        unsafe { ffi::advance(&mut cursor) };
+        assert_eq!(unsafe { *cursor }, 2);
+    };
+    run_test("", hdr, rs, &["make_widget", "advance"], &["Widget"]);
+}
+
+#[test]
+fn test_box_large_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Inner {
+            uint64_t values[32];
+        };
+        struct Big {
+            Inner inner;
+            uint32_t tag;
+        };
+        struct Small {
+            uint32_t tag;
+        };
+        inline Big make_big() {
+            Big big;
+            big.inner.values[31] = 4;
+            big.tag = 5;
+            return big;
+        }
+        inline Small make_small() {
+            Small small;
+            small.tag = 6;
+            return small;
+        }
+    "};
+    let rs = quote! {
+        let big: cxx::UniquePtr<ffi::Big> = ffi::make_big();
+        assert_eq!(big.inner.values[31], 4);
+        assert_eq!(big.tag, 5);
+        let small: ffi::Small = ffi::make_small();
+        assert_eq!(small.tag, 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_big", "make_small"],
+            &["Big", "Inner", "Small"],
+            Some(quote! { box_large_pod!(256) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_clang_arg_defines_reach_compiler() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Widget {
+        #ifdef WIDGET_TESTING
+        public:
+        #endif
+            uint32_t secret() const { return SECRET_VALUE; }
+        public:
+            Widget() {}
+        };
+    "};
+    let rs = quote! {
+        let widget = ffi::Widget::new().within_box();
+        assert_eq!(widget.secret(), 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["Widget"], &[], None),
+        Some(Box::new(AutocxxOnlyClangArgs(vec![
+            "-DWIDGET_TESTING".to_string(),
+            "-D".to_string(),
+            "SECRET_VALUE=7".to_string(),
+        ]))),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_span_params() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <span>
+        inline uint32_t sum(std::span<const uint32_t> values) {
+            uint32_t total = 0;
+            for (auto value : values) {
+                total += value;
+            }
+            return total;
+        }
+        inline void fill(std::span<uint32_t> out, uint32_t value) {
+            for (auto& element : out) {
+                element = value;
+            }
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::sum(&[1, 2, 3]), 6);
+        let mut values = [0u32; 4];
+        ffi::fill(&mut values, 7);
+        assert_eq!(values, [7; 4]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["sum", "fill"],
+            &[],
+            Some(quote! { mut_span_param!("fill", out) }),
+        ),
+        make_clang_arg_adder(&["-std=c++20"]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_span_param_needs_mut_span_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <span>
+        inline void fill(std::span<uint32_t> out, uint32_t value) {
+            for (auto& element : out) {
+                element = value;
+            }
+        }
+    "};
+    let rs = quote! {
+        ffi::fill(&[0u32; 4], 7);
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["fill"], &[], None),
+        make_clang_arg_adder(&["-std=c++20"]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_span_param_fixed_extent() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <span>
+        inline uint32_t sum(std::span<const uint32_t, 4> values) {
+            return values[0] + values[1] + values[2] + values[3];
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::sum(&[1, 2, 3, 4]), 10);
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["sum"], &[], None),
+        make_clang_arg_adder(&["-std=c++20"]),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_method_rename() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Buffer {
+        public:
+            Buffer() : size(3) {}
+            uint32_t GetSize() const { return size; }
+            bool IsEmpty() const { return size == 0; }
+            void Clear() { size = 0; }
+            uint32_t Size() const { return size; }
+        private:
+            uint32_t size;
+        };
+    "};
+    let rs = quote! {
+        let mut buffer = ffi::Buffer::new().within_box();
+        assert_eq!(buffer.size(), 3);
+        assert_eq!(buffer.size1(), 3);
+        assert!(!buffer.is_empty());
+        buffer.as_mut().clear();
+        assert!(buffer.is_empty());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Buffer"],
+            &[],
+            Some(quote! {
+                method_rename!("^Get(.+)$", "$1")
+                snake_case_methods!()
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_nested_enums_in_two_classes() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Widget {
+        public:
+            enum State { Idle, Busy };
+            Widget() : state(Idle) {}
+            void set_state(State s) { state = s; }
+            State get_state() const { return state; }
+        private:
+            State state;
+        };
+        class Gadget {
+        public:
+            enum class State : uint8_t { Off, On };
+        };
+    "};
+    let rs = quote! {
+        let mut w = ffi::Widget::new().within_unique_ptr();
+        w.pin_mut().set_state(ffi::Widget_State::Busy);
+        assert!(w.get_state() == ffi::Widget_State::Busy);
+        assert!(ffi::Gadget_State::On != ffi::Gadget_State::Off);
+    };
+    // Nested types may be named with either C++ or Rust scoping.
+    run_test("", hdr, rs, &["Widget", "Gadget::State"], &[]);
+}
+
+#[test]
+fn test_throwing_void_fns() {
+    let hdr = indoc! {"
+        #include <stdexcept>
+        inline void may_fail(bool fail) {
+            if (fail) {
+                throw std::runtime_error(\"oops\");
+            }
+        }
+        class Valve {
+        public:
+            Valve() {}
+            void open(int turns) {
+                if (turns < 0) {
+                    throw std::invalid_argument(\"negative turns\");
+                }
+            }
+        };
+    "};
+    let rs = quote! {
+        assert!(ffi::may_fail(false).is_ok());
+        assert_eq!(ffi::may_fail(true).unwrap_err().what(), "oops");
+        let mut v = ffi::Valve::new().within_unique_ptr();
+        assert!(v.pin_mut().open(1).is_ok());
+        assert!(v.pin_mut().open(-1).is_err());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["may_fail", "Valve"],
+            &[],
+            Some(quote! {
+                throws!("may_fail")
+                throws!("open")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_vec_params() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <vector>
+        struct Point {
+            int32_t x;
+            int32_t y;
+        };
+        inline uint32_t total(std::vector<uint32_t> values) {
+            uint32_t total = 0;
+            for (auto value : values) {
+                total += value;
+            }
+            return total;
+        }
+        inline int32_t sum_x(std::vector<Point>&& points) {
+            std::vector<Point> mine(std::move(points));
+            int32_t total = 0;
+            for (auto& point : mine) {
+                total += point.x;
+            }
+            return total;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::total(vec![1, 2, 3]), 6);
+        let points = vec![ffi::Point { x: 1, y: 2 }, ffi::Point { x: 3, y: 4 }];
+        assert_eq!(ffi::sum_x(points), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["total", "sum_x"],
+            &["Point"],
+            Some(quote! {
+                vec_param!("total", values)
+                vec_param!("sum_x", points)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_assertion_handler() {
+    let hdr = indoc! {"
+        typedef void (*AssertHandler)(const char*, const char*, int);
+        inline AssertHandler& assert_handler() {
+            static AssertHandler handler = nullptr;
+            return handler;
+        }
+        inline void set_assert_handler(AssertHandler handler) {
+            assert_handler() = handler;
+        }
+        inline int checked_div(int a, int b) {
+            if (b == 0 && assert_handler()) {
+                assert_handler()(\"b != 0\", __FILE__, __LINE__);
+            }
+            return a / b;
+        }
+    "};
+    let cxx = indoc! {"
+        [[noreturn]] void my_assert_failed(const char* expr, const char* file, int line);
+        static bool installed = (set_assert_handler(my_assert_failed), true);
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::checked_div(c_int(6), c_int(3)), c_int(2));
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["checked_div"],
+            &[],
+            Some(quote! { assertion_handler!(my_assert_failed) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_flexible_string_params() {
+    let hdr = indoc! {"
+        #include <string>
+        #include <cstdint>
+        inline uint32_t measure(const std::string& a, const std::string& b) {
+            return a.size() + b.size();
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::measure("hello", String::from("world!")), 11);
+        let binary = ffi::make_string("a\0b");
+        assert_eq!(ffi::measure(&binary, binary.as_ref().unwrap()), 6);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["measure"],
+            &[],
+            Some(quote! { flexible_string_params!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_instantiate_non_type_params() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        template<typename T, int ROWS, int COLS>
+        class Matrix {
+        private:
+            T data[ROWS * COLS];
+        };
+        inline Matrix<double, 3, 3> identity() {
+            return Matrix<double, 3, 3>();
+        }
+        inline void consume(const Matrix<double, 3, 3>&) {}
+    "};
+    let rs = quote! {
+        let m: cxx::UniquePtr<ffi::Matrix3d> = ffi::identity();
+        ffi::consume(&m);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("identity")
+            generate!("consume")
+            instantiate!("Matrix", ["double", 3, 3], Matrix3d)
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_opaque_handles() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Counter;
+        struct Registry;
+        Counter* counter_new(uint32_t start);
+        Counter* counter_default();
+        void counter_increment(Counter* counter);
+        uint32_t counter_get(const Counter* counter);
+        void counter_free(Counter* counter);
+        uint32_t live_counters();
+        Registry* registry_global();
+        uint32_t registry_size(Registry* registry);
+    "};
+    let cxx = indoc! {"
+        struct Counter {
+            uint32_t value;
+        };
+        struct Registry {
+            uint32_t size;
+        };
+        static uint32_t live = 0;
+        Counter* counter_new(uint32_t start) {
+            live++;
+            return new Counter { start };
+        }
+        Counter* counter_default() {
+            static Counter counter { 7 };
+            return &counter;
+        }
+        void counter_increment(Counter* counter) {
+            counter->value++;
+        }
+        uint32_t counter_get(const Counter* counter) {
+            return counter->value;
+        }
+        void counter_free(Counter* counter) {
+            live--;
+            delete counter;
+        }
+        uint32_t live_counters() {
+            return live;
+        }
+        Registry* registry_global() {
+            static Registry registry { 3 };
+            return &registry;
+        }
+        uint32_t registry_size(Registry* registry) {
+            return registry->size;
+        }
+    "};
+    let rs = quote! {
+        let counter = ffi::counter_new(2).unwrap();
+        ffi::counter_increment(&counter);
+        assert_eq!(ffi::counter_get(&counter), 3);
+        assert_eq!(ffi::live_counters(), 1);
+        drop(counter);
+        assert_eq!(ffi::live_counters(), 0);
+        let default_counter: *mut ffi::Counter = ffi::counter_default();
+        assert!(!default_counter.is_null());
+        let registry = ffi::registry_global().unwrap();
+        let copy = registry;
+        assert_eq!(unsafe { ffi::registry_size(&copy) }, 3);
+        assert_eq!(unsafe { ffi::registry_size(&registry) }, 3);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &[
+                "counter_new",
+                "counter_default",
+                "counter_increment",
+                "counter_get",
+                "live_counters",
+                "registry_global",
+                "registry_size",
+            ],
+            &[],
+            Some(quote! {
+                opaque_handle!("Counter", CounterHandle, "counter_free", "counter_new")
+                opaque_handle!("Registry", RegistryHandle)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_restrict_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline void add_into(uint32_t* __restrict out, const uint32_t* __restrict a, const uint32_t* __restrict b) {
+            *out = *a + *b;
+        }
+        inline void saxpy(uint32_t n, float a, const float* __restrict x, float* __restrict y) {
+            for (uint32_t i = 0; i < n; i++) {
+                y[i] += a * x[i];
+            }
+        }
+    "};
+    let rs = quote! {
+        let mut out = 0u32;
+        unsafe { ffi::add_into(std::slice::from_mut(&mut out), &[2], &[3]) };
+        assert_eq!(out, 5);
+        let x = [1.0f32, 2.0, 3.0];
+        let mut y = [1.0f32; 3];
+        unsafe { ffi::saxpy(3, 2.0, &x, &mut y) };
+        assert_eq!(y, [3.0, 5.0, 7.0]);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["add_into", "saxpy"],
+            &[],
+            Some(quote! {
+                restrict_param!("add_into", out)
+                restrict_param!("add_into", a)
+                restrict_param!("add_into", b)
+                restrict_param!("saxpy", x)
+                restrict_param!("saxpy", y)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_strict_allowlist() {
+    let hdr = indoc! {"
+    namespace A {
+        inline void foo() {}
+    }
+    "};
+    let rs = quote! {
+        ffi::A::foo();
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_ns!("A")
+            generate_ns!("Mispelled")
+            safety!(unsafe_ffi)
+        },
+        Some(Box::new(SetStrictAllowlist)),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_reference_wrapper() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <functional>
+        inline std::reference_wrapper<const uint32_t> wrap(const uint32_t& value) {
+            return std::cref(value);
+        }
+        inline void bump(std::reference_wrapper<uint32_t> value) {
+            value.get() += 1;
+        }
+    "};
+    let rs = quote! {
+        let a = 3u32;
+        assert_eq!(*ffi::wrap(&a), 3);
+        let mut b = 4u32;
+        ffi::bump(std::pin::Pin::new(&mut b));
+        assert_eq!(b, 5);
+    };
+    run_test("", hdr, rs, &["wrap", "bump"], &[]);
+}
+
+#[test]
+fn test_opaque_field_of_pod() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Inner {
+            uint32_t secret;
+        };
+        struct Outer {
+            Inner inner;
+            uint32_t visible;
+        };
+    "};
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        quote! {},
+        directives_from_lists(&[], &["Outer"], Some(quote! { opaque!("Inner") })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_callable_member() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <functional>
+        struct Counter {
+            std::function<uint32_t(uint32_t)> scale = [](uint32_t n) { return n * 2; };
+            std::function<uint32_t()> next = [count = 0u]() mutable { return ++count; };
+        };
+    "};
+    let rs = quote! {
+        let mut counter = ffi::Counter::new().within_box();
+        assert_eq!(counter.as_mut().scale(21), 42);
+        assert_eq!(counter.as_mut().next(), 1);
+        assert_eq!(counter.as_mut().next(), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Counter"],
+            &[],
+            Some(quote! {
+                callable_member!("Counter", scale, fn(n: u32) -> u32)
+                callable_member!("Counter", next, fn() -> u32)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_thread_local_var() {
+    let cxx = indoc! {"
+        thread_local uint32_t counter = 5;
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        extern thread_local uint32_t counter;
+        inline uint32_t get_counter() { return counter; }
+    "};
+    let rs = quote! {
+        let c = ffi::counter();
+        assert_eq!(unsafe { c.get() }, 5);
+        unsafe { c.set(6) };
+        assert_eq!(ffi::get_counter(), 6);
+        std::thread::spawn(|| {
+            assert_eq!(unsafe { ffi::counter().get() }, 5);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(unsafe { ffi::counter().get() }, 6);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["get_counter"],
+            &[],
+            Some(quote! {
+                thread_local_var!("counter")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_return_borrows() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Pair {
+            uint32_t a;
+            uint32_t b;
+        };
+        class Chooser {
+        public:
+            bool pick_b = true;
+            const uint32_t& choose(const Pair& pair) const { return pick_b ? pair.b : pair.a; }
+        };
+        inline const uint32_t& larger(const uint32_t& a, const uint32_t& b) { return a > b ? a : b; }
+    "};
+    let rs = quote! {
+        let chooser = ffi::Chooser::new().within_box();
+        let pair = ffi::Pair { a: 1, b: 2 };
+        let chosen = {
+            let chooser = chooser;
+            chooser.choose(&pair)
+        };
+        assert_eq!(*chosen, 2);
+        let (x, y) = (3u32, 4u32);
+        assert_eq!(*ffi::larger(&x, &y), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Chooser", "larger"],
+            &["Pair"],
+            Some(quote! {
+                return_borrows!("choose", pair)
+                return_borrows!("larger", a, b)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_move_assignment() {
+    let hdr = indoc! {"
+        #include <string>
+        class Buffer {
+        public:
+            Buffer(const std::string& contents) : contents(contents) {}
+            Buffer(Buffer&&) = default;
+            Buffer& operator=(Buffer&& other) {
+                contents = std::move(other.contents);
+                return *this;
+            }
+            const std::string& get() const { return contents; }
+        private:
+            std::string contents;
+        };
+        class Fixed {
+        public:
+            Fixed() {}
+            Fixed& operator=(Fixed&&) = delete;
+        };
+    "};
+    let rs = quote! {
+        let mut target = ffi::Buffer::new("old").within_unique_ptr();
+        let source = ffi::Buffer::new("new").within_unique_ptr();
+        target.pin_mut().move_assign_from(source);
+        assert_eq!(target.get().to_str().unwrap(), "new");
+    };
+    run_test("", hdr, rs, &["Buffer", "Fixed"], &[]);
+}
+
+#[test]
+fn test_exception_types() {
+    let hdr = indoc! {"
+        #include <stdexcept>
+        namespace valve {
+            struct Stuck : public std::runtime_error {
+                Stuck() : std::runtime_error(\"stuck\") {}
+            };
+            struct Broken : public std::runtime_error {
+                Broken() : std::runtime_error(\"broken\") {}
+            };
+        }
+        inline void operate(int how) {
+            switch (how) {
+                case 1: throw valve::Stuck();
+                case 2: throw valve::Broken();
+                case 3: throw std::logic_error(\"other\");
+            }
+        }
+    "};
+    let rs = quote! {
+        assert!(ffi::operate(0).is_ok());
+        match ffi::operate(1).map_err(ffi::CppException::from) {
+            Err(ffi::CppException::Stuck(what)) => assert_eq!(what, "stuck"),
+            _ => panic!("Expected Stuck"),
+        }
+        match ffi::operate(2).map_err(ffi::CppException::from) {
+            Err(ffi::CppException::Broken(what)) => assert_eq!(what, "broken"),
+            _ => panic!("Expected Broken"),
+        }
+        match ffi::operate(3).map_err(ffi::CppException::from) {
+            Err(e @ ffi::CppException::Other(_)) => assert_eq!(e.to_string(), "other"),
+            _ => panic!("Expected Other"),
+        }
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["operate"],
+            &[],
+            Some(quote! {
+                throws!("operate")
+                exception_type!("valve::Stuck", Stuck)
+                exception_type!("valve::Broken", Broken)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_exception_type_not_std_exception() {
+    let hdr = indoc! {"
+        struct Oops {};
+        inline void operate() {
+            throw Oops();
+        }
+    "};
+    let rs = quote! {
+        assert!(ffi::operate().is_err());
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["operate"],
+            &[],
+            Some(quote! {
+                throws!("operate")
+                exception_type!("Oops", Oops)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_extra_cpp_includes() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    inline uint32_t get_value() { return 4; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::get_value(), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get_value")
+            extra_cpp_includes!(["cstdint"])
+        },
+        None,
+        Some(Box::new(CppMatcher::new(&["#include \"cstdint\""], &[]))),
+        None,
+    );
+}
+
+#[test]
+fn test_manual_bridge_entries() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    #include <memory>
+    struct A {
+        uint32_t a;
+    };
+    inline std::unique_ptr<A> give_a() {
+        auto a = std::make_unique<A>();
+        a->a = 3;
+        return a;
+    }
+    inline uint32_t take_a(const A& a) { return a.a; }
+    "};
+    let rs = quote! {
+        let a = ffi::give_a();
+        assert_eq!(ffi::take_a(&a), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("take_a")
+            manual!(
+                fn give_a() -> UniquePtr<A>;
+            )
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_trivially_copyable_pod() {
+    let hdr = indoc! {"
+    #include <cstdint>
+    struct Point {
+        int32_t x;
+        int32_t y;
+    };
+    inline int32_t sum(Point p) { return p.x + p.y; }
+    "};
+    let rs = quote! {
+        let p = ffi::Point { x: 1, y: 2 };
+        let q = p;
+        assert_eq!(ffi::sum(p), 3);
+        assert_eq!(ffi::sum(q), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["sum"], &["Point"], None),
+        None,
+        Some(Box::new(CppMatcher::new(
+            &["static_assert(std::is_trivially_copyable<Point>::value"],
+            &[],
+        ))),
+        None,
+    );
+}
+
+#[test]
+fn test_exception_handling_panic() {
+    let hdr = indoc! {"
+        #include <stdexcept>
+        #include <cstdint>
+        inline uint32_t checked_half(uint32_t a) {
+            if (a % 2) {
+                throw std::invalid_argument(\"odd number\");
+            }
+            return a / 2;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::checked_half(4), 2);
+        let err = std::panic::catch_unwind(|| ffi::checked_half(3)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "C++ exception from checked_half: odd number"
+        );
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["checked_half"],
+            &[],
+            Some(quote! { exception_handling!(panic) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_iterator_lookup() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <map>
+        #include <set>
+        struct Entry {
+            uint32_t value;
+        };
+        class Registry {
+        public:
+            Registry() {
+                entries[1] = Entry { 10 };
+            }
+            std::map<uint32_t, Entry>::const_iterator find(uint32_t key) const {
+                return entries.find(key);
+            }
+            std::map<uint32_t, Entry>::const_iterator end() const {
+                return entries.end();
+            }
+        private:
+            std::map<uint32_t, Entry> entries;
+        };
+        class Ids {
+        public:
+            Ids() { ids.insert(7); }
+            std::set<uint32_t>::const_iterator find(const uint32_t& id) const {
+                return ids.find(id);
+            }
+            std::set<uint32_t>::const_iterator end() const {
+                return ids.end();
+            }
+        private:
+            std::set<uint32_t> ids;
+        };
+    "};
+    let rs = quote! {
+        let registry = ffi::Registry::new().within_box();
+        assert_eq!(registry.find(&1).unwrap().value, 10);
+        assert!(registry.find(&2).is_none());
+        let ids = ffi::Ids::new().within_box();
+        assert_eq!(ids.find(&7), Some(&7));
+        assert_eq!(ids.find(&8), None);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Registry", "Ids"],
+            &["Entry"],
+            Some(quote! {
+                iterator_lookup!("Registry", find -> second, fn(key: &u32) -> &Entry)
+                iterator_lookup!("Ids", find, fn(id: &u32) -> &u32)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_cbindgen_type() {
+    // As cbindgen would generate for `Point` below.
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Shape;
+        struct Point {
+            double x;
+            double y;
+            const Shape* owner;
+        };
+        struct Shape {
+            Point origin;
+            uint32_t sides;
+        };
+        inline double sum(Point p) { return p.x + p.y; }
+    "};
+    let rs = quote! {
+        let shape = ffi::Shape {
+            origin: crate::Point { x: 1.0, y: 2.0, owner: std::ptr::null() },
+            sides: 4,
+        };
+        assert_eq!(ffi::sum(shape.origin), 3.0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["sum"],
+            &["Shape"],
+            Some(quote! { cbindgen_type!("Point", crate::Point) }),
+        ),
+        None,
+        None,
+        Some(quote! {
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct Point {
+                pub x: f64,
+                pub y: f64,
+                pub owner: *const ffi::Shape,
+            }
+        }),
+    );
+}
+
+#[test]
+fn test_ptr_array_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <cstring>
+        inline size_t total_len(int argc, const char* const* argv) {
+            size_t total = 0;
+            for (int i = 0; i < argc; i++) {
+                total += strlen(argv[i]);
+            }
+            return argv[argc] == nullptr ? total : 0;
+        }
+        inline uint32_t sum(const uint32_t* const* values, size_t count) {
+            uint32_t total = 0;
+            for (size_t i = 0; i < count; i++) {
+                total += *values[i];
+            }
+            return total;
+        }
+    "};
+    let rs = quote! {
+        let args = [
+            std::ffi::CString::new("ab").unwrap(),
+            std::ffi::CString::new("cde").unwrap(),
+        ];
+        let arg_refs: Vec<&std::ffi::CStr> = args.iter().map(|a| a.as_c_str()).collect();
+        assert_eq!(unsafe { ffi::total_len(autocxx::c_int(2), &arg_refs) }, 5);
+        let (a, b) = (3u32, 4u32);
+        assert_eq!(unsafe { ffi::sum(&[&a, &b], 2) }, 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["total_len", "sum"],
+            &[],
+            Some(quote! {
+                ptr_array_param!("total_len", argv)
+                ptr_array_param!("sum", values)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_allocator() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace arena {
+            struct Allocator {
+                uint32_t id = 0;
+            };
+            inline Allocator current() {
+                Allocator alloc;
+                alloc.id = 42;
+                return alloc;
+            }
+        }
+        class Buffer {
+        public:
+            Buffer(uint32_t size, const arena::Allocator& alloc)
+                : size(size), allocator_id(alloc.id) {}
+            uint32_t get_allocator_id() const { return allocator_id; }
+        private:
+            uint32_t size;
+            uint32_t allocator_id;
+        };
+        inline uint32_t allocator_id(uint32_t, arena::Allocator alloc) {
+            return alloc.id;
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::allocator_id(3), 42);
+        let buffer = ffi::Buffer::new(5).within_unique_ptr();
+        assert_eq!(buffer.get_allocator_id(), 42);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Buffer", "allocator_id"],
+            &[],
+            Some(quote! {
+                allocator!("arena::Allocator", "arena::current()")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_constexpr_fn() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace limits {
+            constexpr uint32_t max_size() { return 16 * 4; }
+        }
+        constexpr bool is_enabled() { return true; }
+    "};
+    let rs = quote! {
+        const LEN: usize = ffi::limits::max_size as usize;
+        let buffer = [0u8; LEN];
+        assert_eq!(buffer.len(), 64);
+        assert!(ffi::is_enabled);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &[],
+            &[],
+            Some(quote! {
+                constexpr_fn!("limits::max_size")
+                constexpr_fn!("is_enabled")
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_rust_mod_path() {
+    let hdr = indoc! {"
+    #include <cstdint>
+
+    struct RustType;
+    namespace geometry {
+        struct Point {
+            uint32_t x;
+            uint32_t y;
+        };
+        inline uint32_t manhattan(const Point& p) {
+            return p.x + p.y;
+        }
+    }
+    inline uint32_t take_rust_reference(const RustType&) {
+        return 4;
+    }
+    "};
+    let rs = quote! {
+        let p = bindings::cpp::geometry::Point { x: 3, y: 4 };
+        assert_eq!(bindings::cpp::geometry::manhattan(&p), 7);
+        let foo = RustType(3);
+        assert_eq!(bindings::cpp::take_rust_reference(&foo), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["geometry::manhattan", "take_rust_reference"],
+            &["geometry::Point"],
+            Some(quote! {
+                rust_mod_path!(crate::bindings::cpp)
+            }),
+        ),
+        None,
+        None,
+        Some(quote! {
+            #[autocxx::extern_rust::extern_rust_type]
+            pub struct RustType(i32);
+        }),
+    );
+}
+
+#[test]
+fn test_nonnull_param() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Counter {
+            uint32_t count;
+        };
+        [[gnu::nonnull]] inline void increment(Counter* counter) {
+            counter->count++;
+        }
+        [[gnu::nonnull]] inline uint32_t read(const Counter* counter) {
+            return counter->count;
+        }
+    "};
+    let rs = quote! {
+        let mut counter = ffi::Counter { count: 1 };
+        ffi::increment(std::pin::Pin::new(&mut counter));
+        assert_eq!(ffi::read(&counter), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["increment", "read"],
+            &["Counter"],
+            Some(quote! {
+                nonnull_param!("increment", counter)
+                nonnull_param!("read", counter)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_call_operator() {
+    let hdr = indoc! {"
+        class Adder {
+        public:
+            Adder(int base) : base_(base) {}
+            int operator()(int a) const { return base_ + a; }
+            int operator()(int a, int b) const { return base_ + a + b; }
+        private:
+            int base_;
+        };
+    "};
+    let rs = quote! {
+        let adder = ffi::Adder::new(autocxx::c_int(10)).within_unique_ptr();
+        assert_eq!(adder.call(autocxx::c_int(1)), autocxx::c_int(11));
+        assert_eq!(adder.call1(autocxx::c_int(1), autocxx::c_int(2)), autocxx::c_int(13));
+    };
+    run_test("", hdr, rs, &["Adder"], &[]);
+}
+
+#[test]
+fn test_max_template_depth() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        #include <memory>
+        #include <vector>
+        inline std::unique_ptr<std::vector<uint32_t>> make_counts() {
+            return std::make_unique<std::vector<uint32_t>>(3, 7);
+        }
+        inline uint32_t first_count(const std::unique_ptr<std::vector<uint32_t>>& counts) {
+            return (*counts)[0];
+        }
+    "};
+    let rs = quote! {
+        let counts = ffi::make_counts();
+        assert_eq!(ffi::first_count(&counts), 7);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_counts", "first_count"],
+            &[],
+            Some(quote! { max_template_depth!(1) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_complex() {
+    let hdr = indoc! {"
+        #include <complex>
+        inline std::complex<double> conjugate(std::complex<double> z) { return std::conj(z); }
+        inline float magnitude(const std::complex<float>& z) { return std::abs(z); }
+    "};
+    let rs = quote! {
+        let z = ffi::conjugate(autocxx::Complex::new(1.0, 2.0));
+        assert_eq!(z, autocxx::Complex::new(1.0, -2.0));
+        assert_eq!(ffi::magnitude(&autocxx::Complex::new(3.0, 4.0)), 5.0);
+    };
+    run_test("", hdr, rs, &["conjugate", "magnitude"], &[]);
+}
+
+#[test]
+fn test_suppress_ignored_items() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Widget {
+            uint32_t size;
+            uint32_t get_size() const { return size; }
+            void scale(long double factor) { size = static_cast<uint32_t>(size * factor); }
+        };
+    "};
+    let rs = quote! {
+        let w = ffi::Widget { size: 3 };
+        assert_eq!(w.get_size(), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&[], &["Widget"], Some(quote! { suppress_ignored_items!() })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_inherited_method() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        template <typename Derived> class Countable {
+        public:
+            uint32_t count() const { return static_cast<const Derived*>(this)->items * 2; }
+        };
+        template <typename T> class Accumulator {
+        public:
+            void add(T n) { total += n; }
+            T get_total() const { return total; }
+        private:
+            T total = 0;
+        };
+        class Basket : public Countable<Basket>, public Accumulator<uint32_t> {
+        public:
+            Basket() : items(3) {}
+            uint32_t items;
+        };
+    "};
+    let rs = quote! {
+        let mut basket = ffi::Basket::new().within_unique_ptr();
+        assert_eq!(basket.count(), 6);
+        basket.pin_mut().add(4);
+        basket.pin_mut().add(5);
+        assert_eq!(basket.get_total(), 9);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Basket"],
+            &[],
+            Some(quote! {
+                inherited_method!("Basket", count, fn() -> u32)
+                inherited_method!("Basket", mut add, fn(n: u32))
+                inherited_method!("Basket", get_total, fn() -> u32)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_reexport() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace storage {
+        class Buffer {
+        public:
+            Buffer() : size(3) {}
+            uint32_t GetSize() const { return size; }
+        private:
+            uint32_t size;
+        };
+        inline uint32_t buffer_limit() { return 16; }
+        }
+    "};
+    let rs = quote! {
+        use ffi::exports::*;
+        let buffer = ByteBuffer::new().within_box();
+        assert_eq!(buffer.size(), 3);
+        assert_eq!(max_buffer_size(), 16);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["storage::Buffer", "storage::buffer_limit"],
+            &[],
+            Some(quote! {
+                method_rename!("^Get(.+)$", "$1")
+                snake_case_methods!()
+                reexport!("storage::Buffer", ByteBuffer)
+                reexport!("storage::buffer_limit", max_buffer_size)
+            }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_enum_aliases() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        enum class Color : uint32_t { Red = 1, Green = 2, Primary = 1 };
+        inline bool is_red(Color c) { return c == Color::Red; }
+        enum Access : uint32_t { ReadOnly = 1, Default = 1 };
+        inline uint32_t access_bits(Access a) { return a; }
+    "};
+    let rs = quote! {
+        assert!(ffi::is_red(ffi::Color::Primary));
+        assert!(ffi::Color::Primary == ffi::Color::Red);
+        assert_eq!(ffi::access_bits(ffi::Access::Default), 1);
+        assert_eq!(ffi::Access::Default.bits(), 1);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Color", "is_red", "Access", "access_bits"],
+            &[],
+            Some(quote! { bitflags_enum!("Access") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_array_reference_params() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline void fill(uint32_t (&arr)[4], uint32_t value) {
+            for (auto& elem : arr) { elem = value; }
+        }
+        inline uint32_t sum(const uint32_t (&arr)[4]) {
+            uint32_t total = 0;
+            for (auto elem : arr) { total += elem; }
+            return total;
+        }
+        inline double trace(const double (&matrix)[2][2]) {
+            return matrix[0][0] + matrix[1][1];
+        }
+    "};
+    let rs = quote! {
+        let mut values = [0u32; 4];
+        ffi::fill(&mut values, 3);
+        assert_eq!(values, [3, 3, 3, 3]);
+        assert_eq!(ffi::sum(&values), 12);
+        assert_eq!(ffi::trace(&[[1.0, 2.0], [3.0, 4.0]]), 5.0);
+    };
+    run_test("", hdr, rs, &["fill", "sum", "trace"], &[]);
+}
+
+#[test]
+fn test_raii_guard() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        class Resource {
+        public:
+            Resource() : holders(0) {}
+            uint32_t holders;
+        };
+        class Holder {
+        public:
+            Holder(Resource& resource) : resource(resource) { resource.holders++; }
+            ~Holder() { resource.holders--; }
+        private:
+            Resource& resource;
+        };
+    "};
+    let rs = quote! {
+        let mut resource = ffi::Resource::new().within_box();
+        {
+            let _guard = ffi::Holder::new(resource.as_mut()).within_guard();
+            assert_eq!(resource.holders, 1);
+        }
+        assert_eq!(resource.holders, 0);
+        let guard = ffi::Holder::new(resource.as_mut()).within_guard();
+        assert_eq!(resource.holders, 1);
+        guard.release();
+        assert_eq!(resource.holders, 0);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Holder"],
+            &["Resource"],
+            Some(quote! { raii_guard!("Holder") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_keyword_names_raw() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t move(uint32_t a) { return a + 1; }
+        struct Cursor {
+            uint32_t pos;
+            uint32_t type() const { return pos; }
+        };
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::r#move(1), 2);
+        let c = ffi::Cursor { pos: 3 };
+        assert_eq!(c.r#type(), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["move_"],
+            &["Cursor"],
+            Some(quote! { keyword_names!(raw) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_keyword_names_prefix() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t move(uint32_t a) { return a + 1; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::cpp_move(1), 2);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["move_"],
+            &[],
+            Some(quote! { keyword_names!(prefix = "cpp_") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_destructure_return() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct MinMax {
+            uint32_t min;
+            uint32_t max;
+        };
+        inline MinMax min_max(uint32_t a, uint32_t b) {
+            return a < b ? MinMax { a, b } : MinMax { b, a };
+        }
+        inline uint32_t range(MinMax m) { return m.max - m.min; }
+    "};
+    let rs = quote! {
+        let (min, max) = ffi::min_max(7, 3);
+        assert_eq!(min, 3);
+        assert_eq!(max, 7);
+        assert_eq!(ffi::range(ffi::MinMax { min, max }), 4);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["min_max", "range"],
+            &["MinMax"],
+            Some(quote! { destructure_return!("min_max", MinMax) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_instantiate_defaulted_template_args() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        template<typename T> struct Alloc {};
+        template<typename T, typename A = Alloc<T>> struct Container {
+            T val;
+        };
+        inline uint32_t get(const Container<uint32_t>& c) { return c.val; }
+    "};
+    let rs = quote! {};
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("get")
+            instantiate!("Container", ["uint32_t"])
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_wrapper_body_customizer() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct A {
+            A() : a(1) {}
+            ~A() {}
+            uint32_t a;
+        };
+        inline uint32_t get(A a) { return a.a; }
+    "};
+    let rs = quote! {
+        let a = ffi::A::new().within_unique_ptr();
+        assert_eq!(ffi::get(a), 42);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["A", "get"], &[], None),
+        Some(Box::new(ReplaceWrapperBody("get", "return 42;"))),
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_instantiate_bitset() {
+    let hdr = indoc! {"
+        #include <bitset>
+        inline std::bitset<8> flags() { return std::bitset<8>(0b101); }
+        inline bool third_flag(const std::bitset<8>& b) { return b.test(2); }
+    "};
+    let rs = quote! {
+        let mut b = ffi::flags();
+        assert!(b.test(0));
+        assert!(!b.test(1));
+        assert_eq!(b.count(), 2);
+        assert_eq!(b.len(), 8);
+        b.pin_mut().set(1, true);
+        b.pin_mut().flip(0);
+        b.pin_mut().reset(2);
+        assert_eq!(b.to_u64(), 0b10);
+        assert!(!ffi::third_flag(&b));
+        let b = ffi::bitset_8::from_u64(0xff);
+        assert!(b.all());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| b.test(8)));
+        assert!(result.is_err());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("flags")
+            generate!("third_flag")
+            instantiate!("std::bitset", [8])
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_instantiate_bitset_all_unsafe() {
+    // The checked methods are safe even when the C++ they call isn't.
+    let hdr = indoc! {"
+        #include <bitset>
+        inline std::bitset<8> flags() { return std::bitset<8>(0b101); }
+    "};
+    let rs = quote! {
+        let mut b = unsafe { ffi::flags() };
+        assert!(b.test(0));
+        b.pin_mut().set(1, true);
+        assert!(unsafe { b.test_unchecked(1) });
+    };
+    do_run_test(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate!("flags")
+            instantiate!("std::bitset", [8])
+        },
+        None,
+        None,
+        None,
+        "unsafe",
+        None,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_opaque_ptr_param() {
+    let cxx = indoc! {"
+        static uint32_t* stored_ptr = nullptr;
+        void store(uint32_t* user_data) { stored_ptr = user_data; }
+        uint32_t* stored() { return stored_ptr; }
+    "};
+    let hdr = indoc! {"
+        #include <cstdint>
+        void store(uint32_t* user_data);
+        uint32_t* stored();
+    "};
+    let rs = quote! {
+        let mut value = 3u32;
+        // No unsafe block needed.
+        ffi::store(&mut value);
+        assert_eq!(ffi::stored(), &mut value as *mut u32);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        quote! {
+            safety!(unsafe_ffi)
+            generate!("store")
+            generate!("stored")
+            opaque_ptr_param!("store", user_data)
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_namespace_type() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        namespace math {
+            inline uint32_t twice(uint32_t a) { return a * 2; }
+            inline uint32_t add(uint32_t a, uint32_t b) { return a + b; }
+            namespace detail {
+                inline uint32_t helper() { return 3; }
+            }
+        }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::math::Math::twice(2), 4);
+        assert_eq!(ffi::math::Math::add(2, 3), 5);
+        assert_eq!(ffi::math::detail::helper(), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        quote! {
+            generate_ns!("math")
+            namespace_type!("math", Math)
+        },
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_abi_version() {
+    // The check's names mustn't clash with those of the C++.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #define MYLIB_ABI_VERSION 3
+        uint32_t mylib_abi_version();
+        inline uint32_t check_abi_version() { return 4; }
+    "};
+    let cxx = indoc! {"
+        uint32_t mylib_abi_version() { return 3; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::check_abi_version(), 4);
+    };
+    run_test_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["check_abi_version"],
+            &[],
+            Some(quote! { abi_version!("mylib_abi_version()", "MYLIB_ABI_VERSION") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_abi_version_mismatch() {
+    // The process aborts as it starts, so this fails even though the Rust
+    // doesn't call anything to check.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #define MYLIB_ABI_VERSION 3
+        uint32_t mylib_abi_version();
+        inline void do_thing() {}
+    "};
+    let cxx = indoc! {"
+        uint32_t mylib_abi_version() { return 2; }
+    "};
+    let rs = quote! {
+        ffi::do_thing();
+    };
+    run_test_expect_fail_ex(
+        cxx,
+        hdr,
+        rs,
+        directives_from_lists(
+            &["do_thing"],
+            &[],
+            Some(quote! { abi_version!("mylib_abi_version()", "MYLIB_ABI_VERSION") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_abi_version_missing_symbol() {
+    // A library which doesn't define its version function can't be
+    // checked, and fails to link.
+    let hdr = indoc! {"
+        #include <cstdint>
+        #define MYLIB_ABI_VERSION 3
+        uint32_t mylib_abi_version();
+        inline void do_thing() {}
+    "};
+    let rs = quote! {
+        ffi::do_thing();
+    };
+    run_test_expect_fail_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["do_thing"],
+            &[],
+            Some(quote! { abi_version!("mylib_abi_version()", "MYLIB_ABI_VERSION") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_platform_typedefs() {
+    let hdr = indoc! {"
+        #include <sys/types.h>
+        #include <cstddef>
+        #include <cstdint>
+        struct Buffer {
+            ssize_t len;
+            std::ptrdiff_t offset;
+        };
+        inline ssize_t remaining(Buffer buf) { return buf.len - buf.offset; }
+        inline uintptr_t address_of(const Buffer& buf) { return reinterpret_cast<uintptr_t>(&buf); }
+    "};
+    let rs = quote! {
+        let buf = ffi::Buffer { len: 10isize, offset: 3isize };
+        let remaining: isize = ffi::remaining(ffi::Buffer { len: 10isize, offset: 3isize });
+        assert_eq!(remaining, 7);
+        let address: usize = ffi::address_of(&buf);
+        assert_eq!(address, &buf as *const ffi::Buffer as usize);
+    };
+    run_test("", hdr, rs, &["remaining", "address_of"], &["Buffer"]);
+}
+
+#[test]
+fn test_enum_try_from() {
+    let hdr = indoc! {"
+        enum class Color { Red = -1, Green = 2, Blue = 7 };
+        enum Permissions : unsigned char { Read = 1, Write = 2 };
+    "};
+    let rs = quote! {
+        use std::convert::TryFrom;
+        assert!(ffi::Color::try_from(-1i64).unwrap() == ffi::Color::Red);
+        assert!(ffi::Color::try_from(7i64).unwrap() == ffi::Color::Blue);
+        assert_eq!(ffi::Color::try_from(3i64).err(), Some(autocxx::InvalidEnumValue(3)));
+        let rw = ffi::Permissions::try_from(3i64).unwrap();
+        assert!(rw == ffi::Permissions::Read | ffi::Permissions::Write);
+        assert!(ffi::Permissions::try_from(256i64).is_err());
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["Color", "Permissions"],
+            &[],
+            Some(quote! { bitflags_enum!("Permissions") }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_vector_fallback() {
+    let hdr = indoc! {"
+        #include <vector>
+        #include <memory>
+        #include <cstdint>
+        inline std::unique_ptr<std::vector<bool>> make_flags() {
+            return std::make_unique<std::vector<bool>>(std::vector<bool> { true, false, true });
+        }
+        inline size_t count_set(const std::vector<bool>& flags) {
+            size_t count = 0;
+            for (bool flag : flags) {
+                count += flag;
+            }
+            return count;
+        }
+        inline std::unique_ptr<std::vector<std::vector<uint32_t>>> make_lines() {
+            return std::make_unique<std::vector<std::vector<uint32_t>>>(3);
+        }
+        inline size_t count_lines(const std::vector<std::vector<uint32_t>>& lines) {
+            return lines.size();
+        }
+    "};
+    let rs = quote! {
+        let flags = ffi::make_flags();
+        assert_eq!(flags.len(), 3);
+        assert!(!flags.is_empty());
+        assert_eq!(ffi::count_set(&flags), 2);
+        assert_eq!(ffi::count_lines(&ffi::make_lines()), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["make_flags", "count_set", "make_lines", "count_lines"],
+            &[],
+            Some(quote! { instantiate!("std::vector", ["bool"], BoolVector) }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_track_caller() {
+    let hdr = indoc! {"
+        #include <stdexcept>
+        #include <cstdint>
+        inline uint32_t checked_half(uint32_t a) {
+            if (a % 2) {
+                throw std::invalid_argument(\"odd number\");
+            }
+            return a / 2;
+        }
+    "};
+    let rs = quote! {
+        let location = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let recorded = location.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            *recorded.lock().unwrap() = info.location().map(|l| l.file().to_string());
+        }));
+        assert!(std::panic::catch_unwind(|| ffi::checked_half(3)).is_err());
+        let _ = std::panic::take_hook();
+        // The panic is reported from this file, not the generated bindings.
+        assert_eq!(location.lock().unwrap().as_deref(), Some(file!()));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["checked_half"],
+            &[],
+            Some(quote! { exception_handling!(panic) track_caller!() }),
+        ),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_inline_passthrough_wrapper() {
+    // A safe wrapper around a function taking a raw pointer calls straight
+    // through to cxx, with no C++ wrapper.
+    let hdr = indoc! {"
+        #include <cstdint>
+        inline uint32_t count(const uint32_t* values) { return values[0]; }
+    "};
+    let rs = quote! {
+        let values = [3u32, 4u32];
+        assert_eq!(ffi::count(values.as_ptr()), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs.clone(),
+        directives_from_lists(&["count"], &[], Some(quote! { safe_fn!("count") })),
+        None,
+        Some(make_rust_code_finder(vec![
+            quote! { #[inline] pub fn count(values: *const u32) -> u32 },
+        ])),
+        None,
+    );
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["count"],
+            &[],
+            Some(quote! {
+                safe_fn!("count")
+                inline_always!()
+            }),
+        ),
+        None,
+        Some(make_rust_code_finder(vec![
+            quote! { #[inline(always)] pub fn count(values: *const u32) -> u32 },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_long_double_rejected() {
+    // On platforms where `long double` is just a `double`, bindgen doesn't
+    // tell us about it, so we only check for this where it's wider.
+    let hdr = indoc! {"
+        inline long double half(long double x) { return x / 2; }
+        inline int unrelated() { return 3; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::unrelated(), autocxx::c_int(3));
+    };
+    let checker = if cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        Some(make_rust_code_absence_checker(vec![quote! { fn half }]))
+    } else {
+        None
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["half", "unrelated"], &[], None),
+        None,
+        checker,
+        None,
+    );
+}
+
+#[test]
+fn test_long_double_f64() {
+    let hdr = indoc! {"
+        inline long double half(long double x) { return x / 2; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::half(3.0f64), 1.5f64);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["half"], &[], Some(quote! { long_double!(f64) })),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_no_std() {
+    let hdr = indoc! {"
+        struct Point {
+            int x;
+            int y;
+        };
+        inline Point flip(Point p) { return Point { p.y, p.x }; }
+    "};
+    let rs = quote! {
+        let p = ffi::flip(ffi::Point { x: autocxx::c_int(1), y: autocxx::c_int(2) });
+        assert_eq!(p.x, autocxx::c_int(2));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(
+            &["flip"],
+            &["Point"],
+            Some(quote! {
+                exclude_utilities!()
+                no_std!()
+            }),
+        ),
+        None,
+        Some(make_rust_code_absence_checker(vec![quote! { ::std:: }])),
+        None,
+    );
+}
+
+#[test]
+fn test_forward_declared_type_documented() {
+    let hdr = indoc! {"
+        #include <cstdint>
+        struct Widget;
+        int32_t take_widget(const Widget* widget);
+        Widget* make_widget();
+    "};
+    let cpp = indoc! {"
+        struct Widget {
+            int32_t a;
+        };
+        int32_t take_widget(const Widget* widget) {
+            return widget->a;
+        }
+        Widget* make_widget() {
+            static Widget w { 3 };
+            return &w;
+        }
+    "};
+    let rs = quote! {
+        let w = ffi::make_widget();
+        assert_eq!(unsafe { ffi::take_widget(w) }, 3);
+    };
+    run_test_ex(
+        cpp,
+        hdr,
+        rs,
+        directives_from_lists(&["take_widget", "make_widget"], &[], None),
+        None,
+        Some(make_string_finder(vec![
+            "`Widget` is only forward-declared".into(),
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_nodiscard_function() {
+    let hdr = indoc! {"
+        #include <cstdlib>
+        [[nodiscard]] inline int compute() { return 3; }
+        [[noreturn]] inline void fail() { std::abort(); }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::compute(), autocxx::c_int(3));
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["compute", "fail"], &[], None),
+        None,
+        Some(make_rust_code_finder(vec![
+            quote! { #[must_use] },
+            quote! { fn fail() },
+        ])),
+        None,
+    );
+}
+
+#[test]
+fn test_deprecated_not_propagated() {
+    // bindgen doesn't tell us about C++ deprecation, so the bindings are
+    // usable but carry no `#[deprecated]`. If that changes, so should the
+    // book.
+    let hdr = indoc! {"
+        #include <cstdint>
+        [[deprecated(\"use new_fn instead\")]] inline uint32_t old_fn() { return 1; }
+        struct [[deprecated]] OldType {
+            uint32_t a;
+        };
+        inline uint32_t read_old(const OldType& t) { return t.a; }
+    "};
+    let rs = quote! {
+        assert_eq!(ffi::old_fn(), 1);
+        let t = ffi::OldType { a: 3 };
+        assert_eq!(ffi::read_old(&t), 3);
+    };
+    run_test_ex(
+        "",
+        hdr,
+        rs,
+        directives_from_lists(&["old_fn", "read_old"], &["OldType"], None),
+        None,
+        Some(make_rust_code_absence_checker(vec![quote! { deprecated }])),
+        None,
+    );
+}
+
 // Yet to test:
 // - Ifdef
 // - Out param pointers
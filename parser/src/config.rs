@@ -20,12 +20,12 @@ use quote::ToTokens;
 use quote::format_ident;
 use syn::{
     parse::{Parse, ParseStream},
-    Signature, Token, TypePath,
+    ForeignItem, Signature, Token, TypeBareFn, TypePath,
 };
 use syn::{Ident, Result as ParseResult};
 use thiserror::Error;
 
-use crate::{directives::get_directives, RustPath};
+use crate::{cpp_operator_ident, directives::get_directives, RustPath};
 
 use quote::quote;
 
@@ -90,6 +90,167 @@ impl UnsafePolicy {
     }
 }
 
+/// What to do with C++ `long double`, which has no Rust equivalent.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub enum LongDoubleHandling {
+    /// Refuse to generate bindings for anything using it.
+    #[default]
+    Reject,
+    /// Pass it to and from functions as an `f64`, losing precision.
+    F64,
+    /// Represent it as an opaque `autocxx::c_longdouble`.
+    Opaque,
+}
+
+impl Parse for LongDoubleHandling {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        let r = if id == "reject" {
+            Self::Reject
+        } else if id == "f64" {
+            Self::F64
+        } else if id == "opaque" {
+            Self::Opaque
+        } else {
+            return Err(syn::Error::new(id.span(), "expected reject, f64 or opaque"));
+        };
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unexpected tokens within long_double directive",
+            ));
+        }
+        Ok(r)
+    }
+}
+
+impl ToTokens for LongDoubleHandling {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Self::Reject => quote! { reject },
+            Self::F64 => quote! { f64 },
+            Self::Opaque => quote! { opaque },
+        })
+    }
+}
+
+/// What to do when a C++ exception escapes from a function which wasn't
+/// listed in `throws!`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub enum ExceptionHandling {
+    /// Let C++ call `std::terminate`, aborting the process.
+    #[default]
+    Abort,
+    /// Catch the exception and panic in Rust with its message.
+    Panic,
+}
+
+impl Parse for ExceptionHandling {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        let r = if id == "abort" {
+            Self::Abort
+        } else if id == "panic" {
+            Self::Panic
+        } else {
+            return Err(syn::Error::new(id.span(), "expected abort or panic"));
+        };
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unexpected tokens within exception_handling directive",
+            ));
+        }
+        Ok(r)
+    }
+}
+
+impl ToTokens for ExceptionHandling {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Self::Abort => quote! { abort },
+            Self::Panic => quote! { panic },
+        })
+    }
+}
+
+/// How to name a function or method whose C++ name is a Rust keyword.
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Default)]
+pub enum KeywordNaming {
+    /// Add an underscore, so `move` becomes `move_`.
+    #[default]
+    TrailingUnderscore,
+    /// Use a raw identifier, so `move` becomes `r#move`. Keywords which
+    /// can't be raw identifiers (`crate`, `self`, `super` and `Self`) get
+    /// a trailing underscore instead.
+    Raw,
+    /// Add the given prefix, so `move` might become `cpp_move`.
+    Prefix(String),
+}
+
+impl KeywordNaming {
+    /// The Rust name to use for a C++ name which is a Rust keyword.
+    pub fn name_for_keyword(&self, keyword: &str) -> String {
+        match self {
+            Self::Raw if can_be_raw_identifier(keyword) => format!("r#{keyword}"),
+            Self::Prefix(prefix) => format!("{prefix}{keyword}"),
+            _ => format!("{keyword}_"),
+        }
+    }
+}
+
+fn can_be_raw_identifier(name: &str) -> bool {
+    !matches!(name, "crate" | "self" | "super" | "Self")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Parse for KeywordNaming {
+    fn parse(input: ParseStream) -> ParseResult<Self> {
+        let id: syn::Ident = input.parse()?;
+        let r = if id == "trailing_underscore" {
+            Self::TrailingUnderscore
+        } else if id == "raw" {
+            Self::Raw
+        } else if id == "prefix" {
+            input.parse::<Token![=]>()?;
+            let prefix: syn::LitStr = input.parse()?;
+            let prefix = prefix.value();
+            let mut chars = prefix.chars();
+            if !matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                return Err(syn::Error::new(
+                    id.span(),
+                    "prefix must be the start of a valid identifier",
+                ));
+            }
+            Self::Prefix(prefix)
+        } else {
+            return Err(syn::Error::new(
+                id.span(),
+                "expected trailing_underscore, raw or prefix = \"...\"",
+            ));
+        };
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "unexpected tokens within keyword_names directive",
+            ));
+        }
+        Ok(r)
+    }
+}
+
+impl ToTokens for KeywordNaming {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Self::TrailingUnderscore => quote! { trailing_underscore },
+            Self::Raw => quote! { raw },
+            Self::Prefix(prefix) => quote! { prefix = #prefix },
+        })
+    }
+}
+
 /// An entry in the allowlist.
 #[derive(Hash, Debug)]
 pub enum AllowlistEntry {
@@ -100,7 +261,7 @@ pub enum AllowlistEntry {
 impl AllowlistEntry {
     fn to_bindgen_item(&self) -> String {
         match self {
-            AllowlistEntry::Item(i) => i.clone(),
+            AllowlistEntry::Item(i) => cpp_operator_ident(i).unwrap_or_else(|| i.clone()),
             AllowlistEntry::Namespace(ns) => format!("{ns}::.*"),
         }
     }
@@ -161,6 +322,97 @@ pub struct Subclass {
     pub subclass: Ident,
 }
 
+/// A C++ type which exposes a contiguous byte buffer through a pair
+/// of accessor methods, for which we should generate `AsRef<[u8]>`.
+#[derive(Debug, Hash)]
+pub struct ByteBuffer {
+    pub ty: String,
+    pub data_method: Ident,
+    pub size_method: Ident,
+}
+
+/// An incomplete C++ type, only ever handled through pointers, for which
+/// we should generate a `#[repr(transparent)]` handle type.
+#[derive(Debug, Hash)]
+pub struct OpaqueHandle {
+    pub ty: String,
+    pub handle: Ident,
+    /// The C++ function which frees the object. If there isn't one, the
+    /// handle doesn't own the object, and can be copied.
+    pub destroy: Option<String>,
+    /// The C++ functions which return a new object, whose ownership passes
+    /// to the caller. Only these return an owning handle.
+    pub constructors: Vec<String>,
+}
+
+/// A C++ struct containing a union alongside a discriminant field,
+/// for which we should generate a safe accessor.
+#[derive(Debug, Hash)]
+pub struct TaggedUnion {
+    pub ty: String,
+    pub tag_field: Ident,
+    pub union_field: Ident,
+    pub variants: Vec<TaggedUnionVariant>,
+}
+
+/// A C++ type providing `begin()` and `end()`, for which we should
+/// generate an iterator and `IntoIterator` for references to it.
+#[derive(Debug, Hash)]
+pub struct Range {
+    pub ty: String,
+    /// C++ type of the elements, as obtained by dereferencing an iterator.
+    pub item_ty: String,
+}
+
+/// A data member of a C++ type which is callable, such as a lambda or a
+/// `std::function`, and the signature with which Rust should call it.
+#[derive(Debug, Hash)]
+pub struct CallableMember {
+    pub ty: String,
+    pub member: Ident,
+    pub signature: TypeBareFn,
+}
+
+/// A method of a C++ type which returns an iterator, such as `find`, which
+/// Rust should call with the signature given, getting `None` where the
+/// iterator is the type's `end()`.
+#[derive(Debug, Hash)]
+pub struct IteratorLookup {
+    pub ty: String,
+    pub method: Ident,
+    /// Member of the element to return, such as `second` for a map.
+    pub member: Option<Ident>,
+    pub signature: TypeBareFn,
+}
+
+/// A method which a C++ type inherits from a base class which bindgen
+/// can't tell us about, such as an instantiation of a template, and the
+/// signature with which Rust should call it.
+#[derive(Debug, Hash)]
+pub struct InheritedMethod {
+    pub ty: String,
+    pub method: Ident,
+    /// Whether to call the method through a mutable receiver.
+    pub mutable: bool,
+    pub signature: TypeBareFn,
+}
+
+/// A free function which takes a pointer to a C++ type as its first
+/// parameter, and which should be exposed as a method of that type.
+#[derive(Debug, Hash)]
+pub struct MethodOf {
+    pub ty: String,
+    pub function: String,
+    pub method: Ident,
+}
+
+/// A tag value and the union member which it selects.
+#[derive(Debug, Hash)]
+pub struct TaggedUnionVariant {
+    pub tag: i64,
+    pub member: Ident,
+}
+
 #[derive(Clone, Hash)]
 pub struct RustFun {
     pub path: RustPath,
@@ -181,6 +433,9 @@ impl std::fmt::Debug for RustFun {
 pub struct ExternCppType {
     pub rust_path: TypePath,
     pub opaque: bool,
+    /// Whether this is a `#[repr(C)]` Rust type which cbindgen exported
+    /// to C++, so we need to implement `cxx::ExternType` for it.
+    pub from_cbindgen: bool,
 }
 
 /// Newtype wrapper so we can implement Hash.
@@ -213,19 +468,81 @@ impl std::hash::Hash for ConcretesMap {
 pub struct IncludeCppConfig {
     pub inclusions: Vec<String>,
     pub unsafe_policy: UnsafePolicy,
+    pub(crate) long_double: LongDoubleHandling,
+    pub(crate) exception_handling: ExceptionHandling,
+    pub(crate) keyword_names: KeywordNaming,
     pub parse_only: bool,
     pub exclude_impls: bool,
     pub(crate) pod_requests: Vec<String>,
+    pub(crate) opaque_requests: Vec<String>,
+    pub(crate) thread_local_vars: Vec<String>,
+    pub(crate) constexpr_fns: Vec<String>,
     pub allowlist: Allowlist,
     pub(crate) blocklist: Vec<String>,
     pub(crate) constructor_blocklist: Vec<String>,
+    pub(crate) blocked_constructors: Vec<(String, String)>,
+    pub(crate) make_shared: Vec<String>,
+    pub(crate) bitflags_enums: Vec<String>,
+    pub(crate) raii_guards: Vec<String>,
+    pub(crate) string_constructible: Vec<String>,
+    pub(crate) safe_fns: Vec<String>,
+    pub(crate) unsafe_fns: Vec<String>,
+    pub(crate) mutable_receivers: Vec<String>,
+    pub(crate) const_receivers: Vec<String>,
+    pub(crate) checked_narrowing: Vec<String>,
+    pub(crate) const_smart_ptr_returns: Vec<String>,
+    pub(crate) throwing_fns: Vec<String>,
+    pub(crate) bool_params: Vec<(String, Ident)>,
+    pub(crate) wide_string_params: Vec<(String, Ident)>,
+    pub(crate) mut_span_params: Vec<(String, Ident)>,
+    pub(crate) vec_params: Vec<(String, Ident)>,
+    pub(crate) array_params: Vec<(String, Ident, usize)>,
+    pub(crate) restrict_params: Vec<(String, Ident)>,
+    pub(crate) ptr_array_params: Vec<(String, Ident)>,
+    pub(crate) nonnull_params: Vec<(String, Ident)>,
+    pub(crate) opaque_ptr_params: Vec<(String, Ident)>,
+    pub(crate) return_borrows: Vec<(String, Vec<Ident>)>,
+    pub(crate) allocators: Vec<(String, Option<String>)>,
+    pub(crate) methods_of: Vec<MethodOf>,
+    pub(crate) namespace_types: Vec<(String, Ident)>,
     pub instantiable: Vec<String>,
     pub(crate) exclude_utilities: bool,
+    pub(crate) boxed_returns: bool,
+    pub(crate) explicit_padding: bool,
+    pub(crate) mut_suffix_overloads: bool,
+    pub(crate) inline_always: bool,
+    pub(crate) track_caller: bool,
+    pub(crate) snake_case_methods: bool,
+    pub(crate) flexible_string_params: bool,
+    pub(crate) str_constants: bool,
+    pub(crate) no_std: bool,
+    pub(crate) suppress_ignored_items: bool,
+    pub(crate) method_renames: Vec<(String, String)>,
+    pub(crate) instrumentation_macro: Option<syn::Path>,
     pub(crate) mod_name: Option<Ident>,
+    pub(crate) rust_mod_path: Option<syn::Path>,
+    pub(crate) symbol_prefix: Option<Ident>,
+    pub(crate) assertion_handler: Option<Ident>,
+    pub(crate) exception_types: Vec<(String, Ident)>,
+    pub(crate) abi_version: Option<(String, String)>,
+    pub(crate) reexports: Vec<(String, Ident)>,
+    pub(crate) destructured_returns: Vec<(String, RustPath)>,
+    pub(crate) extra_cpp_includes: Vec<String>,
+    pub(crate) manual_bridge_items: Vec<ForeignItem>,
+    pub(crate) box_large_pod: Option<usize>,
+    pub(crate) max_template_depth: Option<usize>,
     pub rust_types: Vec<RustPath>,
     pub subclasses: Vec<Subclass>,
+    pub(crate) byte_buffers: Vec<ByteBuffer>,
+    pub(crate) opaque_handles: Vec<OpaqueHandle>,
+    pub tagged_unions: Vec<TaggedUnion>,
+    pub ranges: Vec<Range>,
+    pub(crate) callable_members: Vec<CallableMember>,
+    pub(crate) iterator_lookups: Vec<IteratorLookup>,
+    pub(crate) inherited_methods: Vec<InheritedMethod>,
     pub extern_rust_funs: Vec<RustFun>,
     pub concretes: ConcretesMap,
+    pub(crate) instantiations: Vec<Ident>,
     pub externs: ExternCppTypeMap,
 }
 
@@ -265,6 +582,14 @@ impl Parse for IncludeCppConfig {
                 break;
             }
         }
+        // The string utilities are built around Rust `String`s, and convert
+        // from them implicitly in ways which don't fit a `no_std` crate.
+        if config.no_std && !config.exclude_utilities {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "no_std!() can only be used along with exclude_utilities!(), because the string utilities depend on std",
+            ));
+        }
         Ok(config)
     }
 }
@@ -274,6 +599,12 @@ impl IncludeCppConfig {
         &self.pod_requests
     }
 
+    /// Types which must remain opaque, even if some other type which
+    /// we've been asked to make POD contains them.
+    pub fn get_opaque_requests(&self) -> &[String] {
+        &self.opaque_requests
+    }
+
     pub fn get_mod_name(&self) -> Ident {
         self.mod_name
             .as_ref()
@@ -281,12 +612,135 @@ impl IncludeCppConfig {
             .unwrap_or_else(|| Ident::new("ffi", Span::call_site()))
     }
 
+    /// The path given in `rust_mod_path!`, if any. Its last segment is
+    /// also the mod name.
+    pub fn get_rust_mod_path(&self) -> Option<&syn::Path> {
+        self.rust_mod_path.as_ref()
+    }
+
+    /// The modules which should enclose the generated mod, outermost
+    /// first, relative to the `include_cpp!`. These come from
+    /// `rust_mod_path!`, ignoring any leading `crate` or `self`.
+    pub fn get_enclosing_mods(&self) -> impl Iterator<Item = &Ident> {
+        self.rust_mod_path
+            .iter()
+            .flat_map(|path| {
+                let segments = path.segments.len();
+                path.segments.iter().take(segments - 1)
+            })
+            .map(|seg| &seg.ident)
+            .filter(|id| *id != "crate" && *id != "self")
+    }
+
     /// Whether to avoid generating the standard helpful utility
     /// functions which we normally include in every mod.
     pub fn exclude_utilities(&self) -> bool {
         self.exclude_utilities
     }
 
+    /// Whether constructors and functions returning C++ objects by value
+    /// should return a `Pin<Box<T>>` rather than an `impl New`.
+    pub fn boxed_returns(&self) -> bool {
+        self.boxed_returns
+    }
+
+    /// Whether bindgen should give every struct explicit fields for
+    /// its padding bytes.
+    pub fn explicit_padding(&self) -> bool {
+        self.explicit_padding
+    }
+
+    /// Whether a method with both `const` and non-`const` overloads should
+    /// give the non-`const` one a `_mut` suffix, rather than a number.
+    pub fn mut_suffix_overloads(&self) -> bool {
+        self.mut_suffix_overloads
+    }
+
+    /// What to do with C++ `long double`.
+    pub fn long_double_handling(&self) -> LongDoubleHandling {
+        self.long_double
+    }
+
+    /// What to do with C++ exceptions from functions not listed in
+    /// `throws!`.
+    pub fn exception_handling(&self) -> ExceptionHandling {
+        self.exception_handling
+    }
+
+    /// How to name functions and methods whose C++ names are Rust
+    /// keywords.
+    pub fn keyword_naming(&self) -> &KeywordNaming {
+        &self.keyword_names
+    }
+
+    /// Whether thin Rust wrappers around cxx functions should be marked
+    /// `#[inline(always)]` rather than just `#[inline]`.
+    pub fn inline_always(&self) -> bool {
+        self.inline_always
+    }
+
+    /// Whether generated Rust functions which may panic should be marked
+    /// `#[track_caller]`, so that panics report the caller's location.
+    pub fn track_caller(&self) -> bool {
+        self.track_caller
+    }
+
+    /// Whether method names should be converted to snake case.
+    pub fn snake_case_methods(&self) -> bool {
+        self.snake_case_methods
+    }
+
+    /// Whether the generated Rust should refer to `core` and `alloc` rather
+    /// than `std`, so that it can be used in `no_std` crates.
+    pub fn no_std(&self) -> bool {
+        self.no_std
+    }
+
+    /// Whether to leave out the placeholders which we otherwise generate
+    /// to explain why an item couldn't be generated.
+    pub fn suppress_ignored_items(&self) -> bool {
+        self.suppress_ignored_items
+    }
+
+    /// Whether `const std::string&` parameters should accept anything which
+    /// can be turned into a C++ string, rather than just a `&CxxString`.
+    pub fn flexible_string_params(&self) -> bool {
+        self.flexible_string_params
+    }
+
+    /// Whether string constants which are valid UTF-8 should be exposed as
+    /// `&str`, rather than as null-terminated byte arrays.
+    pub fn str_constants(&self) -> bool {
+        self.str_constants
+    }
+
+    /// Rules for renaming methods, as pairs of a regular expression and its
+    /// replacement, to be applied in order.
+    pub fn method_renames(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.method_renames
+            .iter()
+            .map(|(pattern, replacement)| (pattern.as_str(), replacement.as_str()))
+    }
+
+    /// The size in bytes above which POD structs returned by value should
+    /// instead be returned in a `UniquePtr`, if any.
+    pub fn box_large_pod(&self) -> Option<usize> {
+        self.box_large_pod
+    }
+
+    /// How deeply templates understood by cxx may be nested before we
+    /// stop converting their arguments and treat them as opaque, if
+    /// there's a limit.
+    pub fn max_template_depth(&self) -> Option<usize> {
+        self.max_template_depth
+    }
+
+    /// A macro, if any, through which every generated function should
+    /// route its call into C++, for the sake of tracing or profiling.
+    pub fn instrumentation_macro(&self) -> Option<&syn::Path> {
+        self.instrumentation_macro.as_ref()
+    }
+
     /// Items which the user has explicitly asked us to generate;
     /// we should raise an error if we weren't able to do so.
     pub fn must_generate_list(&self) -> Box<dyn Iterator<Item = String> + '_> {
@@ -306,6 +760,20 @@ impl IncludeCppConfig {
         }
     }
 
+    /// Namespaces which the user has asked us to generate with
+    /// `generate_ns!`. It's legitimate for these to contain nothing we
+    /// can generate, so we only insist otherwise if asked to be strict.
+    pub fn allowlisted_namespaces(&self) -> Box<dyn Iterator<Item = &String> + '_> {
+        if let Allowlist::Specific(items) = &self.allowlist {
+            Box::new(items.iter().filter_map(|i| match i {
+                AllowlistEntry::Namespace(ns) => Some(ns),
+                AllowlistEntry::Item(_) => None,
+            }))
+        } else {
+            Box::new(std::iter::empty())
+        }
+    }
+
     /// The allowlist of items to be passed into bindgen, if any.
     pub fn bindgen_allowlist(&self) -> Option<Box<dyn Iterator<Item = String> + '_>> {
         match &self.allowlist {
@@ -329,11 +797,26 @@ impl IncludeCppConfig {
     }
 
     fn active_utilities(&self) -> Vec<String> {
-        if self.exclude_utilities {
-            Vec::new()
-        } else {
-            vec![self.get_makestring_name()]
+        let mut utilities = Vec::new();
+        if !self.exclude_utilities {
+            utilities.push(self.get_makestring_name());
+        }
+        if self.assertion_handler.is_some() {
+            utilities.push(self.get_assertion_handler_rust_name());
+        }
+        if !self.exception_types.is_empty() {
+            utilities.push(self.get_exception_enum_name().to_string());
         }
+        if self.abi_version.is_some() {
+            utilities.push(self.get_abi_version_check_name());
+        }
+        utilities.extend(
+            self.constexpr_fns
+                .iter()
+                .map(|fun| self.get_constexpr_probe_name(fun)),
+        );
+        utilities.extend(self.instantiations.iter().map(Ident::to_string));
+        utilities
     }
 
     fn is_subclass_or_superclass(&self, cpp_name: &str) -> bool {
@@ -364,11 +847,15 @@ impl IncludeCppConfig {
             || self.is_rust_fun(cpp_name)
             || self.is_rust_type_name(cpp_name)
             || self.is_concrete_type(cpp_name)
+            || self.is_constexpr_fn(cpp_name)
+            || self.is_namespace_type(cpp_name)
             || match &self.allowlist {
                 Allowlist::Unspecified(_) => panic!("Eek no allowlist yet"),
                 Allowlist::All => true,
                 Allowlist::Specific(items) => items.iter().any(|entry| match entry {
-                    AllowlistEntry::Item(i) => i == cpp_name,
+                    AllowlistEntry::Item(i) => {
+                        i == cpp_name || cpp_operator_ident(i).as_deref() == Some(cpp_name)
+                    }
                     AllowlistEntry::Namespace(ns) => cpp_name.starts_with(ns),
                 }),
             }
@@ -378,14 +865,344 @@ impl IncludeCppConfig {
         self.blocklist.contains(&cpp_name.to_string())
     }
 
+    /// Every type listed in `byte_buffer!`, with its accessors.
+    pub fn get_byte_buffers(&self) -> &[ByteBuffer] {
+        &self.byte_buffers
+    }
+
+    /// If this type was listed in `byte_buffer!`, the accessors
+    /// which give its data pointer and length.
+    pub fn get_byte_buffer(&self, cpp_name: &str) -> Option<&ByteBuffer> {
+        self.byte_buffers.iter().find(|bb| bb.ty == cpp_name)
+    }
+
+    /// If this type was listed in `opaque_handle!`, the details of the
+    /// handle type to generate for pointers to it.
+    pub fn get_opaque_handle(&self, cpp_name: &str) -> Option<&OpaqueHandle> {
+        self.opaque_handles.iter().find(|oh| oh.ty == cpp_name)
+    }
+
+    /// Whether this function frees an object whose pointers are wrapped
+    /// in an owning handle, and so must continue to take a raw pointer.
+    pub fn is_opaque_handle_destroyer(&self, cpp_name: &str) -> bool {
+        self.opaque_handles
+            .iter()
+            .any(|oh| oh.destroy.as_deref() == Some(cpp_name))
+    }
+
+    /// If this type was listed in `tagged_union!`, which fields hold
+    /// the tag and the union, and how tags map to union members.
+    pub fn get_tagged_union(&self, cpp_name: &str) -> Option<&TaggedUnion> {
+        self.tagged_unions.iter().find(|tu| tu.ty == cpp_name)
+    }
+
+    /// If this type was listed in `range!`, the type of its elements.
+    pub fn get_range(&self, cpp_name: &str) -> Option<&Range> {
+        self.ranges.iter().find(|r| r.ty == cpp_name)
+    }
+
+    /// Members of this type listed in `callable_member!`.
+    pub fn get_callable_members<'a>(
+        &'a self,
+        cpp_name: &'a str,
+    ) -> impl Iterator<Item = &'a CallableMember> + 'a {
+        self.callable_members
+            .iter()
+            .filter(move |cm| cm.ty == cpp_name)
+    }
+
+    pub fn has_callable_members(&self) -> bool {
+        !self.callable_members.is_empty()
+    }
+
+    /// Methods of this type listed in `iterator_lookup!`.
+    pub fn get_iterator_lookups<'a>(
+        &'a self,
+        cpp_name: &'a str,
+    ) -> impl Iterator<Item = &'a IteratorLookup> + 'a {
+        self.iterator_lookups
+            .iter()
+            .filter(move |il| il.ty == cpp_name)
+    }
+
+    pub fn has_iterator_lookups(&self) -> bool {
+        !self.iterator_lookups.is_empty()
+    }
+
+    /// Methods of this type listed in `inherited_method!`.
+    pub fn get_inherited_methods<'a>(
+        &'a self,
+        cpp_name: &'a str,
+    ) -> impl Iterator<Item = &'a InheritedMethod> + 'a {
+        self.inherited_methods
+            .iter()
+            .filter(move |im| im.ty == cpp_name)
+    }
+
+    pub fn has_inherited_methods(&self) -> bool {
+        !self.inherited_methods.is_empty()
+    }
+
     pub fn is_on_constructor_blocklist(&self, cpp_name: &str) -> bool {
         self.constructor_blocklist.contains(&cpp_name.to_string())
     }
 
+    /// Whether each constructor of this type should be accompanied by a
+    /// function which creates the object within a `std::shared_ptr`.
+    pub fn is_make_shared(&self, cpp_name: &str) -> bool {
+        self.make_shared.contains(&cpp_name.to_string())
+    }
+
+    /// Whether this enum is a set of flags which may be combined, and
+    /// therefore may hold values other than its named variants.
+    pub fn is_bitflags_enum(&self, cpp_name: &str) -> bool {
+        self.bitflags_enums.contains(&cpp_name.to_string())
+    }
+
+    /// Whether this type releases some resource in its destructor, and
+    /// so may be held in an `autocxx::CppGuard`.
+    pub fn is_raii_guard(&self, cpp_name: &str) -> bool {
+        self.raii_guards.contains(&cpp_name.to_string())
+    }
+
+    /// Whether we should implement `From<&str>` for this type, using its
+    /// constructor which takes a string.
+    pub fn is_string_constructible(&self, cpp_name: &str) -> bool {
+        self.string_constructible.contains(&cpp_name.to_string())
+    }
+
+    /// Whether the user has overridden the [`UnsafePolicy`] for this
+    /// function: `Some(true)` if it should be `unsafe`, `Some(false)` if it
+    /// should be safe to call even though it takes raw pointers.
+    pub fn get_unsafety_override(&self, cpp_name: &str) -> Option<bool> {
+        if self.unsafe_fns.iter().any(|f| f == cpp_name) {
+            Some(true)
+        } else if self.safe_fns.iter().any(|f| f == cpp_name) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the user has overridden the mutability of this method's
+    /// receiver, given as `Type::method`: `Some(true)` if it should take
+    /// `Pin<&mut Self>` even though it's `const` in C++, `Some(false)` if
+    /// it should take `&self` even though it isn't.
+    pub fn get_receiver_mutability_override(&self, cpp_name: &str) -> Option<bool> {
+        if self.mutable_receivers.iter().any(|f| f == cpp_name) {
+            Some(true)
+        } else if self.const_receivers.iter().any(|f| f == cpp_name) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Whether this explicit constructor of the given type, identified by its
+    /// C++ parameter types (e.g. `const std::string&, int`), was listed in
+    /// `block_constructor!`. Whitespace is ignored.
+    pub fn is_blocked_constructor(&self, cpp_type: &str, cpp_params: &str) -> bool {
+        self.blocked_constructors
+            .iter()
+            .any(|(ty, params)| ty == cpp_type && same_constructor_params(params, cpp_params))
+    }
+
+    /// The first entry in `block_constructor!`, as (type, parameter types),
+    /// which matches none of the constructors we found. The function gives
+    /// the parameter types of each constructor of the named type.
+    pub fn find_unmatched_blocked_constructor<'b, I: IntoIterator<Item = &'b String>>(
+        &self,
+        constructors_of: impl Fn(&str) -> I,
+    ) -> Option<&(String, String)> {
+        self.blocked_constructors.iter().find(|(ty, params)| {
+            !constructors_of(ty)
+                .into_iter()
+                .any(|found| same_constructor_params(params, found))
+        })
+    }
+
+    /// Whether narrow integer parameters of this function should accept
+    /// a wider Rust integer, with a bounds check in the generated wrapper.
+    pub fn is_checked_narrowing(&self, cpp_name: &str) -> bool {
+        self.checked_narrowing.contains(&cpp_name.to_string())
+    }
+
+    /// Whether this function returns a `std::shared_ptr` or `std::weak_ptr`
+    /// to a `const` target.
+    pub fn is_const_smart_ptr_return(&self, cpp_name: &str) -> bool {
+        self.const_smart_ptr_returns.contains(&cpp_name.to_string())
+    }
+
+    /// Whether this function may throw, and so should return a `Result`
+    /// with any C++ exception.
+    pub fn is_throwing(&self, cpp_name: &str) -> bool {
+        self.throwing_fns.contains(&cpp_name.to_string())
+    }
+
+    /// Every (function, parameter) pair listed in `bool_param!`.
+    pub fn get_bool_params(&self) -> &[(String, Ident)] {
+        &self.bool_params
+    }
+
+    /// Whether the given integer parameter of this function should be
+    /// exposed to Rust as a `bool`.
+    pub fn is_bool_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.bool_params, cpp_name, param_name)
+    }
+
+    /// Whether the given `const wchar_t*` parameter of this function should
+    /// accept a Rust string or slice of wide characters.
+    pub fn is_wide_string_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.wide_string_params, cpp_name, param_name)
+    }
+
+    /// Whether the given `std::span` parameter of this function has
+    /// mutable elements, and so should accept a `&mut [T]`.
+    pub fn is_mut_span_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.mut_span_params, cpp_name, param_name)
+    }
+
+    /// Whether the given `std::vector` parameter of this function should
+    /// accept a Rust `Vec`.
+    pub fn is_vec_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.vec_params, cpp_name, param_name)
+    }
+
+    /// If the given pointer parameter of this function in fact points to
+    /// a fixed number of elements, returns that number.
+    pub fn get_array_param_len(&self, cpp_name: &str, param_name: &Ident) -> Option<usize> {
+        self.array_params
+            .iter()
+            .find(|(fun, param, _)| fun == cpp_name && param == param_name)
+            .map(|(_, _, len)| *len)
+    }
+
+    /// Whether the given pointer parameter of this function is `restrict`,
+    /// and so can be passed as a Rust reference.
+    pub fn is_restrict_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.restrict_params, cpp_name, param_name)
+    }
+
+    /// Whether this `const T* const*` parameter was listed in
+    /// `ptr_array_param!`, so should accept a slice of references.
+    pub fn is_ptr_array_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.ptr_array_params, cpp_name, param_name)
+    }
+
+    /// If this type was listed in `allocator!`, the C++ expression to pass
+    /// for parameters of this type. That's `{}` - a value-initialized
+    /// allocator - unless the directive gave one.
+    pub fn get_allocator(&self, cpp_type: &str) -> Option<&str> {
+        self.allocators
+            .iter()
+            .find(|(ty, _)| ty == cpp_type)
+            .map(|(_, value)| value.as_deref().unwrap_or("{}"))
+    }
+
+    /// Whether this pointer parameter was listed in `nonnull_param!`, so
+    /// can be passed as a reference.
+    pub fn is_nonnull_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.nonnull_params, cpp_name, param_name)
+    }
+
+    /// Whether this pointer parameter was listed in `opaque_ptr_param!`,
+    /// so is merely passed along by the function rather than dereferenced.
+    pub fn is_opaque_ptr_param(&self, cpp_name: &str, param_name: &Ident) -> bool {
+        is_fn_param_listed(&self.opaque_ptr_params, cpp_name, param_name)
+    }
+
+    /// If `return_borrows!` said which parameters the reference returned by
+    /// this function borrows from, returns their names. The receiver is
+    /// called `self`.
+    pub fn get_return_borrows(&self, cpp_name: &str) -> Option<&[Ident]> {
+        self.return_borrows
+            .iter()
+            .find(|(fun, _)| fun == cpp_name)
+            .map(|(_, params)| params.as_slice())
+    }
+
+    /// Whether this global variable was listed in `thread_local_var!`.
+    pub fn is_thread_local_var(&self, cpp_name: &str) -> bool {
+        self.thread_local_vars.iter().any(|var| var == cpp_name)
+    }
+
+    /// Whether this function was listed in `constexpr_fn!`, so should be
+    /// evaluated at build time into a Rust constant.
+    pub fn is_constexpr_fn(&self, cpp_name: &str) -> bool {
+        self.constexpr_fns.iter().any(|fun| fun == cpp_name)
+    }
+
+    /// The functions listed in `constexpr_fn!`.
+    pub fn constexpr_fns(&self) -> impl Iterator<Item = &str> {
+        self.constexpr_fns.iter().map(String::as_str)
+    }
+
+    /// The name of the variable, in the same namespace as this
+    /// `constexpr_fn!`, which is initialized by calling it. We have
+    /// bindgen evaluate that variable to find the function's result.
+    pub fn get_constexpr_probe_name(&self, cpp_name: &str) -> String {
+        match cpp_name.rsplit_once("::") {
+            Some((ns, name)) => format!("{ns}::autocxx_constexpr_{name}"),
+            None => format!("autocxx_constexpr_{cpp_name}"),
+        }
+    }
+
+    /// If this is the variable used to evaluate a `constexpr_fn!`,
+    /// returns that function.
+    pub fn get_constexpr_fn_for_probe(&self, cpp_name: &str) -> Option<&str> {
+        self.constexpr_fns()
+            .find(|fun| self.get_constexpr_probe_name(fun) == cpp_name)
+    }
+
+    /// If this free function should instead be exposed as a method,
+    /// returns the details requested by `method_of!`.
+    pub fn get_method_of(&self, cpp_name: &str) -> Option<&MethodOf> {
+        self.methods_of.iter().find(|m| m.function == cpp_name)
+    }
+
+    /// The types listed in `namespace_type!`, each with the namespace
+    /// whose free functions become its associated functions.
+    pub fn get_namespace_types(&self) -> impl Iterator<Item = (&str, &Ident)> {
+        self.namespace_types
+            .iter()
+            .map(|(ns, ty)| (ns.as_str(), ty))
+    }
+
+    /// If `namespace_type!` asked for the free functions directly within
+    /// this namespace to be associated functions of a type, its name.
+    pub fn get_namespace_type(&self, ns: &str) -> Option<&Ident> {
+        self.namespace_types
+            .iter()
+            .find(|(this_ns, _)| this_ns == ns)
+            .map(|(_, ty)| ty)
+    }
+
+    fn is_namespace_type(&self, cpp_name: &str) -> bool {
+        self.namespace_types
+            .iter()
+            .any(|(ns, ty)| format!("{ns}::{ty}") == cpp_name)
+    }
+
     pub fn get_blocklist(&self) -> impl Iterator<Item = &String> {
         self.blocklist.iter()
     }
 
+    /// The C++ definitions requested by `instantiate!`, each with the
+    /// name of the concrete type to make for it. The definition may omit
+    /// defaulted template arguments.
+    pub fn get_instantiations(&self) -> impl Iterator<Item = (&str, &Ident)> {
+        self.concretes
+            .0
+            .iter()
+            .filter(|(_, rust_id)| self.instantiations.contains(rust_id))
+            .map(|(cpp_definition, rust_id)| (cpp_definition.as_str(), rust_id))
+    }
+
+    /// Whether this concrete type was requested by `instantiate!`.
+    pub fn is_instantiation(&self, rust_id: &Ident) -> bool {
+        self.instantiations.contains(rust_id)
+    }
+
     fn is_concrete_type(&self, cpp_name: &str) -> bool {
         self.concretes.0.values().any(|val| *val == cpp_name)
     }
@@ -399,15 +1216,126 @@ impl IncludeCppConfig {
 
     /// In case there are multiple sets of ffi mods in a single binary,
     /// endeavor to return a name which can be used to make symbols
-    /// unique.
+    /// unique. If the user gave a `symbol_prefix!`, that's applied too,
+    /// so that crates wrapping the same C++ in the same way can coexist.
     pub fn uniquify_name_per_mod(&self, name: &str) -> String {
-        format!("{}_{:#x}", name, self.get_hash())
+        match &self.symbol_prefix {
+            Some(prefix) => format!("{}_{}_{:#x}", prefix, name, self.get_hash()),
+            None => format!("{}_{:#x}", name, self.get_hash()),
+        }
     }
 
     pub fn get_makestring_name(&self) -> String {
         self.uniquify_name_per_mod("autocxx_make_string")
     }
 
+    /// The name of the C++ function which should be called when an
+    /// assertion fails, if the user asked for one with `assertion_handler!`.
+    pub fn get_assertion_handler(&self) -> Option<&Ident> {
+        self.assertion_handler.as_ref()
+    }
+
+    /// The name of the Rust function, called by the C++ assertion handler,
+    /// which panics.
+    pub fn get_assertion_handler_rust_name(&self) -> String {
+        self.uniquify_name_per_mod("autocxx_assertion_failed")
+    }
+
+    /// The C++ exception types listed in `exception_type!`, each with the
+    /// name of the enum variant which represents it.
+    pub fn get_exception_types(&self) -> &[(String, Ident)] {
+        &self.exception_types
+    }
+
+    /// The name of the enum into which C++ exceptions can be converted,
+    /// if any were listed in `exception_type!`.
+    pub fn get_exception_enum_name(&self) -> &'static str {
+        "CppException"
+    }
+
+    /// The C++ expressions given in `abi_version!`: the first gives the
+    /// version of the library we're linked against, and the second the
+    /// version of the headers we were built against.
+    pub fn get_abi_version(&self) -> Option<(&str, &str)> {
+        self.abi_version
+            .as_ref()
+            .map(|(actual, expected)| (actual.as_str(), expected.as_str()))
+    }
+
+    /// The name of the C++ function which compares the versions given in
+    /// `abi_version!`. This is unique to this mod, so that it can't clash
+    /// with anything in the C++ or with another `include_cpp!`.
+    pub fn get_abi_version_check_name(&self) -> String {
+        self.uniquify_name_per_mod("autocxx_abi_version_check")
+    }
+
+    /// The name of the C++ variable whose initialization calls the function
+    /// named by [`Self::get_abi_version_check_name`] as the program starts.
+    pub fn get_abi_version_checked_name(&self) -> String {
+        self.uniquify_name_per_mod("autocxx_abi_version_checked")
+    }
+
+    /// The generated items listed in `reexport!`, each given by its
+    /// C++ name, along with the name under which to re-export it.
+    pub fn get_reexports(&self) -> &[(String, Ident)] {
+        &self.reexports
+    }
+
+    /// The struct, if any, which `destructure_return!` says this
+    /// function returns only to bundle several values together, given
+    /// by its C++ name.
+    pub fn get_destructured_return(&self, fn_name: &str) -> Option<String> {
+        self.destructured_returns
+            .iter()
+            .find(|(name, _)| name == fn_name)
+            .map(|(_, struct_path)| Self::rust_path_to_cpp_name(struct_path))
+    }
+
+    /// Whether this struct was named in any `destructure_return!`.
+    pub fn is_destructured_return_type(&self, cpp_name: &str) -> bool {
+        self.destructured_returns
+            .iter()
+            .any(|(_, struct_path)| Self::rust_path_to_cpp_name(struct_path) == cpp_name)
+    }
+
+    fn rust_path_to_cpp_name(path: &RustPath) -> String {
+        path.to_token_stream().to_string().replace(' ', "")
+    }
+
+    /// The name of the mod, within the generated mod, which holds the
+    /// items listed in `reexport!`.
+    pub fn get_reexports_mod_name(&self) -> &'static str {
+        "exports"
+    }
+
+    /// Extra headers, listed in `extra_cpp_includes!`, which should be
+    /// `#include`d at the very top of each generated C++ implementation
+    /// file.
+    pub fn get_extra_cpp_includes(&self) -> &[String] {
+        &self.extra_cpp_includes
+    }
+
+    /// Hand-written entries, listed in `manual!`, to be passed through
+    /// verbatim into the `extern "C++"` section of the cxx::bridge.
+    pub fn get_manual_bridge_items(&self) -> &[ForeignItem] {
+        &self.manual_bridge_items
+    }
+
+    /// The names of the functions and types listed in `manual!`.
+    pub fn get_manual_bridge_names(&self) -> impl Iterator<Item = &Ident> {
+        self.manual_bridge_items
+            .iter()
+            .filter_map(|item| match item {
+                ForeignItem::Fn(f) => Some(&f.sig.ident),
+                ForeignItem::Type(t) => Some(&t.ident),
+                _ => None,
+            })
+    }
+
+    pub fn is_manual_bridge_name(&self, name: &str) -> bool {
+        self.get_manual_bridge_names().any(|id| id == name)
+    }
+
     pub fn is_rust_type(&self, id: &Ident) -> bool {
         let id_string = id.to_string();
         self.is_rust_type_name(&id_string) || self.is_subclass_holder(&id_string)
@@ -493,9 +1421,24 @@ impl ToTokens for IncludeCppConfig {
     }
 }
 
+/// Whether two spellings of a constructor's parameter types are the same,
+/// ignoring whitespace.
+fn same_constructor_params(a: &str, b: &str) -> bool {
+    let strip = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    strip(a) == strip(b)
+}
+
+/// Whether this parameter of this function was given to one of the
+/// directives which list `(function, param)` pairs.
+fn is_fn_param_listed(list: &[(String, Ident)], cpp_name: &str, param_name: &Ident) -> bool {
+    list.iter()
+        .any(|(fun, param)| fun == cpp_name && param == param_name)
+}
+
 #[cfg(test)]
 mod parse_tests {
-    use crate::config::UnsafePolicy;
+    use crate::config::{IncludeCppConfig, UnsafePolicy};
+    use quote::{quote, ToTokens};
     use syn::parse_quote;
     #[test]
     fn test_safety_unsafe() {
@@ -518,4 +1461,101 @@ mod parse_tests {
         let us: UnsafePolicy = parse_quote! {};
         assert_eq!(us, UnsafePolicy::AllFunctionsUnsafe)
     }
+
+    #[test]
+    fn test_symbol_prefix() {
+        let config: IncludeCppConfig = parse_quote! { symbol_prefix!(my_crate) };
+        assert!(config
+            .uniquify_name_per_mod("foo")
+            .starts_with("my_crate_foo_0x"));
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { symbol_prefix!(_my_crate) }).is_err());
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { symbol_prefix!(my__crate) }).is_err());
+    }
+
+    #[test]
+    fn test_rust_mod_path() {
+        let config: IncludeCppConfig = parse_quote! { rust_mod_path!(crate::bindings::cpp) };
+        assert_eq!(config.get_mod_name(), "cpp");
+        let enclosing: Vec<_> = config
+            .get_enclosing_mods()
+            .map(|id| id.to_string())
+            .collect();
+        assert_eq!(enclosing, ["bindings"]);
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { rust_mod_path!(super::cpp) }).is_err());
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { rust_mod_path!(a::crate::cpp) }).is_err());
+        assert!(syn::parse2::<IncludeCppConfig>(quote! {
+            name!(ffi2)
+            rust_mod_path!(cpp)
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let config: IncludeCppConfig = parse_quote! {
+            instantiate!("std::bitset", [64])
+            instantiate!("Eigen::Matrix", ["double", 3, 3], Matrix3d)
+        };
+        let concretes: Vec<_> = config
+            .concretes
+            .0
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.to_string()))
+            .collect();
+        assert_eq!(
+            concretes,
+            [
+                ("std::bitset<64>", "bitset_64".to_string()),
+                ("Eigen::Matrix<double, 3, 3>", "Matrix3d".to_string()),
+            ]
+        );
+        assert!(
+            syn::parse2::<IncludeCppConfig>(quote! { instantiate!("std::bitset", []) }).is_err()
+        );
+    }
+
+    #[test]
+    fn test_no_std_requires_exclude_utilities() {
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { no_std!() }).is_err());
+        let config: IncludeCppConfig = parse_quote! { no_std!() exclude_utilities!() };
+        assert!(config.no_std());
+    }
+
+    #[test]
+    fn test_block_constructor() {
+        let config: IncludeCppConfig = parse_quote! {
+            block_constructor!("Widget", "const std::string &, int")
+            block_constructor!("Widget", "")
+        };
+        assert!(config.is_blocked_constructor("Widget", "const std::string&,int"));
+        assert!(config.is_blocked_constructor("Widget", ""));
+        assert!(!config.is_blocked_constructor("Widget", "int"));
+        assert!(!config.is_blocked_constructor("Gadget", ""));
+        let found = vec!["const std::string&, int".to_string()];
+        assert_eq!(
+            config.find_unmatched_blocked_constructor(|_| &found),
+            Some(&("Widget".to_string(), "".to_string()))
+        );
+        let found = vec!["".to_string(), "const std::string&, int".to_string()];
+        assert_eq!(config.find_unmatched_blocked_constructor(|_| &found), None);
+    }
+
+    #[test]
+    fn test_fn_params() {
+        let config: IncludeCppConfig = parse_quote! {
+            generate!("set_flag")
+            bool_param!("set_flag", enabled)
+            bool_param!("set_flag", visible)
+            nonnull_param!("fill", out)
+        };
+        let enabled = parse_quote! { enabled };
+        assert!(config.is_bool_param("set_flag", &enabled));
+        assert!(config.is_bool_param("set_flag", &parse_quote! { visible }));
+        assert!(!config.is_bool_param("fill", &enabled));
+        assert!(!config.is_nonnull_param("set_flag", &enabled));
+        assert!(config.is_nonnull_param("fill", &parse_quote! { out }));
+        let reparsed: IncludeCppConfig = syn::parse2(config.to_token_stream()).unwrap();
+        assert!(reparsed.is_bool_param("set_flag", &parse_quote! { visible }));
+        assert!(syn::parse2::<IncludeCppConfig>(quote! { vec_param!("f") }).is_err());
+    }
 }
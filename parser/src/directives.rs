@@ -12,7 +12,9 @@ use once_cell::sync::OnceCell;
 use proc_macro2::Span;
 use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
 use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
 
 use crate::config::{Allowlist, AllowlistErr};
 use crate::directive_names::{EXTERN_RUST_FUN, EXTERN_RUST_TYPE, SUBCLASS};
@@ -34,8 +36,13 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
         need_exclamation.insert("generate".into(), Box::new(Generate(false)));
         need_exclamation.insert("generate_pod".into(), Box::new(Generate(true)));
         need_exclamation.insert("generate_ns".into(), Box::new(GenerateNs));
+        need_exclamation.insert("thread_local_var".into(), Box::new(ThreadLocalVar));
+        need_exclamation.insert("constexpr_fn".into(), Box::new(ConstexprFn));
         need_exclamation.insert("generate_all".into(), Box::new(GenerateAll));
         need_exclamation.insert("safety".into(), Box::new(Safety));
+        need_exclamation.insert("long_double".into(), Box::new(LongDouble));
+        need_exclamation.insert("exception_handling".into(), Box::new(ExceptionHandling));
+        need_exclamation.insert("keyword_names".into(), Box::new(KeywordNames));
         need_exclamation.insert(
             "pod".into(),
             Box::new(StringList(
@@ -43,6 +50,13 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
                 |config| &config.pod_requests,
             )),
         );
+        need_exclamation.insert(
+            "opaque".into(),
+            Box::new(StringList(
+                |config| &mut config.opaque_requests,
+                |config| &config.opaque_requests,
+            )),
+        );
         need_exclamation.insert(
             "block".into(),
             Box::new(StringList(
@@ -57,6 +71,83 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
                 |config| &config.constructor_blocklist,
             )),
         );
+        need_exclamation.insert(
+            "make_shared".into(),
+            Box::new(StringList(
+                |config| &mut config.make_shared,
+                |config| &config.make_shared,
+            )),
+        );
+        need_exclamation.insert(
+            "bitflags_enum".into(),
+            Box::new(StringList(
+                |config| &mut config.bitflags_enums,
+                |config| &config.bitflags_enums,
+            )),
+        );
+        need_exclamation.insert(
+            "raii_guard".into(),
+            Box::new(StringList(
+                |config| &mut config.raii_guards,
+                |config| &config.raii_guards,
+            )),
+        );
+        need_exclamation.insert(
+            "string_constructible".into(),
+            Box::new(StringList(
+                |config| &mut config.string_constructible,
+                |config| &config.string_constructible,
+            )),
+        );
+        need_exclamation.insert(
+            "safe_fn".into(),
+            Box::new(StringList(
+                |config| &mut config.safe_fns,
+                |config| &config.safe_fns,
+            )),
+        );
+        need_exclamation.insert(
+            "unsafe_fn".into(),
+            Box::new(StringList(
+                |config| &mut config.unsafe_fns,
+                |config| &config.unsafe_fns,
+            )),
+        );
+        need_exclamation.insert(
+            "mutable_receiver".into(),
+            Box::new(StringList(
+                |config| &mut config.mutable_receivers,
+                |config| &config.mutable_receivers,
+            )),
+        );
+        need_exclamation.insert(
+            "const_receiver".into(),
+            Box::new(StringList(
+                |config| &mut config.const_receivers,
+                |config| &config.const_receivers,
+            )),
+        );
+        need_exclamation.insert(
+            "checked_narrowing".into(),
+            Box::new(StringList(
+                |config| &mut config.checked_narrowing,
+                |config| &config.checked_narrowing,
+            )),
+        );
+        need_exclamation.insert(
+            "const_smart_ptr_return".into(),
+            Box::new(StringList(
+                |config| &mut config.const_smart_ptr_returns,
+                |config| &config.const_smart_ptr_returns,
+            )),
+        );
+        need_exclamation.insert(
+            "throws".into(),
+            Box::new(StringList(
+                |config| &mut config.throwing_fns,
+                |config| &config.throwing_fns,
+            )),
+        );
         need_exclamation.insert(
             "instantiable".into(),
             Box::new(StringList(
@@ -85,19 +176,185 @@ pub(crate) fn get_directives() -> &'static DirectivesMap {
                 |config| &config.exclude_utilities,
             )),
         );
+        need_exclamation.insert(
+            "boxed_returns".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.boxed_returns,
+                |config| &config.boxed_returns,
+            )),
+        );
+        need_exclamation.insert(
+            "explicit_padding".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.explicit_padding,
+                |config| &config.explicit_padding,
+            )),
+        );
+        need_exclamation.insert(
+            "mut_suffix_overloads".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.mut_suffix_overloads,
+                |config| &config.mut_suffix_overloads,
+            )),
+        );
+        need_exclamation.insert(
+            "inline_always".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.inline_always,
+                |config| &config.inline_always,
+            )),
+        );
+        need_exclamation.insert(
+            "track_caller".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.track_caller,
+                |config| &config.track_caller,
+            )),
+        );
+        need_exclamation.insert(
+            "snake_case_methods".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.snake_case_methods,
+                |config| &config.snake_case_methods,
+            )),
+        );
+        need_exclamation.insert(
+            "no_std".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.no_std,
+                |config| &config.no_std,
+            )),
+        );
+        need_exclamation.insert(
+            "suppress_ignored_items".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.suppress_ignored_items,
+                |config| &config.suppress_ignored_items,
+            )),
+        );
+        need_exclamation.insert(
+            "flexible_string_params".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.flexible_string_params,
+                |config| &config.flexible_string_params,
+            )),
+        );
+        need_exclamation.insert(
+            "str_constants".into(),
+            Box::new(BoolFlag(
+                |config| &mut config.str_constants,
+                |config| &config.str_constants,
+            )),
+        );
+        need_exclamation.insert("method_rename".into(), Box::new(MethodRename));
         need_exclamation.insert("name".into(), Box::new(ModName));
+        need_exclamation.insert("rust_mod_path".into(), Box::new(RustModPath));
+        need_exclamation.insert("symbol_prefix".into(), Box::new(SymbolPrefix));
+        need_exclamation.insert("assertion_handler".into(), Box::new(AssertionHandler));
+        need_exclamation.insert("exception_type".into(), Box::new(ExceptionType));
+        need_exclamation.insert("abi_version".into(), Box::new(AbiVersion));
+        need_exclamation.insert("reexport".into(), Box::new(Reexport));
+        need_exclamation.insert("destructure_return".into(), Box::new(DestructureReturn));
+        need_exclamation.insert("extra_cpp_includes".into(), Box::new(ExtraCppIncludes));
+        need_exclamation.insert("manual".into(), Box::new(Manual));
+        need_exclamation.insert("box_large_pod".into(), Box::new(BoxLargePod));
+        need_exclamation.insert("max_template_depth".into(), Box::new(MaxTemplateDepth));
+        need_exclamation.insert("instrument_calls".into(), Box::new(InstrumentCalls));
         need_exclamation.insert("concrete".into(), Box::new(Concrete));
+        need_exclamation.insert("instantiate".into(), Box::new(Instantiate));
         need_exclamation.insert("rust_type".into(), Box::new(RustType { output: false }));
         need_exclamation.insert(EXTERN_RUST_TYPE.into(), Box::new(RustType { output: true }));
         need_exclamation.insert(SUBCLASS.into(), Box::new(Subclass));
+        need_exclamation.insert("block_constructor".into(), Box::new(BlockConstructor));
+        need_exclamation.insert(
+            "bool_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.bool_params,
+                |config| &config.bool_params,
+            )),
+        );
+        need_exclamation.insert(
+            "wide_string_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.wide_string_params,
+                |config| &config.wide_string_params,
+            )),
+        );
+        need_exclamation.insert(
+            "mut_span_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.mut_span_params,
+                |config| &config.mut_span_params,
+            )),
+        );
+        need_exclamation.insert(
+            "vec_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.vec_params,
+                |config| &config.vec_params,
+            )),
+        );
+        need_exclamation.insert("array_param".into(), Box::new(ArrayParam));
+        need_exclamation.insert(
+            "restrict_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.restrict_params,
+                |config| &config.restrict_params,
+            )),
+        );
+        need_exclamation.insert(
+            "ptr_array_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.ptr_array_params,
+                |config| &config.ptr_array_params,
+            )),
+        );
+        need_exclamation.insert(
+            "nonnull_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.nonnull_params,
+                |config| &config.nonnull_params,
+            )),
+        );
+        need_exclamation.insert(
+            "opaque_ptr_param".into(),
+            Box::new(FnParamList(
+                |config| &mut config.opaque_ptr_params,
+                |config| &config.opaque_ptr_params,
+            )),
+        );
+        need_exclamation.insert("return_borrows".into(), Box::new(ReturnBorrows));
+        need_exclamation.insert("allocator".into(), Box::new(Allocator));
+        need_exclamation.insert("method_of".into(), Box::new(MethodOf));
+        need_exclamation.insert("namespace_type".into(), Box::new(NamespaceType));
+        need_exclamation.insert("byte_buffer".into(), Box::new(ByteBuffer));
+        need_exclamation.insert("opaque_handle".into(), Box::new(OpaqueHandle));
+        need_exclamation.insert("tagged_union".into(), Box::new(TaggedUnion));
+        need_exclamation.insert("range".into(), Box::new(Range));
+        need_exclamation.insert("callable_member".into(), Box::new(CallableMember));
+        need_exclamation.insert("iterator_lookup".into(), Box::new(IteratorLookup));
+        need_exclamation.insert("inherited_method".into(), Box::new(InheritedMethod));
         need_exclamation.insert(EXTERN_RUST_FUN.into(), Box::new(ExternRustFun));
         need_exclamation.insert(
             "extern_cpp_type".into(),
-            Box::new(ExternCppType { opaque: false }),
+            Box::new(ExternCppType {
+                opaque: false,
+                from_cbindgen: false,
+            }),
         );
         need_exclamation.insert(
             "extern_cpp_opaque_type".into(),
-            Box::new(ExternCppType { opaque: true }),
+            Box::new(ExternCppType {
+                opaque: true,
+                from_cbindgen: false,
+            }),
+        );
+        need_exclamation.insert(
+            "cbindgen_type".into(),
+            Box::new(ExternCppType {
+                opaque: false,
+                from_cbindgen: true,
+            }),
         );
 
         DirectivesMap {
@@ -184,6 +441,54 @@ impl Directive for Generate {
     }
 }
 
+struct ConstexprFn;
+
+impl Directive for ConstexprFn {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let fun: syn::LitStr = args.parse()?;
+        config.constexpr_fns.push(fun.value());
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.constexpr_fns.iter().map(|fun| quote! { #fun }))
+    }
+}
+
+struct ThreadLocalVar;
+
+impl Directive for ThreadLocalVar {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        span: &Span,
+    ) -> ParseResult<()> {
+        let var: syn::LitStr = args.parse()?;
+        config
+            .allowlist
+            .push(AllowlistEntry::Item(var.value()))
+            .map_err(|e| allowlist_err_to_syn_err(e, span))?;
+        config.thread_local_vars.push(var.value());
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.thread_local_vars.iter().map(|var| quote! { #var }))
+    }
+}
+
 struct GenerateNs;
 
 impl Directive for GenerateNs {
@@ -274,6 +579,78 @@ impl Directive for Safety {
     }
 }
 
+struct LongDouble;
+
+impl Directive for LongDouble {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.long_double = args.parse()?;
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match config.long_double {
+            crate::LongDoubleHandling::Reject => Box::new(std::iter::empty()),
+            handling => Box::new(std::iter::once(handling.to_token_stream())),
+        }
+    }
+}
+
+struct ExceptionHandling;
+
+impl Directive for ExceptionHandling {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.exception_handling = args.parse()?;
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match config.exception_handling {
+            crate::ExceptionHandling::Abort => Box::new(std::iter::empty()),
+            handling => Box::new(std::iter::once(handling.to_token_stream())),
+        }
+    }
+}
+
+struct KeywordNames;
+
+impl Directive for KeywordNames {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.keyword_names = args.parse()?;
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match &config.keyword_names {
+            crate::KeywordNaming::TrailingUnderscore => Box::new(std::iter::empty()),
+            naming => Box::new(std::iter::once(naming.to_token_stream())),
+        }
+    }
+}
+
 fn allowlist_err_to_syn_err(err: AllowlistErr, span: &Span) -> syn::Error {
     syn::Error::new(*span, format!("{err}"))
 }
@@ -343,6 +720,43 @@ where
     }
 }
 
+/// A directive naming a parameter of a function, as
+/// `directive!("function", param)`, which may be given many times.
+struct FnParamList<SET, GET>(SET, GET)
+where
+    SET: Fn(&mut IncludeCppConfig) -> &mut Vec<(String, Ident)>,
+    GET: Fn(&IncludeCppConfig) -> &Vec<(String, Ident)>;
+
+impl<SET, GET> Directive for FnParamList<SET, GET>
+where
+    SET: Fn(&mut IncludeCppConfig) -> &mut Vec<(String, Ident)> + Sync + Send,
+    GET: Fn(&IncludeCppConfig) -> &Vec<(String, Ident)> + Sync + Send,
+{
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let fun: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let param: syn::Ident = args.parse()?;
+        self.0(config).push((fun.value(), param));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(self.1(config).iter().map(|(fun, param)| {
+            quote! {
+                #fun,#param
+            }
+        }))
+    }
+}
+
 struct ModName;
 
 impl Directive for ModName {
@@ -353,6 +767,12 @@ impl Directive for ModName {
         _ident_span: &Span,
     ) -> ParseResult<()> {
         let id: Ident = args.parse()?;
+        if config.rust_mod_path.is_some() {
+            return Err(syn::Error::new(
+                id.span(),
+                "name! can't be used alongside rust_mod_path!, which already names the mod",
+            ));
+        }
         config.mod_name = Some(id);
         Ok(())
     }
@@ -362,25 +782,61 @@ impl Directive for ModName {
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
         match &config.mod_name {
-            None => Box::new(std::iter::empty()),
-            Some(id) => Box::new(std::iter::once(quote! { #id })),
+            Some(id) if config.rust_mod_path.is_none() => Box::new(std::iter::once(quote! { #id })),
+            _ => Box::new(std::iter::empty()),
         }
     }
 }
 
-struct Concrete;
+struct RustModPath;
 
-impl Directive for Concrete {
+impl Directive for RustModPath {
     fn parse(
         &self,
         args: ParseStream,
         config: &mut IncludeCppConfig,
         _ident_span: &Span,
     ) -> ParseResult<()> {
-        let definition: syn::LitStr = args.parse()?;
-        args.parse::<syn::token::Comma>()?;
-        let rust_id: syn::Ident = args.parse()?;
-        config.concretes.0.insert(definition.value(), rust_id);
+        let path: syn::Path = args.parse()?;
+        if config.mod_name.is_some() {
+            return Err(syn::Error::new_spanned(
+                path,
+                "rust_mod_path! can't be used alongside name!, since it names the mod itself",
+            ));
+        }
+        if path.leading_colon.is_some() || path.segments.iter().any(|seg| !seg.arguments.is_empty())
+        {
+            return Err(syn::Error::new_spanned(
+                path,
+                "rust_mod_path! expects a simple path of modules, such as crate::bindings::cpp",
+            ));
+        }
+        if path.segments.iter().any(|seg| seg.ident == "super") {
+            return Err(syn::Error::new_spanned(
+                path,
+                "rust_mod_path! is relative to the include_cpp! and can't use super",
+            ));
+        }
+        if let Some(seg) = path
+            .segments
+            .iter()
+            .skip(1)
+            .find(|seg| seg.ident == "crate" || seg.ident == "self")
+        {
+            return Err(syn::Error::new_spanned(
+                seg,
+                "crate or self may only appear at the start of rust_mod_path!",
+            ));
+        }
+        let mod_name = &path.segments.last().unwrap().ident;
+        if mod_name == "crate" || mod_name == "self" {
+            return Err(syn::Error::new_spanned(
+                path,
+                "rust_mod_path! must end with the name of the mod to generate",
+            ));
+        }
+        config.mod_name = Some(mod_name.clone());
+        config.rust_mod_path = Some(path);
         Ok(())
     }
 
@@ -388,19 +844,13 @@ impl Directive for Concrete {
         &self,
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
-        Box::new(config.concretes.0.iter().map(|(k, v)| {
-            quote! {
-                #k,#v
-            }
-        }))
+        Box::new(config.rust_mod_path.iter().map(|path| quote! { #path }))
     }
 }
 
-struct RustType {
-    output: bool,
-}
+struct SymbolPrefix;
 
-impl Directive for RustType {
+impl Directive for SymbolPrefix {
     fn parse(
         &self,
         args: ParseStream,
@@ -408,7 +858,17 @@ impl Directive for RustType {
         _ident_span: &Span,
     ) -> ParseResult<()> {
         let id: Ident = args.parse()?;
-        config.rust_types.push(RustPath::new_from_ident(id));
+        // Identifiers beginning with an underscore, or containing a double
+        // underscore, are reserved for the C++ implementation, so they
+        // might collide with real symbols.
+        let id_string = id.to_string();
+        if id_string.starts_with('_') || id_string.contains("__") {
+            return Err(syn::Error::new(
+                id.span(),
+                "symbol_prefix! must not start with an underscore or contain a double underscore, since such names are reserved in C++",
+            ));
+        }
+        config.symbol_prefix = Some(id);
         Ok(())
     }
 
@@ -416,29 +876,755 @@ impl Directive for RustType {
         &self,
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
-        if self.output {
+        match &config.symbol_prefix {
+            None => Box::new(std::iter::empty()),
+            Some(id) => Box::new(std::iter::once(quote! { #id })),
+        }
+    }
+}
+
+struct AssertionHandler;
+
+impl Directive for AssertionHandler {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        config.assertion_handler = Some(args.parse()?);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.assertion_handler.iter().map(|id| quote! { #id }))
+    }
+}
+
+struct AbiVersion;
+
+impl Directive for AbiVersion {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let actual: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let expected: syn::LitStr = args.parse()?;
+        config.abi_version = Some((actual.value(), expected.value()));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .abi_version
+                .iter()
+                .map(|(actual, expected)| quote! { #actual,#expected }),
+        )
+    }
+}
+
+struct ExceptionType;
+
+impl Directive for ExceptionType {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let cpp_type: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let variant: Ident = args.parse()?;
+        config.exception_types.push((cpp_type.value(), variant));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .exception_types
+                .iter()
+                .map(|(cpp_type, variant)| quote! { #cpp_type, #variant }),
+        )
+    }
+}
+
+struct Reexport;
+
+impl Directive for Reexport {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let cpp_name: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let alias: Ident = args.parse()?;
+        config.reexports.push((cpp_name.value(), alias));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .reexports
+                .iter()
+                .map(|(cpp_name, alias)| quote! { #cpp_name, #alias }),
+        )
+    }
+}
+
+struct DestructureReturn;
+
+impl Directive for DestructureReturn {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let fn_name: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let struct_path: RustPath = args.parse()?;
+        config
+            .destructured_returns
+            .push((fn_name.value(), struct_path));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .destructured_returns
+                .iter()
+                .map(|(fn_name, struct_path)| quote! { #fn_name, #struct_path }),
+        )
+    }
+}
+
+struct ExtraCppIncludes;
+
+impl Directive for ExtraCppIncludes {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let headers;
+        syn::bracketed!(headers in args);
+        config.extra_cpp_includes.extend(
+            headers
+                .parse_terminated::<syn::LitStr, syn::token::Comma>(|input| input.parse())?
+                .into_iter()
+                .map(|hdr| hdr.value()),
+        );
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        let headers = &config.extra_cpp_includes;
+        Box::new(
+            (!headers.is_empty())
+                .then(|| quote! { [ #(#headers),* ] })
+                .into_iter(),
+        )
+    }
+}
+
+struct Manual;
+
+impl Directive for Manual {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        while !args.is_empty() {
+            let item: syn::ForeignItem = args.parse()?;
+            if !matches!(item, syn::ForeignItem::Fn(_) | syn::ForeignItem::Type(_)) {
+                return Err(syn::Error::new_spanned(
+                    item,
+                    "manual! may only contain function and type declarations",
+                ));
+            }
+            config.manual_bridge_items.push(item);
+        }
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(
+            config
+                .manual_bridge_items
+                .iter()
+                .map(|item| item.to_token_stream()),
+        )
+    }
+}
+
+struct MethodRename;
+
+impl Directive for MethodRename {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let pattern: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let replacement: syn::LitStr = args.parse()?;
+        if let Err(err) = regex::Regex::new(&pattern.value()) {
+            return Err(syn::Error::new(
+                pattern.span(),
+                format!("method_rename! pattern is not a valid regular expression: {err}"),
+            ));
+        }
+        config
+            .method_renames
+            .push((pattern.value(), replacement.value()));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.method_renames.iter().map(|(pattern, replacement)| {
+            quote! {
+                #pattern,#replacement
+            }
+        }))
+    }
+}
+
+struct BoxLargePod;
+
+impl Directive for BoxLargePod {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let threshold: syn::LitInt = args.parse()?;
+        config.box_large_pod = Some(threshold.base10_parse()?);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match config.box_large_pod {
+            None => Box::new(std::iter::empty()),
+            Some(threshold) => {
+                let threshold = syn::LitInt::new(&threshold.to_string(), Span::call_site());
+                Box::new(std::iter::once(quote! { #threshold }))
+            }
+        }
+    }
+}
+
+struct MaxTemplateDepth;
+
+impl Directive for MaxTemplateDepth {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let depth: syn::LitInt = args.parse()?;
+        let value: usize = depth.base10_parse()?;
+        if value == 0 {
+            return Err(syn::Error::new(
+                depth.span(),
+                "max_template_depth must be at least 1",
+            ));
+        }
+        config.max_template_depth = Some(value);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match config.max_template_depth {
+            None => Box::new(std::iter::empty()),
+            Some(depth) => {
+                let depth = syn::LitInt::new(&depth.to_string(), Span::call_site());
+                Box::new(std::iter::once(quote! { #depth }))
+            }
+        }
+    }
+}
+
+struct InstrumentCalls;
+
+impl Directive for InstrumentCalls {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let path: syn::Path = args.parse()?;
+        config.instrumentation_macro = Some(path);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        match &config.instrumentation_macro {
+            None => Box::new(std::iter::empty()),
+            Some(path) => Box::new(std::iter::once(path.to_token_stream())),
+        }
+    }
+}
+
+struct Concrete;
+
+impl Directive for Concrete {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let definition: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let rust_id: syn::Ident = args.parse()?;
+        config.concretes.0.insert(definition.value(), rust_id);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.concretes.0.iter().map(|(k, v)| {
+            quote! {
+                #k,#v
+            }
+        }))
+    }
+}
+
+/// Like `concrete!`, but with the template's arguments given separately,
+/// so that they can include integers as well as types, and with a Rust
+/// name made up from them if none is given.
+struct Instantiate;
+
+impl Directive for Instantiate {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let template: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let template_args;
+        syn::bracketed!(template_args in args);
+        let template_args: Vec<String> = template_args
+            .parse_terminated::<_, syn::token::Comma>(parse_template_arg)?
+            .into_iter()
+            .collect();
+        if template_args.is_empty() {
+            return Err(syn::Error::new(
+                template.span(),
+                "instantiate! needs at least one template argument",
+            ));
+        }
+        let rust_id: Ident = if args.parse::<Option<syn::token::Comma>>()?.is_some() {
+            args.parse()?
+        } else {
+            let template_name = template.value();
+            let final_name = template_name.rsplit("::").next().unwrap_or_default();
+            let name = format!("{final_name}_{}", template_args.join("_"))
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+            syn::parse_str(&name).map_err(|_| {
+                syn::Error::new(
+                    template.span(),
+                    "couldn't make a Rust name for this instantiation, so please provide one",
+                )
+            })?
+        };
+        let definition = format!("{}<{}>", template.value(), template_args.join(", "));
+        config.instantiations.push(rust_id.clone());
+        config.concretes.0.insert(definition, rust_id);
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        _config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        // These are output as concrete! directives instead.
+        Box::new(std::iter::empty())
+    }
+}
+
+/// A template argument is either a C++ type, as a string, or a non-type
+/// parameter such as an integer or boolean.
+fn parse_template_arg(input: ParseStream) -> ParseResult<String> {
+    if input.peek(syn::LitStr) {
+        Ok(input.parse::<syn::LitStr>()?.value())
+    } else if input.peek(syn::LitBool) {
+        Ok(input.parse::<syn::LitBool>()?.value.to_string())
+    } else {
+        let negative = input.parse::<Option<syn::token::Sub>>()?.is_some();
+        let value: syn::LitInt = input.parse()?;
+        let digits = value.base10_digits();
+        Ok(if negative {
+            format!("-{digits}")
+        } else {
+            digits.to_string()
+        })
+    }
+}
+
+struct RustType {
+    output: bool,
+}
+
+impl Directive for RustType {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let id: Ident = args.parse()?;
+        config.rust_types.push(RustPath::new_from_ident(id));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        if self.output {
             Box::new(config.rust_types.iter().map(|rp| rp.to_token_stream()))
         } else {
-            Box::new(std::iter::empty())
+            Box::new(std::iter::empty())
+        }
+    }
+}
+
+struct Subclass;
+
+impl Directive for Subclass {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let superclass: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let subclass: syn::Ident = args.parse()?;
+        config.subclasses.push(crate::config::Subclass {
+            superclass: superclass.value(),
+            subclass,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.subclasses.iter().map(|sc| {
+            let superclass = &sc.superclass;
+            let subclass = &sc.subclass;
+            quote! {
+                #superclass,#subclass
+            }
+        }))
+    }
+}
+
+struct BlockConstructor;
+
+impl Directive for BlockConstructor {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let params: syn::LitStr = args.parse()?;
+        config
+            .blocked_constructors
+            .push((ty.value(), params.value()));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.blocked_constructors.iter().map(|(ty, params)| {
+            quote! {
+                #ty,#params
+            }
+        }))
+    }
+}
+
+struct ArrayParam;
+
+impl Directive for ArrayParam {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let fun: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let param: syn::Ident = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let len: syn::LitInt = args.parse()?;
+        config
+            .array_params
+            .push((fun.value(), param, len.base10_parse()?));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.array_params.iter().map(|(fun, param, len)| {
+            let len = syn::LitInt::new(&len.to_string(), param.span());
+            quote! {
+                #fun,#param,#len
+            }
+        }))
+    }
+}
+
+struct Allocator;
+
+impl Directive for Allocator {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        let value = if args.parse::<Option<syn::token::Comma>>()?.is_some() {
+            Some(args.parse::<syn::LitStr>()?.value())
+        } else {
+            None
+        };
+        config.allocators.push((ty.value(), value));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.allocators.iter().map(|(ty, value)| match value {
+            Some(value) => quote! { #ty,#value },
+            None => quote! { #ty },
+        }))
+    }
+}
+
+struct ReturnBorrows;
+
+impl Directive for ReturnBorrows {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let fun: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        // `self` is a keyword, so isn't accepted by a plain `Ident` parse.
+        let params = Punctuated::<Ident, syn::token::Comma>::parse_separated_nonempty_with(
+            args,
+            Ident::parse_any,
+        )?;
+        config
+            .return_borrows
+            .push((fun.value(), params.into_iter().collect()));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.return_borrows.iter().map(|(fun, params)| {
+            quote! {
+                #fun, #(#params),*
+            }
+        }))
+    }
+}
+
+struct ByteBuffer;
+
+impl Directive for ByteBuffer {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let data_method: syn::Ident = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let size_method: syn::Ident = args.parse()?;
+        config.byte_buffers.push(crate::config::ByteBuffer {
+            ty: ty.value(),
+            data_method,
+            size_method,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.byte_buffers.iter().map(|bb| {
+            let ty = &bb.ty;
+            let data_method = &bb.data_method;
+            let size_method = &bb.size_method;
+            quote! {
+                #ty,#data_method,#size_method
+            }
+        }))
+    }
+}
+
+struct OpaqueHandle;
+
+impl Directive for OpaqueHandle {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let handle: syn::Ident = args.parse()?;
+        let destroy = if args.parse::<Option<syn::token::Comma>>()?.is_some() {
+            Some(args.parse::<syn::LitStr>()?.value())
+        } else {
+            None
+        };
+        let mut constructors = Vec::new();
+        while destroy.is_some() && args.parse::<Option<syn::token::Comma>>()?.is_some() {
+            constructors.push(args.parse::<syn::LitStr>()?.value());
         }
+        config.opaque_handles.push(crate::config::OpaqueHandle {
+            ty: ty.value(),
+            handle,
+            destroy,
+            constructors,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.opaque_handles.iter().map(|oh| {
+            let ty = &oh.ty;
+            let handle = &oh.handle;
+            let constructors = &oh.constructors;
+            match &oh.destroy {
+                Some(destroy) => quote! {
+                    #ty,#handle,#destroy #(,#constructors)*
+                },
+                None => quote! {
+                    #ty,#handle
+                },
+            }
+        }))
     }
 }
 
-struct Subclass;
+struct TaggedUnion;
 
-impl Directive for Subclass {
+impl Directive for TaggedUnion {
     fn parse(
         &self,
         args: ParseStream,
         config: &mut IncludeCppConfig,
         _ident_span: &Span,
     ) -> ParseResult<()> {
-        let superclass: syn::LitStr = args.parse()?;
+        let ty: syn::LitStr = args.parse()?;
         args.parse::<syn::token::Comma>()?;
-        let subclass: syn::Ident = args.parse()?;
-        config.subclasses.push(crate::config::Subclass {
-            superclass: superclass.value(),
-            subclass,
+        let tag_field: syn::Ident = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let union_field: syn::Ident = args.parse()?;
+        let mut variants = Vec::new();
+        while args.parse::<Option<syn::token::Comma>>()?.is_some() {
+            if args.is_empty() {
+                break;
+            }
+            let negative = args.parse::<Option<syn::token::Sub>>()?.is_some();
+            let tag: syn::LitInt = args.parse()?;
+            let tag: i64 = tag.base10_parse()?;
+            args.parse::<syn::token::FatArrow>()?;
+            let member: syn::Ident = args.parse()?;
+            variants.push(crate::config::TaggedUnionVariant {
+                tag: if negative { -tag } else { tag },
+                member,
+            });
+        }
+        config.tagged_unions.push(crate::config::TaggedUnion {
+            ty: ty.value(),
+            tag_field,
+            union_field,
+            variants,
         });
         Ok(())
     }
@@ -447,11 +1633,256 @@ impl Directive for Subclass {
         &self,
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
-        Box::new(config.subclasses.iter().map(|sc| {
-            let superclass = &sc.superclass;
-            let subclass = &sc.subclass;
+        Box::new(config.tagged_unions.iter().map(|tu| {
+            let ty = &tu.ty;
+            let tag_field = &tu.tag_field;
+            let union_field = &tu.union_field;
+            let variants = tu.variants.iter().map(|v| {
+                let tag = proc_macro2::Literal::u64_unsuffixed(v.tag.unsigned_abs());
+                let sign = if v.tag < 0 { Some(quote! { - }) } else { None };
+                let member = &v.member;
+                quote! { #sign #tag => #member }
+            });
             quote! {
-                #superclass,#subclass
+                #ty,#tag_field,#union_field #(,#variants)*
+            }
+        }))
+    }
+}
+
+struct Range;
+
+impl Directive for Range {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let item_ty: syn::LitStr = args.parse()?;
+        config.ranges.push(crate::config::Range {
+            ty: ty.value(),
+            item_ty: item_ty.value(),
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.ranges.iter().map(|r| {
+            let ty = &r.ty;
+            let item_ty = &r.item_ty;
+            quote! {
+                #ty,#item_ty
+            }
+        }))
+    }
+}
+
+struct CallableMember;
+
+impl Directive for CallableMember {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let member: syn::Ident = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let signature: syn::TypeBareFn = args.parse()?;
+        config.callable_members.push(crate::config::CallableMember {
+            ty: ty.value(),
+            member,
+            signature,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.callable_members.iter().map(|cm| {
+            let ty = &cm.ty;
+            let member = &cm.member;
+            let signature = &cm.signature;
+            quote! {
+                #ty,#member,#signature
+            }
+        }))
+    }
+}
+
+struct IteratorLookup;
+
+impl Directive for IteratorLookup {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let method: syn::Ident = args.parse()?;
+        let member = if args.peek(syn::token::RArrow) {
+            args.parse::<syn::token::RArrow>()?;
+            Some(args.parse()?)
+        } else {
+            None
+        };
+        args.parse::<syn::token::Comma>()?;
+        let signature: syn::TypeBareFn = args.parse()?;
+        let returns_shared_ref = match &signature.output {
+            syn::ReturnType::Type(_, ty) => matches!(
+                ty.as_ref(),
+                syn::Type::Reference(syn::TypeReference {
+                    mutability: None,
+                    ..
+                })
+            ),
+            syn::ReturnType::Default => false,
+        };
+        if !returns_shared_ref {
+            return Err(syn::Error::new_spanned(
+                &signature,
+                "iterator_lookup! signatures must return a shared reference",
+            ));
+        }
+        config.iterator_lookups.push(crate::config::IteratorLookup {
+            ty: ty.value(),
+            method,
+            member,
+            signature,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.iterator_lookups.iter().map(|il| {
+            let ty = &il.ty;
+            let method = &il.method;
+            let member = il.member.iter();
+            let signature = &il.signature;
+            quote! {
+                #ty,#method #(-> #member)*,#signature
+            }
+        }))
+    }
+}
+
+struct InheritedMethod;
+
+impl Directive for InheritedMethod {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let mutable = args.parse::<Option<syn::token::Mut>>()?.is_some();
+        let method: syn::Ident = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let signature: syn::TypeBareFn = args.parse()?;
+        config
+            .inherited_methods
+            .push(crate::config::InheritedMethod {
+                ty: ty.value(),
+                method,
+                mutable,
+                signature,
+            });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.inherited_methods.iter().map(|im| {
+            let ty = &im.ty;
+            let mutability = im.mutable.then(|| quote! { mut });
+            let method = &im.method;
+            let signature = &im.signature;
+            quote! {
+                #ty,#mutability #method,#signature
+            }
+        }))
+    }
+}
+
+struct MethodOf;
+
+impl Directive for MethodOf {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ty: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let function: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let method: syn::LitStr = args.parse()?;
+        config.methods_of.push(crate::config::MethodOf {
+            ty: ty.value(),
+            function: function.value(),
+            method: method.parse()?,
+        });
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.methods_of.iter().map(|m| {
+            let ty = &m.ty;
+            let function = &m.function;
+            let method = m.method.to_string();
+            quote! {
+                #ty,#function,#method
+            }
+        }))
+    }
+}
+
+struct NamespaceType;
+
+impl Directive for NamespaceType {
+    fn parse(
+        &self,
+        args: ParseStream,
+        config: &mut IncludeCppConfig,
+        _ident_span: &Span,
+    ) -> ParseResult<()> {
+        let ns: syn::LitStr = args.parse()?;
+        args.parse::<syn::token::Comma>()?;
+        let ty: Ident = args.parse()?;
+        config.namespace_types.push((ns.value(), ty));
+        Ok(())
+    }
+
+    fn output<'a>(
+        &self,
+        config: &'a IncludeCppConfig,
+    ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
+        Box::new(config.namespace_types.iter().map(|(ns, ty)| {
+            quote! {
+                #ns,#ty
             }
         }))
     }
@@ -491,6 +1922,7 @@ impl Directive for ExternRustFun {
 
 struct ExternCppType {
     opaque: bool,
+    from_cbindgen: bool,
 }
 
 impl Directive for ExternCppType {
@@ -508,6 +1940,7 @@ impl Directive for ExternCppType {
             crate::config::ExternCppType {
                 rust_path,
                 opaque: self.opaque,
+                from_cbindgen: self.from_cbindgen,
             },
         );
         Ok(())
@@ -518,13 +1951,16 @@ impl Directive for ExternCppType {
         config: &'a IncludeCppConfig,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'a> {
         let opaque_needed = self.opaque;
+        let from_cbindgen_needed = self.from_cbindgen;
         Box::new(
             config
                 .externs
                 .0
                 .iter()
                 .filter_map(move |(definition, details)| {
-                    if details.opaque == opaque_needed {
+                    if details.opaque == opaque_needed
+                        && details.from_cbindgen == from_cbindgen_needed
+                    {
                         let rust_path = &details.rust_path;
                         Some(quote! {
                             #definition, #rust_path
@@ -12,14 +12,18 @@ mod config;
 mod directives;
 pub mod file_locations;
 mod multi_bindings;
+mod operators;
 mod path;
 mod subclass_attrs;
 
 pub use config::{
-    AllowlistEntry, ExternCppType, IncludeCppConfig, RustFun, Subclass, UnsafePolicy,
+    AllowlistEntry, ByteBuffer, CallableMember, ExceptionHandling, ExternCppType, IncludeCppConfig,
+    InheritedMethod, IteratorLookup, KeywordNaming, LongDoubleHandling, MethodOf, OpaqueHandle,
+    Range, RustFun, Subclass, TaggedUnion, TaggedUnionVariant, UnsafePolicy,
 };
 use file_locations::FileLocationStrategy;
 pub use multi_bindings::{MultiBindings, MultiBindingsErr};
+pub use operators::{cpp_operator_ident, cpp_operator_symbol};
 pub use path::RustPath;
 use proc_macro2::TokenStream as TokenStream2;
 pub use subclass_attrs::SubclassAttrs;
@@ -0,0 +1,105 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! C++ operator functions have names such as `operator+` which aren't
+//! valid identifiers. We ask bindgen to refer to them by substitute
+//! identifiers instead, and users can still ask for them by their
+//! C++ names in `generate!`.
+
+/// Prefix for the identifiers we give to C++ operator functions.
+const OPERATOR_IDENT_PREFIX: &str = "autocxx_operator_";
+
+/// Each C++ operator we know how to wrap, and the suffix used to make
+/// an identifier for it. Operators not listed here (e.g. `operator=`,
+/// `operator++`, `operator new` or conversion operators) are left alone.
+const CPP_OPERATORS: &[(&str, &str)] = &[
+    ("+", "plus"),
+    ("-", "minus"),
+    ("*", "star"),
+    ("/", "slash"),
+    ("%", "percent"),
+    ("^", "caret"),
+    ("&", "amp"),
+    ("|", "pipe"),
+    ("~", "tilde"),
+    ("!", "bang"),
+    ("<", "lt"),
+    (">", "gt"),
+    ("<<", "shl"),
+    (">>", "shr"),
+    ("+=", "plus_eq"),
+    ("-=", "minus_eq"),
+    ("*=", "star_eq"),
+    ("/=", "slash_eq"),
+    ("%=", "percent_eq"),
+    ("^=", "caret_eq"),
+    ("&=", "amp_eq"),
+    ("|=", "pipe_eq"),
+    ("<<=", "shl_eq"),
+    (">>=", "shr_eq"),
+    ("==", "eq_eq"),
+    ("!=", "bang_eq"),
+    ("<=", "le"),
+    (">=", "ge"),
+    ("&&", "and_and"),
+    ("||", "pipe_pipe"),
+    ("[]", "index"),
+    ("()", "call"),
+];
+
+/// If the final segment of `cpp_name` is a C++ operator which we can
+/// wrap (e.g. `ns::operator+`), returns the same name with that segment
+/// replaced by the identifier we use for the operator.
+pub fn cpp_operator_ident(cpp_name: &str) -> Option<String> {
+    let (ns, last) = match cpp_name.rsplit_once("::") {
+        Some((ns, last)) => (Some(ns), last),
+        None => (None, cpp_name),
+    };
+    let symbol = last.strip_prefix("operator")?.trim();
+    let (_, suffix) = CPP_OPERATORS.iter().find(|(sym, _)| *sym == symbol)?;
+    Some(match ns {
+        Some(ns) => format!("{ns}::{OPERATOR_IDENT_PREFIX}{suffix}"),
+        None => format!("{OPERATOR_IDENT_PREFIX}{suffix}"),
+    })
+}
+
+/// The inverse of [`cpp_operator_ident`] for a single identifier: returns
+/// the operator symbol (e.g. `+`) if this identifier represents one.
+/// Any numeric suffix added to distinguish overloads is ignored.
+pub fn cpp_operator_symbol(ident: &str) -> Option<&'static str> {
+    let suffix = ident
+        .strip_prefix(OPERATOR_IDENT_PREFIX)?
+        .trim_end_matches(|c: char| c.is_ascii_digit());
+    CPP_OPERATORS
+        .iter()
+        .find(|(_, s)| *s == suffix)
+        .map(|(sym, _)| *sym)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cpp_operator_ident, cpp_operator_symbol};
+
+    #[test]
+    fn test_operator_idents() {
+        assert_eq!(
+            cpp_operator_ident("operator+").as_deref(),
+            Some("autocxx_operator_plus")
+        );
+        assert_eq!(
+            cpp_operator_ident("a::b::operator<<=").as_deref(),
+            Some("a::b::autocxx_operator_shl_eq")
+        );
+        assert_eq!(cpp_operator_ident("operator="), None);
+        assert_eq!(cpp_operator_ident("operator int"), None);
+        assert_eq!(cpp_operator_ident("a::b::c"), None);
+        assert_eq!(cpp_operator_symbol("autocxx_operator_plus"), Some("+"));
+        assert_eq!(cpp_operator_symbol("autocxx_operator_shl_eq1"), Some("<<="));
+        assert_eq!(cpp_operator_symbol("plus"), None);
+    }
+}
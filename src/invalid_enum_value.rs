@@ -0,0 +1,25 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+/// The error returned by the `TryFrom<i64>` implementation which autocxx
+/// generates for each C++ enum, when the integer isn't the value of any of
+/// its enumerators. For a [`bitflags_enum`](crate::bitflags_enum), any
+/// combination of bits is valid, so this is only returned if the integer
+/// doesn't fit in the enum's underlying type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidEnumValue(pub i64);
+
+impl fmt::Display for InvalidEnumValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a valid value for this enum", self.0)
+    }
+}
+
+impl std::error::Error for InvalidEnumValue {}
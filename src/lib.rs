@@ -14,10 +14,14 @@
 // do anything - all the magic is handled entirely by
 // autocxx_macro::include_cpp_impl.
 
+mod invalid_enum_value;
+mod raii_guard;
 mod reference_wrapper;
 mod rvalue_param;
 pub mod subclass;
+mod thread_local;
 mod value_param;
+mod wide_string;
 
 pub use reference_wrapper::{AsCppMutRef, AsCppRef, CppMutRef, CppPin, CppRef, CppUniquePtrPin};
 
@@ -64,7 +68,10 @@ pub use reference_wrapper::{AsCppMutRef, AsCppRef, CppMutRef, CppPin, CppRef, Cp
 /// Within the braces of the `include_cpp!{...}` macro, you should provide
 /// a list of at least the following:
 ///
-/// * `#include "cpp_header.h"`: a header filename to parse and include
+/// * `#include "cpp_header.h"`: a header filename to parse and include.
+///   You may give several, and all their bindings end up in the one module.
+///   Types declared in more than one of them (for instance, forward declared
+///   in one and defined in another) are generated only once.
 /// * `generate!("type_or_function_name")`: a type or function name whose declaration
 ///   should be made available to C++. (See the section on Allowlisting, below).
 /// * Optionally, `safety!(unsafe)` - see discussion of [`safety`].
@@ -120,231 +127,1584 @@ macro_rules! include {
 }
 
 /// Generate Rust bindings for the given C++ type or function.
+///
+/// Any types needed by the item are generated too, and so on
+/// transitively, but only so far as is required to call the item.
+/// For example, if you `generate!` a function taking a `const Foo&`,
+/// you'll get the type `Foo` but not its methods (and thus not the
+/// types _they_ use). To get those methods, `generate!("Foo")` as well.
+/// This means a short list of entry points into a large library
+/// produces a correspondingly small set of bindings.
+///
+/// Functions marked `[[nodiscard]]` in C++ are generated with
+/// `#[must_use]`. Any reason given in `[[nodiscard("reason")]]` is
+/// dropped, because the version of bindgen which autocxx uses doesn't
+/// report it.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+/// See also [generate_pod].
+#[macro_export]
+macro_rules! generate {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate as "plain old data" and add to allowlist.
+/// Generate Rust bindings for the given C++ type such that
+/// it can be passed and owned by value in Rust. This only works
+/// for C++ types which have trivial move constructors and no
+/// destructor - you'll encounter a compile error otherwise.
+/// If your type doesn't match that description, use [generate]
+/// instead, and own the type using [UniquePtr][cxx::UniquePtr].
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! generate_pod {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate an accessor for a C++ `thread_local` variable.
+/// For example, `thread_local_var!("counter")` generates a function
+/// `counter()` returning a [`CppThreadLocal`] for the calling thread's
+/// instance of `counter`. This must be listed explicitly because
+/// we can't tell from bindgen output whether a global variable is
+/// `thread_local`. Only mutable variables are supported. Reading and
+/// writing the variable is `unsafe`, because C++ may already have
+/// destroyed it if you do so from a Rust thread-local destructor.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! thread_local_var {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Evaluate a zero-argument C++ `constexpr` function at build time and
+/// expose its result as a Rust `const` of the same name, usable in Rust
+/// `const` contexts.
+///
+/// The syntax is:
+/// `constexpr_fn!("namespace::function_name")`
+///
+/// The function isn't itself bound. Its result must be a compile-time
+/// constant of a type which Rust can represent as a `const` - an integer,
+/// floating point number or `bool` - or code generation fails.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! constexpr_fn {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate Rust bindings for all C++ types and functions
+/// in a given namespace.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+/// See also [generate].
+#[macro_export]
+macro_rules! generate_ns {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate Rust bindings for all C++ types and functions
+/// found. Highly experimental and not recommended.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+/// See also [generate].
+#[macro_export]
+macro_rules! generate_all {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate as "plain old data". For use with [generate_all]
+/// and similarly experimental.
+#[macro_export]
+macro_rules! pod {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Insist that a C++ type is opaque (non-POD), even where it would
+/// otherwise need to be POD because it's a field of some type requested
+/// using [generate_pod] or [pod]. Such a request is then an error,
+/// naming this type, rather than silently exposing its fields to Rust.
+/// This doesn't itself add the type to the allowlist.
+///
+/// The syntax is:
+/// `opaque!("Foo")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! opaque {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Skip the normal generation of a `make_string` function
+/// and other utilities which we might generate normally.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! exclude_utilities {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make constructors, and functions which return C++ objects by value,
+/// return a `Pin<Box<T>>` instead of an `impl New`. This is an experimental
+/// option for codebases where objects created from Rust are owned by Rust
+/// alone, since it avoids needing to call `within_box()` or
+/// `within_unique_ptr()` on every such return value.
+///
+/// Objects returned in this way live in a Rust [`Box`], so they can't be
+/// passed to C++ APIs which expect to take ownership via a
+/// `std::unique_ptr`. Such APIs still need a [`cxx::UniquePtr`], which
+/// you can get by moving the object out of the `Box` using
+/// [`moveit::new::mov`] and [`WithinUniquePtr::within_unique_ptr`].
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! boxed_returns {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Give each struct explicit `__bindgen_padding_N` fields wherever C++
+/// would leave padding bytes, including at the end of the struct, rather
+/// than relying on the Rust compiler to insert matching padding. This makes
+/// the layout of POD types self-documenting, and suits structs whose bytes
+/// are also serialized or shared with other languages. The padding fields
+/// are byte arrays, so they can be zeroed when such a struct is built in
+/// Rust.
+///
+/// bindgen computes the padding from the layout which clang reports, so
+/// bitfields (which bindgen represents as opaque storage units) and
+/// over-aligned members are accounted for. The size and alignment of each
+/// POD type are checked against that layout at compile time regardless of
+/// this option.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! explicit_padding {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Where a C++ method has both a `const` and a non-`const` overload with
+/// the same parameters, such as
+/// ```cpp
+/// const char* data() const;
+/// char* data();
+/// ```
+/// name the non-`const` one with a `_mut` suffix (`data` and `data_mut`),
+/// following the usual Rust convention for accessor pairs. Without this,
+/// the second overload which autocxx encounters would get a numeric
+/// suffix (`data1`), whichever it is. Methods with only a non-`const`
+/// form keep their names.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! mut_suffix_overloads {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Rename generated methods by applying a regular expression substitution
+/// to their names, for example to strip a `Get` prefix so that `GetSize`
+/// becomes `Size`. Each rule is applied in turn to every method (other than
+/// constructors and virtual methods), using the syntax of the
+/// [regex](https://docs.rs/regex) crate, so `$1` in the replacement refers
+/// to the first capture group. If two methods end up with the same name,
+/// they're disambiguated by number, just like overloads.
+///
+/// The syntax is:
+/// `method_rename!("^Get(.+)$", "$1")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! method_rename {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Convert the names of generated methods to snake case, so that (for
+/// example) `IsEmpty` becomes `is_empty`. This happens after any
+/// [`method_rename`] rules are applied.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! snake_case_methods {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Mark the thin Rust wrappers which autocxx generates around calls into
+/// C++ as `#[inline(always)]`, rather than just `#[inline]`. Such
+/// wrappers (those which call straight through to the cxx bridge, with
+/// no extra C++ shim) are always at least `#[inline]`, so the compiler
+/// is free to inline them across crates; this forces it to do so, which
+/// may help for very frequently called functions.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! inline_always {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Mark the Rust functions which autocxx generates as `#[track_caller]`
+/// where they may panic, so that the panic message gives the location of
+/// your call rather than a line within the generated bindings. This
+/// applies to functions which panic on C++ exceptions (see
+/// [`exception_handling`]) and to the bounds-checked methods of
+/// `std::bitset`s.
+///
+/// This may be combined with [`inline_always`]: `#[track_caller]` adds a
+/// hidden location parameter, but doesn't stop the compiler inlining
+/// these functions. Functions which return `impl New` call into C++ only
+/// once the result is emplaced, so any panic there is still reported
+/// within autocxx.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! track_caller {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Return POD types larger than the given number of bytes in a
+/// [`cxx::UniquePtr`], rather than by value. A large POD type is costly to
+/// copy around on the stack, and this avoids doing so. Smaller POD types,
+/// and POD types whose size autocxx doesn't know, are still returned by
+/// value. This only affects return values: such types are still passed to
+/// functions by value, and can still be constructed on the Rust stack.
+///
+/// The syntax is:
+/// `box_large_pod!(256)`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! box_large_pod {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Limit how deeply templates which cxx understands, such as
+/// `std::vector` and `std::unique_ptr`, may be nested within one another
+/// before autocxx stops converting their arguments. A template nested
+/// more deeply than this becomes an opaque concrete type, as though cxx
+/// didn't understand it. A type nested exactly this deeply is converted
+/// in full. This keeps heavily generic C++ APIs from generating
+/// an excessive amount of code.
+///
+/// The syntax is:
+/// `max_template_depth!(3)`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! max_template_depth {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Entirely block some type from appearing in the generated
+/// code. This can be useful if there is a type which is not
+/// understood by bindgen or autocxx, and incorrect code is
+/// otherwise generated.
+/// This is 'greedy' in the sense that any functions/methods
+/// which take or return such a type will _also_ be blocked.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! block {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Avoid generating implicit constructors for this type.
+/// The rules for when to generate C++ implicit constructors
+/// are complex, and if autocxx gets it wrong, you can block
+/// such constructors using this.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! block_constructors {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// For each constructor of this type, also generate a `make_shared`
+/// function which creates the object within a [`cxx::SharedPtr`], for
+/// C++ APIs which share ownership of objects using `std::shared_ptr`.
+/// These are numbered to match the constructors, so the constructor
+/// `new2` is accompanied by `make_shared2`.
+///
+/// For example, `make_shared!("Widget")`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! make_shared {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Implement `From<&str>` for a type which has a constructor taking a
+/// single `std::string` (by value or by const reference), so that you can
+/// write `let p: Pin<Box<Path>> = "/tmp".into()`.
+///
+/// The conversion produces a `Pin<Box<T>>` because Rust's orphan rules
+/// don't allow autocxx to implement `From` for a [`cxx::UniquePtr`]. If you
+/// need a `UniquePtr`, call the constructor and then
+/// [`WithinUniquePtr::within_unique_ptr`].
+///
+/// If the type has more than one constructor taking a string, it's not
+/// clear which `From` should use, so none is generated. The same is true
+/// if the constructor would be `unsafe` to call.
+///
+/// The syntax is:
+/// `string_constructible!("Path")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! string_constructible {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Treat a C++ type as an RAII guard, which acquires some resource such
+/// as a lock in its constructor and releases it in its destructor. Such a
+/// type can then be constructed straight into a [`CppGuard`], which is
+/// `#[must_use]` so that the resource isn't released by accident as soon as
+/// it's acquired, and which can't be sent to another thread, since many
+/// such resources must be released by the thread which acquired them.
+///
+/// For example, `raii_guard!("LockGuard")` lets you write
+/// `let guard = ffi::LockGuard::new(mutex).within_guard();`, holding the
+/// lock until `guard` is dropped.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! raii_guard {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Treat a C++ enum as a set of flags which can be combined using bitwise
+/// operators. Instead of a Rust `enum`, the type is generated as a
+/// `#[repr(transparent)]` struct wrapping the enum's underlying integer,
+/// with an associated constant for each enumerator, implementations of
+/// `BitOr`, `BitAnd`, `BitXor` and `Not` (and their assigning variants),
+/// and some helper methods such as `contains`. This means that values
+/// which don't correspond to any single enumerator can be represented.
+/// Its `TryFrom<i64>` implementation accepts any value which fits in the
+/// underlying integer.
+///
+/// For example, `bitflags_enum!("Permissions")`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! bitflags_enum {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Avoid generating bindings for one explicit constructor of a type,
+/// while keeping its others. This is useful if one constructor takes
+/// a parameter which autocxx can't handle.
+///
+/// The syntax is:
+/// `block_constructor!("Type", "parameter types")`
+///
+/// The parameter types are spelled as in C++ and separated by commas,
+/// e.g. `block_constructor!("Widget", "const std::string&, int")`,
+/// ignoring whitespace. Use `""` for the default constructor. The
+/// remaining constructors keep the names (`new1`, `new2` etc.) they'd
+/// have had anyway.
+///
+/// Spell each type as it's written in the constructor's declaration,
+/// with any namespace in full: typedefs aren't resolved, so a `uint32_t`
+/// parameter must be given as `uint32_t` rather than `unsigned int`, and
+/// vice versa. It's an error if an entry matches none of the type's
+/// constructors; the error lists how autocxx spells each of them.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! block_constructor {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make narrow integer parameters of a function (such as `uint8_t`,
+/// `int16_t` or `unsigned int`) accept an `i64` in Rust. The generated wrapper checks
+/// that each value fits into the C++ parameter type, and the function
+/// returns a `Result` whose error is [`core::num::TryFromIntError`]
+/// if it doesn't. This also catches negative values passed to unsigned
+/// parameters, rather than silently truncating them.
+///
+/// The syntax is:
+/// `checked_narrowing!("function_name")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! checked_narrowing {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a function returns a `std::shared_ptr<const T>` or a
+/// `std::weak_ptr<const T>`. `cxx` can't represent a smart pointer to a
+/// `const` type, so the generated wrapper casts away the `const` and
+/// the function returns a [`cxx::SharedPtr<T>`] or [`cxx::WeakPtr<T>`].
+/// That's fine from Rust, which only gets shared access to the target,
+/// but be careful passing the result back to C++ APIs which take a
+/// smart pointer to a non-`const` `T`: they can then mutate an object
+/// which was handed out as `const`.
+///
+/// The syntax is:
+/// `const_smart_ptr_return!("function_name")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! const_smart_ptr_return {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Expose an integer parameter of a function to Rust as a `bool`.
+/// This is useful where C++ APIs use `int`, or a `BOOL` typedef of
+/// `int`, to represent a flag. The generated wrapper accepts a `bool`
+/// and passes `0` or `1` to the original C++ function.
+///
+/// It's an error if the function isn't generated, or has no integer
+/// parameter of that name.
+///
+/// The syntax is:
+/// `bool_param!("function_name", parameter_name)`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! bool_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Expose a `const wchar_t*` parameter of a function (for example, a Win32
+/// `LPCWSTR`) so that it accepts a Rust string. The generated wrapper
+/// accepts anything implementing [`ToWideCString`] - such as `&str`,
+/// `String` or a slice of wide characters - converts it to a
+/// null-terminated buffer, and passes a pointer to that buffer to the
+/// original C++ function. The function no longer needs to be `unsafe` on
+/// account of this parameter.
+///
+/// The syntax is:
+/// `wide_string_param!("function_name", parameter_name)`
+///
+/// The buffer only lives for the duration of the call, so this isn't
+/// suitable if the C++ function retains the pointer. It's also not possible
+/// to pass a null pointer this way.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! wide_string_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Don't generate the placeholder structs and methods which otherwise
+/// explain why autocxx couldn't generate bindings for some item. This
+/// makes the generated code smaller and tidier, at the cost of these
+/// explanations: referring to such an item is simply an error, and you
+/// may find it useful to leave this out while developing. Items for which
+/// bindings were generated are unaffected.
+///
+/// The syntax is:
+/// `suppress_ignored_items!()`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! suppress_ignored_items {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate Rust code which refers to `core` and `alloc` rather than `std`,
+/// so that it can be used within a `no_std` crate (which must also declare
+/// `extern crate alloc`). This must be used together with
+/// [`exclude_utilities`], since the string utilities depend on `std`.
+///
+/// This only affects the code generated by autocxx. It doesn't (yet) make
+/// the `autocxx` crate itself `no_std`, and `cxx` has its own restrictions
+/// on what's available without `std`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! no_std {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Allow functions taking a `const std::string&` to accept anything which
+/// implements `ToCppStringRef`, rather than only a `&CxxString`. That
+/// includes `&str`, `String`, `&CxxString` and `&UniquePtr<CxxString>`.
+/// Rust strings are copied into a new C++ string for the duration of the
+/// call, whereas C++ strings are passed directly, so they remain the way
+/// to pass strings which aren't valid UTF-8.
+///
+/// This has no effect if [`exclude_utilities`] is used.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! flexible_string_params {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Expose string constants, such as `constexpr const char* VERSION = "1.2.3";`
+/// or `#define VERSION "1.2.3"`, as `&str`s. Otherwise they're byte arrays
+/// including the null terminator. Any string which isn't valid UTF-8 remains
+/// a byte array.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! str_constants {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Pass a C++20 `std::span` parameter of a function from a `&mut [T]`,
+/// rather than a `&[T]`. autocxx passes any `std::span` parameter from a
+/// Rust slice, but can't tell whether the span's elements are `const`, so
+/// by default assumes they are. Use this for parameters declared like
+/// `std::span<T>`, as opposed to `std::span<const T>`. Without it, the
+/// generated C++ for such a function fails to compile.
+///
+/// The syntax is:
+/// `mut_span_param!("function_name", parameter_name)`
+///
+/// The span only refers to the slice for the duration of the call, so
+/// this isn't suitable if the C++ function retains the span.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! mut_span_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Pass a `std::vector` parameter of a function from a Rust `Vec`, rather
+/// than from a `UniquePtr<CxxVector<T>>`. This works for parameters taken
+/// by value or by rvalue reference (`std::vector<T>&&`).
+///
+/// The syntax is:
+/// `vec_param!("function_name", parameter_name)`
+///
+/// The vector's elements must be primitives or POD types. The C++ wrapper
+/// function copies them into a new `std::vector`, which is then passed
+/// (or moved) into the C++ function, and the Rust `Vec` is dropped.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! vec_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Expose a pointer parameter of a function, which in fact points to a
+/// fixed number of elements, as a reference to a Rust array of that size.
+/// This is typical of C APIs declared like `void f(int arr[4])`, where the
+/// array decays to a pointer. The generated wrapper accepts `&[T; N]` (or
+/// `&mut [T; N]` where the pointer is to mutable data) and passes a pointer to
+/// its first element, so the function no longer needs to be `unsafe` on
+/// account of this parameter, and callers can't pass the wrong number
+/// of elements.
+///
+/// The syntax is:
+/// `array_param!("function_name", parameter_name, length)`
+///
+/// Multi-dimensional arrays such as `int m[3][4]` aren't yet supported.
+/// Only the outer dimension decays, leaving a pointer to an array, which
+/// autocxx can't yet represent.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! array_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a pointer parameter of a function is `restrict`
+/// (or `__restrict`), meaning that while the function runs, the object it
+/// points to is accessed only through it. bindgen doesn't tell autocxx
+/// about such qualifiers, so you have to. The generated wrapper accepts a
+/// `&mut [T]` (or `&[T]` where the pointer is to `const` data) instead, and
+/// passes a pointer to its first element.
+///
+/// The syntax is:
+/// `restrict_param!("function_name", parameter_name)`
+///
+/// Rust's borrow checker then enforces the no-aliasing contract: you can't
+/// pass the same elements as two mutable `restrict` parameters, or as one
+/// mutable and one shared. (Use [`core::slice::from_mut`] or
+/// [`core::slice::from_ref`] to pass a single object.) autocxx can't tell how
+/// many elements the function will access, though - as in
+/// `void saxpy(int n, float a, const float* restrict x, float* restrict y)` -
+/// so the function remains `unsafe`: it's your responsibility to make sure
+/// each slice is long enough, and that the function doesn't hold on to the
+/// pointer after it returns.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! restrict_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a `const T* const*` parameter of a function is an array
+/// of pointers, as in `void run(int argc, const char* const* argv)`.
+/// The generated wrapper accepts a slice of references, `&[&T]` - or
+/// `&[&CStr]` where the pointers are to `char` - and builds the array of
+/// pointers to pass to C++.
+///
+/// The syntax is:
+/// `ptr_array_param!("function_name", parameter_name)`
+///
+/// The array is followed by a null pointer, as `argv`-style APIs commonly
+/// expect. autocxx can't know how many elements the function will read -
+/// typically it's told by another parameter - so the function remains
+/// `unsafe`.
+///
+/// The array is dropped as soon as the function returns, so this isn't
+/// suitable if the C++ function retains the array. (The strings or other
+/// objects it points to are merely borrowed, so their lifetimes are up to
+/// you as usual.)
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! ptr_array_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a pointer parameter of a function is never null, as
+/// C++ might say using `[[gnu::nonnull]]` or `_Nonnull` (which autocxx
+/// can't see for itself). The parameter is then treated just like a C++
+/// reference: it accepts `&T` for a const pointer, or `Pin<&mut T>` for a
+/// mutable one, so it no longer makes the function `unsafe`.
+///
+/// The syntax is:
+/// `nonnull_param!("function_name", parameter_name)`
+///
+/// The parameter can't also be listed in a directive which passes it as a
+/// pointer, such as [restrict_param] or [array_param]; if it is, the
+/// function isn't generated.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! nonnull_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a function doesn't dereference a pointer parameter, but
+/// merely passes it along or stores it: for instance, a `void* user_data`
+/// which is later handed back to a callback. With a [`safety`] policy
+/// which makes functions safe, a raw pointer parameter otherwise makes a
+/// function `unsafe`, since the caller must promise that the pointer is
+/// valid; this tells autocxx that no such promise is needed, so the
+/// function can be safe if nothing else makes it `unsafe`. The parameter
+/// is still a raw pointer, so anything which later does dereference it is
+/// still `unsafe`.
+///
+/// The syntax is:
+/// `opaque_ptr_param!("function_name", parameter_name)`
+///
+/// For pointers to incomplete types which work as handles throughout an
+/// API, [opaque_handle] is usually a better fit.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! opaque_ptr_param {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Says which parameters the reference returned by a function or method
+/// borrows from, where autocxx would otherwise refuse to guess because
+/// it takes several references.
+///
+/// The syntax is:
+/// `return_borrows!("function_name", parameter_name, ...)`
+///
+/// Use `self` for the receiver of a method. For example, for
+/// `const Value& Map::get(const Key& key) const` where the returned
+/// reference points into some storage owned by `key`, you'd write
+/// `return_borrows!("get", key)`, and the method becomes
+/// `fn get<'a>(&self, key: &'a Key) -> &'a Value`.
+/// If you list several parameters, all of them (and the output) share a
+/// single lifetime, so the output can't outlive any of them.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! return_borrows {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Names a C++ allocator type which autocxx should supply itself,
+/// rather than asking Rust callers for one. Wherever the trailing
+/// parameters of a function, method or constructor are of this type
+/// (by value or const reference) they're left out of the Rust signature,
+/// and the generated C++ wrapper passes the allocator instead.
+///
+/// The syntax is:
+/// `allocator!("arena::Allocator")` or
+/// `allocator!("arena::Allocator", "arena::default_allocator()")`
+///
+/// The second argument is any C++ expression yielding the allocator. If
+/// it's missing, the wrapper passes `{}`, which value-initializes it. The
+/// type is matched without any template arguments, so this also covers
+/// each instantiation of a templated allocator.
+///
+/// This only affects parameters. The allocator of a container such as
+/// `std::vector<T, arena::Allocator<T>>` is part of its type, so such a
+/// container is never a [`cxx::CxxVector`] and can't be given a
+/// different allocator.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! allocator {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Expose a free function as a method of the type to which its first
+/// parameter points. This suits C libraries written in an object-oriented
+/// style, where `widget_draw(Widget* w, int x)` is really a method of
+/// `Widget`: it becomes `Widget::draw`, taking `&self` (or
+/// `self: Pin<&mut Self>` if the pointer is to mutable data) in place of
+/// the pointer.
+///
+/// The syntax is:
+/// `method_of!("Type", "function_name", "method_name")`
+///
+/// The function must also be requested using [generate] or similar.
+/// If its first parameter isn't a pointer to the given type, it's not
+/// generated at all, and the reason is noted in the documentation of the
+/// generated bindings.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! method_of {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate a type with no contents for a namespace, and make the free
+/// functions of the namespace its associated functions, so that C++'s
+/// `math::sqrt(x)` is called as `ffi::math::Math::sqrt(x)` rather than
+/// `ffi::math::sqrt(x)`. This can read better for libraries which use
+/// namespaces like modules.
+///
+/// The syntax is:
+/// `namespace_type!("namespace", TypeName)`
+///
+/// The type goes in the namespace's own Rust module. Functions must still
+/// be requested using [generate], [generate_ns] or similar. Only functions
+/// directly within the namespace are affected: those in nested namespaces
+/// remain free functions unless their namespace is listed too, such as
+/// `namespace_type!("math::detail", Detail)`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! namespace_type {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// The name of the mod to be generated with the FFI code.
+/// The default is `ffi`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! name {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Where to put the generated mod, if not directly alongside the
+/// `include_cpp!`. For example, `rust_mod_path!(bindings::cpp)` generates
+/// a `pub mod bindings` containing `pub mod cpp`, which holds the bindings.
+/// The last segment names the mod, so this can't be combined with [name].
+///
+/// The syntax is:
+/// `rust_mod_path!(bindings::cpp)` or `rust_mod_path!(crate::bindings::cpp)`
+///
+/// The path is relative to the `include_cpp!`. A leading `crate` says that
+/// the `include_cpp!` is at the root of the crate, which is checked when
+/// the bindings are compiled. autocxx creates all the modules in the path,
+/// so they mustn't already exist. Each of them imports everything from its
+/// parent, so that the bindings can still refer to Rust types and functions
+/// named in [extern_rust_type], [extern_cpp_type] and similar using paths
+/// relative to the `include_cpp!`.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! rust_mod_path {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// A prefix to add to the names of C++ wrapper functions and other symbols
+/// which autocxx generates. Use this if several crates in the same binary
+/// use autocxx to wrap the same C++ library in the same way, which would
+/// otherwise produce duplicate symbols at link time. Typically you'd use
+/// the name of your crate.
+///
+/// The syntax is:
+/// `symbol_prefix!(my_crate)`
+///
+/// The prefix must not start with an underscore nor contain a double
+/// underscore, since such names are reserved in C++.
+///
+/// This doesn't affect C++ functions which cxx can call directly, without
+/// an autocxx wrapper, since cxx names those symbols after the C++ function.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! symbol_prefix {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Generate a C++ function to which a C++ library's assertion failures
+/// can be routed, which will turn them into a Rust panic.
+///
+/// The syntax is:
+/// `assertion_handler!(my_assert_failed)`
+///
+/// This generates a C++ function with the signature
+/// `[[noreturn]] void my_assert_failed(const char* expr, const char* file, int line)`.
+/// autocxx can't intercept the standard `assert` macro, so this is only
+/// useful for libraries which allow a custom assertion handler: you'll need
+/// to install this function as that handler from your own C++ code.
+///
+/// The panic can't unwind back into C++, so the process will still abort,
+/// but only after the panic hook has reported the failed expression and
+/// its location, along with a Rust backtrace if enabled.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! assertion_handler {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// A concrete type to make, for example
+/// `concrete!("Container<Contents>")`.
+/// All types must already be on the allowlist by having used
+/// `generate!` or similar.
+///
+/// If the type is a `std::variant`, e.g.
+/// `concrete!("std::variant<int, float>", IntOrFloat)`, it also gets a
+/// `variant()` method returning an enum (here `IntOrFloatVariant`) with
+/// a member for each alternative.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! concrete {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// A concrete instantiation of a template to make, with its arguments
+/// listed separately. This is like [`concrete!`], but the arguments can
+/// be integers or booleans as well as types (given as strings), for
+/// templates with non-type parameters, for example
+/// `instantiate!("Eigen::Matrix", ["double", 3, 3], Matrix3d)`.
+///
+/// The final Rust name is optional. Without it, a name is made from the
+/// template's name and its arguments, so `instantiate!("std::bitset", [64])`
+/// makes `bitset_64`.
+///
+/// Template arguments which have defaults may be left out, just as in
+/// C++: `instantiate!("std::vector", ["uint32_t"])` gives the same type
+/// as a function taking a `std::vector<uint32_t>` would use. clang fills
+/// in the defaults, so they may depend on earlier arguments.
+///
+/// As with [`concrete!`], the type is opaque. In general autocxx doesn't
+/// bind the template's member functions, so members which are only valid
+/// for some arguments (through SFINAE or `if constexpr`) can't cause the
+/// generated C++ to fail to compile. There are two exceptions, for
+/// standard library templates whose members autocxx knows it can use.
+///
+/// A `std::bitset` gets methods to use its bits: `test`, `set`, `reset`
+/// and `flip` for a single bit (which panic if it's out of range),
+/// `set_all`, `reset_all` and `flip_all`, `count`, `len`, `all`, `any` and
+/// `none`. Bitsets of up to 64 bits can also be converted to and from a
+/// `u64` with `to_u64` and `from_u64`; no integer can hold all the bits of
+/// a wider one. The single-bit methods are built upon `test_unchecked`
+/// and friends, which are also available but which abort the process if
+/// the bit is out of range, since C++ throws an exception that can't be
+/// passed back to Rust.
+///
+/// A `std::vector` whose elements cxx's `CxxVector` can't hold, such as
+/// `std::vector<bool>`, also gets `len` and `is_empty` methods.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! instantiate {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Specifies a global safety policy for functions generated
+/// from these headers. By default (without such a `safety!`
+/// directive) all such functions are marked as `unsafe` and
+/// therefore can only be called within an `unsafe {}` block
+/// or some `unsafe` function which you create.
+///
+/// Alternatively, by specifying a `safety!` block you can
+/// declare that most generated functions are in fact safe.
+/// Specifically, you'd specify:
+/// `safety!(unsafe)`
+/// or
+/// `safety!(unsafe_ffi)`
+/// These two options are functionally identical. If you're
+/// unsure, simply use `unsafe`. The reason for the
+/// latter option is if you have code review policies which
+/// might want to give a different level of scrutiny to
+/// C++ interop as opposed to other types of unsafe Rust code.
+/// Maybe in your organization, C++ interop is less scary than
+/// a low-level Rust data structure using pointer manipulation.
+/// Or maybe it's more scary. Either way, using `unsafe` for
+/// the data structure and using `unsafe_ffi` for the C++
+/// interop allows you to apply different linting tools and
+/// policies to the different options.
+///
+/// Irrespective, C++ code is of course unsafe. It's worth
+/// noting that use of C++ can cause unexpected unsafety at
+/// a distance in faraway Rust code. As with any use of the
+/// `unsafe` keyword in Rust, *you the human* are declaring
+/// that you've analyzed all possible ways that the code
+/// can be used and you are guaranteeing to the compiler that
+/// no badness can occur. Good luck.
+///
+/// Generated C++ APIs which use raw pointers remain `unsafe`
+/// no matter what policy you choose. To override the policy for
+/// individual functions, see [`safe_fn`] and [`unsafe_fn`].
+///
+/// There's an additional possible experimental safety
+/// policy available here:
+/// `safety!(unsafe_references_wrapped)`
+/// This policy treats C++ references as scary and requires
+/// them to be wrapped in a `CppRef` type: see [`CppRef`].
+/// This only works on nightly Rust because it
+/// depends upon an unstable feature
+/// (`arbitrary_self_types`). However, it should
+/// eliminate all undefined behavior related to Rust's
+/// stricter aliasing rules than C++.
+#[macro_export]
+macro_rules! safety {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make a function safe to call, overriding the [`safety`] policy.
+/// This is intended for functions which take raw pointers, yet which
+/// you've verified can't cause undefined behavior whatever pointers
+/// they're given - for instance because they merely compare them. The
+/// function still accepts raw pointers, but calling it no longer
+/// requires an `unsafe` block.
+///
+/// autocxx takes your word for it, so if the function does in fact
+/// dereference its pointers, safe Rust code will be able to cause
+/// undefined behavior.
+///
+/// The syntax is:
+/// `safe_fn!("function_name")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! safe_fn {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make a function `unsafe` to call, overriding the [`safety`] policy.
+/// This is useful for functions which have preconditions that Rust
+/// can't check, even though their parameters are all safe Rust types.
+///
+/// The syntax is:
+/// `unsafe_fn!("function_name")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! unsafe_fn {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make a `const` C++ method take `Pin<&mut Self>` rather than `&self`.
+/// Use this where a method is `const` but nevertheless changes state
+/// which Rust code can observe, for example through `mutable` members
+/// (such as a cache) which aren't synchronized. This doesn't apply to
+/// virtual methods.
+///
+/// The syntax is:
+/// `mutable_receiver!("Type::method")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! mutable_receiver {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Make a non-`const` C++ method take `&self` rather than
+/// `Pin<&mut Self>`. Use this where you know a method is safe to call
+/// on a shared object, for instance because it's internally synchronized.
+/// The generated C++ wrapper will `const_cast` the receiver.
+///
+/// autocxx takes your word for it, so if the method isn't in fact
+/// thread-safe, safe Rust code will be able to cause data races.
+/// This doesn't apply to virtual methods, nor when using
+/// [`safety!(unsafe_references_wrapped)`](safety).
+///
+/// The syntax is:
+/// `const_receiver!("Type::method")`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! const_receiver {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Whether to avoid generating [`cxx::UniquePtr`] and [`cxx::Vector`]
+/// implementations. This is primarily useful for reducing test cases and
+/// shouldn't be used in normal operation.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! exclude_impls {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a C++ type is not to be generated by autocxx in this case,
+/// but instead should refer to some pre-existing Rust type.
+///
+/// If you wish for the type to be POD, you can use a `pod!` directive too
+/// (but see the "requirements" section below).
+///
+/// The syntax is:
+/// `extern_cpp_type!("CppNameGoesHere", path::to::rust::type)`
+///
+/// Generally speaking, this should be used only to refer to types
+/// generated elsewhere by `autocxx` or `cxx` to ensure that they meet
+/// all the right requirements. It's possible - but fragile - to
+/// define such types yourself.
+///
+/// # Requirements for externally defined Rust types
+///
+/// It's generally expected that you would make such a type
+/// in Rust using a separate `include_cpp!` macro, or
+/// a manual `#[cxx::bridge]` directive somehwere. That is, this
+/// directive is intended mainly for use in cross-linking different
+/// sets of bindings in different mods, rather than truly to point to novel
+/// external types.
+///
+/// But with that in mind, here are the requirements you must stick to.
+///
+/// For non-POD external types:
+/// * The size and alignment of this type *must* be correct.
+///
+/// For POD external types:
+/// * As above
+/// * Your type must correspond to the requirements of
+///   [`cxx::kind::Trivial`]. In general, that means, no move constructor
+///   and no destructor. If you generate this type using `cxx` itself
+///   (or `autocxx`) this will be enforced using `static_assert`s
+///   within the generated C++ code. Without using those tools, you're
+///   on your own for determining this... and it's hard because the presence
+///   of particular fields or base classes may well result in your type
+///   violating those rules.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! extern_cpp_type {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a C++ type is not to be generated by autocxx in this case,
+/// but instead should refer to some pre-existing Rust type. Unlike
+/// `extern_cpp_type!`, there's no need for the size and alignment of this
+/// type to be correct.
+///
+/// The syntax is:
+/// `extern_cpp_opaque_type!("CppNameGoesHere", path::to::rust::type)`
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! extern_cpp_opaque_type {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a type in the C++ headers is in fact a `#[repr(C)]` Rust
+/// type, exported to C++ by [cbindgen](https://github.com/mozilla/cbindgen).
+/// Rather than generating the type from its C++ definition, autocxx
+/// refers to the Rust type, and implements [`cxx::ExternType`] for it so
+/// that cxx will accept it as the C++ type of the same name. It's treated
+/// as POD, so it can be passed by value and used as a field of C++ POD
+/// types.
+///
+/// The syntax is:
+/// `cbindgen_type!("CppNameGoesHere", path::to::rust::type)`
+///
+/// The Rust type must be defined in the same crate as the `include_cpp!`,
+/// since otherwise Rust won't let us implement `ExternType` for it.
+/// A cbindgen type and a C++ type may refer to one another - for example
+/// a field of the Rust type may be a pointer to a C++ type, which in turn
+/// contains the Rust type by value - so long as the C++ headers declare
+/// them in an order that C++ accepts.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! cbindgen_type {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Indicates that a C++ type holds a contiguous buffer of bytes, exposed
+/// through a pair of `const` accessor methods returning a data pointer and
+/// a length. autocxx will then implement `AsRef<[u8]>` for the type, so
+/// the contents can be read from Rust without copying.
+///
+/// The syntax is:
+/// `byte_buffer!("CppNameGoesHere", data_method, size_method)`
+///
+/// The resulting slice borrows the C++ object, so it can't outlive it,
+/// and the object can't be mutated through a `Pin<&mut>` while the slice
+/// exists. It's still your responsibility to ensure that the C++ type doesn't
+/// reallocate its buffer from within `const` methods.
+///
+/// The length may be of any integer type, such as `size_t` or `int`.
+/// `as_ref` panics if it doesn't fit in a `usize`, for example if it's
+/// negative.
+///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
-/// See also [generate_pod].
 #[macro_export]
-macro_rules! generate {
+macro_rules! byte_buffer {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Generate as "plain old data" and add to allowlist.
-/// Generate Rust bindings for the given C++ type such that
-/// it can be passed and owned by value in Rust. This only works
-/// for C++ types which have trivial move constructors and no
-/// destructor - you'll encounter a compile error otherwise.
-/// If your type doesn't match that description, use [generate]
-/// instead, and own the type using [UniquePtr][cxx::UniquePtr].
+/// Indicates that an incomplete C++ type is only ever handled through
+/// pointers, as is typical for C-style APIs. autocxx will then generate a
+/// `#[repr(transparent)]` handle type wrapping a (non-null) pointer to it.
+/// Functions returning such a pointer return an `Option` of the handle,
+/// and functions taking one accept a reference to the handle.
+///
+/// The syntax is:
+/// `opaque_handle!("CppNameGoesHere", HandleName, "destroy_function", "create_function", ...)`
+///
+/// The handle owns the object and calls the destroy function when it's
+/// dropped. Because the object is then known to be alive, functions taking
+/// the handle need not be `unsafe`. The destroy function itself continues
+/// to take a raw pointer.
+///
+/// Only the functions listed after the destroy function, which return a new
+/// object whose ownership passes to the caller, return an owning handle.
+/// Other functions returning a pointer to the type return a raw pointer,
+/// since it may be borrowed from an object owned elsewhere.
+///
+/// The destroy function can be omitted for handles which don't own their
+/// objects. Such handles are `Copy`, and functions taking them remain
+/// `unsafe`, since the object might have been freed elsewhere.
+///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! generate_pod {
+macro_rules! opaque_handle {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Generate Rust bindings for all C++ types and functions
-/// in a given namespace.
+/// Generates a safe accessor for a C++ struct which contains a union
+/// alongside a field indicating which union member is active - the
+/// common "tagged union" pattern.
+///
+/// The syntax is:
+/// `tagged_union!("CppNameGoesHere", tag_field, union_field, 0 => member_a, 1 => member_b)`
+///
+/// For a struct `Shape` this generates a `ShapeVariant<'a>` enum with a
+/// variant per listed union member, each holding a reference to that member,
+/// plus an `Unknown(i64)` variant for any tag value not listed. The method
+/// `Shape::variant()` reads the tag and returns the appropriate variant.
+/// The members are only ever read through that method, so that safe code
+/// can't read an inactive member.
+///
+/// It's an error if the struct, either field or any listed union member
+/// doesn't exist. The union must currently be of a named type; anonymous unions
+/// aren't supported.
+///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
-/// See also [generate].
 #[macro_export]
-macro_rules! generate_ns {
+macro_rules! tagged_union {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Generate Rust bindings for all C++ types and functions
-/// found. Highly experimental and not recommended.
+/// Indicates that a C++ type is a range - that is, it has `const` `begin()`
+/// and `end()` methods returning iterators - so that it can be iterated
+/// from Rust.
+///
+/// The syntax is:
+/// `range!("CppNameGoesHere", "ElementCppType")`
+///
+/// For a type `Numbers` this generates a `NumbersIter<'a>` iterator which
+/// yields `&'a` references to each element, a `Numbers::iter()` method which
+/// returns one, and `impl IntoIterator for &Numbers`, so you can write
+/// `for n in &numbers { ... }`. Iteration always borrows the range; there's
+/// no consuming form.
+///
+/// The Rust iterator holds a C++ iterator from `begin()`, which it advances
+/// one element at a time until it reaches `end()`, so any forward iterator
+/// will do. Creating the Rust iterator also counts the elements using
+/// `std::distance`, which for other than random-access iterators means a
+/// walk over the whole range. Dereferencing the iterator must yield a
+/// reference to an element held within the range, rather than a temporary.
+///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
-/// See also [generate].
 #[macro_export]
-macro_rules! generate_all {
+macro_rules! range {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Generate as "plain old data". For use with [generate_all]
-/// and similarly experimental.
+/// Generates a method to call a callable data member of a C++ type, such
+/// as a lambda or a `std::function`, with the signature you give. autocxx
+/// can't otherwise call such members: a lambda's type can't even be named.
+///
+/// The syntax is:
+/// `callable_member!("CppTypeName", member_name, fn(a: u32, b: &Foo) -> bool)`
+///
+/// The method has the same name as the member. Parameter and return types
+/// are written as they'd appear in Rust, using `&T` or `&mut T` for C++
+/// references; types other than primitives are looked up in the same
+/// namespace as the C++ type. The method always takes `self: Pin<&mut Self>`,
+/// since calling a `mutable` lambda changes the state it has captured.
+/// If the member can't be called with the given signature, the generated
+/// C++ won't compile.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! pod {
+macro_rules! callable_member {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Skip the normal generation of a `make_string` function
-/// and other utilities which we might generate normally.
+/// Generates a method which calls a `find`-style method of a C++ type,
+/// which returns an iterator, and compares the iterator against the
+/// type's `end()`. The generated method returns `None` if they're equal,
+/// and a reference to the element otherwise, so no iterator ever reaches
+/// Rust.
+///
+/// The syntax is:
+/// `iterator_lookup!("CppTypeName", method_name, fn(key: &u32) -> &Foo)`
+///
+/// For map-like types, where the element is a `std::pair`, name the member
+/// to return after an arrow:
+/// `iterator_lookup!("CppTypeName", method_name -> second, fn(key: &u32) -> &Foo)`
+///
+/// The method has the same name as the C++ method, and takes `&self`: it
+/// calls the `const` overloads of the method and of `end()`. Parameter
+/// types are written as for [`callable_member`], and the return type must
+/// be a shared reference.
+///
+/// The returned reference borrows the object, so Rust won't let you
+/// modify the object - which might invalidate the iterator - while you
+/// hold it. C++ code which modifies the object meanwhile can still leave
+/// it dangling, as with any other reference.
+///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! exclude_utilities {
+macro_rules! iterator_lookup {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Entirely block some type from appearing in the generated
-/// code. This can be useful if there is a type which is not
-/// understood by bindgen or autocxx, and incorrect code is
-/// otherwise generated.
-/// This is 'greedy' in the sense that any functions/methods
-/// which take or return such a type will _also_ be blocked.
+/// Generates a method to call a method which a C++ type inherits from a
+/// base class that autocxx can't see into, with the signature you give.
+/// The members of template instantiations aren't available to autocxx, so
+/// this is needed for methods inherited from a base such as `Base<int>`,
+/// including the curiously recurring template pattern, `Foo : Base<Foo>`.
+///
+/// The syntax is:
+/// `inherited_method!("CppTypeName", method_name, fn(a: u32, b: &Foo) -> bool)`
+///
+/// The method has the same name as the inherited method, and takes `&self`,
+/// calling the `const` overload. Write `mut` before the method name for one
+/// which takes `self: Pin<&mut Self>` instead:
+/// `inherited_method!("CppTypeName", mut method_name, fn())`.
+/// Parameter and return types are written as for [`callable_member`]. If
+/// the method can't be called with the given signature, the generated C++
+/// won't compile.
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! block {
+macro_rules! inherited_method {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Avoid generating implicit constructors for this type.
-/// The rules for when to generate C++ implicit constructors
-/// are complex, and if autocxx gets it wrong, you can block
-/// such constructors using this.
+/// Routes every call into C++ through a macro of your choice, for example
+/// to add tracing spans or timing around each FFI call. The macro is invoked
+/// as `your_macro!("CppName", call_expression)` and must evaluate to the
+/// result of the call expression. The name is the C++ name of the function,
+/// qualified by its namespace and (for methods) its type, for example
+/// `"ns::Type::method"`; overloads share a name. For example:
+///
+/// ```ignore
+/// macro_rules! trace_ffi {
+///     ($name:literal, $call:expr) => {{
+///         let _span = tracing::trace_span!($name).entered();
+///         $call
+///     }};
+/// }
+/// ```
+///
+/// Functions which would otherwise be passed straight through from
+/// `cxx` will be given a Rust wrapper so that they, too, can be
+/// instrumented. No extra C++ is generated for the sake of instrumentation.
+///
+/// The syntax is:
+/// `instrument_calls!(path::to::macro)`
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! block_constructors {
+macro_rules! instrument_calls {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// The name of the mod to be generated with the FFI code.
-/// The default is `ffi`.
+/// Indicates that a C++ function or method which returns `void` might
+/// throw an exception. It will instead return a
+/// `Result<(), cxx::Exception>`, which holds any exception thrown.
+///
+/// The syntax is:
+/// `throws!("function_name")`
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! name {
+macro_rules! throws {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// A concrete type to make, for example
-/// `concrete!("Container<Contents>")`.
-/// All types must already be on the allowlist by having used
-/// `generate!` or similar.
+/// Checks, as the program starts, that the C++ library we're linked
+/// against has the ABI version of the headers which the bindings were
+/// generated from. If not, the bindings may not match the library's types
+/// or functions, so rather than risk a crash later, the process prints
+/// both versions and aborts before `main` is reached.
+///
+/// The syntax is:
+/// `abi_version!("runtime_expression", "header_expression")`
+///
+/// Each is a C++ expression convertible to a `u64`. The first is evaluated
+/// at runtime and should ask the library for its version, for instance by
+/// calling a function such as `mylib_abi_version()` or reading a global
+/// variable which the library defines. The second is evaluated when the
+/// generated C++ is compiled, and should be the version given by the
+/// headers, typically a macro such as `MYLIB_ABI_VERSION`.
+///
+/// The check runs from a static initializer in the C++ which cxx generates
+/// for these bindings, so it happens whenever that's linked in, and there's
+/// nothing to call from Rust. Its names are unique to this `include_cpp!`,
+/// so it can't clash with your C++.
+///
+/// This can only help if the library reports its version somehow. If it
+/// doesn't have the symbol named in the first expression, you'll get an
+/// error when linking, or when loading a shared library, rather than from
+/// this check. Don't use this directive for versions of a library which
+/// predate its version symbol.
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! concrete {
+macro_rules! abi_version {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Specifies a global safety policy for functions generated
-/// from these headers. By default (without such a `safety!`
-/// directive) all such functions are marked as `unsafe` and
-/// therefore can only be called within an `unsafe {}` block
-/// or some `unsafe` function which you create.
+/// Sorts C++ exceptions of a given type into their own variant of an enum,
+/// `ffi::CppException`, so that Rust code can `match` on the kind of error
+/// rather than inspecting its message. The enum also has a variant,
+/// `Other`, holding a `cxx::Exception` for exceptions of any other type.
 ///
-/// Alternatively, by specifying a `safety!` block you can
-/// declare that most generated functions are in fact safe.
-/// Specifically, you'd specify:
-/// `safety!(unsafe)`
-/// or
-/// `safety!(unsafe_ffi)`
-/// These two options are functionally identical. If you're
-/// unsure, simply use `unsafe`. The reason for the
-/// latter option is if you have code review policies which
-/// might want to give a different level of scrutiny to
-/// C++ interop as opposed to other types of unsafe Rust code.
-/// Maybe in your organization, C++ interop is less scary than
-/// a low-level Rust data structure using pointer manipulation.
-/// Or maybe it's more scary. Either way, using `unsafe` for
-/// the data structure and using `unsafe_ffi` for the C++
-/// interop allows you to apply different linting tools and
-/// policies to the different options.
+/// The syntax is:
+/// `exception_type!("CppTypeName", VariantName)`
 ///
-/// Irrespective, C++ code is of course unsafe. It's worth
-/// noting that use of C++ can cause unexpected unsafety at
-/// a distance in faraway Rust code. As with any use of the
-/// `unsafe` keyword in Rust, *you the human* are declaring
-/// that you've analyzed all possible ways that the code
-/// can be used and you are guaranteeing to the compiler that
-/// no badness can occur. Good luck.
+/// Functions listed in [throws] still return a `Result<(), cxx::Exception>`,
+/// but `ffi::CppException` implements `From<cxx::Exception>`, so you
+/// can convert the error with `?` or `map_err(ffi::CppException::from)`.
+/// Each variant holds the message from the exception's `what()`, so the
+/// exception types must derive from `std::exception`; the generated C++
+/// fails to compile with a `static_assert` if not. An exception matching
+/// several listed types goes to the first of them which was listed, so list
+/// derived classes before their bases.
 ///
-/// Generated C++ APIs which use raw pointers remain `unsafe`
-/// no matter what policy you choose.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! exception_type {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Returns the fields of a struct returned by a function as a tuple,
+/// rather than the struct itself. That's more idiomatic for a C++ struct
+/// which only exists to return several values at once.
 ///
-/// There's an additional possible experimental safety
-/// policy available here:
-/// `safety!(unsafe_references_wrapped)`
-/// This policy treats C++ references as scary and requires
-/// them to be wrapped in a `CppRef` type: see [`CppRef`].
-/// This only works on nightly Rust because it
-/// depends upon an unstable feature
-/// (`arbitrary_self_types`). However, it should
-/// eliminate all undefined behavior related to Rust's
-/// stricter aliasing rules than C++.
+/// The syntax is:
+/// `destructure_return!("compute", MyResult)`
+///
+/// The struct must be a POD type (see [generate_pod]) with only plain
+/// named fields, returned by value. It's still generated as usual, so
+/// other functions which use it are unaffected.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! safety {
+macro_rules! destructure_return {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Whether to avoid generating [`cxx::UniquePtr`] and [`cxx::Vector`]
-/// implementations. This is primarily useful for reducing test cases and
-/// shouldn't be used in normal operation.
+/// Re-exports a generated item under a name of your choosing, from a mod
+/// called `exports` within the generated mod. Together with
+/// `pub use ffi::exports::*;` this lets you offer a tidy public API without
+/// exposing the whole of the generated mod.
+///
+/// The syntax is:
+/// `reexport!("ns::CppName", RustName)`
+///
+/// The item is given by its C++ name, and may be a type or a free
+/// function. It must be generated anyway, for instance because it's listed
+/// in [generate]; if not, or if it names several overloaded functions,
+/// code generation fails. Methods can't be re-exported by themselves, but
+/// they're always available on their type under whatever name it's
+/// re-exported, including any names given by [method_rename].
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! exclude_impls {
+macro_rules! reexport {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Indicates that a C++ type is not to be generated by autocxx in this case,
-/// but instead should refer to some pre-existing Rust type.
+/// Adds `#include`s to the very top of each C++ implementation file which
+/// autocxx generates, before any of autocxx's or cxx's own `#include`s.
+/// Use this to set up macros or forward declarations which your headers
+/// expect to see, without post-processing the generated files.
 ///
-/// If you wish for the type to be POD, you can use a `pod!` directive too
-/// (but see the "requirements" section below).
+/// The syntax is:
+/// `extra_cpp_includes!(["first.h", "second.h"])`
+///
+/// The headers are included in the order listed. They aren't seen
+/// by bindgen when it reads your headers, so shouldn't change the
+/// declarations which autocxx will find there.
+///
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! extra_cpp_includes {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Passes hand-written `cxx` declarations through verbatim into the
+/// `extern "C++"` section of the generated `cxx::bridge`. Use this for the
+/// occasional function which autocxx can't handle, without having to
+/// write a separate `cxx::bridge` mod.
 ///
 /// The syntax is:
-/// `extern_cpp_type!("CppNameGoesHere", path::to::rust::type)`
+/// `manual!(fn give_a() -> UniquePtr<A>; /* ... */)`
 ///
-/// Generally speaking, this should be used only to refer to types
-/// generated elsewhere by `autocxx` or `cxx` to ensure that they meet
-/// all the right requirements. It's possible - but fragile - to
-/// define such types yourself.
+/// Only functions and types may be declared. They may refer to the
+/// types generated by autocxx by their (unqualified) names, and appear in
+/// the `ffi` mod alongside the generated bindings. Any function which
+/// autocxx would have generated at the top level with the same name
+/// is skipped in favor of yours.
 ///
-/// # Requirements for externally defined Rust types
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! manual {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Chooses what happens when a C++ exception escapes from a function
+/// which isn't listed in [`throws`]. The options are:
+/// * `abort` (the default): C++ calls `std::terminate`, and the process
+///   dies.
+/// * `panic`: cxx catches the exception, and the generated Rust wrapper
+///   panics with its message. The panic can be caught with
+///   `std::panic::catch_unwind` like any other.
 ///
-/// It's generally expected that you would make such a type
-/// in Rust using a separate `include_cpp!` macro, or
-/// a manual `#[cxx::bridge]` directive somehwere. That is, this
-/// directive is intended mainly for use in cross-linking different
-/// sets of bindings in different mods, rather than truly to point to novel
-/// external types.
+/// Either way, C++ can't unwind through Rust functions which it calls,
+/// such as subclass methods or the [`assertion_handler`], so a panic
+/// there still aborts.
 ///
-/// But with that in mind, here are the requirements you must stick to.
+/// The syntax is:
+/// `exception_handling!(panic)`
 ///
-/// For non-POD external types:
-/// * The size and alignment of this type *must* be correct.
+/// A directive to be included inside
+/// [include_cpp] - see [include_cpp] for general information.
+#[macro_export]
+macro_rules! exception_handling {
+    ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
+}
+
+/// Chooses what to call functions and methods whose C++ names are Rust
+/// keywords, such as `move` or `type`. The options are:
+/// * `trailing_underscore` (the default): `move` becomes `move_`.
+/// * `raw`: use a raw identifier, so `move` becomes `r#move`. `crate`,
+///   `self`, `super` and `Self` can't be raw identifiers, so they get
+///   a trailing underscore instead.
+/// * `prefix = "..."`: add the given prefix, so with `prefix = "cpp_"`,
+///   `move` becomes `cpp_move`.
 ///
-/// For POD external types:
-/// * As above
-/// * Your type must correspond to the requirements of
-///   [`cxx::kind::Trivial`]. In general, that means, no move constructor
-///   and no destructor. If you generate this type using `cxx` itself
-///   (or `autocxx`) this will be enforced using `static_assert`s
-///   within the generated C++ code. Without using those tools, you're
-///   on your own for determining this... and it's hard because the presence
-///   of particular fields or base classes may well result in your type
-///   violating those rules.
+/// Whichever you choose, name such a function in [`generate`] as bindgen
+/// does, with a trailing underscore.
+///
+/// The syntax is:
+/// `keyword_names!(raw)`
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! extern_cpp_type {
+macro_rules! keyword_names {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
-/// Indicates that a C++ type is not to be generated by autocxx in this case,
-/// but instead should refer to some pre-existing Rust type. Unlike
-/// `extern_cpp_type!`, there's no need for the size and alignment of this
-/// type to be correct.
+/// Chooses what to do with C++ `long double`, which has no Rust equivalent
+/// and whose size varies between platforms. The options are:
+/// * `reject` (the default): don't generate bindings for anything which
+///   uses `long double`.
+/// * `f64`: pass `long double` parameters and return values as `f64`,
+///   with a C++ wrapper function converting them. Precision is lost.
+///   Structs with `long double` fields are still rejected, since their
+///   layout wouldn't match.
+/// * `opaque`: represent `long double` as an opaque [`c_longdouble`],
+///   which can be passed around and stored in structs, but not used
+///   for arithmetic in Rust.
+///
+/// autocxx can only spot `long double` where it's wider than `double`, as on
+/// x86-64 Linux. Elsewhere bindgen reports it as a `double`, so this
+/// directive has no effect.
 ///
 /// The syntax is:
-/// `extern_cpp_opaque_type!("CppNameGoesHere", path::to::rust::type)`
+/// `long_double!(f64)`
 ///
 /// A directive to be included inside
 /// [include_cpp] - see [include_cpp] for general information.
 #[macro_export]
-macro_rules! extern_cpp_opaque_type {
+macro_rules! long_double {
     ($($tt:tt)*) => { $crate::usage!{$($tt)*} };
 }
 
@@ -489,6 +1849,82 @@ unsafe impl cxx::ExternType for c_char16_t {
     type Kind = cxx::kind::Trivial;
 }
 
+/// An opaque C++ `long double`, for use with `long_double!(opaque)`.
+/// There's no Rust equivalent, so all you can do is pass it around.
+/// This is only used where `long double` is 16 bytes, since otherwise
+/// it's simply a `double`.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy)]
+#[repr(C, align(16))]
+pub struct c_longdouble([u8; 16]);
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_longdouble {
+    type Id = cxx::type_id!(c_longdouble);
+    type Kind = cxx::kind::Trivial;
+}
+
+/// A C++ `std::complex<T>`. autocxx passes `std::complex<float>` and
+/// `std::complex<double>` by value as [`c_complex_float`] and
+/// [`c_complex_double`], since C++ lays them out just like this: the real
+/// part followed by the imaginary part.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Complex<T> {
+    /// The real part.
+    pub re: T,
+    /// The imaginary part.
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    /// Make a complex number from its real and imaginary parts.
+    pub fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+}
+
+impl<T> From<(T, T)> for Complex<T> {
+    fn from((re, im): (T, T)) -> Self {
+        Self { re, im }
+    }
+}
+
+impl<T> From<Complex<T>> for (T, T) {
+    fn from(val: Complex<T>) -> Self {
+        (val.re, val.im)
+    }
+}
+
+/// A C++ `std::complex<float>`.
+#[allow(non_camel_case_types)]
+pub type c_complex_float = Complex<f32>;
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_complex_float {
+    type Id = cxx::type_id!(c_complex_float);
+    type Kind = cxx::kind::Trivial;
+}
+
+/// A C++ `std::complex<double>`.
+#[allow(non_camel_case_types)]
+pub type c_complex_double = Complex<f64>;
+
+/// # Safety
+///
+/// We assert that the namespace and type ID refer to a C++
+/// type which is equivalent to this Rust type.
+unsafe impl cxx::ExternType for c_complex_double {
+    type Id = cxx::type_id!(c_complex_double);
+    type Kind = cxx::kind::Trivial;
+}
+
 /// autocxx couldn't generate these bindings.
 /// If you come across a method, type or function which refers to this type,
 /// it indicates that autocxx couldn't generate that binding. A documentation
@@ -654,14 +2090,18 @@ where
 
 use cxx::memory::UniquePtrTarget;
 use cxx::UniquePtr;
+pub use invalid_enum_value::InvalidEnumValue;
 use moveit::New;
+pub use raii_guard::{CppGuard, RaiiGuard, WithinGuard};
 pub use rvalue_param::RValueParam;
 pub use rvalue_param::RValueParamHandler;
+pub use thread_local::CppThreadLocal;
 pub use value_param::as_copy;
 pub use value_param::as_mov;
 pub use value_param::as_new;
 pub use value_param::ValueParam;
 pub use value_param::ValueParamHandler;
+pub use wide_string::ToWideCString;
 
 /// Imports which you're likely to want to use.
 pub mod prelude {
@@ -691,6 +2131,7 @@ pub mod prelude {
     pub use crate::ValueParam;
     pub use crate::WithinBox;
     pub use crate::WithinBoxTrivial;
+    pub use crate::WithinGuard;
     pub use crate::WithinUniquePtr;
     pub use crate::WithinUniquePtrTrivial;
     pub use cxx::UniquePtr;
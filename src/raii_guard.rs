@@ -0,0 +1,76 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::{marker::PhantomData, ops::Deref, pin::Pin};
+
+use cxx::{memory::UniquePtrTarget, UniquePtr};
+use moveit::{new::New, MakeCppStorage};
+
+use crate::WithinUniquePtr;
+
+/// A C++ type which acquires some resource in its constructor and
+/// releases it in its destructor, such as a `std::lock_guard`. autocxx
+/// implements this for each type listed in [`raii_guard`](crate::raii_guard),
+/// so that it can be held in a [`CppGuard`].
+pub trait RaiiGuard: UniquePtrTarget {}
+
+/// Holds a C++ RAII guard, whose resource is held for exactly as long as
+/// this exists: dropping it runs the C++ destructor, which releases the
+/// resource. Make one using [`WithinGuard::within_guard`], and keep it in
+/// a named variable for the scope in which you need the resource.
+///
+/// Many resources, such as most mutexes, must be released on the thread
+/// which acquired them, so this is neither [`Send`] nor [`Sync`].
+#[must_use = "dropping a guard releases its resource immediately"]
+pub struct CppGuard<T: RaiiGuard> {
+    inner: UniquePtr<T>,
+    // A raw pointer ensures that we're !Send and !Sync.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T: RaiiGuard> CppGuard<T> {
+    /// Get a pinned mutable reference to the C++ guard object.
+    pub fn pin_mut(&mut self) -> Pin<&mut T> {
+        self.inner.pin_mut()
+    }
+
+    /// Release the resource now. This is the same as dropping the guard,
+    /// but says so more clearly.
+    pub fn release(self) {}
+}
+
+impl<T: RaiiGuard> Deref for CppGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+/// Provides a way to construct a C++ RAII guard straight into a
+/// [`CppGuard`]. Automatically imported by the autocxx prelude and
+/// implemented by any [`moveit::New`] of a type listed in
+/// [`raii_guard`](crate::raii_guard).
+pub trait WithinGuard {
+    type Inner: RaiiGuard;
+    fn within_guard(self) -> CppGuard<Self::Inner>;
+}
+
+impl<N, T> WithinGuard for N
+where
+    N: New<Output = T>,
+    T: RaiiGuard + MakeCppStorage,
+{
+    type Inner = T;
+    fn within_guard(self) -> CppGuard<T> {
+        CppGuard {
+            inner: self.within_unique_ptr(),
+            _not_send: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::pin::Pin;
+
+/// The calling thread's instance of a C++ `thread_local` variable, as
+/// returned by the accessor function which autocxx generates for each
+/// variable listed in [`thread_local_var`](crate::thread_local_var).
+///
+/// Each thread has its own instance of a C++ `thread_local` variable, so
+/// this is neither [`Send`] nor [`Sync`]: it may only be used on the thread
+/// which obtained it. Ask again on each thread which needs the variable.
+///
+/// # Lifetime
+///
+/// If the variable needs dynamic initialization, C++ performs it the
+/// first time that each thread accesses the variable - which may be
+/// the call to the accessor function. C++ destroys each thread's instance
+/// as that thread exits, so don't use this from within Rust thread-local
+/// destructors, which may run afterwards.
+pub struct CppThreadLocal<T> {
+    // A raw pointer also ensures that we're !Send and !Sync.
+    ptr: *mut T,
+}
+
+impl<T> CppThreadLocal<T> {
+    /// Wrap the address of the calling thread's instance of a variable.
+    /// Used by autocxx-generated code.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a thread-local variable belonging to the
+    /// calling thread.
+    #[doc(hidden)]
+    pub unsafe fn from_raw(ptr: *mut T) -> Self {
+        Self { ptr }
+    }
+
+    /// Get the address of this thread's instance of the variable.
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr
+    }
+
+    /// Get a Rust reference to this thread's instance of the variable.
+    ///
+    /// # Safety
+    ///
+    /// C++ code running on this thread must not modify the variable
+    /// while the reference exists.
+    pub unsafe fn as_ref(&self) -> &T {
+        &*self.ptr
+    }
+
+    /// Get a mutable Rust reference to this thread's instance of the
+    /// variable.
+    ///
+    /// # Safety
+    ///
+    /// No other reference to the variable, in Rust or C++, may be used
+    /// while this one exists.
+    pub unsafe fn as_mut(&mut self) -> Pin<&mut T> {
+        Pin::new_unchecked(&mut *self.ptr)
+    }
+}
+
+impl<T: Copy> CppThreadLocal<T> {
+    /// Read this thread's instance of the variable.
+    ///
+    /// # Safety
+    ///
+    /// C++ must not yet have destroyed this thread's instance, which it
+    /// may have done if this is called from a Rust thread-local
+    /// destructor. See [Lifetime](#lifetime).
+    pub unsafe fn get(&self) -> T {
+        self.ptr.read()
+    }
+
+    /// Overwrite this thread's instance of the variable.
+    ///
+    /// # Safety
+    ///
+    /// As for [`get`](Self::get).
+    pub unsafe fn set(&self, value: T) {
+        self.ptr.write(value)
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Something which can be turned into a null-terminated buffer of wide
+/// characters, for passing to a C++ function which takes a
+/// `const wchar_t*`. See [`wide_string_param`](crate::wide_string_param).
+///
+/// `T` is the Rust representation of `wchar_t` on the target platform:
+/// `u16` on Windows, where strings are encoded as UTF-16, and `u32`
+/// elsewhere, where they're encoded as UTF-32.
+///
+/// Slices of wide characters are passed through unchanged, other than
+/// adding a terminating null if there isn't one already. If they contain
+/// a null before the end, C++ will see only the characters up to that
+/// point.
+pub trait ToWideCString<T> {
+    fn to_wide_c_string(self) -> Vec<T>;
+}
+
+fn null_terminated<T: Copy + Default + PartialEq>(chars: &[T]) -> Vec<T> {
+    let mut buffer = Vec::with_capacity(chars.len() + 1);
+    buffer.extend_from_slice(chars);
+    if buffer.last() != Some(&T::default()) {
+        buffer.push(T::default());
+    }
+    buffer
+}
+
+impl ToWideCString<u16> for &str {
+    fn to_wide_c_string(self) -> Vec<u16> {
+        self.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+}
+
+impl ToWideCString<u32> for &str {
+    fn to_wide_c_string(self) -> Vec<u32> {
+        self.chars()
+            .map(u32::from)
+            .chain(std::iter::once(0))
+            .collect()
+    }
+}
+
+impl<T> ToWideCString<T> for String
+where
+    for<'a> &'a str: ToWideCString<T>,
+{
+    fn to_wide_c_string(self) -> Vec<T> {
+        self.as_str().to_wide_c_string()
+    }
+}
+
+impl<T> ToWideCString<T> for &String
+where
+    for<'a> &'a str: ToWideCString<T>,
+{
+    fn to_wide_c_string(self) -> Vec<T> {
+        self.as_str().to_wide_c_string()
+    }
+}
+
+impl<T: Copy + Default + PartialEq> ToWideCString<T> for &[T] {
+    fn to_wide_c_string(self) -> Vec<T> {
+        null_terminated(self)
+    }
+}
+
+impl<T: Copy + Default + PartialEq> ToWideCString<T> for &Vec<T> {
+    fn to_wide_c_string(self) -> Vec<T> {
+        null_terminated(self)
+    }
+}
+
+impl<T: Copy + Default + PartialEq> ToWideCString<T> for Vec<T> {
+    fn to_wide_c_string(mut self) -> Vec<T> {
+        if self.last() != Some(&T::default()) {
+            self.push(T::default());
+        }
+        self
+    }
+}